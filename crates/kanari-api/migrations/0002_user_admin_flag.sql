@@ -0,0 +1,3 @@
+-- Admins may list users and read the debug dump
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
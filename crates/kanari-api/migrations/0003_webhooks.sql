@@ -0,0 +1,34 @@
+-- Callback URLs registered through /webhooks, and the outcome of every delivery
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    id SERIAL PRIMARY KEY,
+    owner VARCHAR(255) NOT NULL REFERENCES users(username) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    -- Key of the HMAC-SHA256 signature sent with each delivery
+    secret VARCHAR(255) NOT NULL,
+    -- Symbols to notify about; empty matches every configured symbol
+    symbols TEXT[] NOT NULL DEFAULT '{}',
+    -- Any of 'update', 'threshold', 'staleness'
+    events TEXT[] NOT NULL,
+    threshold_percent DOUBLE PRECISION,
+    stale_after_secs BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhooks_owner_idx ON webhooks (owner);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event VARCHAR(32) NOT NULL,
+    asset_type VARCHAR(16) NOT NULL,
+    symbol VARCHAR(255) NOT NULL,
+    -- HTTP status of the callback's response; NULL when no response arrived
+    status_code INTEGER,
+    error TEXT,
+    duration_ms BIGINT NOT NULL,
+    delivered_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_idx
+    ON webhook_deliveries (webhook_id, delivered_at DESC);
@@ -0,0 +1,23 @@
+-- Daily and weekly market summaries registered through /reports
+
+CREATE TABLE IF NOT EXISTS report_schedules (
+    id SERIAL PRIMARY KEY,
+    owner VARCHAR(255) NOT NULL REFERENCES users(username) ON DELETE CASCADE,
+    -- 'daily' or 'weekly'
+    frequency VARCHAR(16) NOT NULL,
+    -- Symbols to summarize; empty summarizes every configured symbol
+    symbols TEXT[] NOT NULL DEFAULT '{}',
+    -- Delivered by email, or to one of the owner's webhooks
+    email VARCHAR(255),
+    webhook_id INTEGER REFERENCES webhooks(id) ON DELETE CASCADE,
+    -- Claimed by the replica sending the report, so each period is sent once
+    last_sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK ((email IS NULL) <> (webhook_id IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS report_schedules_owner_idx ON report_schedules (owner);
+
+-- Reports delivered to a webhook are not about a single symbol
+ALTER TABLE webhook_deliveries ALTER COLUMN asset_type DROP NOT NULL;
+ALTER TABLE webhook_deliveries ALTER COLUMN symbol DROP NOT NULL;
@@ -0,0 +1,11 @@
+-- Named symbol lists managed through /watchlists
+
+CREATE TABLE IF NOT EXISTS watchlists (
+    id SERIAL PRIMARY KEY,
+    owner VARCHAR(255) NOT NULL REFERENCES users(username) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    -- Entries such as "btc", "crypto:btc" or "stock:AAPL", in the order added
+    symbols TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (owner, name)
+);
@@ -0,0 +1,12 @@
+-- Symbols users register through /users/symbols on top of the server config
+
+CREATE TABLE IF NOT EXISTS user_symbols (
+    id SERIAL PRIMARY KEY,
+    owner VARCHAR(255) NOT NULL REFERENCES users(username) ON DELETE CASCADE,
+    -- 'crypto' or 'stock'
+    asset_type VARCHAR(16) NOT NULL,
+    -- Lowercase, as passed to the providers
+    symbol VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (owner, asset_type, symbol)
+);
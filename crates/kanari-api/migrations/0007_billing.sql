@@ -0,0 +1,17 @@
+-- Stripe subscriptions, kept current by /billing/stripe/webhook
+
+CREATE TABLE IF NOT EXISTS subscriptions (
+    owner VARCHAR(255) PRIMARY KEY REFERENCES users(username) ON DELETE CASCADE,
+    stripe_customer VARCHAR(255) NOT NULL,
+    stripe_subscription VARCHAR(255) NOT NULL,
+    -- Stripe price ID, mapped to a plan by KANARI_BILLING_PLANS
+    price_id VARCHAR(255) NOT NULL,
+    -- Stripe status, e.g. 'active', 'past_due' or 'canceled'
+    status VARCHAR(32) NOT NULL,
+    current_period_end TIMESTAMP WITH TIME ZONE,
+    -- Creation time of the Stripe event last stored, so late deliveries are ignored
+    event_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS subscriptions_customer_idx ON subscriptions (stripe_customer);
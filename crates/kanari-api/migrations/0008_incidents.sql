@@ -0,0 +1,18 @@
+-- Incidents recorded through /admin/incidents and listed on /status.json
+
+CREATE TABLE IF NOT EXISTS incidents (
+    id BIGSERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    -- 'degraded' or 'major_outage'
+    impact VARCHAR(16) NOT NULL,
+    -- 'investigating', 'identified', 'monitoring' or 'resolved'
+    status VARCHAR(16) NOT NULL,
+    -- Affected component, e.g. 'sources'; NULL for the whole service
+    component VARCHAR(64),
+    message TEXT,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS incidents_started_idx ON incidents (started_at DESC);
@@ -1,6 +1,11 @@
+use anyhow::anyhow;
 use axum::{
     Router,
-    routing::{get, post},
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post},
 };
 use dotenvy;
 use std::sync::Arc;
@@ -10,35 +15,168 @@ use tower_http::trace::TraceLayer;
 
 use kanari_oracle::oracle::Oracle;
 
-use crate::database::{DbPool, create_db_pool, initialize_database};
+use crate::auth::{AdminAccess, Auth, AuthMode, TokenValidator, static_tokens};
+use crate::billing::Billing;
+use crate::cache::ResponseCache;
+use crate::control::UpdateControl;
+use crate::database::{DbHealth, DbPool, check_connection, create_db_pool, spawn_health_monitor};
+use crate::drain::Drain;
 use crate::handlers::{
-    change_user_email, change_user_password, create_user_token, delete_user_account,
-    delete_user_token, get_all_prices, get_price, get_stats, get_user_profile, health_check,
-    list_symbols, list_user_tokens, list_users, login_user, register_user, update_prices,
+    add_watchlist_symbols, change_user_email, change_user_password, coingecko_simple_price,
+    create_custom_symbol, create_incident, create_report, create_user_token, create_watchlist,
+    create_webhook, debug_feeds, delete_basket, delete_custom_symbol, delete_report,
+    delete_user_account, delete_user_token, delete_watchlist, delete_webhook, drain_server,
+    get_all_prices, get_anomalies, get_billing_status, get_custom_symbol_prices, get_fx_rates,
+    get_history, get_last_round, get_market_cap_index, get_network_stats, get_price,
+    get_provenance, get_sentiment, get_slo, get_source_stats, get_stats, get_tvl,
+    get_update_control, get_user_profile, get_version, get_watchlist_prices, grafana_annotations,
+    grafana_query, grafana_search, grafana_test, health_check, import_symbols, list_baskets,
+    list_custom_symbols, list_reports, list_symbols, list_user_tokens, list_users, list_watchlists,
+    list_webhook_deliveries, list_webhooks, login_user, metrics, pause_updates,
+    pyth_latest_price_feeds, pyth_price_feed_ids, readiness_check, refresh_symbol, register_user,
+    remove_watchlist_symbol, resume_updates, run_symbol_update, save_basket, set_update_interval,
+    status_page, status_summary, stream_prices, stripe_webhook, update_incident, update_prices,
+    user_symbol_limit,
 };
+use crate::incidents::Incidents;
+use crate::models::ApiResponse;
+use crate::refresh_limit::RefreshLimiter;
+use crate::reports::Reports;
+use crate::signing::{ResponseSigner, sign_responses};
+use crate::webhooks::Webhooks;
 
 pub type SharedOracle = Arc<RwLock<Oracle>>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub oracle: SharedOracle,
+    pub auth: Auth,
+    pub admin: AdminAccess,
+    /// Set when running with a database, which may be temporarily down
+    pub db_health: Option<DbHealth>,
+    pub prices_cache: ResponseCache,
+    /// Config file the oracle was loaded from, updated by symbol imports
+    pub config_path: Option<String>,
+    /// Set by `/admin/drain`; fails readiness and stops background updates
+    pub drain: Drain,
+    /// Pause and interval of background updates, changed through `/admin/updates`
+    pub updates: UpdateControl,
+    /// Per-token budget of `/update/{asset_type}/{symbol}` calls
+    pub refresh_limit: RefreshLimiter,
+    /// Signs price responses when KANARI_SIGNING_KEY is set
+    pub signer: Option<ResponseSigner>,
+    /// Incidents listed on `/status.json`
+    pub incidents: Incidents,
+}
+
+/// State of the `/users` endpoints, which only exist with a database
+#[derive(Clone)]
+pub struct UserState {
     pub db: DbPool,
+    /// Prices watchlists are quoted from
+    pub oracle: SharedOracle,
+    /// Symbols each user may register through `/users/symbols`
+    pub symbol_limit: i64,
+    pub health: DbHealth,
+    pub webhooks: Webhooks,
+    pub reports: Reports,
+    /// Stripe plans and subscriptions when STRIPE_WEBHOOK_SECRET is set
+    pub billing: Option<Billing>,
 }
 
-pub fn create_router(oracle: SharedOracle, db: DbPool) -> Router {
-    let state = AppState { oracle, db };
-    Router::new()
+/// Price endpoints checked by `state.auth`; the `/users` endpoints are added
+/// when a database is available. Symbol imports are written to
+/// `state.config_path` when set. With `state.signer`, responses carrying price
+/// data are signed.
+pub fn create_router(state: AppState, users: Option<UserState>) -> Router {
+    // Price data, signed when a signing key is set
+    let signed = Router::new()
+        .route("/price/{asset_type}/{symbol}", get(get_price))
+        .route("/prices/{asset_type}", get(get_all_prices))
+        .route("/history/{asset_type}/{symbol}", get(get_history))
+        .route("/index/market-cap", get(get_market_cap_index))
+        .route("/fx", get(get_fx_rates))
+        .route("/tvl/{protocol}", get(get_tvl))
+        .route(
+            "/compat/coingecko/simple/price",
+            get(coingecko_simple_price),
+        )
+        .route(
+            "/compat/pyth/api/latest_price_feeds",
+            get(pyth_latest_price_feeds),
+        )
+        .route("/update/{asset_type}/{symbol}", post(refresh_symbol))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            sign_responses,
+        ));
+
+    let mut router = Router::new()
         // Health check
         .route("/health", get(health_check))
+        // Deployment
+        .route("/ready", get(readiness_check))
+        .route("/version", get(get_version))
         // Price endpoints
-        .route("/price/{asset_type}/{symbol}", get(get_price))
-        .route("/prices/{asset_type}", get(get_all_prices))
+        .route("/provenance/{asset_type}/{symbol}", get(get_provenance))
+        .route("/sentiment", get(get_sentiment))
+        .route("/network", get(get_network_stats))
+        .route("/stream", get(stream_prices))
         // Symbols
         .route("/symbols", get(list_symbols))
+        .route("/baskets", get(list_baskets))
         // Statistics
         .route("/stats", get(get_stats))
+        .route("/stats/sources", get(get_source_stats))
+        .route("/stats/anomalies", get(get_anomalies))
+        .route("/stats/slo", get(get_slo))
+        .route("/stats/last-round", get(get_last_round))
+        // Operator dashboard
+        .route("/status", get(status_page))
+        .route("/status.json", get(status_summary))
+        .route("/admin/incidents", post(create_incident))
+        .route("/admin/incidents/{id}", post(update_incident))
+        // Prometheus metrics
+        .route("/metrics", get(metrics))
+        // Troubleshooting
+        .route("/debug/feeds", get(debug_feeds))
+        .route("/admin/symbols/import", post(import_symbols))
+        .route("/admin/drain", post(drain_server))
+        .route("/admin/baskets", post(save_basket))
+        .route("/admin/baskets/{name}", delete(delete_basket))
+        .route("/admin/updates", get(get_update_control))
+        .route("/admin/updates/pause", post(pause_updates))
+        .route("/admin/updates/resume", post(resume_updates))
+        .route("/admin/updates/interval", post(set_update_interval))
+        .route(
+            "/admin/updates/run/{asset_type}/{symbol}",
+            post(run_symbol_update),
+        )
+        // Drop-in replacements for other price APIs
+        .route("/compat/pyth/api/price_feed_ids", get(pyth_price_feed_ids))
+        // Grafana SimpleJSON datasource
+        .route("/grafana", get(grafana_test))
+        .route("/grafana/search", post(grafana_search))
+        .route("/grafana/query", post(grafana_query))
+        .route("/grafana/annotations", post(grafana_annotations))
         // Update endpoints
         .route("/update/{asset_type}", post(update_prices))
+        .merge(signed)
+        // Add state
+        .with_state(state);
+
+    if let Some(users) = users {
+        router = router.merge(user_router(users));
+    }
+
+    router
+        // Add middleware
+        .layer(CorsLayer::permissive())
+        .layer(TraceLayer::new_for_http())
+}
+
+fn user_router(state: UserState) -> Router {
+    Router::new()
         // User endpoints
         .route("/users/register", post(register_user))
         .route("/users/login", post(login_user))
@@ -53,70 +191,355 @@ pub fn create_router(oracle: SharedOracle, db: DbPool) -> Router {
         .route("/users/change-password", post(change_user_password))
         .route("/users/change-email", post(change_user_email))
         .route("/users/delete", post(delete_user_account))
-        // Add state
+        // Stripe billing
+        .route("/users/billing", get(get_billing_status))
+        .route("/billing/stripe/webhook", post(stripe_webhook))
+        // Symbols registered on top of the server config
+        .route(
+            "/users/symbols",
+            get(list_custom_symbols).post(create_custom_symbol),
+        )
+        .route("/users/symbols/prices", get(get_custom_symbol_prices))
+        .route("/users/symbols/{id}", delete(delete_custom_symbol))
+        // Webhooks
+        .route("/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/webhooks/{id}", delete(delete_webhook))
+        .route("/webhooks/{id}/deliveries", get(list_webhook_deliveries))
+        // Scheduled summary reports
+        .route("/reports", get(list_reports).post(create_report))
+        .route("/reports/{id}", delete(delete_report))
+        // Watchlists
+        .route("/watchlists", get(list_watchlists).post(create_watchlist))
+        .route("/watchlists/{id}", delete(delete_watchlist))
+        .route("/watchlists/{id}/symbols", post(add_watchlist_symbols))
+        .route(
+            "/watchlists/{id}/symbols/{symbol}",
+            delete(remove_watchlist_symbol),
+        )
+        .route("/watchlists/{id}/prices", get(get_watchlist_prices))
+        .route_layer(middleware::from_fn_with_state(
+            state.health.clone(),
+            require_database,
+        ))
         .with_state(state)
-        // Add middleware
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
 }
 
+/// Answer 503 instead of running a `/users` handler while the database is down
+async fn require_database(
+    State(health): State<DbHealth>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if health.is_up() {
+        return next.run(request).await;
+    }
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ApiResponse::<()>::error(
+            "Database unavailable, try again later".to_string(),
+        )),
+    )
+        .into_response()
+}
+
+/// Serve the API until `shutdown` resolves, then stop accepting connections,
+/// finish in-flight requests and close the database pool. Only
+/// [`AuthMode::Database`] connects to Postgres. `config_path` is the file the
+/// oracle's config came from, where `/admin/symbols/import` adds symbols;
+/// `drain` is started by `/admin/drain` and `updates` is changed through
+/// `/admin/updates`.
 pub async fn start_api_server_with_shared_oracle(
     shared_oracle: SharedOracle,
     port: u16,
+    auth_mode: AuthMode,
+    config_path: Option<String>,
+    drain: Drain,
+    updates: UpdateControl,
+    shutdown: impl Future<Output = ()> + Send + 'static,
 ) -> anyhow::Result<()> {
     // Load .env file (if present) so DATABASE_URL and other env vars are available
     dotenvy::dotenv().ok();
 
-    // Build DB pool from DATABASE_URL env var
-    let pool = create_db_pool().await?;
+    let (auth, users) = match auth_mode {
+        AuthMode::Database => {
+            // Build DB pool from the database section or DATABASE_URL
+            let database = shared_oracle.read().await.config().database.clone();
+            let pool = create_db_pool(&database).map_err(|e| {
+                anyhow!(
+                    "{} (use --auth tokens or --auth none to run without a database)",
+                    e
+                )
+            })?;
+            // Say at startup when Postgres is unreachable, instead of at the
+            // first request that needs it
+            if let Err(e) = check_connection(&pool, &database).await {
+                if database.required {
+                    return Err(anyhow!("{} (database.required is set)", e));
+                }
+                log::error!("{}; serving in degraded mode until it answers", e);
+            }
+            let webhooks = Webhooks::new(pool.clone())?;
+            let billing = Billing::from_env(pool.clone())?;
+            match &billing {
+                Some(billing) => log::info!(
+                    "Stripe billing enabled with {} plans; {} requests a day without a subscription",
+                    billing.plan_count(),
+                    billing.free_requests()
+                ),
+                None => log::info!("STRIPE_WEBHOOK_SECRET not set; requests are not metered"),
+            }
+            let users = UserState {
+                db: pool.clone(),
+                oracle: shared_oracle.clone(),
+                symbol_limit: user_symbol_limit()?,
+                health: DbHealth::default(),
+                reports: Reports::new(pool.clone(), webhooks.clone())?,
+                webhooks,
+                billing: billing.clone(),
+            };
+            (
+                Auth::Database(TokenValidator::new(pool, billing)),
+                Some(users),
+            )
+        }
+        AuthMode::Tokens => {
+            let tokens = static_tokens()?;
+            log::info!("Authenticating with {} static API tokens", tokens.len());
+            // Tokens of a tenant the config does not define are served nothing
+            let oracle = shared_oracle.read().await;
+            let unknown: std::collections::BTreeSet<&String> = tokens
+                .values()
+                .flatten()
+                .filter(|tenant| oracle.config().tenant(tenant).is_none())
+                .collect();
+            for tenant in unknown {
+                log::warn!(
+                    "KANARI_API_TOKENS lists tokens of tenant '{}', which the config does not define",
+                    tenant
+                );
+            }
+            drop(oracle);
+            (Auth::Tokens(Arc::new(tokens)), None)
+        }
+        AuthMode::None => {
+            log::warn!("Authentication is disabled; every request is accepted");
+            (Auth::Disabled, None)
+        }
+    };
+    let admin = AdminAccess::from_env(auth_mode)?;
 
-    // Initialize database tables
-    initialize_database(&pool).await?;
-    log::info!("Database tables initialized successfully");
+    // Applies migrations once Postgres is reachable and tracks outages
+    let monitor = users
+        .as_ref()
+        .map(|users| spawn_health_monitor(users.db.clone(), users.health.clone()));
+    let pool = users.as_ref().map(|users| users.db.clone());
+    let dispatcher = users.as_ref().map(|users| {
+        users
+            .webhooks
+            .clone()
+            .spawn(shared_oracle.clone(), users.health.clone())
+    });
+    let scheduler = users.as_ref().map(|users| {
+        users
+            .reports
+            .clone()
+            .spawn(shared_oracle.clone(), users.health.clone())
+    });
 
-    let app = create_router(shared_oracle, pool);
+    let incidents = Incidents::new(pool.clone());
+    let incident_loader = users
+        .as_ref()
+        .map(|users| incidents.clone().spawn(users.health.clone()));
+    let biller = users.as_ref().and_then(|users| {
+        let billing = users.billing.clone()?;
+        Some(billing.spawn(users.health.clone()))
+    });
+
+    let refresh_limit = RefreshLimiter::from_env()?;
+    let signer = ResponseSigner::from_env()?;
+    if let Some(signer) = &signer {
+        log::info!(
+            "Signing price responses with Ed25519 public key {} (valid {}s)",
+            signer.public_key(),
+            signer.ttl()
+        );
+    }
+    let state = AppState {
+        oracle: shared_oracle,
+        auth,
+        admin,
+        db_health: users.as_ref().map(|users| users.health.clone()),
+        prices_cache: ResponseCache::default(),
+        config_path,
+        drain,
+        updates,
+        refresh_limit,
+        signer,
+        incidents,
+    };
+    let app = create_router(state, users);
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
 
     log::info!("🚀 API server starting on http://0.0.0.0:{}", port);
     log::info!("📚 API Documentation:");
     log::info!("  GET  /health                     - Health check");
-    log::info!("  GET  /price/:type/:symbol        - Get specific price (crypto/btc, stock/aapl)");
-    log::info!("  GET  /prices/:type               - Get all prices for type (crypto, stock)");
-    log::info!("  GET  /symbols?asset_type=type    - List available symbols");
-    log::info!("  GET  /stats                      - Oracle statistics");
-    log::info!("  POST /update/:type               - Force update prices (crypto, stock, all)");
-    log::info!("  POST /users/register             - Register new user (public)");
-    log::info!("  POST /users/login                - User login (public)");
+    log::info!("  GET  /ready                      - Readiness (503 while warming or draining)");
+    log::info!("  GET  /version                    - Build version, commit and enabled features");
     log::info!(
-        "  GET  /users/list                 - List all users (admin, requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        "  GET  /price/:type/:symbol        - Get specific price (crypto/btc, stock/aapl, any/aapl)"
     );
+    log::info!("  GET  /prices/:type               - Get all prices for type (crypto, stock, tvl)");
+    log::info!("  GET  /history/:type/:symbol      - Price history, split and dividend adjusted");
+    log::info!("  GET  /index/market-cap           - Market-cap-weighted crypto index");
+    log::info!("  GET  /sentiment                  - Fear & Greed index and funding rates");
     log::info!(
-        "  GET  /users/profile              - Get user profile (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
-    );
-    log::info!(
-        "  POST /users/change-password      - Change password (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
-    );
-    log::info!(
-        "  POST /users/change-email         - Change account email (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
-    );
-    log::info!(
-        "  POST /users/delete               - Delete user account (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
-    );
-    log::info!(
-        "  GET  /users/tokens               - List your API tokens (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
-    );
-    log::info!(
-        "  POST /users/tokens               - Create a new API token (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
-    );
-    log::info!(
-        "  POST /users/tokens/revoke        - Revoke an API token (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        "  GET  /network                    - Bitcoin hashrate, difficulty and fee estimates"
     );
+    log::info!("  GET  /fx                         - Exchange rates against USD");
+    log::info!("  GET  /tvl/:protocol              - Total value locked of a protocol or chain");
+    log::info!("  GET  /stream                     - WebSocket stream of published prices");
+    log::info!("  GET  /symbols?asset_type=type    - List available symbols");
+    log::info!("  GET  /baskets                    - Configured baskets");
+    log::info!("  GET  /stats                      - Oracle statistics");
+    log::info!("  GET  /stats/sources              - Latency and error rates per source");
+    log::info!("  GET  /stats/anomalies            - Prices held back as anomalies");
+    log::info!("  GET  /stats/slo                  - Freshness SLO compliance");
+    log::info!("  GET  /stats/last-round           - Outcome of the last update round");
+    log::info!("  GET  /status                     - HTML status dashboard (admin, ?token=)");
+    log::info!("  GET  /status.json                - Component health and incidents (public)");
+    log::info!("  POST /admin/incidents            - Open an incident (admin)");
+    log::info!("  POST /admin/incidents/:id        - Update or resolve an incident (admin)");
+    log::info!("  GET  /metrics                    - Prometheus metrics");
+    log::info!("  GET  /debug/feeds                - In-memory state dump (admin)");
+    log::info!("  POST /admin/symbols/import       - Bulk-add symbols from CSV or JSON (admin)");
+    log::info!("  POST /admin/drain                - Stop updates and fail readiness (admin)");
+    log::info!("  POST /admin/baskets              - Define or replace a basket (admin)");
+    log::info!("  DELETE /admin/baskets/:name      - Remove a basket (admin)");
+    log::info!("  GET  /admin/updates              - Background update state (admin)");
+    log::info!("  POST /admin/updates/pause        - Pause background updates (admin)");
+    log::info!("  POST /admin/updates/resume       - Resume background updates (admin)");
+    log::info!("  POST /admin/updates/interval     - Change the update interval (admin)");
+    log::info!("  POST /admin/updates/run/:type/:symbol - Refresh one symbol now (admin)");
+    log::info!("  POST /grafana/query              - Grafana SimpleJSON datasource");
+    log::info!("  GET  /compat/coingecko/simple/price - CoinGecko-compatible prices");
+    log::info!("  GET  /compat/pyth/api/latest_price_feeds - Pyth-compatible prices");
+    log::info!("  POST /update/:type               - Force update prices (crypto, stock, all)");
     log::info!(
-        "  Example (curl): curl -H \"Authorization: Bearer <YOUR_TOKEN_HERE>\" http://localhost:3000/users/profile"
+        "  POST /update/:type/:symbol       - Force update one symbol (rate limited per token)"
     );
+    if pool.is_some() {
+        log::info!("  POST /users/register             - Register new user (public)");
+        log::info!("  POST /users/login                - User login (public)");
+        log::info!(
+            "  GET  /users/list                 - List all users (admin, requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /users/profile              - Get user profile (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /users/change-password      - Change password (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /users/change-email         - Change account email (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /users/delete               - Delete user account (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /users/billing              - Your plan and usage today (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /billing/stripe/webhook     - Stripe subscription events (Stripe-signed)"
+        );
+        log::info!(
+            "  GET  /users/tokens               - List your API tokens (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /users/tokens               - Create a new API token (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /users/tokens/revoke        - Revoke an API token (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /users/symbols              - Register a custom symbol (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /users/symbols              - List your custom symbols (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /users/symbols/prices       - Quotes for your custom symbols (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  DELETE /users/symbols/:id        - Remove a custom symbol (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /webhooks                   - Register a webhook (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /webhooks                   - List your webhooks (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  DELETE /webhooks/:id             - Delete a webhook (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /webhooks/:id/deliveries    - Webhook delivery log (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /reports                    - Schedule a daily/weekly summary (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /reports                    - List your scheduled reports (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  DELETE /reports/:id              - Cancel a scheduled report (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /watchlists                 - Create a watchlist (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /watchlists                 - List your watchlists (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  DELETE /watchlists/:id           - Delete a watchlist (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  POST /watchlists/:id/symbols     - Add symbols to a watchlist (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  DELETE /watchlists/:id/symbols/:symbol - Remove a symbol (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  GET  /watchlists/:id/prices      - Quotes for a watchlist (requires Authorization: Bearer <YOUR_TOKEN_HERE>)"
+        );
+        log::info!(
+            "  Example (curl): curl -H \"Authorization: Bearer <YOUR_TOKEN_HERE>\" http://localhost:3000/users/profile"
+        );
+    }
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await?;
 
-    axum::serve(listener, app).await?;
+    if let Some(monitor) = monitor {
+        monitor.abort();
+    }
+    if let Some(dispatcher) = dispatcher {
+        dispatcher.abort();
+    }
+    if let Some(scheduler) = scheduler {
+        scheduler.abort();
+    }
+    if let Some(biller) = biller {
+        biller.abort();
+    }
+    if let Some(incident_loader) = incident_loader {
+        incident_loader.abort();
+    }
+    if let Some(pool) = pool {
+        log::info!("API server stopped, closing database pool");
+        pool.close().await;
+    }
 
     Ok(())
 }
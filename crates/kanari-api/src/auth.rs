@@ -1,10 +1,290 @@
 use anyhow::anyhow;
 use chrono::{DateTime, Duration, Utc};
 use sqlx::Row;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+use crate::billing::Billing;
 use crate::database::DbPool;
+use crate::tenancy::Namespace;
+
+/// How API requests are authenticated, chosen with `kanari server --auth`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Tokens issued through the `/users` endpoints, stored in Postgres
+    #[default]
+    Database,
+    /// A fixed token list from `KANARI_API_TOKENS`; no database needed
+    Tokens,
+    /// Every request is accepted; no database needed
+    None,
+}
+
+impl AuthMode {
+    /// Name accepted by `--auth`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthMode::Database => "database",
+            AuthMode::Tokens => "tokens",
+            AuthMode::None => "none",
+        }
+    }
+}
+
+impl FromStr for AuthMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "database" => Ok(Self::Database),
+            "tokens" => Ok(Self::Tokens),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "unknown auth mode '{}', expected database, tokens or none",
+                s
+            )),
+        }
+    }
+}
+
+/// Token check used by the price endpoints
+#[derive(Clone)]
+pub enum Auth {
+    Database(TokenValidator),
+    /// Static tokens, each with the tenant it was listed for, if any
+    Tokens(Arc<HashMap<String, Option<String>>>),
+    Disabled,
+}
+
+impl Auth {
+    /// The namespace of symbols the token is served. `Err` carries the message
+    /// returned to the client.
+    pub async fn check(&self, token: Option<&str>) -> Result<Namespace, String> {
+        let namespace = match (self, token) {
+            (Auth::Disabled, _) => return Ok(Namespace::All),
+            (_, None) => return Err("Missing authentication token".to_string()),
+            (Auth::Database(validator), Some(token)) => match validator.validate(token).await {
+                Some(known) => {
+                    // Admins are never metered
+                    if let (Some(billing), false) = (&validator.billing, known.is_admin) {
+                        billing.admit(&known.owner)?;
+                    }
+                    Some(Namespace::User(known.owner))
+                }
+                None => None,
+            },
+            (Auth::Tokens(tokens), Some(token)) => tokens.get(token).map(|tenant| match tenant {
+                Some(tenant) => Namespace::Tenant(tenant.clone()),
+                None => Namespace::All,
+            }),
+        };
+        namespace.ok_or_else(|| "Invalid or expired token".to_string())
+    }
+
+    /// Like [`Auth::check`], and the caller must be an admin: the token is one of
+    /// `admin`'s, or with `--auth database` it belongs to a user with `is_admin`.
+    /// Static API tokens are never admins.
+    pub async fn check_admin(
+        &self,
+        admin: &AdminAccess,
+        token: Option<&str>,
+    ) -> Result<(), String> {
+        if admin.allows(token) {
+            return Ok(());
+        }
+        self.check(token).await?;
+        match (self, token) {
+            (Auth::Database(validator), Some(token)) if validator.is_admin(token).await => Ok(()),
+            _ => Err("Admin privileges required".to_string()),
+        }
+    }
+}
+
+/// Credentials for the admin endpoints, separate from the price endpoint tokens
+#[derive(Debug, Clone, Default)]
+pub struct AdminAccess {
+    tokens: Arc<HashSet<String>>,
+    /// Every caller is an admin; only allowed with `--auth none`
+    anonymous: bool,
+}
+
+impl AdminAccess {
+    /// Read admin tokens from KANARI_ADMIN_TOKENS (comma-separated) or the file named
+    /// by KANARI_ADMIN_TOKENS_FILE (one token per line). Without `--auth` there is no
+    /// caller to check, so `--auth none` only opens the admin endpoints to everyone
+    /// when KANARI_ANONYMOUS_ADMIN=true.
+    pub fn from_env(auth_mode: AuthMode) -> anyhow::Result<Self> {
+        let list = match std::env::var("KANARI_ADMIN_TOKENS") {
+            Ok(list) => list,
+            Err(_) => match std::env::var("KANARI_ADMIN_TOKENS_FILE") {
+                Ok(path) => std::fs::read_to_string(&path).map_err(|e| {
+                    anyhow!("Failed to read KANARI_ADMIN_TOKENS_FILE '{}': {}", path, e)
+                })?,
+                Err(_) => String::new(),
+            },
+        };
+        let tokens: HashSet<String> = list
+            .split([',', '\n'])
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let anonymous = match std::env::var("KANARI_ANONYMOUS_ADMIN") {
+            Ok(value) => value.trim().parse::<bool>().map_err(|_| {
+                anyhow!(
+                    "KANARI_ANONYMOUS_ADMIN must be true or false, got '{}'",
+                    value
+                )
+            })?,
+            Err(_) => false,
+        };
+        if anonymous && auth_mode != AuthMode::None {
+            return Err(anyhow!(
+                "KANARI_ANONYMOUS_ADMIN only applies to --auth none"
+            ));
+        }
+
+        Ok(Self {
+            tokens: Arc::new(tokens),
+            anonymous,
+        })
+    }
+
+    fn allows(&self, token: Option<&str>) -> bool {
+        self.anonymous || token.is_some_and(|token| self.tokens.contains(token))
+    }
+}
+
+/// A validated token
+#[derive(Debug, Clone)]
+pub struct KnownToken {
+    pub expires_at: DateTime<Utc>,
+    pub owner: String,
+    pub is_admin: bool,
+}
+
+/// Database token lookups that remember each valid token's expiry and owner, so
+/// tokens seen before keep working while Postgres is unreachable. With billing,
+/// the requests of non-admin owners are metered against their plan's quota.
+#[derive(Clone)]
+pub struct TokenValidator {
+    db: DbPool,
+    billing: Option<Billing>,
+    known: Arc<Mutex<HashMap<String, KnownToken>>>,
+}
+
+impl TokenValidator {
+    pub fn new(db: DbPool, billing: Option<Billing>) -> Self {
+        Self {
+            db,
+            billing,
+            known: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Owner and admin flag of the token, or None when it is unknown or expired
+    pub async fn validate(&self, token: &str) -> Option<KnownToken> {
+        let lookup = sqlx::query(
+            "SELECT api_tokens.expires_at, api_tokens.owner, users.is_admin FROM api_tokens \
+             JOIN users ON users.username = api_tokens.owner \
+             WHERE api_tokens.token = $1",
+        )
+        .bind(token)
+        .fetch_optional(&self.db)
+        .await;
+        let now = Utc::now();
+        let mut known = self.known.lock().unwrap();
+
+        match lookup {
+            Ok(Some(row)) => match (
+                row.try_get::<DateTime<Utc>, _>("expires_at"),
+                row.try_get::<String, _>("owner"),
+                row.try_get::<bool, _>("is_admin"),
+            ) {
+                (Ok(expires_at), Ok(owner), Ok(is_admin)) if expires_at > now => {
+                    let token_info = KnownToken {
+                        expires_at,
+                        owner,
+                        is_admin,
+                    };
+                    if !known.contains_key(token) {
+                        known.retain(|_, cached| cached.expires_at > now);
+                    }
+                    known.insert(token.to_string(), token_info.clone());
+                    Some(token_info)
+                }
+                _ => {
+                    known.remove(token);
+                    None
+                }
+            },
+            // Revoked or never issued
+            Ok(None) => {
+                known.remove(token);
+                None
+            }
+            Err(e) => {
+                log::warn!("Token lookup failed, using cached tokens: {}", e);
+                known
+                    .get(token)
+                    .filter(|cached| cached.expires_at > now)
+                    .cloned()
+            }
+        }
+    }
+
+    /// Whether the token's owner is an admin; false when the database is down
+    pub async fn is_admin(&self, token: &str) -> bool {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT users.is_admin FROM api_tokens \
+             JOIN users ON users.username = api_tokens.owner \
+             WHERE api_tokens.token = $1",
+        )
+        .bind(token)
+        .fetch_optional(&self.db)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+    }
+}
+
+// Read the static token list from KANARI_API_TOKENS (comma-separated), or from the
+// file named by KANARI_API_TOKENS_FILE (one token per line). A token written
+// `<tenant>:<token>` belongs to that tenant.
+pub fn static_tokens() -> anyhow::Result<HashMap<String, Option<String>>> {
+    let list = match std::env::var("KANARI_API_TOKENS") {
+        Ok(list) => list,
+        Err(_) => match std::env::var("KANARI_API_TOKENS_FILE") {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read KANARI_API_TOKENS_FILE '{}': {}", path, e))?,
+            Err(_) => {
+                return Err(anyhow!(
+                    "KANARI_API_TOKENS or KANARI_API_TOKENS_FILE must be set for --auth tokens"
+                ));
+            }
+        },
+    };
+
+    let tokens: HashMap<String, Option<String>> = list
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(|entry| match entry.split_once(':') {
+            Some((tenant, token)) => (token.trim(), Some(tenant.trim().to_string())),
+            None => (entry, None),
+        })
+        .filter(|(token, _)| !token.is_empty())
+        .map(|(token, tenant)| (token.to_string(), tenant))
+        .collect();
+    if tokens.is_empty() {
+        return Err(anyhow!("The static API token list is empty"));
+    }
+    Ok(tokens)
+}
 
 // Validate a token exists and is not expired
 pub async fn validate_token(db: &DbPool, token: &str) -> bool {
@@ -24,6 +304,18 @@ pub async fn validate_token(db: &DbPool, token: &str) -> bool {
     }
 }
 
+// Username owning a token, if the token exists and has not expired
+pub async fn token_owner(db: &DbPool, token: &str) -> Option<String> {
+    match sqlx::query("SELECT owner FROM api_tokens WHERE token = $1 AND expires_at > NOW()")
+        .bind(token)
+        .fetch_optional(db)
+        .await
+    {
+        Ok(Some(row)) => row.try_get("owner").ok(),
+        _ => None,
+    }
+}
+
 // Extract token from Authorization header or query parameter
 pub fn extract_token_from_request(
     headers: &axum::http::HeaderMap,
@@ -57,3 +349,55 @@ pub async fn create_monthly_token(db: &DbPool, owner: &str) -> anyhow::Result<St
 
     Ok(token)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn static_auth(tokens: &[&str]) -> Auth {
+        Auth::Tokens(Arc::new(
+            tokens.iter().map(|t| (t.to_string(), None)).collect(),
+        ))
+    }
+
+    fn admin_tokens(tokens: &[&str]) -> AdminAccess {
+        AdminAccess {
+            tokens: Arc::new(tokens.iter().map(|t| t.to_string()).collect()),
+            anonymous: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn static_api_tokens_are_not_admins() {
+        let auth = static_auth(&["token-a"]);
+        let admin = admin_tokens(&["ops-secret"]);
+        assert!(auth.check(Some("token-a")).await.is_ok());
+        assert_eq!(
+            auth.check_admin(&admin, Some("token-a")).await,
+            Err("Admin privileges required".to_string())
+        );
+        assert!(auth.check_admin(&admin, Some("ops-secret")).await.is_ok());
+        assert!(auth.check_admin(&admin, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn disabled_auth_needs_an_opt_in_for_anonymous_admins() {
+        let auth = Auth::Disabled;
+        assert!(
+            auth.check_admin(&AdminAccess::default(), None)
+                .await
+                .is_err()
+        );
+        assert!(
+            auth.check_admin(&admin_tokens(&["ops-secret"]), Some("ops-secret"))
+                .await
+                .is_ok()
+        );
+
+        let anonymous = AdminAccess {
+            anonymous: true,
+            ..AdminAccess::default()
+        };
+        assert!(auth.check_admin(&anonymous, None).await.is_ok());
+    }
+}
@@ -0,0 +1,459 @@
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::database::{DbHealth, DbPool};
+
+/// Header carrying `t=<unix seconds>,v1=<hex HMAC-SHA256>` on Stripe webhooks
+pub const STRIPE_SIGNATURE_HEADER: &str = "Stripe-Signature";
+
+/// Requests per UTC day of users without an active subscription when
+/// KANARI_BILLING_FREE_REQUESTS is not set
+pub const DEFAULT_FREE_REQUESTS: u64 = 1000;
+
+/// Webhooks signed longer ago than this are refused as replays
+const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// How often subscriptions are reloaded, so webhooks received by another
+/// replica are picked up
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Subscriptions keep their plan this long past the end of their period, in
+/// case the webhook renewing them is late
+const PERIOD_GRACE_DAYS: i64 = 3;
+
+/// Stripe subscription statuses that keep a plan's quota. `past_due` is
+/// included so a failed payment doesn't cut access while Stripe retries it.
+const ACTIVE_STATUSES: [&str; 3] = ["active", "trialing", "past_due"];
+
+/// A paid plan, from KANARI_BILLING_PLANS
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub name: String,
+    /// Requests per UTC day; None is unlimited
+    pub daily_requests: Option<u64>,
+}
+
+/// A row of the `subscriptions` table
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub price_id: String,
+    /// Stripe status, e.g. "active" or "canceled"
+    pub status: String,
+    pub current_period_end: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    /// Whether the subscription still pays for its plan
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        ACTIVE_STATUSES.contains(&self.status.as_str())
+            && self
+                .current_period_end
+                .is_none_or(|end| end + chrono::Duration::days(PERIOD_GRACE_DAYS) > now)
+    }
+}
+
+/// A user's plan and usage as served at `/users/billing`
+#[derive(Debug, Clone)]
+pub struct BillingStatus {
+    /// None on the free quota
+    pub plan: Option<String>,
+    pub subscription: Option<Subscription>,
+    /// None is unlimited
+    pub daily_quota: Option<u64>,
+    pub used_today: u64,
+}
+
+/// The parts of a Stripe event the subscription webhooks are read from
+#[derive(Debug, Deserialize)]
+pub struct StripeEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Unix seconds the event was created at; orders deliveries
+    pub created: i64,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeEventData {
+    pub object: serde_json::Value,
+}
+
+/// Subscription state carried by a `customer.subscription.*` event
+#[derive(Debug, Clone)]
+pub struct SubscriptionEvent {
+    pub stripe_subscription: String,
+    pub stripe_customer: String,
+    /// `metadata.username` of the subscription, when checkout set it
+    pub username: Option<String>,
+    pub price_id: String,
+    pub status: String,
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub event_at: DateTime<Utc>,
+}
+
+impl StripeEvent {
+    /// The subscription state of a `customer.subscription.created`, `.updated`
+    /// or `.deleted` event; None for any other event
+    pub fn subscription(&self) -> Option<SubscriptionEvent> {
+        let status = match self.kind.as_str() {
+            "customer.subscription.created" | "customer.subscription.updated" => {
+                self.data.object["status"].as_str()?.to_string()
+            }
+            "customer.subscription.deleted" => "canceled".to_string(),
+            _ => return None,
+        };
+        let object = &self.data.object;
+        let item = &object["items"]["data"][0];
+        // Newer API versions moved the period end onto the subscription items
+        let current_period_end = object["current_period_end"]
+            .as_i64()
+            .or_else(|| item["current_period_end"].as_i64())
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+        Some(SubscriptionEvent {
+            stripe_subscription: object["id"].as_str()?.to_string(),
+            stripe_customer: object["customer"].as_str()?.to_string(),
+            username: object["metadata"]["username"]
+                .as_str()
+                .map(str::trim)
+                .filter(|username| !username.is_empty())
+                .map(str::to_string),
+            price_id: item["price"]["id"].as_str()?.to_string(),
+            status,
+            current_period_end,
+            event_at: Utc.timestamp_opt(self.created, 0).single()?,
+        })
+    }
+}
+
+/// Optional Stripe integration: maps Stripe price IDs to plans with a daily
+/// request quota, keeps subscriptions current from Stripe's webhooks, and meters
+/// the price requests of database tokens. Users without an active subscription
+/// get the free quota, so a subscription that lapses falls back to it.
+///
+/// Usage is counted in memory by each instance, so replicas each allow the
+/// quota.
+#[derive(Clone)]
+pub struct Billing {
+    db: DbPool,
+    webhook_secret: Arc<String>,
+    /// Plans keyed by Stripe price ID
+    plans: Arc<HashMap<String, Plan>>,
+    free_requests: u64,
+    /// Subscriptions keyed by username
+    subscriptions: Arc<RwLock<HashMap<String, Subscription>>>,
+    /// Requests each user made on the given UTC day
+    usage: Arc<Mutex<HashMap<String, (NaiveDate, u64)>>>,
+}
+
+impl Billing {
+    /// Billing from the environment; None unless STRIPE_WEBHOOK_SECRET, or the
+    /// file named by STRIPE_WEBHOOK_SECRET_FILE, is set. KANARI_BILLING_PLANS
+    /// lists plans as `<price id>=<name>:<daily requests or unlimited>`,
+    /// comma-separated, and KANARI_BILLING_FREE_REQUESTS sets the free quota.
+    pub fn from_env(db: DbPool) -> anyhow::Result<Option<Self>> {
+        let secret = match std::env::var("STRIPE_WEBHOOK_SECRET") {
+            Ok(secret) => secret,
+            Err(_) => match std::env::var("STRIPE_WEBHOOK_SECRET_FILE") {
+                Ok(path) => std::fs::read_to_string(&path).map_err(|e| {
+                    anyhow!(
+                        "Failed to read STRIPE_WEBHOOK_SECRET_FILE '{}': {}",
+                        path,
+                        e
+                    )
+                })?,
+                Err(_) => return Ok(None),
+            },
+        };
+        let secret = secret.trim().to_string();
+        if secret.is_empty() {
+            return Err(anyhow!("The Stripe webhook secret is empty"));
+        }
+
+        let plans = parse_plans(&std::env::var("KANARI_BILLING_PLANS").unwrap_or_default())?;
+        if plans.is_empty() {
+            return Err(anyhow!(
+                "KANARI_BILLING_PLANS must list at least one plan when Stripe billing is enabled"
+            ));
+        }
+        let free_requests = match std::env::var("KANARI_BILLING_FREE_REQUESTS") {
+            Ok(value) => value.trim().parse().map_err(|_| {
+                anyhow!(
+                    "KANARI_BILLING_FREE_REQUESTS must be a number of requests, got '{}'",
+                    value
+                )
+            })?,
+            Err(_) => DEFAULT_FREE_REQUESTS,
+        };
+
+        Ok(Some(Self {
+            db,
+            webhook_secret: Arc::new(secret),
+            plans: Arc::new(plans),
+            free_requests,
+            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            usage: Arc::new(Mutex::new(HashMap::new())),
+        }))
+    }
+
+    /// Number of configured plans
+    pub fn plan_count(&self) -> usize {
+        self.plans.len()
+    }
+
+    /// Requests per UTC day without an active subscription
+    pub fn free_requests(&self) -> u64 {
+        self.free_requests
+    }
+
+    /// Read the subscriptions from the database
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let rows =
+            sqlx::query("SELECT owner, price_id, status, current_period_end FROM subscriptions")
+                .fetch_all(&self.db)
+                .await?;
+
+        let mut subscriptions = HashMap::with_capacity(rows.len());
+        for row in &rows {
+            subscriptions.insert(
+                row.try_get("owner")?,
+                Subscription {
+                    price_id: row.try_get("price_id")?,
+                    status: row.try_get("status")?,
+                    current_period_end: row.try_get("current_period_end")?,
+                },
+            );
+        }
+        *self.subscriptions.write().unwrap() = subscriptions;
+
+        let today = Utc::now().date_naive();
+        self.usage
+            .lock()
+            .unwrap()
+            .retain(|_, (day, _)| *day == today);
+        Ok(())
+    }
+
+    /// Reload subscriptions every [`RELOAD_INTERVAL`] while the database is up,
+    /// until the task is aborted
+    pub fn spawn(self, health: DbHealth) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if health.is_up()
+                    && let Err(e) = self.reload().await
+                {
+                    log::warn!("Failed to reload subscriptions: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Plan paid for by the user's subscription, if it is active
+    fn active_plan(&self, owner: &str, now: DateTime<Utc>) -> Option<(&Plan, Subscription)> {
+        let subscription = self
+            .subscriptions
+            .read()
+            .unwrap()
+            .get(owner)
+            .filter(|subscription| subscription.is_active(now))
+            .cloned()?;
+        let plan = self.plans.get(&subscription.price_id)?;
+        Some((plan, subscription))
+    }
+
+    /// Requests per UTC day the user may make; None is unlimited
+    fn daily_quota(&self, owner: &str, now: DateTime<Utc>) -> Option<u64> {
+        match self.active_plan(owner, now) {
+            Some((plan, _)) => plan.daily_requests,
+            None => Some(self.free_requests),
+        }
+    }
+
+    /// Count a request of `owner`. `Err` carries the message returned to the
+    /// client once the day's quota is used up.
+    pub fn admit(&self, owner: &str) -> Result<(), String> {
+        let now = Utc::now();
+        let today = now.date_naive();
+        let quota = self.daily_quota(owner, now);
+
+        let mut usage = self.usage.lock().unwrap();
+        let (day, used) = usage.entry(owner.to_string()).or_insert((today, 0));
+        if *day != today {
+            *day = today;
+            *used = 0;
+        }
+        if let Some(quota) = quota
+            && *used >= quota
+        {
+            return Err(format!(
+                "Daily request quota of {} exhausted; it resets at 00:00 UTC",
+                quota
+            ));
+        }
+        *used += 1;
+        Ok(())
+    }
+
+    /// The user's plan, subscription and usage today
+    pub fn status(&self, owner: &str) -> BillingStatus {
+        let now = Utc::now();
+        let today = now.date_naive();
+        let used_today = match self.usage.lock().unwrap().get(owner) {
+            Some((day, used)) if *day == today => *used,
+            _ => 0,
+        };
+        BillingStatus {
+            plan: self
+                .active_plan(owner, now)
+                .map(|(plan, _)| plan.name.clone()),
+            subscription: self.subscriptions.read().unwrap().get(owner).cloned(),
+            daily_quota: self.daily_quota(owner, now),
+            used_today,
+        }
+    }
+
+    /// Check the `Stripe-Signature` header of a webhook body against the
+    /// webhook secret. `Err` says why the webhook is refused.
+    pub fn verify(&self, header: &str, body: &[u8]) -> Result<(), String> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signatures.extend(hex::decode(value).ok()),
+                _ => {}
+            }
+        }
+        let timestamp = timestamp.ok_or_else(|| "Missing signature timestamp".to_string())?;
+        if (Utc::now().timestamp() - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+            return Err("Signature timestamp outside the tolerance".to_string());
+        }
+
+        let mut signed = format!("{}.", timestamp).into_bytes();
+        signed.extend_from_slice(body);
+        let valid = signatures.iter().any(|signature| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(self.webhook_secret.as_bytes())
+                .expect("HMAC takes keys of any length");
+            mac.update(&signed);
+            mac.verify_slice(signature).is_ok()
+        });
+        if valid {
+            Ok(())
+        } else {
+            Err("No valid signature".to_string())
+        }
+    }
+
+    /// Store the subscription state of a webhook, unless a later event was
+    /// already stored. Returns the user it belongs to, or None when the user is
+    /// not known here.
+    pub async fn record(&self, event: &SubscriptionEvent) -> anyhow::Result<Option<String>> {
+        let owner = match &event.username {
+            Some(username) => Some(username.clone()),
+            None => {
+                sqlx::query_scalar("SELECT owner FROM subscriptions WHERE stripe_customer = $1")
+                    .bind(&event.stripe_customer)
+                    .fetch_optional(&self.db)
+                    .await?
+            }
+        };
+        let Some(owner) = owner else {
+            return Ok(None);
+        };
+
+        let stored = sqlx::query(
+            "INSERT INTO subscriptions \
+             (owner, stripe_customer, stripe_subscription, price_id, status, current_period_end, event_at) \
+             SELECT $1, $2, $3, $4, $5, $6, $7 WHERE EXISTS (SELECT 1 FROM users WHERE username = $1) \
+             ON CONFLICT (owner) DO UPDATE SET \
+             stripe_customer = EXCLUDED.stripe_customer, \
+             stripe_subscription = EXCLUDED.stripe_subscription, \
+             price_id = EXCLUDED.price_id, status = EXCLUDED.status, \
+             current_period_end = EXCLUDED.current_period_end, \
+             event_at = EXCLUDED.event_at, updated_at = NOW() \
+             WHERE subscriptions.event_at <= EXCLUDED.event_at",
+        )
+        .bind(&owner)
+        .bind(&event.stripe_customer)
+        .bind(&event.stripe_subscription)
+        .bind(&event.price_id)
+        .bind(&event.status)
+        .bind(event.current_period_end)
+        .bind(event.event_at)
+        .execute(&self.db)
+        .await?;
+
+        if stored.rows_affected() > 0 {
+            if !self.plans.contains_key(&event.price_id) {
+                log::warn!(
+                    "Subscription of '{}' is for price {}, which KANARI_BILLING_PLANS does not list",
+                    owner,
+                    event.price_id
+                );
+            }
+            self.subscriptions.write().unwrap().insert(
+                owner.clone(),
+                Subscription {
+                    price_id: event.price_id.clone(),
+                    status: event.status.clone(),
+                    current_period_end: event.current_period_end,
+                },
+            );
+        } else {
+            // The user doesn't exist, or the event is older than the stored one
+            let known: bool =
+                sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE username = $1)")
+                    .bind(&owner)
+                    .fetch_one(&self.db)
+                    .await?;
+            if !known {
+                return Ok(None);
+            }
+        }
+        Ok(Some(owner))
+    }
+}
+
+/// Plans written `<price id>=<name>:<daily requests or unlimited>`
+fn parse_plans(list: &str) -> anyhow::Result<HashMap<String, Plan>> {
+    let mut plans = HashMap::new();
+    for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let invalid = || {
+            anyhow!(
+                "Invalid KANARI_BILLING_PLANS entry '{}', expected <price id>=<name>:<daily requests or unlimited>",
+                entry
+            )
+        };
+        let (price_id, plan) = entry.split_once('=').ok_or_else(invalid)?;
+        let (name, quota) = plan.split_once(':').ok_or_else(invalid)?;
+        let (price_id, name, quota) = (price_id.trim(), name.trim(), quota.trim());
+        if price_id.is_empty() || name.is_empty() {
+            return Err(invalid());
+        }
+        let daily_requests = match quota {
+            "unlimited" => None,
+            quota => Some(quota.parse::<u64>().map_err(|_| invalid())?),
+        };
+        let plan = Plan {
+            name: name.to_string(),
+            daily_requests,
+        };
+        if plans.insert(price_id.to_string(), plan).is_some() {
+            return Err(anyhow!(
+                "KANARI_BILLING_PLANS lists price {} twice",
+                price_id
+            ));
+        }
+    }
+    Ok(plans)
+}
@@ -0,0 +1,65 @@
+use axum::body::Bytes;
+use kanari_oracle::oracle::Snapshot;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A serialized body is rebuilt at least this often so `age_seconds` stays accurate
+const MAX_BODY_AGE: Duration = Duration::from_secs(1);
+
+/// Serialized response body with its `Last-Modified` header value
+#[derive(Clone)]
+pub struct CachedBody {
+    pub body: Bytes,
+    /// Round the body was built from
+    pub round_id: u64,
+    pub last_modified: String,
+}
+
+struct Entry {
+    snapshot: Snapshot,
+    built: Instant,
+    cached: CachedBody,
+}
+
+/// Serialized `/prices/{asset_type}` payloads, rebuilt only when the oracle
+/// publishes a new feed snapshot
+#[derive(Clone, Default)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl ResponseCache {
+    /// Cached body for `key` if it was built from `snapshot`, otherwise the result
+    /// of `build`, which is stored for the next request
+    pub fn get_or_build<E>(
+        &self,
+        key: &str,
+        snapshot: &Snapshot,
+        build: impl FnOnce() -> Result<CachedBody, E>,
+    ) -> Result<CachedBody, E> {
+        let hit = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|entry| {
+                Arc::ptr_eq(&entry.snapshot, snapshot) && entry.built.elapsed() < MAX_BODY_AGE
+            })
+            .map(|entry| entry.cached.clone());
+        if let Some(cached) = hit {
+            return Ok(cached);
+        }
+
+        let cached = build()?;
+        self.entries.lock().unwrap().insert(
+            key.to_string(),
+            Entry {
+                snapshot: snapshot.clone(),
+                built: Instant::now(),
+                cached: cached.clone(),
+            },
+        );
+        Ok(cached)
+    }
+}
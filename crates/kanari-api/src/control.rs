@@ -0,0 +1,56 @@
+use tokio::sync::watch;
+
+/// Runtime control of background updates through the `/admin/updates`
+/// endpoints: pausing rounds and overriding the update interval without a
+/// restart. Shared between the API and the process running the updater.
+#[derive(Clone)]
+pub struct UpdateControl {
+    paused: watch::Sender<bool>,
+    /// Interval in seconds set at runtime; takes precedence over the config and
+    /// the command line until cleared or the process restarts
+    interval: watch::Sender<Option<u64>>,
+}
+
+impl Default for UpdateControl {
+    fn default() -> Self {
+        Self {
+            paused: watch::Sender::new(false),
+            interval: watch::Sender::new(None),
+        }
+    }
+}
+
+impl UpdateControl {
+    /// Skip background rounds until resumed; returns false if already paused
+    pub fn pause(&self) -> bool {
+        !self.paused.send_replace(true)
+    }
+
+    /// Resume background rounds; returns false if they were not paused
+    pub fn resume(&self) -> bool {
+        self.paused.send_replace(false)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Override the update interval, or go back to the configured one with None
+    pub fn set_interval(&self, seconds: Option<u64>) {
+        self.interval.send_replace(seconds);
+    }
+
+    pub fn interval(&self) -> Option<u64> {
+        *self.interval.borrow()
+    }
+
+    /// Receiver notified when rounds are paused or resumed
+    pub fn subscribe_paused(&self) -> watch::Receiver<bool> {
+        self.paused.subscribe()
+    }
+
+    /// Receiver notified when the interval override changes
+    pub fn subscribe_interval(&self) -> watch::Receiver<Option<u64>> {
+        self.interval.subscribe()
+    }
+}
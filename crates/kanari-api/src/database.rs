@@ -1,52 +1,213 @@
 use anyhow::anyhow;
-use sqlx::PgPool;
+use kanari_oracle::config::{DatabaseConfig, HaConfig};
 use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 
 pub type DbPool = PgPool;
 
-// Initialize database tables if they don't exist
-pub async fn initialize_database(pool: &DbPool) -> anyhow::Result<()> {
-    // Create users table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id SERIAL PRIMARY KEY,
-            username VARCHAR(255) UNIQUE NOT NULL,
-            password_hash VARCHAR(255) NOT NULL,
-            email VARCHAR(255),
-            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create api_tokens table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS api_tokens (
-            id SERIAL PRIMARY KEY,
-            token VARCHAR(255) UNIQUE NOT NULL,
-            owner VARCHAR(255) NOT NULL,
-            expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
-            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-            FOREIGN KEY (owner) REFERENCES users(username) ON DELETE CASCADE
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    log::info!("Database tables created/verified: users, api_tokens");
+/// How often the database is pinged to detect outages and recoveries
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// Apply the migrations in `migrations/` that the database hasn't seen yet
+pub async fn run_migrations(pool: &DbPool) -> anyhow::Result<()> {
+    sqlx::migrate!("./migrations").run(pool).await?;
+    log::info!("Database schema is up to date");
     Ok(())
 }
 
-pub async fn create_db_pool() -> anyhow::Result<DbPool> {
-    let database_url =
-        std::env::var("DATABASE_URL").map_err(|_| anyhow!("DATABASE_URL must be set"))?;
+// Read DATABASE_URL, or the file named by DATABASE_URL_FILE (e.g. a Docker secret),
+// or else `database.url` of the config. Also returns where the URL came from.
+pub fn database_url(config: &DatabaseConfig) -> anyhow::Result<(String, &'static str)> {
+    if let Ok(url) = std::env::var("DATABASE_URL") {
+        return Ok((url, "DATABASE_URL"));
+    }
+    if let Ok(path) = std::env::var("DATABASE_URL_FILE") {
+        return std::fs::read_to_string(&path)
+            .map(|url| (url.trim().to_string(), "DATABASE_URL_FILE"))
+            .map_err(|e| anyhow!("Failed to read DATABASE_URL_FILE '{}': {}", path, e));
+    }
+
+    match &config.url {
+        Some(url) => Ok((url.clone(), "database.url of the config")),
+        None => Err(anyhow!(
+            "No database configured; set database.url in the config, or DATABASE_URL or DATABASE_URL_FILE"
+        )),
+    }
+}
+
+// Host, port and database of `url` without its credentials, for messages
+fn describe_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => format!(
+            "{}:{}{}",
+            url.host_str().unwrap_or("localhost"),
+            url.port().unwrap_or(5432),
+            url.path()
+        ),
+        Err(_) => "an unparseable URL".to_string(),
+    }
+}
+
+// Read a positive number from an environment variable, falling back to `default`
+fn env_number(name: &str, default: u64) -> anyhow::Result<u64> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| anyhow!("{} must be a positive number, got '{}'", name, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+// Build a pool sized by `database.max_connections` whose requests give up after
+// `database.acquire_timeout` seconds, or DATABASE_MAX_CONNECTIONS and
+// DATABASE_ACQUIRE_TIMEOUT when set. Connections are opened on demand, so the
+// server starts, and recovers, while Postgres is unreachable.
+pub fn create_db_pool(config: &DatabaseConfig) -> anyhow::Result<DbPool> {
+    let (database_url, source) = database_url(config)?;
+    let max_connections = env_number("DATABASE_MAX_CONNECTIONS", config.max_connections as u64)?;
+    let acquire_timeout = env_number("DATABASE_ACQUIRE_TIMEOUT", config.acquire_timeout)?;
+
     let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
-        .await?;
+        .max_connections(max_connections as u32)
+        .acquire_timeout(Duration::from_secs(acquire_timeout))
+        .connect_lazy(&database_url)
+        .map_err(|e| anyhow!("Invalid database URL from {}: {}", source, e))?;
     Ok(pool)
 }
+
+// Run one query, failing with the database's host and where its URL came from
+pub async fn check_connection(pool: &DbPool, config: &DatabaseConfig) -> anyhow::Result<()> {
+    if let Err(e) = sqlx::query("SELECT 1").execute(pool).await {
+        let (url, source) = database_url(config)?;
+        return Err(anyhow!(
+            "Cannot reach Postgres at {} (from {}): {}",
+            describe_url(&url),
+            source,
+            e
+        ));
+    }
+    Ok(())
+}
+
+/// Whether Postgres answered the last health check. While it is down the API runs
+/// degraded: price endpoints keep working and `/users` returns 503.
+#[derive(Clone, Default)]
+pub struct DbHealth {
+    up: Arc<AtomicBool>,
+}
+
+impl DbHealth {
+    pub fn is_up(&self) -> bool {
+        self.up.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, up: bool) {
+        let was_up = self.up.swap(up, Ordering::Relaxed);
+        match (was_up, up) {
+            (false, true) => log::info!("Database available"),
+            (true, false) => log::warn!("Database unavailable, serving in degraded mode"),
+            _ => {}
+        }
+    }
+}
+
+// Apply migrations once the database is reachable, then ping it until the task
+// is aborted, keeping `health` current
+pub fn spawn_health_monitor(pool: DbPool, health: DbHealth) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut migrated = false;
+        let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let up = if migrated {
+                sqlx::query("SELECT 1").execute(&pool).await.is_ok()
+            } else {
+                match run_migrations(&pool).await {
+                    Ok(()) => {
+                        migrated = true;
+                        true
+                    }
+                    Err(e) => {
+                        log::warn!("Database migrations not applied yet: {}", e);
+                        false
+                    }
+                }
+            };
+            health.set(up);
+        }
+    })
+}
+
+// Compete for leadership through a Postgres advisory lock until the task is
+// aborted. The receiver is true while this instance holds the lock. The lock
+// lives on a connection taken out of the pool, so Postgres releases it as soon
+// as that connection drops, including when the process dies.
+pub fn spawn_leader_election(
+    pool: DbPool,
+    ha: &HaConfig,
+) -> (watch::Receiver<bool>, JoinHandle<()>) {
+    let (leader_tx, leader_rx) = watch::channel(false);
+    let lock_id = ha.lock_id;
+    let check_interval = Duration::from_secs(ha.check_interval);
+
+    let handle = tokio::spawn(async move {
+        let mut held: Option<PgConnection> = None;
+        let mut interval = tokio::time::interval(check_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match held.as_mut() {
+                Some(connection) => {
+                    // A hung connection may already have lost the lock to another
+                    // instance, so a check without an answer counts as a loss
+                    let check = sqlx::query("SELECT 1").execute(&mut *connection);
+                    let lost = match tokio::time::timeout(check_interval, check).await {
+                        Ok(Ok(_)) => None,
+                        Ok(Err(e)) => Some(e.to_string()),
+                        Err(_) => Some(format!("no answer within {:?}", check_interval)),
+                    };
+                    if let Some(reason) = lost {
+                        log::warn!("Lost the leader lock connection, stepping down: {}", reason);
+                        held = None;
+                        leader_tx.send_replace(false);
+                    }
+                }
+                None => {
+                    let attempt = try_lock(&pool, lock_id);
+                    match tokio::time::timeout(check_interval, attempt).await {
+                        Ok(Ok(Some(connection))) => {
+                            log::info!("Elected leader (advisory lock {})", lock_id);
+                            held = Some(connection);
+                            leader_tx.send_replace(true);
+                        }
+                        Ok(Ok(None)) => {}
+                        Ok(Err(e)) => log::warn!("Leader election failed: {}", e),
+                        Err(_) => log::warn!("Leader election timed out"),
+                    }
+                }
+            }
+        }
+    });
+    (leader_rx, handle)
+}
+
+// Take the advisory lock on a connection of its own, or None if another
+// instance holds it. The connection leaves the pool before locking, so a lock
+// taken by an attempt cut short by the timeout goes away with the connection
+// instead of staying on a pooled one.
+async fn try_lock(pool: &DbPool, lock_id: i64) -> sqlx::Result<Option<PgConnection>> {
+    let mut connection = pool.acquire().await?.detach();
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(lock_id)
+        .fetch_one(&mut connection)
+        .await?;
+    Ok(locked.then_some(connection))
+}
@@ -0,0 +1,33 @@
+use tokio::sync::watch;
+
+/// Whether the server is draining: background updates stop and `/ready` fails,
+/// while in-flight and new requests are still answered until shutdown. Shared
+/// between the API (`POST /admin/drain`) and the process running the updater.
+#[derive(Clone)]
+pub struct Drain {
+    draining: watch::Sender<bool>,
+}
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self {
+            draining: watch::Sender::new(false),
+        }
+    }
+}
+
+impl Drain {
+    /// Start draining; returns false if the server was already draining
+    pub fn start(&self) -> bool {
+        !self.draining.send_replace(true)
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.draining.borrow()
+    }
+
+    /// Receiver flipped to true once draining starts
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.draining.subscribe()
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    response::{IntoResponse, Json, Response},
+};
+use kanari_oracle::errors::OracleError;
+
+use crate::models::ApiResponse;
+
+/// An oracle error rendered as an `ApiResponse` with a matching HTTP status
+pub struct OracleApiError(pub OracleError);
+
+impl From<OracleError> for OracleApiError {
+    fn from(e: OracleError) -> Self {
+        Self(e)
+    }
+}
+
+/// HTTP status and machine-readable code for an oracle error
+pub fn classify(e: &OracleError) -> (StatusCode, &'static str) {
+    match e {
+        OracleError::PriceNotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+        OracleError::InvalidSymbol(_) => (StatusCode::NOT_FOUND, "invalid_symbol"),
+        OracleError::AmbiguousSymbol(_) => (StatusCode::CONFLICT, "ambiguous_symbol"),
+        OracleError::UnknownSource { .. } => (StatusCode::BAD_REQUEST, "unknown_source"),
+        OracleError::UnknownCurrency(_) => (StatusCode::BAD_REQUEST, "unknown_currency"),
+        OracleError::RateLimited { .. } => (StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+        OracleError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "timeout"),
+        OracleError::SourceUnavailable(_) => {
+            (StatusCode::SERVICE_UNAVAILABLE, "source_unavailable")
+        }
+        OracleError::StaleData { .. } => (StatusCode::SERVICE_UNAVAILABLE, "stale_data"),
+        OracleError::AnomalousPrice { .. } => (StatusCode::SERVICE_UNAVAILABLE, "anomalous_price"),
+        OracleError::StoreError(_) => (StatusCode::SERVICE_UNAVAILABLE, "store_unavailable"),
+        OracleError::HttpError(_)
+        | OracleError::HttpStatus { .. }
+        | OracleError::ApiError(_)
+        | OracleError::JsonError(_) => (StatusCode::BAD_GATEWAY, "upstream_error"),
+        OracleError::ConfigError(_)
+        | OracleError::IoError(_)
+        | OracleError::IoOperationFailed(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+        }
+    }
+}
+
+impl IntoResponse for OracleApiError {
+    fn into_response(self) -> Response {
+        let (status, code) = classify(&self.0);
+        let body = ApiResponse::<()>::error_with_code(self.0.to_string(), code);
+        let mut response = (status, Json(body)).into_response();
+
+        let retry_after = self
+            .0
+            .retry_after()
+            .and_then(|d| HeaderValue::from_str(&d.as_secs().max(1).to_string()).ok());
+        if let Some(value) = retry_after {
+            response.headers_mut().insert(RETRY_AFTER, value);
+        }
+
+        response
+    }
+}
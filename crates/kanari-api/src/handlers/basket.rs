@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use kanari_oracle::config::{Basket, Config, write_baskets_to_file};
+use kanari_oracle::models::AssetType;
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::models::{ApiResponse, BasketUpdateResponse};
+
+// Apply `change` to the current config under the write lock, so a reload that
+// ran meanwhile is not undone. The baskets are written to the config file when
+// the server has one. Returns every basket after the change and whether the
+// file was written.
+async fn change_baskets(
+    state: &AppState,
+    change: impl FnOnce(&mut Config) -> Result<(), String>,
+) -> Result<BasketUpdateResponse, String> {
+    let mut oracle_lock = state.oracle.write().await;
+    let mut config = oracle_lock.config().clone();
+    change(&mut config)?;
+    config
+        .validate()
+        .map_err(|e| format!("Basket would make the config invalid: {}", e))?;
+
+    // Written first so the file watcher does not undo the change
+    let mut persisted = false;
+    if let Some(path) = &state.config_path {
+        write_baskets_to_file(path, &config.baskets)
+            .await
+            .map_err(|e| format!("Failed to update config file: {}", e))?;
+        persisted = true;
+    }
+    let baskets = config.baskets.clone();
+    oracle_lock
+        .reload_config(config)
+        .map_err(|e| format!("Failed to apply baskets: {}", e))?;
+    Ok(BasketUpdateResponse { baskets, persisted })
+}
+
+// List the configured baskets and their components
+pub async fn list_baskets(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<Basket>>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+    let oracle_lock = state.oracle.read().await;
+    let config = oracle_lock.config();
+    let baskets = config
+        .baskets
+        .iter()
+        .filter(|basket| namespace.allows(config, AssetType::Basket, &basket.name))
+        .cloned()
+        .collect();
+    Json(ApiResponse::success(baskets))
+}
+
+// Define a basket, or replace the basket of the same name (admin only). It is
+// published from the next update round on.
+pub async fn save_basket(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(basket): Json<Basket>,
+) -> Json<ApiResponse<BasketUpdateResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    let name = basket.name.clone();
+    let result = change_baskets(&state, |config| {
+        config.set_basket(basket);
+        Ok(())
+    })
+    .await;
+
+    match result {
+        Ok(response) => {
+            log::info!("Basket '{}' saved", name);
+            Json(ApiResponse::success(response))
+        }
+        Err(message) => Json(ApiResponse::error(message)),
+    }
+}
+
+// Remove a basket (admin only). Its price history is dropped.
+pub async fn delete_basket(
+    Path(name): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<BasketUpdateResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    let result = change_baskets(&state, |config| {
+        if config.remove_basket(&name) {
+            Ok(())
+        } else {
+            Err(format!("No basket named '{}'", name))
+        }
+    })
+    .await;
+
+    match result {
+        Ok(response) => {
+            log::info!("Basket '{}' removed", name);
+            Json(ApiResponse::success(response))
+        }
+        Err(message) => Json(ApiResponse::error(message)),
+    }
+}
@@ -0,0 +1,96 @@
+use axum::{
+    body::Bytes,
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+};
+
+use crate::api::UserState;
+use crate::billing::{STRIPE_SIGNATURE_HEADER, StripeEvent};
+use crate::handlers::webhook::authenticated_owner;
+use crate::models::{ApiResponse, BillingStatusResponse};
+
+// Receive Stripe's subscription webhooks. Public, but every event must carry a
+// valid Stripe-Signature. Events Stripe should not retry, including those of
+// users unknown here, are acknowledged with 200.
+pub async fn stripe_webhook(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+    body: Bytes,
+) -> StatusCode {
+    let Some(billing) = &state.billing else {
+        return StatusCode::NOT_FOUND;
+    };
+    let signature = headers
+        .get(STRIPE_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if let Err(message) = billing.verify(signature, &body) {
+        log::warn!("Refused a Stripe webhook: {}", message);
+        return StatusCode::BAD_REQUEST;
+    }
+
+    let event: StripeEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => {
+            log::warn!("Unreadable Stripe webhook: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+    let Some(subscription) = event.subscription() else {
+        return StatusCode::OK;
+    };
+
+    match billing.record(&subscription).await {
+        Ok(Some(owner)) => {
+            log::info!(
+                "Subscription of '{}' is {} on price {}",
+                owner,
+                subscription.status,
+                subscription.price_id
+            );
+            StatusCode::OK
+        }
+        Ok(None) => {
+            log::warn!(
+                "Ignored Stripe subscription {} of customer {}: no matching user",
+                subscription.stripe_subscription,
+                subscription.stripe_customer
+            );
+            StatusCode::OK
+        }
+        // Stripe retries the delivery
+        Err(e) => {
+            log::error!("Failed to store Stripe subscription: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+// Plan, subscription and today's usage of the authenticated user
+pub async fn get_billing_status(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<BillingStatusResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+    let Some(billing) = &state.billing else {
+        return Ok(Json(ApiResponse::error(
+            "Billing is not enabled on this server".to_string(),
+        )));
+    };
+
+    let status = billing.status(&owner);
+    let subscription = status.subscription.as_ref();
+    Ok(Json(ApiResponse::success(BillingStatusResponse {
+        plan: status.plan,
+        status: subscription.map(|s| s.status.clone()),
+        price_id: subscription.map(|s| s.price_id.clone()),
+        current_period_end: subscription
+            .and_then(|s| s.current_period_end)
+            .map(|end| end.to_rfc3339()),
+        daily_quota: status.daily_quota,
+        used_today: status.used_today,
+    })))
+}
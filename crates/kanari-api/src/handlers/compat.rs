@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use kanari_oracle::fx::Conversion;
+use kanari_oracle::models::AssetType;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+
+/// Token from the usual places, or from where CoinGecko clients send their API
+/// key, so they only need a new base URL and key
+fn coingecko_token(headers: &HeaderMap, query: &HashMap<String, String>) -> Option<String> {
+    extract_token_from_request(headers, query)
+        .or_else(|| {
+            ["x-cg-pro-api-key", "x-cg-demo-api-key"]
+                .iter()
+                .find_map(|name| headers.get(*name)?.to_str().ok().map(str::to_string))
+        })
+        .or_else(|| {
+            ["x_cg_pro_api_key", "x_cg_demo_api_key"]
+                .iter()
+                .find_map(|name| query.get(*name).cloned())
+        })
+}
+
+/// Error body in CoinGecko's shape
+fn coingecko_error(status: StatusCode, message: &str) -> Response {
+    let body = json!({
+        "status": {
+            "error_code": status.as_u16(),
+            "error_message": message,
+        }
+    });
+    (status, Json(body)).into_response()
+}
+
+fn flag(query: &HashMap<String, String>, name: &str) -> bool {
+    query.get(name).is_some_and(|value| value == "true")
+}
+
+// CoinGecko `/simple/price` served from the oracle. Prices are quoted in
+// `crypto.default_vs_currency` and converted through the FX table to the other
+// requested currencies; currencies the table does not have are left out of the
+// response, as CoinGecko does for currencies it does not know. Ids that cannot
+// be priced, or are not served to the token's tenant, are left out too.
+pub async fn coingecko_simple_price(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    let token = coingecko_token(&headers, &query);
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return coingecko_error(StatusCode::UNAUTHORIZED, &message),
+    };
+
+    let ids = query
+        .get("ids")
+        .or_else(|| query.get("symbols"))
+        .map(String::as_str)
+        .unwrap_or_default();
+    let Some(vs_currencies) = query.get("vs_currencies") else {
+        return coingecko_error(StatusCode::BAD_REQUEST, "Missing parameter vs_currencies");
+    };
+    let precision = query
+        .get("precision")
+        .and_then(|p| p.parse::<i32>().ok())
+        .filter(|p| (0..=18).contains(p));
+
+    let oracle = state.oracle.read().await.clone();
+    let quote_currency = oracle.config().quote_currency(AssetType::Crypto);
+    let conversions: Vec<Conversion> = vs_currencies
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .filter_map(|c| oracle.converter().rate(&quote_currency, c).ok())
+        .collect();
+
+    let round = |value: f64| match precision {
+        Some(digits) => {
+            let factor = 10f64.powi(digits);
+            (value * factor).round() / factor
+        }
+        None => value,
+    };
+
+    let mut response = Map::new();
+    for id in ids
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .filter(|id| namespace.allows(oracle.config(), AssetType::Crypto, id))
+    {
+        let Ok(price_data) = oracle.get_crypto_price(id).await else {
+            continue;
+        };
+
+        let mut entry = Map::new();
+        for conversion in &conversions {
+            let Some(converted) = conversion.apply_price(&price_data) else {
+                continue;
+            };
+            let currency = &conversion.to;
+            entry.insert(currency.clone(), json!(round(converted.price)));
+            if flag(&query, "include_market_cap") {
+                entry.insert(
+                    format!("{}_market_cap", currency),
+                    json!(converted.market_cap),
+                );
+            }
+            if flag(&query, "include_24hr_vol") {
+                entry.insert(format!("{}_24h_vol", currency), json!(converted.volume_24h));
+            }
+            if flag(&query, "include_24hr_change") {
+                entry.insert(
+                    format!("{}_24h_change", currency),
+                    json!(converted.change_24h_percent),
+                );
+            }
+        }
+        if flag(&query, "include_last_updated_at") {
+            entry.insert(
+                "last_updated_at".to_string(),
+                json!(price_data.timestamp.timestamp()),
+            );
+        }
+        response.insert(id.to_lowercase(), Value::Object(entry));
+    }
+
+    Json(Value::Object(response)).into_response()
+}
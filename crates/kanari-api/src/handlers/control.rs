@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use kanari_oracle::errors::OracleError;
+use kanari_oracle::models::AssetType;
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::errors::OracleApiError;
+use crate::models::{ApiResponse, UpdateControlResponse, UpdateIntervalRequest};
+
+async fn control_state(state: &AppState) -> UpdateControlResponse {
+    UpdateControlResponse {
+        paused: state.updates.is_paused(),
+        interval_override: state.updates.interval(),
+        config_interval: state.oracle.read().await.config().general.update_interval,
+    }
+}
+
+// Whether background updates are paused and which interval applies (admin only)
+pub async fn get_update_control(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<UpdateControlResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    Json(ApiResponse::success(control_state(&state).await))
+}
+
+// Skip background rounds until resumed; a round already running finishes (admin only)
+pub async fn pause_updates(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<UpdateControlResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    if state.updates.pause() {
+        log::warn!("Background updates paused");
+    }
+    Json(ApiResponse::success(control_state(&state).await))
+}
+
+// Resume background rounds paused by `/admin/updates/pause` (admin only)
+pub async fn resume_updates(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<UpdateControlResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    if state.updates.resume() {
+        log::info!("Background updates resumed");
+    }
+    Json(ApiResponse::success(control_state(&state).await))
+}
+
+// Change the update interval until restart, or clear the change with
+// `{"seconds": null}` (admin only)
+pub async fn set_update_interval(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateIntervalRequest>,
+) -> Json<ApiResponse<UpdateControlResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    if payload.seconds == Some(0) {
+        return Json(ApiResponse::error(
+            "Update interval must be at least 1 second".to_string(),
+        ));
+    }
+
+    state.updates.set_interval(payload.seconds);
+    match payload.seconds {
+        Some(seconds) => log::info!("Update interval set to {} seconds at runtime", seconds),
+        None => log::info!("Update interval override cleared"),
+    }
+    Json(ApiResponse::success(control_state(&state).await))
+}
+
+// Refresh one configured symbol right away, even while updates are paused (admin only)
+pub async fn run_symbol_update(
+    Path((asset_type, symbol)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<String>>, OracleApiError> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Ok(Json(ApiResponse::error(message)));
+    }
+    let asset_type = match asset_type.as_str() {
+        "crypto" => AssetType::Crypto,
+        "stock" => AssetType::Stock,
+        _ => {
+            return Ok(Json(ApiResponse::error(
+                "Invalid asset type. Use 'crypto' or 'stock'".to_string(),
+            )));
+        }
+    };
+    // Update on a clone so other requests keep reading while the price is fetched
+    let oracle = state.oracle.read().await.clone();
+
+    let canonical = oracle.config().canonical_symbol(asset_type, &symbol);
+    if !oracle
+        .config()
+        .canonical_symbols(asset_type)
+        .contains(&canonical)
+    {
+        return Err(OracleError::PriceNotFound(symbol).into());
+    }
+
+    let report = oracle.update_symbols(asset_type, &[canonical]).await?;
+    report.log();
+    Ok(Json(ApiResponse::success(format!(
+        "Updated {} price feeds",
+        report.updated
+    ))))
+}
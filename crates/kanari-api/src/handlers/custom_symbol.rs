@@ -0,0 +1,225 @@
+use anyhow::anyhow;
+use axum::{
+    extract::{Json, Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use kanari_oracle::models::AssetType;
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+
+use crate::api::UserState;
+use crate::handlers::watchlist::quote_entries;
+use crate::handlers::webhook::authenticated_owner;
+use crate::models::{
+    ApiResponse, CreateCustomSymbolRequest, CustomSymbolInfo, CustomSymbolListResponse,
+    CustomSymbolPricesResponse,
+};
+use crate::tenancy::Namespace;
+
+/// Symbols each user may register when USER_SYMBOL_LIMIT is not set
+const DEFAULT_USER_SYMBOL_LIMIT: i64 = 10;
+
+const MAX_SYMBOL_LEN: usize = 64;
+
+/// Symbols each user may register, from USER_SYMBOL_LIMIT
+pub fn user_symbol_limit() -> anyhow::Result<i64> {
+    match std::env::var("USER_SYMBOL_LIMIT") {
+        Ok(value) => value
+            .parse::<i64>()
+            .ok()
+            .filter(|limit| *limit >= 0)
+            .ok_or_else(|| anyhow!("Invalid USER_SYMBOL_LIMIT '{}'", value)),
+        Err(_) => Ok(DEFAULT_USER_SYMBOL_LIMIT),
+    }
+}
+
+/// The owner's registered symbols as "asset_type:symbol" entries
+pub(crate) async fn registered_symbols(
+    state: &UserState,
+    owner: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT asset_type || ':' || symbol FROM user_symbols WHERE owner = $1 ORDER BY id",
+    )
+    .bind(owner)
+    .fetch_all(&state.db)
+    .await
+}
+
+fn custom_symbol_info(row: &PgRow) -> Result<CustomSymbolInfo, sqlx::Error> {
+    let created: DateTime<Utc> = row.try_get("created_at")?;
+    Ok(CustomSymbolInfo {
+        id: row.try_get("id")?,
+        asset_type: row.try_get("asset_type")?,
+        symbol: row.try_get("symbol")?,
+        created_at: created.to_rfc3339(),
+    })
+}
+
+// Register a symbol the server config does not include. It must be priced by
+// at least one source; the price fetched to check that is cached like any other.
+pub async fn create_custom_symbol(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+    Json(payload): Json<CreateCustomSymbolRequest>,
+) -> Result<Json<ApiResponse<CustomSymbolInfo>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let asset_type = match payload.asset_type.to_lowercase().as_str() {
+        "crypto" => AssetType::Crypto,
+        "stock" => AssetType::Stock,
+        _ => {
+            return Ok(Json(ApiResponse::error(
+                "Invalid asset type. Use 'crypto' or 'stock'".to_string(),
+            )));
+        }
+    };
+    let symbol = payload.symbol.trim().to_lowercase();
+    let valid = !symbol.is_empty()
+        && symbol.len() <= MAX_SYMBOL_LEN
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    if !valid {
+        return Ok(Json(ApiResponse::error(format!(
+            "symbol must be 1-{} letters, digits, '-', '_', '.' or '/'",
+            MAX_SYMBOL_LEN
+        ))));
+    }
+
+    let oracle = state.oracle.read().await.clone();
+    let canonical = oracle.config().canonical_symbol(asset_type, &symbol);
+    if oracle
+        .config()
+        .canonical_symbols(asset_type)
+        .contains(&canonical)
+    {
+        return Ok(Json(ApiResponse::error(format!(
+            "{} is already configured on this server; no need to register it",
+            symbol
+        ))));
+    }
+    let priced = match asset_type {
+        AssetType::Crypto => oracle.get_crypto_price(&symbol).await,
+        AssetType::Stock => oracle.get_stock_price(&symbol).await,
+        AssetType::Basket => oracle.get_basket_price(&symbol).await,
+        AssetType::Tvl => oracle.get_tvl(&symbol).await,
+    };
+    if let Err(e) = priced {
+        return Ok(Json(ApiResponse::error(format!(
+            "No source could price {} {}: {}",
+            asset_type.as_str(),
+            symbol,
+            e
+        ))));
+    }
+
+    // The limit is checked by the insert itself rather than a separate query
+    match sqlx::query(
+        "INSERT INTO user_symbols (owner, asset_type, symbol) SELECT $1, $2, $3 WHERE (SELECT COUNT(*) FROM user_symbols WHERE owner = $1) < $4 ON CONFLICT (owner, asset_type, symbol) DO NOTHING RETURNING id, asset_type, symbol, created_at",
+    )
+    .bind(&owner)
+    .bind(asset_type.as_str())
+    .bind(&symbol)
+    .bind(state.symbol_limit)
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(Some(row)) => match custom_symbol_info(&row) {
+            Ok(info) => Ok(Json(ApiResponse::success(info))),
+            Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        },
+        Ok(None) => Ok(Json(ApiResponse::error(format!(
+            "{} is already registered, or you have reached your limit of {} symbols",
+            symbol, state.symbol_limit
+        )))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// List the authenticated user's registered symbols
+pub async fn list_custom_symbols(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<CustomSymbolListResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let rows = match sqlx::query(
+        "SELECT id, asset_type, symbol, created_at FROM user_symbols WHERE owner = $1 ORDER BY id",
+    )
+    .bind(&owner)
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+
+    match rows
+        .iter()
+        .map(custom_symbol_info)
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+    {
+        Ok(symbols) => Ok(Json(ApiResponse::success(CustomSymbolListResponse {
+            symbols,
+            limit: state.symbol_limit,
+        }))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Unregister one of the authenticated user's symbols
+pub async fn delete_custom_symbol(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    match sqlx::query("DELETE FROM user_symbols WHERE id = $1 AND owner = $2")
+        .bind(id)
+        .bind(&owner)
+        .execute(&state.db)
+        .await
+    {
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(Json(ApiResponse::error("Symbol not found".to_string())))
+        }
+        Ok(_) => Ok(Json(ApiResponse::success("Symbol removed".to_string()))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Current quotes for every symbol the authenticated user registered, fetched on
+// demand and cached like configured symbols
+pub async fn get_custom_symbol_prices(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<CustomSymbolPricesResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let entries = match registered_symbols(&state, &owner).await {
+        Ok(entries) => entries,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    let oracle = state.oracle.read().await.clone();
+    let (prices, unavailable) = quote_entries(&oracle, &Namespace::User(owner), entries).await;
+
+    Ok(Json(ApiResponse::success(CustomSymbolPricesResponse {
+        prices,
+        unavailable,
+    })))
+}
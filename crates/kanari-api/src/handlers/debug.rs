@@ -0,0 +1,27 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use kanari_oracle::oracle::DebugDump;
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::models::ApiResponse;
+
+// Dump the oracle's in-memory state for troubleshooting (admin only)
+pub async fn debug_feeds(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<DebugDump>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let oracle_lock = state.oracle.read().await;
+
+    Json(ApiResponse::success(oracle_lock.debug_dump()))
+}
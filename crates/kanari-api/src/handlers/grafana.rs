@@ -0,0 +1,204 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Oracle;
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::models::{
+    GrafanaAnnotation, GrafanaAnnotationRequest, GrafanaQueryRequest, GrafanaSearchRequest,
+    GrafanaTimeSeries,
+};
+use crate::tenancy::Namespace;
+
+/// Reject the request with 401, since Grafana shows the status rather than an
+/// `ApiResponse` body
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> Result<Namespace, Response> {
+    let token = extract_token_from_request(headers, query);
+    state
+        .auth
+        .check(token.as_deref())
+        .await
+        .map_err(|message| (StatusCode::UNAUTHORIZED, message).into_response())
+}
+
+/// Asset type and symbol of a target written "crypto:btc", "stock:AAPL" or as a
+/// bare configured symbol
+pub(crate) fn parse_target(oracle: &Oracle, target: &str) -> Option<(AssetType, String)> {
+    match target.split_once(':') {
+        Some(("crypto", symbol)) => Some((AssetType::Crypto, symbol.to_string())),
+        Some(("stock", symbol)) => Some((AssetType::Stock, symbol.to_string())),
+        Some(("basket", symbol)) => Some((AssetType::Basket, symbol.to_string())),
+        Some(("tvl", symbol)) => Some((AssetType::Tvl, symbol.to_string())),
+        _ => {
+            let asset_type = match oracle.resolve_asset_type(target).ok()? {
+                "crypto" => AssetType::Crypto,
+                _ => AssetType::Stock,
+            };
+            Some((asset_type, target.to_string()))
+        }
+    }
+}
+
+/// Every symbol served to `namespace` as a target
+fn all_targets(oracle: &Oracle, namespace: &Namespace) -> Vec<String> {
+    [
+        AssetType::Crypto,
+        AssetType::Stock,
+        AssetType::Basket,
+        AssetType::Tvl,
+    ]
+    .into_iter()
+    .flat_map(|asset_type| {
+        namespace
+            .symbols(oracle.config(), asset_type)
+            .into_iter()
+            .map(move |symbol| format!("{}:{}", asset_type.as_str(), symbol))
+    })
+    .collect()
+}
+
+// Datasource connection test
+pub async fn grafana_test(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    if let Err(rejection) = authorize(&state, &headers, &query).await {
+        return rejection;
+    }
+    "OK".into_response()
+}
+
+// Targets matching the metric name typed in the query editor
+pub async fn grafana_search(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(request): Json<GrafanaSearchRequest>,
+) -> Response {
+    let namespace = match authorize(&state, &headers, &query).await {
+        Ok(namespace) => namespace,
+        Err(rejection) => return rejection,
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    let filter = request.target.to_lowercase();
+    let targets: Vec<String> = all_targets(&oracle_lock, &namespace)
+        .into_iter()
+        .filter(|target| target.to_lowercase().contains(&filter))
+        .collect();
+
+    Json(targets).into_response()
+}
+
+// Price history of each target in the dashboard's time range, with stock prices
+// adjusted for splits and dividends
+pub async fn grafana_query(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(request): Json<GrafanaQueryRequest>,
+) -> Response {
+    let namespace = match authorize(&state, &headers, &query).await {
+        Ok(namespace) => namespace,
+        Err(rejection) => return rejection,
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    let mut series = Vec::new();
+    for target in &request.targets {
+        let Some((asset_type, symbol)) = parse_target(&oracle_lock, &target.target) else {
+            continue;
+        };
+        if !namespace.allows(oracle_lock.config(), asset_type, &symbol) {
+            continue;
+        }
+        let points =
+            oracle_lock.adjusted_history(asset_type, &symbol, request.range.from, request.range.to);
+
+        // Keep every n-th point so at most `maxDataPoints` are returned
+        let step = match request.max_data_points {
+            Some(max) if max > 0 => points.len().div_ceil(max).max(1),
+            _ => 1,
+        };
+        series.push(GrafanaTimeSeries {
+            target: target.target.clone(),
+            datapoints: points
+                .iter()
+                .step_by(step)
+                .map(|point| (point.price, point.timestamp.timestamp_millis()))
+                .collect(),
+        });
+    }
+
+    Json(series).into_response()
+}
+
+// Source switches in the dashboard's time range, for the annotation's `query`
+// targets (comma-separated) or every symbol when it is empty
+pub async fn grafana_annotations(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(request): Json<GrafanaAnnotationRequest>,
+) -> Response {
+    let namespace = match authorize(&state, &headers, &query).await {
+        Ok(namespace) => namespace,
+        Err(rejection) => return rejection,
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    let wanted = request
+        .annotation
+        .get("query")
+        .and_then(|q| q.as_str())
+        .unwrap_or_default();
+    let targets: Vec<String> = if wanted.trim().is_empty() {
+        all_targets(&oracle_lock, &namespace)
+    } else {
+        wanted
+            .split(',')
+            .map(|target| target.trim().to_string())
+            .filter(|target| !target.is_empty())
+            .collect()
+    };
+
+    let mut annotations = Vec::new();
+    for target in &targets {
+        let Some((asset_type, symbol)) = parse_target(&oracle_lock, target) else {
+            continue;
+        };
+        if !namespace.allows(oracle_lock.config(), asset_type, &symbol) {
+            continue;
+        }
+        let points = oracle_lock.history(asset_type, &symbol, request.range.from, request.range.to);
+        for pair in points.windows(2) {
+            let (previous, point) = (&pair[0], &pair[1]);
+            if previous.source == point.source {
+                continue;
+            }
+            annotations.push(GrafanaAnnotation {
+                annotation: request.annotation.clone(),
+                time: point.timestamp.timestamp_millis(),
+                title: format!("{}: source changed", target),
+                text: format!("{} -> {}", previous.source, point.source),
+                tags: vec![
+                    "source_change".to_string(),
+                    asset_type.as_str().to_string(),
+                    point.source.clone(),
+                ],
+            });
+        }
+    }
+
+    Json(annotations).into_response()
+}
@@ -7,11 +7,23 @@ use crate::models::{ApiResponse, HealthResponse};
 pub async fn health_check(State(state): State<AppState>) -> Json<ApiResponse<HealthResponse>> {
     let oracle_lock = state.oracle.read().await;
 
+    let sources = oracle_lock.registry().health();
+    let database_up = state.db_health.as_ref().map(|health| health.is_up());
+    // Serving continues from other sources while a circuit is open, and prices
+    // keep being served while the database is down
+    let status = if sources.iter().all(|s| s.health.healthy) && database_up != Some(false) {
+        "healthy"
+    } else {
+        "degraded"
+    };
+
     let response = HealthResponse {
-        status: "healthy".to_string(),
+        status: status.to_string(),
         last_update: oracle_lock.get_last_update().to_rfc3339(),
         total_symbols: oracle_lock.get_crypto_symbols().len()
             + oracle_lock.get_stock_symbols().len(),
+        sources,
+        database: database_up.map(|up| if up { "up" } else { "down" }.to_string()),
     };
 
     Json(ApiResponse::success(response))
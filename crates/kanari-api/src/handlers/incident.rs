@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::incidents::{Impact, Incident, IncidentStatus, NewIncident};
+use crate::models::{ApiResponse, CreateIncidentRequest, UpdateIncidentRequest};
+
+const MAX_TITLE_LEN: usize = 200;
+
+/// Trimmed `text`, or None when it is blank
+fn non_blank(text: Option<String>) -> Option<String> {
+    text.map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+// Open an incident, listed on /status.json until resolved (admin only)
+pub async fn create_incident(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateIncidentRequest>,
+) -> Json<ApiResponse<Incident>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let title = payload.title.trim().to_string();
+    if title.is_empty() || title.len() > MAX_TITLE_LEN {
+        return Json(ApiResponse::error(format!(
+            "Incident title must be 1 to {} characters",
+            MAX_TITLE_LEN
+        )));
+    }
+    let impact = match payload.impact.as_deref() {
+        Some(impact) => match impact.parse::<Impact>() {
+            Ok(impact) => impact,
+            Err(message) => return Json(ApiResponse::error(message)),
+        },
+        None => Impact::Degraded,
+    };
+
+    let incident = NewIncident {
+        title,
+        impact,
+        component: non_blank(payload.component).map(|component| component.to_lowercase()),
+        message: non_blank(payload.message),
+    };
+    match state.incidents.open(incident).await {
+        Ok(incident) => {
+            log::warn!(
+                "Incident {} opened ({}): {}",
+                incident.id,
+                incident.impact.as_str(),
+                incident.title
+            );
+            Json(ApiResponse::success(incident))
+        }
+        Err(e) => Json(ApiResponse::error(format!(
+            "Failed to record incident: {}",
+            e
+        ))),
+    }
+}
+
+// Move an incident along, e.g. to "monitoring" or "resolved" (admin only)
+pub async fn update_incident(
+    Path(id): Path<i64>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateIncidentRequest>,
+) -> Json<ApiResponse<Incident>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let status = match payload.status.parse::<IncidentStatus>() {
+        Ok(status) => status,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+
+    match state
+        .incidents
+        .update(id, status, non_blank(payload.message))
+        .await
+    {
+        Ok(Some(incident)) => {
+            log::info!("Incident {} is {}", incident.id, incident.status.as_str());
+            Json(ApiResponse::success(incident))
+        }
+        Ok(None) => Json(ApiResponse::error("Incident not found".to_string())),
+        Err(e) => Json(ApiResponse::error(format!(
+            "Failed to update incident: {}",
+            e
+        ))),
+    }
+}
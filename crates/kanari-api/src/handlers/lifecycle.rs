@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::DateTime;
+use kanari_oracle::config::{Config, StoreBackend};
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::{Auth, extract_token_from_request};
+use crate::models::{ApiResponse, DrainResponse, ReadinessResponse, VersionResponse};
+
+// Readiness for load balancers and orchestrators: 503 while draining or before
+// any price has been published
+pub async fn readiness_check(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ApiResponse<ReadinessResponse>>) {
+    let snapshot = state.oracle.read().await.snapshot();
+    let warming = snapshot.feeds.values().all(|feed| feed.prices.is_empty());
+    let (code, status) = if state.drain.is_draining() {
+        (StatusCode::SERVICE_UNAVAILABLE, "draining")
+    } else if warming {
+        (StatusCode::SERVICE_UNAVAILABLE, "warming")
+    } else {
+        (StatusCode::OK, "ready")
+    };
+
+    (
+        code,
+        Json(ApiResponse::success(ReadinessResponse {
+            status: status.to_string(),
+        })),
+    )
+}
+
+// Stop background updates and fail readiness so traffic moves elsewhere; requests
+// keep being answered until the process is stopped (admin only)
+pub async fn drain_server(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<DrainResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    let started = state.drain.start();
+    if started {
+        log::warn!("Draining: background updates stopped, readiness failing");
+    }
+    Json(ApiResponse::success(DrainResponse { started }))
+}
+
+// Build and deployment information (public)
+pub async fn get_version(State(state): State<AppState>) -> Json<ApiResponse<VersionResponse>> {
+    let build_time = env!("KANARI_BUILD_TIME")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+    let mut features = {
+        let oracle_lock = state.oracle.read().await;
+        features(oracle_lock.config(), &state.auth)
+    };
+    if state.signer.is_some() {
+        features.push("signing".to_string());
+    }
+
+    Json(ApiResponse::success(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("KANARI_GIT_SHA").to_string(),
+        build_time,
+        features,
+        signing_key: state.signer.as_ref().map(|signer| signer.public_key()),
+    }))
+}
+
+fn features(config: &Config, auth: &Auth) -> Vec<String> {
+    let auth = match auth {
+        Auth::Database(_) => "auth:database",
+        Auth::Tokens(_) => "auth:tokens",
+        Auth::Disabled => "auth:none",
+    };
+    let store = match config.store.backend {
+        StoreBackend::Memory => "store:memory",
+        StoreBackend::Redis => "store:redis",
+    };
+    let optional = [
+        ("ha", config.ha.is_some()),
+        ("replication", config.replication.is_some()),
+        ("offline", config.offline.is_some()),
+        ("sandbox", config.sandbox.is_some()),
+        ("cassette", config.cassette.is_some()),
+        ("anomaly", config.anomaly.is_some()),
+        ("slo", config.slo.is_some()),
+        ("audit", config.audit.is_some()),
+        ("sink:clickhouse", config.sinks.clickhouse.is_some()),
+        ("sink:influxdb", config.sinks.influxdb.is_some()),
+    ];
+
+    [auth, store]
+        .into_iter()
+        .chain(
+            optional
+                .into_iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| name),
+        )
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,75 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use std::fmt::Write;
+
+use crate::api::AppState;
+
+// Per-source statistics in the Prometheus text exposition format
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    let stats = state.oracle.read().await.registry().stats();
+    let mut body = String::new();
+
+    let _ = writeln!(
+        body,
+        "# HELP kanari_source_latency_seconds Request latency over the rolling window"
+    );
+    let _ = writeln!(body, "# TYPE kanari_source_latency_seconds gauge");
+    for source in &stats {
+        for (quantile, ms) in [
+            ("0.5", source.p50_latency_ms),
+            ("0.95", source.p95_latency_ms),
+        ] {
+            let _ = writeln!(
+                body,
+                "kanari_source_latency_seconds{{source=\"{}\",quantile=\"{}\"}} {}",
+                source.name,
+                quantile,
+                ms as f64 / 1000.0
+            );
+        }
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP kanari_source_error_rate Share of failed requests over the rolling window"
+    );
+    let _ = writeln!(body, "# TYPE kanari_source_error_rate gauge");
+    for source in &stats {
+        let _ = writeln!(
+            body,
+            "kanari_source_error_rate{{source=\"{}\"}} {}",
+            source.name, source.error_rate
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP kanari_source_requests_total Requests sent to the source"
+    );
+    let _ = writeln!(body, "# TYPE kanari_source_requests_total counter");
+    for source in &stats {
+        let _ = writeln!(
+            body,
+            "kanari_source_requests_total{{source=\"{}\"}} {}",
+            source.name, source.requests_total
+        );
+    }
+
+    let _ = writeln!(
+        body,
+        "# HELP kanari_source_errors_total Failed requests to the source"
+    );
+    let _ = writeln!(body, "# TYPE kanari_source_errors_total counter");
+    for source in &stats {
+        let _ = writeln!(
+            body,
+            "kanari_source_errors_total{{source=\"{}\"}} {}",
+            source.name, source.errors_total
+        );
+    }
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response()
+}
@@ -1,7 +1,41 @@
+pub mod basket;
+pub mod billing;
+pub mod compat;
+pub mod control;
+pub mod custom_symbol;
+pub mod debug;
+pub mod grafana;
 pub mod health;
+pub mod incident;
+pub mod lifecycle;
+pub mod metrics;
 pub mod price;
+pub mod pyth;
+pub mod report;
+pub mod status;
+pub mod stream;
+pub mod symbol_import;
 pub mod user;
+pub mod watchlist;
+pub mod webhook;
 
+pub use basket::*;
+pub use billing::*;
+pub use compat::*;
+pub use control::*;
+pub use custom_symbol::*;
+pub use debug::*;
+pub use grafana::*;
 pub use health::*;
+pub use incident::*;
+pub use lifecycle::*;
+pub use metrics::*;
 pub use price::*;
+pub use pyth::*;
+pub use report::*;
+pub use status::*;
+pub use stream::*;
+pub use symbol_import::*;
 pub use user::*;
+pub use watchlist::*;
+pub use webhook::*;
@@ -1,13 +1,88 @@
 use axum::{
+    Extension,
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
 };
+use chrono::{DateTime, Duration, Utc};
+use kanari_oracle::anomaly::Anomaly;
+use kanari_oracle::errors::OracleError;
+use kanari_oracle::fx::{Conversion, FxTable};
+use kanari_oracle::index::MarketCapIndex;
+use kanari_oracle::models::{AssetType, PriceData};
+use kanari_oracle::network::NetworkStats;
+use kanari_oracle::oracle::Oracle;
+use kanari_oracle::provenance::Provenance;
+use kanari_oracle::round::UpdateReport;
+use kanari_oracle::sentiment::Sentiment;
+use kanari_oracle::slo::SloReport;
+use kanari_oracle::sources::SourceStats;
 use std::collections::HashMap;
 
 use crate::api::AppState;
-use crate::auth::{extract_token_from_request, validate_token};
-use crate::models::{ApiResponse, ListQuery, PriceResponse, StatsResponse, SymbolsResponse};
+use crate::auth::extract_token_from_request;
+use crate::cache::CachedBody;
+use crate::errors::OracleApiError;
+use crate::models::{
+    ApiResponse, HistoryResponse, ListQuery, PriceResponse, StatsResponse, SymbolsResponse,
+};
+use crate::signing::ServedRound;
+
+/// `?reject_stale=true` asks for an error instead of a price flagged as stale
+fn reject_stale(query: &HashMap<String, String>) -> bool {
+    matches!(
+        query.get("reject_stale").map(String::as_str),
+        Some("true") | Some("1")
+    )
+}
+
+/// `price_data` converted to the `?currency=` of the request through the FX
+/// table, or left in its asset type's quote currency when none was asked for
+fn in_requested_currency(
+    oracle: &Oracle,
+    asset_type: AssetType,
+    price_data: PriceData,
+    query: &HashMap<String, String>,
+) -> Result<(PriceData, Option<Conversion>), OracleError> {
+    match query.get("currency") {
+        Some(currency) => {
+            let (converted, conversion) = oracle.price_in(asset_type, &price_data, currency)?;
+            Ok((converted, Some(conversion)))
+        }
+        None => Ok((price_data, None)),
+    }
+}
+
+fn price_response(
+    oracle: &Oracle,
+    symbol: &str,
+    asset_type: AssetType,
+    price_data: &PriceData,
+    fx: Option<Conversion>,
+) -> PriceResponse {
+    PriceResponse {
+        symbol: symbol.to_uppercase(),
+        price: oracle
+            .config()
+            .api_price(asset_type, symbol, price_data.price),
+        currency: match &fx {
+            Some(conversion) => conversion.to.clone(),
+            None => oracle.config().quote_currency(asset_type),
+        },
+        timestamp: price_data.timestamp.to_rfc3339(),
+        source: price_data.source.clone(),
+        asset_type: asset_type.as_str().to_string(),
+        age_seconds: price_data.age_seconds,
+        is_stale: price_data.is_stale,
+        market_state: price_data.market_state,
+        session: price_data.session,
+        market_cap: price_data.market_cap,
+        circulating_supply: price_data.circulating_supply,
+        total_supply: price_data.total_supply,
+        depth: price_data.depth.clone(),
+        fx,
+    }
+}
 
 // Get price for a specific symbol
 pub async fn get_price(
@@ -15,93 +90,347 @@ pub async fn get_price(
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<ApiResponse<PriceResponse>>, StatusCode> {
+) -> Result<Json<ApiResponse<PriceResponse>>, OracleApiError> {
     // Validate token from header or query parameter
     let token = extract_token_from_request(&headers, &query);
 
-    if let Some(token) = token {
-        if !validate_token(&state.db, &token).await {
-            return Ok(Json(ApiResponse::error(
-                "Invalid or expired token".to_string(),
-            )));
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    // "any" resolves the asset type from the configured symbol sets
+    let asset_type = if asset_type == "any" {
+        match oracle_lock.resolve_asset_type(&symbol) {
+            Ok(resolved) => resolved.to_string(),
+            Err(e) => return Err(e.into()),
         }
     } else {
-        return Ok(Json(ApiResponse::error(
-            "Missing authentication token".to_string(),
-        )));
+        asset_type
+    };
+
+    // Symbols outside the token's tenant are answered as if they did not exist
+    let kind = match asset_type.as_str() {
+        "crypto" => Some(AssetType::Crypto),
+        "stock" => Some(AssetType::Stock),
+        "basket" => Some(AssetType::Basket),
+        "tvl" => Some(AssetType::Tvl),
+        _ => None,
+    };
+    if kind.is_some_and(|kind| !namespace.allows(oracle_lock.config(), kind, &symbol)) {
+        return Err(OracleError::PriceNotFound(symbol).into());
     }
-    let oracle_lock = state.oracle.read().await;
 
-    let result = match asset_type.as_str() {
-        "crypto" => oracle_lock.get_crypto_price(&symbol).await,
-        "stock" => oracle_lock.get_stock_price(&symbol).await,
+    // `?source=` forces one provider instead of the cached, aggregated price
+    let source = query.get("source");
+    let (asset_type, result) = match (asset_type.as_str(), source) {
+        ("crypto", None) => (
+            AssetType::Crypto,
+            oracle_lock.get_crypto_price(&symbol).await,
+        ),
+        ("crypto", Some(source)) => (
+            AssetType::Crypto,
+            oracle_lock.get_crypto_price_from(&symbol, source).await,
+        ),
+        ("stock", None) => (AssetType::Stock, oracle_lock.get_stock_price(&symbol).await),
+        ("stock", Some(source)) => (
+            AssetType::Stock,
+            oracle_lock.get_stock_price_from(&symbol, source).await,
+        ),
+        // Baskets have no source of their own
+        ("basket", None) => (
+            AssetType::Basket,
+            oracle_lock.get_basket_price(&symbol).await,
+        ),
+        ("tvl", None) => (AssetType::Tvl, oracle_lock.get_tvl(&symbol).await),
+        ("tvl", Some(source)) => (
+            AssetType::Tvl,
+            oracle_lock.get_tvl_from(&symbol, source).await,
+        ),
         _ => {
             return Ok(Json(ApiResponse::error(
-                "Invalid asset type. Use 'crypto' or 'stock'".to_string(),
+                "Invalid asset type. Use 'any', 'crypto', 'stock', 'basket' or 'tvl'".to_string(),
             )));
         }
     };
 
     match result {
+        Ok(price_data) if price_data.is_stale && reject_stale(&query) => {
+            Err(OracleError::StaleData {
+                symbol: symbol.clone(),
+                age_secs: price_data.age_seconds,
+            }
+            .into())
+        }
         Ok(price_data) => {
-            let response = PriceResponse {
-                symbol: symbol.to_uppercase(),
-                price: price_data.price,
-                timestamp: price_data.timestamp.to_rfc3339(),
-                asset_type: asset_type.clone(),
-            };
-            Ok(Json(ApiResponse::success(response)))
+            let (price_data, fx) =
+                in_requested_currency(&oracle_lock, asset_type, price_data, &query)?;
+            Ok(Json(ApiResponse::success(price_response(
+                &oracle_lock,
+                &symbol,
+                asset_type,
+                &price_data,
+                fx,
+            ))))
         }
-        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        Err(e) => Err(e.into()),
     }
 }
 
-// Get all prices for an asset type
-pub async fn get_all_prices(
-    Path(asset_type): Path<String>,
+// Get the total value locked of a protocol ("aave") or chain ("chain:ethereum");
+// the same as /price/tvl/{protocol}
+pub async fn get_tvl(
+    Path(protocol): Path<String>,
+    query: Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    state: State<AppState>,
+) -> Result<Json<ApiResponse<PriceResponse>>, OracleApiError> {
+    get_price(Path(("tvl".to_string(), protocol)), query, headers, state).await
+}
+
+// Get how the published price of a symbol was obtained
+pub async fn get_provenance(
+    Path((asset_type, symbol)): Path<(String, String)>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<ApiResponse<Vec<PriceResponse>>>, StatusCode> {
+) -> Result<Json<ApiResponse<Provenance>>, OracleApiError> {
     // Validate token from header or query parameter
     let token = extract_token_from_request(&headers, &query);
 
-    if let Some(token) = token {
-        if !validate_token(&state.db, &token).await {
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    let asset_type = match asset_type.as_str() {
+        "crypto" => AssetType::Crypto,
+        "stock" => AssetType::Stock,
+        "basket" => AssetType::Basket,
+        "tvl" => AssetType::Tvl,
+        "any" => match oracle_lock.resolve_asset_type(&symbol)? {
+            "crypto" => AssetType::Crypto,
+            _ => AssetType::Stock,
+        },
+        _ => {
             return Ok(Json(ApiResponse::error(
-                "Invalid or expired token".to_string(),
+                "Invalid asset type. Use 'any', 'crypto', 'stock', 'basket' or 'tvl'".to_string(),
             )));
         }
-    } else {
-        return Ok(Json(ApiResponse::error(
-            "Missing authentication token".to_string(),
-        )));
+    };
+    if !namespace.allows(oracle_lock.config(), asset_type, &symbol) {
+        return Err(OracleError::PriceNotFound(symbol).into());
+    }
+
+    match oracle_lock.provenance(asset_type, &symbol) {
+        Some(provenance) => Ok(Json(ApiResponse::success(provenance))),
+        None => Err(OracleError::PriceNotFound(symbol).into()),
     }
+}
+
+// Get the recorded price history of a symbol between `from` and `to` (RFC 3339,
+// default: the whole retention window). Stock prices are adjusted for splits and
+// dividends unless `adjusted=false`.
+pub async fn get_history(
+    Path((asset_type, symbol)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<HistoryResponse>>, OracleApiError> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
     let oracle_lock = state.oracle.read().await;
 
-    let prices = match asset_type.as_str() {
-        "crypto" => oracle_lock.get_all_crypto_prices_map(),
-        "stock" => oracle_lock.get_all_stock_prices_map(),
+    let asset_type = match asset_type.as_str() {
+        "crypto" => AssetType::Crypto,
+        "stock" => AssetType::Stock,
+        "basket" => AssetType::Basket,
+        "tvl" => AssetType::Tvl,
+        "any" => match oracle_lock.resolve_asset_type(&symbol)? {
+            "crypto" => AssetType::Crypto,
+            _ => AssetType::Stock,
+        },
         _ => {
             return Ok(Json(ApiResponse::error(
-                "Invalid asset type. Use 'crypto' or 'stock'".to_string(),
+                "Invalid asset type. Use 'any', 'crypto', 'stock', 'basket' or 'tvl'".to_string(),
             )));
         }
     };
+    if !namespace.allows(oracle_lock.config(), asset_type, &symbol) {
+        return Err(OracleError::PriceNotFound(symbol).into());
+    }
+
+    let now = Utc::now();
+    let retention = oracle_lock.config().history.retention as i64;
+    let mut range = [("from", now - Duration::seconds(retention)), ("to", now)];
+    for (name, bound) in &mut range {
+        if let Some(value) = query.get(*name) {
+            match DateTime::parse_from_rfc3339(value) {
+                Ok(parsed) => *bound = parsed.with_timezone(&Utc),
+                Err(_) => {
+                    return Ok(Json(ApiResponse::error(format!(
+                        "Invalid '{}' timestamp '{}'; use RFC 3339, e.g. 2025-10-03T15:00:00Z",
+                        name, value
+                    ))));
+                }
+            }
+        }
+    }
+    let [(_, from), (_, to)] = range;
+    let adjusted = !matches!(
+        query.get("adjusted").map(String::as_str),
+        Some("false") | Some("0")
+    );
+
+    let points = if adjusted {
+        oracle_lock.adjusted_history(asset_type, &symbol, from, to)
+    } else {
+        oracle_lock.history(asset_type, &symbol, from, to)
+    };
+    Ok(Json(ApiResponse::success(HistoryResponse {
+        symbol: symbol.to_uppercase(),
+        asset_type: asset_type.as_str().to_string(),
+        adjusted: adjusted && asset_type == AssetType::Stock,
+        points,
+    })))
+}
+
+// Get all prices for an asset type
+pub async fn get_all_prices(
+    Path(asset_type): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::<()>::error(message)).into_response(),
+    };
+    let kind = match asset_type.as_str() {
+        "crypto" => AssetType::Crypto,
+        "stock" => AssetType::Stock,
+        "basket" => AssetType::Basket,
+        "tvl" => AssetType::Tvl,
+        _ => {
+            return Json(ApiResponse::<()>::error(
+                "Invalid asset type. Use 'crypto', 'stock', 'basket' or 'tvl'".to_string(),
+            ))
+            .into_response();
+        }
+    };
+
+    let oracle_lock = state.oracle.read().await;
+
+    // Every price of the asset type shares one quote currency, so one rate
+    // converts them all
+    let quote_currency = oracle_lock.config().quote_currency(kind);
+    let fx = match query.get("currency") {
+        Some(currency) => match oracle_lock.converter().rate(&quote_currency, currency) {
+            Ok(conversion) => Some(conversion),
+            Err(e) => return OracleApiError::from(e).into_response(),
+        },
+        None => None,
+    };
+    let currency = fx
+        .as_ref()
+        .map_or(quote_currency, |conversion| conversion.to.clone());
+
+    // The payload is serialized once per feed snapshot and shared by every request
+    let skip_stale = reject_stale(&query);
+    let tenant = namespace.tenant(oracle_lock.config()).unwrap_or_default();
+    let key = format!("{}:{}:{}:{}", asset_type, skip_stale, currency, tenant);
+    let snapshot = oracle_lock.snapshot();
+    let cached = state.prices_cache.get_or_build(&key, &snapshot, || {
+        // Built from the snapshot the cache entry is keyed on, so the list is one
+        // round even when another is published meanwhile
+        let prices = oracle_lock.round_prices(&snapshot, kind);
 
-    log::info!("API: Found {} {} prices", prices.len(), asset_type);
+        log::info!("API: Found {} {} prices", prices.len(), asset_type);
 
-    let response: Vec<PriceResponse> = prices
-        .iter()
-        .map(|(symbol, price_data)| PriceResponse {
-            symbol: symbol.clone(),
-            price: price_data.price,
-            timestamp: price_data.timestamp.to_rfc3339(),
-            asset_type: asset_type.clone(),
+        // With reject_stale, stale symbols are left out instead of failing the whole list
+        let response: Vec<PriceResponse> = prices
+            .iter()
+            .filter(|(_, price_data)| !(skip_stale && price_data.is_stale))
+            .filter(|(symbol, _)| namespace.allows(oracle_lock.config(), kind, symbol))
+            .filter_map(|(symbol, price_data)| match &fx {
+                Some(conversion) => Some((symbol, conversion.apply_price(price_data)?)),
+                None => Some((symbol, price_data.clone())),
+            })
+            .map(|(symbol, price_data)| PriceResponse {
+                symbol: symbol.clone(),
+                price: oracle_lock
+                    .config()
+                    .api_price(kind, symbol, price_data.price),
+                currency: currency.clone(),
+                timestamp: price_data.timestamp.to_rfc3339(),
+                source: price_data.source.clone(),
+                asset_type: asset_type.clone(),
+                age_seconds: price_data.age_seconds,
+                is_stale: price_data.is_stale,
+                market_state: price_data.market_state,
+                session: price_data.session,
+                market_cap: price_data.market_cap,
+                circulating_supply: price_data.circulating_supply,
+                total_supply: price_data.total_supply,
+                depth: price_data.depth,
+                fx: fx.clone(),
+            })
+            .collect();
+
+        let last_modified = snapshot
+            .feed(kind)
+            .map(|feed| feed.last_update)
+            .unwrap_or_else(chrono::Utc::now);
+
+        serde_json::to_vec(&ApiResponse::success(response)).map(|body| CachedBody {
+            body: body.into(),
+            round_id: snapshot.id,
+            last_modified: last_modified
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
         })
-        .collect();
+    });
+    drop(oracle_lock);
+
+    let cached = match cached {
+        Ok(cached) => cached,
+        Err(e) => {
+            log::error!("Failed to serialize {} prices: {}", asset_type, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let not_modified = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|since| since == cached.last_modified);
+    if not_modified {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [(header::LAST_MODIFIED, cached.last_modified)],
+        )
+            .into_response();
+    }
 
-    Ok(Json(ApiResponse::success(response)))
+    (
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::LAST_MODIFIED, cached.last_modified),
+        ],
+        Extension(ServedRound(cached.round_id)),
+        cached.body,
+    )
+        .into_response()
 }
 
 // List available symbols
@@ -114,19 +443,14 @@ pub async fn list_symbols(
     // Validate token from header or query parameter
     let token = extract_token_from_request(&headers, &query);
 
-    if let Some(token) = token {
-        if !validate_token(&state.db, &token).await {
-            return Json(ApiResponse::error("Invalid or expired token".to_string()));
-        }
-    } else {
-        return Json(ApiResponse::error(
-            "Missing authentication token".to_string(),
-        ));
-    }
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
     let oracle_lock = state.oracle.read().await;
 
-    let crypto_symbols = oracle_lock.get_crypto_symbols();
-    let stock_symbols = oracle_lock.get_stock_symbols();
+    let crypto_symbols = namespace.symbols(oracle_lock.config(), AssetType::Crypto);
+    let stock_symbols = namespace.symbols(oracle_lock.config(), AssetType::Stock);
 
     let response = match params.asset_type.as_deref() {
         Some("crypto") => SymbolsResponse {
@@ -155,14 +479,8 @@ pub async fn get_stats(
     // Validate token from header or query parameter
     let token = extract_token_from_request(&headers, &query);
 
-    if let Some(token) = token {
-        if !validate_token(&state.db, &token).await {
-            return Json(ApiResponse::error("Invalid or expired token".to_string()));
-        }
-    } else {
-        return Json(ApiResponse::error(
-            "Missing authentication token".to_string(),
-        ));
+    if let Err(message) = state.auth.check(token.as_deref()).await {
+        return Json(ApiResponse::error(message));
     }
     let oracle_lock = state.oracle.read().await;
     let stats = oracle_lock.get_price_statistics();
@@ -191,45 +509,371 @@ pub async fn get_stats(
     Json(ApiResponse::success(response))
 }
 
+// Get rolling latency and error statistics per source
+pub async fn get_source_stats(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<SourceStats>>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check(token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let oracle_lock = state.oracle.read().await;
+
+    Json(ApiResponse::success(oracle_lock.registry().stats()))
+}
+
+// Get recently held or accepted anomalous prices
+pub async fn get_anomalies(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<Anomaly>>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    let config = oracle_lock.config();
+    let anomalies = oracle_lock
+        .anomalies()
+        .into_iter()
+        .filter(|anomaly| namespace.allows(config, anomaly.asset_type, &anomaly.symbol))
+        .collect();
+    Json(ApiResponse::success(anomalies))
+}
+
+// Get freshness SLO compliance for every configured symbol
+pub async fn get_slo(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<SloReport>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    match oracle_lock.slo_report() {
+        Some(mut report) => {
+            let config = oracle_lock.config();
+            report
+                .symbols
+                .retain(|status| namespace.allows(config, status.asset_type, &status.symbol));
+            Json(ApiResponse::success(report))
+        }
+        None => Json(ApiResponse::error(
+            "SLO monitoring is not configured; add an slo section to the config".to_string(),
+        )),
+    }
+}
+
+// Get the Fear & Greed index and funding rates from the last sentiment fetch
+pub async fn get_sentiment(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Sentiment>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check(token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let oracle_lock = state.oracle.read().await;
+
+    match oracle_lock.sentiment() {
+        Some(sentiment) => Json(ApiResponse::success(sentiment)),
+        None if oracle_lock.config().sentiment.is_none() => Json(ApiResponse::error(
+            "Sentiment is not configured; add a sentiment section to the config".to_string(),
+        )),
+        None => Json(ApiResponse::error(
+            "Sentiment has not been fetched yet".to_string(),
+        )),
+    }
+}
+
+// Get Bitcoin block height, hashrate, difficulty and fee estimates from the last
+// network fetch
+pub async fn get_network_stats(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<NetworkStats>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check(token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let oracle_lock = state.oracle.read().await;
+
+    match oracle_lock.network_stats() {
+        Some(stats) => Json(ApiResponse::success(stats)),
+        None if oracle_lock.config().network.is_none() => Json(ApiResponse::error(
+            "Network statistics are not configured; add a network section to the config"
+                .to_string(),
+        )),
+        None => Json(ApiResponse::error(
+            "Network statistics have not been fetched yet".to_string(),
+        )),
+    }
+}
+
+// Get the exchange rates against the US dollar from the last FX fetch
+pub async fn get_fx_rates(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<FxTable>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check(token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+    let oracle_lock = state.oracle.read().await;
+
+    match oracle_lock.fx_rates() {
+        Some(table) => Json(ApiResponse::success(table)),
+        None if oracle_lock.config().fx.is_none() => Json(ApiResponse::error(
+            "FX rates are not configured; add an fx section to the config".to_string(),
+        )),
+        None => Json(ApiResponse::error(
+            "FX rates have not been fetched yet".to_string(),
+        )),
+    }
+}
+
+// Get the per-symbol outcome of the most recent update round
+pub async fn get_last_round(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<UpdateReport>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    match oracle_lock.last_round() {
+        Some(report) => {
+            let config = oracle_lock.config();
+            Json(ApiResponse::success(report.filtered(|outcome| {
+                namespace.allows(config, outcome.asset_type, &outcome.symbol)
+            })))
+        }
+        None => Json(ApiResponse::error(
+            "No update round has completed yet".to_string(),
+        )),
+    }
+}
+
+// Market-cap-weighted index of `?symbols=btc,eth`, or of every configured crypto
+// symbol by default
+pub async fn get_market_cap_index(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<MarketCapIndex>> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+    let symbols: Vec<String> = query
+        .get("symbols")
+        .map(|symbols| {
+            symbols
+                .split(',')
+                .map(str::trim)
+                .filter(|symbol| !symbol.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let oracle_lock = state.oracle.read().await;
+    // A tenant's index covers only its own symbols, all of them by default.
+    // An empty list would mean every configured symbol, so it is refused.
+    let symbols = match namespace.tenant(oracle_lock.config()) {
+        Some(_) => {
+            let symbols = if symbols.is_empty() {
+                namespace.symbols(oracle_lock.config(), AssetType::Crypto)
+            } else {
+                symbols
+                    .into_iter()
+                    .filter(|symbol| {
+                        namespace.allows(oracle_lock.config(), AssetType::Crypto, symbol)
+                    })
+                    .collect()
+            };
+            if symbols.is_empty() {
+                return Json(ApiResponse::error(
+                    "No market cap available for the requested symbols".to_string(),
+                ));
+            }
+            symbols
+        }
+        None => symbols,
+    };
+    let index = oracle_lock.market_cap_index(&symbols);
+    if index.constituents.is_empty() {
+        return Json(ApiResponse::error(
+            "No market cap available for the requested symbols".to_string(),
+        ));
+    }
+    Json(ApiResponse::success(index))
+}
+
 // Force update prices
 pub async fn update_prices(
     Path(asset_type): Path<String>,
     Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> Result<Json<ApiResponse<String>>, OracleApiError> {
     // Validate token from header or query parameter
     let token = extract_token_from_request(&headers, &query);
 
-    if let Some(token) = token {
-        if !validate_token(&state.db, &token).await {
-            return Ok(Json(ApiResponse::error(
-                "Invalid or expired token".to_string(),
-            )));
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+    // Update on a clone so other requests keep reading while prices are fetched
+    let oracle = state.oracle.read().await.clone();
+
+    // A tenant refreshes only its own symbols
+    if let Some(tenant) = namespace.tenant(oracle.config()) {
+        let asset_types: &[AssetType] = match asset_type.as_str() {
+            "crypto" => &[AssetType::Crypto],
+            "stock" => &[AssetType::Stock],
+            "tvl" => &[AssetType::Tvl],
+            "all" => &[AssetType::Crypto, AssetType::Stock, AssetType::Tvl],
+            _ => {
+                return Ok(Json(ApiResponse::error(
+                    "Invalid asset type. Use 'crypto', 'stock', 'tvl' or 'all'".to_string(),
+                )));
+            }
+        };
+        let mut updated = 0;
+        for asset_type in asset_types {
+            let symbols = namespace.symbols(oracle.config(), *asset_type);
+            if !symbols.is_empty() {
+                updated += oracle.update_symbols(*asset_type, &symbols).await?.updated;
+            }
         }
-    } else {
-        return Ok(Json(ApiResponse::error(
-            "Missing authentication token".to_string(),
-        )));
+        return Ok(Json(ApiResponse::success(format!(
+            "Updated {} price feeds of tenant {}",
+            updated, tenant
+        ))));
     }
-    let mut oracle_lock = state.oracle.write().await;
 
     let result = match asset_type.as_str() {
-        "crypto" => oracle_lock.update_crypto_prices().await,
-        "stock" => oracle_lock.update_stock_prices().await,
-        "all" => oracle_lock.update_all_prices().await,
+        "crypto" => oracle.update_crypto_prices().await,
+        "stock" => oracle.update_stock_prices().await,
+        "tvl" => oracle.update_tvl().await,
+        "all" => oracle.update_all_prices().await,
         _ => {
             return Ok(Json(ApiResponse::error(
-                "Invalid asset type. Use 'crypto', 'stock', or 'all'".to_string(),
+                "Invalid asset type. Use 'crypto', 'stock', 'tvl' or 'all'".to_string(),
             )));
         }
     };
 
     match result {
-        Ok(count) => Ok(Json(ApiResponse::success(format!(
+        Ok(report) => Ok(Json(ApiResponse::success(format!(
             "Updated {} price feeds",
-            count
+            report.updated
         )))),
-        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Refetch one configured symbol and return its fresh price, without touching
+// the rest of the feed. Rate limited per token.
+pub async fn refresh_symbol(
+    Path((asset_type, symbol)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<PriceResponse>>, OracleApiError> {
+    // Validate token from header or query parameter
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+    if let Err(retry_after) = state
+        .refresh_limit
+        .check(token.as_deref().unwrap_or_default())
+    {
+        return Err(OracleError::RateLimited {
+            retry_after: Some(retry_after),
+            message: "Too many refreshes for this token, try again later".to_string(),
+        }
+        .into());
+    }
+    // Update on a clone so other requests keep reading while the price is fetched
+    let oracle = state.oracle.read().await.clone();
+
+    let asset_type = match asset_type.as_str() {
+        "crypto" => AssetType::Crypto,
+        "stock" => AssetType::Stock,
+        "tvl" => AssetType::Tvl,
+        "any" => match oracle.resolve_asset_type(&symbol)? {
+            "crypto" => AssetType::Crypto,
+            _ => AssetType::Stock,
+        },
+        _ => {
+            return Ok(Json(ApiResponse::error(
+                "Invalid asset type. Use 'any', 'crypto', 'stock' or 'tvl'".to_string(),
+            )));
+        }
+    };
+    // Only configured symbols, so refreshes cannot grow the feed
+    let canonical = oracle.config().canonical_symbol(asset_type, &symbol);
+    if !oracle
+        .config()
+        .canonical_symbols(asset_type)
+        .contains(&canonical)
+        || !namespace.allows(oracle.config(), asset_type, &canonical)
+    {
+        return Err(OracleError::PriceNotFound(symbol).into());
     }
+
+    oracle.update_symbols(asset_type, &[canonical]).await?;
+    let price_data = match asset_type {
+        AssetType::Crypto => oracle.get_crypto_price(&symbol).await?,
+        AssetType::Stock => oracle.get_stock_price(&symbol).await?,
+        AssetType::Basket => oracle.get_basket_price(&symbol).await?,
+        AssetType::Tvl => oracle.get_tvl(&symbol).await?,
+    };
+
+    let (price_data, fx) = in_requested_currency(&oracle, asset_type, price_data, &query)?;
+    Ok(Json(ApiResponse::success(price_response(
+        &oracle,
+        &symbol,
+        asset_type,
+        &price_data,
+        fx,
+    ))))
 }
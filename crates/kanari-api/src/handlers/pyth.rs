@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{Duration, Utc};
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Oracle;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::tenancy::Namespace;
+
+/// Prices are published as integers scaled by 10^EXPO
+const EXPO: i32 = -8;
+
+/// Window of the exponential moving average in `ema_price`
+const EMA_WINDOW_SECS: i64 = 3600;
+
+#[derive(Serialize)]
+pub struct PythPrice {
+    /// Integer price; the real price is `price * 10^expo`
+    pub price: String,
+    /// Confidence interval in the same units; 0, since prices are not
+    /// published with a confidence
+    pub conf: String,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+#[derive(Serialize)]
+pub struct PythPriceFeed {
+    pub id: String,
+    pub price: PythPrice,
+    pub ema_price: PythPrice,
+}
+
+/// Feed id of a symbol: "crypto:btc" as hex, so ids are stable and map back
+/// to the symbol without a lookup table
+fn feed_id(asset_type: AssetType, symbol: &str) -> String {
+    let name = format!("{}:{}", asset_type.as_str(), symbol.to_lowercase());
+    let mut id = String::with_capacity(name.len() * 2);
+    for byte in name.bytes() {
+        let _ = write!(id, "{:02x}", byte);
+    }
+    id
+}
+
+fn parse_feed_id(id: &str) -> Option<(AssetType, String)> {
+    let hex = id.trim().trim_start_matches("0x");
+    // An odd length leaves a one-digit chunk, which `get` rejects
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let name = String::from_utf8(bytes).ok()?;
+    match name.split_once(':')? {
+        ("crypto", symbol) => Some((AssetType::Crypto, symbol.to_string())),
+        ("stock", symbol) => Some((AssetType::Stock, symbol.to_string())),
+        _ => None,
+    }
+}
+
+fn scaled(value: f64) -> String {
+    ((value * 10f64.powi(-EXPO)).round() as i64).to_string()
+}
+
+fn pyth_price(value: f64, publish_time: i64) -> PythPrice {
+    PythPrice {
+        price: scaled(value),
+        conf: "0".to_string(),
+        expo: EXPO,
+        publish_time,
+    }
+}
+
+/// Exponential moving average of the symbol's history over the last hour, or
+/// `latest` without history
+fn ema(oracle: &Oracle, asset_type: AssetType, symbol: &str, latest: f64) -> f64 {
+    let now = Utc::now();
+    let points = oracle.history(
+        asset_type,
+        symbol,
+        now - Duration::seconds(EMA_WINDOW_SECS),
+        now,
+    );
+    let alpha = 2.0 / (points.len() as f64 + 1.0);
+    points
+        .iter()
+        .map(|point| point.price)
+        .reduce(|average, price| alpha * price + (1.0 - alpha) * average)
+        .unwrap_or(latest)
+}
+
+fn symbol_ids(oracle: &Oracle, namespace: &Namespace) -> Vec<(AssetType, String)> {
+    [AssetType::Crypto, AssetType::Stock]
+        .into_iter()
+        .flat_map(|asset_type| {
+            namespace
+                .symbols(oracle.config(), asset_type)
+                .into_iter()
+                .map(move |symbol| (asset_type, symbol))
+        })
+        .collect()
+}
+
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    params: &[(String, String)],
+) -> Result<Namespace, Response> {
+    let query: HashMap<String, String> = params.iter().cloned().collect();
+    let token = extract_token_from_request(headers, &query);
+    state
+        .auth
+        .check(token.as_deref())
+        .await
+        .map_err(|message| (StatusCode::UNAUTHORIZED, message).into_response())
+}
+
+// Feed ids of every configured symbol, as Pyth's `/api/price_feed_ids`
+pub async fn pyth_price_feed_ids(
+    Query(params): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    let namespace = match authorize(&state, &headers, &params).await {
+        Ok(namespace) => namespace,
+        Err(rejection) => return rejection,
+    };
+    let oracle_lock = state.oracle.read().await;
+
+    let ids: Vec<String> = symbol_ids(&oracle_lock, &namespace)
+        .iter()
+        .map(|(asset_type, symbol)| feed_id(*asset_type, symbol))
+        .collect();
+    Json(ids).into_response()
+}
+
+// Latest prices in the shape of Pyth's `/api/latest_price_feeds?ids[]=...`
+pub async fn pyth_latest_price_feeds(
+    Query(params): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    let namespace = match authorize(&state, &headers, &params).await {
+        Ok(namespace) => namespace,
+        Err(rejection) => return rejection,
+    };
+    let oracle = state.oracle.read().await.clone();
+
+    let ids: Vec<&str> = params
+        .iter()
+        .filter(|(key, _)| key == "ids[]" || key == "ids")
+        .map(|(_, id)| id.as_str())
+        .collect();
+
+    let mut feeds = Vec::new();
+    let mut missing = Vec::new();
+    for id in ids {
+        let Some((asset_type, symbol)) = parse_feed_id(id)
+            .filter(|(asset_type, symbol)| namespace.allows(oracle.config(), *asset_type, symbol))
+        else {
+            missing.push(id);
+            continue;
+        };
+        let result = match asset_type {
+            AssetType::Crypto => oracle.get_crypto_price(&symbol).await,
+            AssetType::Stock => oracle.get_stock_price(&symbol).await,
+            AssetType::Basket => oracle.get_basket_price(&symbol).await,
+            AssetType::Tvl => oracle.get_tvl(&symbol).await,
+        };
+        let Ok(price_data) = result else {
+            missing.push(id);
+            continue;
+        };
+
+        let publish_time = price_data.timestamp.timestamp();
+        let average = ema(&oracle, asset_type, &symbol, price_data.price);
+        feeds.push(PythPriceFeed {
+            id: id.trim().trim_start_matches("0x").to_string(),
+            price: pyth_price(price_data.price, publish_time),
+            ema_price: pyth_price(average, publish_time),
+        });
+    }
+
+    if !missing.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("Price ids not found: {}", missing.join(", ")),
+        )
+            .into_response();
+    }
+    Json(feeds).into_response()
+}
@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+
+use crate::api::UserState;
+use crate::handlers::webhook::authenticated_owner;
+use crate::models::{ApiResponse, CreateReportRequest, ReportInfo, ReportListResponse};
+use crate::reports::ReportFrequency;
+
+fn report_info(row: &PgRow) -> Result<ReportInfo, sqlx::Error> {
+    let last_sent: DateTime<Utc> = row.try_get("last_sent_at")?;
+    let created: DateTime<Utc> = row.try_get("created_at")?;
+    Ok(ReportInfo {
+        id: row.try_get("id")?,
+        frequency: row.try_get("frequency")?,
+        symbols: row.try_get("symbols")?,
+        email: row.try_get("email")?,
+        webhook_id: row.try_get("webhook_id")?,
+        last_sent_at: last_sent.to_rfc3339(),
+        created_at: created.to_rfc3339(),
+    })
+}
+
+// Schedule a daily or weekly market summary for the authenticated user. The
+// first report is sent at the end of the current period.
+pub async fn create_report(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+    Json(payload): Json<CreateReportRequest>,
+) -> Result<Json<ApiResponse<ReportInfo>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let frequency = match payload.frequency.parse::<ReportFrequency>() {
+        Ok(frequency) => frequency,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+    let symbols: Vec<String> = payload
+        .symbols
+        .iter()
+        .map(|symbol| symbol.trim().to_string())
+        .filter(|symbol| !symbol.is_empty())
+        .collect();
+
+    let email = match payload.webhook_id {
+        Some(webhook_id) => {
+            match sqlx::query("SELECT 1 FROM webhooks WHERE id = $1 AND owner = $2")
+                .bind(webhook_id)
+                .bind(&owner)
+                .fetch_optional(&state.db)
+                .await
+            {
+                Ok(Some(_)) => None,
+                Ok(None) => return Ok(Json(ApiResponse::error("Webhook not found".to_string()))),
+                Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+            }
+        }
+        None => {
+            if !state.reports.can_email() {
+                return Ok(Json(ApiResponse::error(
+                    "Email reports are not available on this server; use webhook_id".to_string(),
+                )));
+            }
+            let account_email = match sqlx::query("SELECT email FROM users WHERE username = $1")
+                .bind(&owner)
+                .fetch_one(&state.db)
+                .await
+                .and_then(|row| row.try_get::<Option<String>, _>("email"))
+            {
+                Ok(email) => email,
+                Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+            };
+            match payload.email.clone().or(account_email) {
+                Some(email) => Some(email),
+                None => {
+                    return Ok(Json(ApiResponse::error(
+                        "No email address; set one on your account or pass email".to_string(),
+                    )));
+                }
+            }
+        }
+    };
+
+    match sqlx::query(
+        "INSERT INTO report_schedules (owner, frequency, symbols, email, webhook_id) VALUES ($1, $2, $3, $4, $5) RETURNING id, frequency, symbols, email, webhook_id, last_sent_at, created_at",
+    )
+    .bind(&owner)
+    .bind(frequency.as_str())
+    .bind(&symbols)
+    .bind(email)
+    .bind(payload.webhook_id)
+    .fetch_one(&state.db)
+    .await
+    {
+        Ok(row) => match report_info(&row) {
+            Ok(report) => Ok(Json(ApiResponse::success(report))),
+            Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        },
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// List the authenticated user's scheduled reports
+pub async fn list_reports(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<ReportListResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let rows = match sqlx::query(
+        "SELECT id, frequency, symbols, email, webhook_id, last_sent_at, created_at FROM report_schedules WHERE owner = $1 ORDER BY created_at DESC",
+    )
+    .bind(&owner)
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+
+    match rows
+        .iter()
+        .map(report_info)
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+    {
+        Ok(reports) => Ok(Json(ApiResponse::success(ReportListResponse { reports }))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Cancel one of the authenticated user's scheduled reports
+pub async fn delete_report(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    match sqlx::query("DELETE FROM report_schedules WHERE id = $1 AND owner = $2")
+        .bind(id)
+        .bind(&owner)
+        .execute(&state.db)
+        .await
+    {
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(Json(ApiResponse::error("Report not found".to_string())))
+        }
+        Ok(_) => Ok(Json(ApiResponse::success("Report deleted".to_string()))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
@@ -0,0 +1,375 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use kanari_oracle::fetchers::circuit_breaker::CircuitState;
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Oracle;
+use kanari_oracle::round::SymbolStatus;
+use kanari_oracle::symbols;
+use maud::{DOCTYPE, Markup, html};
+use std::collections::HashMap;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::incidents::ServiceStatus;
+use crate::models::{ApiResponse, ComponentStatus, StatusSummaryResponse};
+
+/// Seconds between automatic reloads of the page
+const REFRESH_SECS: u64 = 15;
+
+/// Errors listed under "Recent errors"
+const MAX_ERRORS: usize = 50;
+
+/// The updater is degraded once no round has completed for this many update
+/// intervals
+const MISSED_ROUNDS: i64 = 3;
+
+const ASSET_TYPES: [AssetType; 4] = [
+    AssetType::Crypto,
+    AssetType::Stock,
+    AssetType::Tvl,
+    AssetType::Basket,
+];
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; }
+h1 { margin-bottom: 0.2rem; }
+h2 { margin-top: 2rem; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+th, td { text-align: left; padding: 0.3rem 0.6rem; border-bottom: 1px solid #ddd; }
+th { background: #f4f4f4; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.muted { color: #777; }
+.ok { color: #1a7f37; font-weight: 600; }
+.warn { color: #9a6700; font-weight: 600; }
+.bad { color: #cf222e; font-weight: 600; }
+.summary span { margin-right: 1.5rem; }
+";
+
+/// One configured symbol and its current price, if any
+struct FeedRow {
+    asset_type: AssetType,
+    symbol: String,
+    price: Option<f64>,
+    source: Option<String>,
+    age_seconds: Option<i64>,
+    is_stale: bool,
+}
+
+/// An entry of "Recent errors"
+struct ErrorRow {
+    at: Option<DateTime<Utc>>,
+    origin: String,
+    message: String,
+}
+
+// Self-hosted HTML dashboard of feed freshness, source health and recent errors
+// (admin only). Browsers pass the token as `?token=`; the page reloads itself
+// every REFRESH_SECS seconds.
+pub async fn status_page(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> (StatusCode, Markup) {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return (
+            StatusCode::UNAUTHORIZED,
+            page("Unauthorized", html! { p.bad { (message) } }),
+        );
+    }
+    let oracle_lock = state.oracle.read().await;
+    let config = oracle_lock.config();
+    let snapshot = oracle_lock.snapshot();
+
+    let mut feeds = Vec::new();
+    for asset_type in ASSET_TYPES {
+        let prices = oracle_lock.round_prices(&snapshot, asset_type);
+        for symbol in config.canonical_symbols(asset_type) {
+            let price_data = prices.get(&symbols::feed_key(asset_type, &symbol));
+            feeds.push(FeedRow {
+                asset_type,
+                symbol,
+                price: price_data.map(|p| p.price),
+                source: price_data.map(|p| p.source.clone()),
+                age_seconds: price_data.map(|p| p.age_seconds),
+                is_stale: price_data.is_some_and(|p| p.is_stale),
+            });
+        }
+    }
+    let missing = feeds.iter().filter(|feed| feed.price.is_none()).count();
+    let stale = feeds.iter().filter(|feed| feed.is_stale).count();
+    let fresh = feeds.len() - missing - stale;
+
+    let sources = oracle_lock.registry().stats();
+    let last_round = oracle_lock.last_round();
+
+    let mut errors: Vec<ErrorRow> = sources
+        .iter()
+        .filter_map(|source| {
+            Some(ErrorRow {
+                at: source.last_error_at,
+                origin: format!("source {}", source.name),
+                message: source.last_error.clone()?,
+            })
+        })
+        .collect();
+    if let Some(report) = &last_round {
+        errors.extend(
+            report
+                .symbols
+                .iter()
+                .filter(|outcome| {
+                    matches!(outcome.status, SymbolStatus::Failed | SymbolStatus::Held)
+                })
+                .map(|outcome| ErrorRow {
+                    at: Some(report.started_at),
+                    origin: format!("{} {}", outcome.asset_type.as_str(), outcome.symbol),
+                    message: outcome.reason.clone().unwrap_or_else(|| {
+                        match outcome.status {
+                            SymbolStatus::Held => "held back as an anomaly",
+                            _ => "no source returned a price",
+                        }
+                        .to_string()
+                    }),
+                }),
+        );
+    }
+    errors.sort_by_key(|error| std::cmp::Reverse(error.at));
+    errors.truncate(MAX_ERRORS);
+
+    let database = state.db_health.as_ref().map(|health| health.is_up());
+    let body = html! {
+        p.muted {
+            "Round " (oracle_lock.round_id())
+            " · last update " (oracle_lock.get_last_update().format("%Y-%m-%d %H:%M:%S UTC"))
+            " · refreshes every " (REFRESH_SECS) "s"
+        }
+        p.summary {
+            span.ok { (fresh) " fresh" }
+            span.warn { (stale) " stale" }
+            span.bad { (missing) " missing" }
+            @if state.drain.is_draining() {
+                span.bad { "draining" }
+            } @else if state.updates.is_paused() {
+                span.warn { "updates paused" }
+            } @else {
+                span.ok { "updating" }
+            }
+            @match database {
+                Some(true) => span.ok { "database up" },
+                Some(false) => span.bad { "database down" },
+                None => {},
+            }
+        }
+
+        h2 { "Feeds" }
+        table {
+            tr { th { "Type" } th { "Symbol" } th { "Price" } th { "Source" } th { "Age" } th { "State" } }
+            @for feed in &feeds {
+                tr {
+                    td { (feed.asset_type.as_str()) }
+                    td { (feed.symbol) }
+                    td.num {
+                        @if let Some(price) = feed.price {
+                            (config.precision(feed.asset_type, &feed.symbol).format(price))
+                        }
+                    }
+                    td { (feed.source.as_deref().unwrap_or("")) }
+                    td.num { @if let Some(age) = feed.age_seconds { (format_age(age)) } }
+                    td {
+                        @if feed.price.is_none() {
+                            span.bad { "missing" }
+                        } @else if feed.is_stale {
+                            span.warn { "stale" }
+                        } @else {
+                            span.ok { "fresh" }
+                        }
+                    }
+                }
+            }
+        }
+
+        h2 { "Sources" }
+        table {
+            tr {
+                th { "Source" } th { "Type" } th { "Circuit" } th { "Error rate" }
+                th { "p50" } th { "p95" } th { "Requests" } th { "Errors" }
+            }
+            @for source in &sources {
+                tr {
+                    td { (source.name) }
+                    td { (source.asset_type.as_str()) }
+                    td {
+                        @match source.circuit {
+                            CircuitState::Closed => span.ok { "closed" },
+                            CircuitState::HalfOpen => span.warn { "half open" },
+                            CircuitState::Open => span.bad { "open" },
+                        }
+                    }
+                    td.num { (format!("{:.1}%", source.error_rate * 100.0)) }
+                    td.num { (source.p50_latency_ms) " ms" }
+                    td.num { (source.p95_latency_ms) " ms" }
+                    td.num { (source.requests_total) }
+                    td.num { (source.errors_total) }
+                }
+            }
+        }
+
+        h2 { "Last round" }
+        @match &last_round {
+            Some(report) => p {
+                (report.started_at.format("%Y-%m-%d %H:%M:%S UTC")) ": "
+                (report.updated) " updated, " (report.failed) " failed, "
+                (report.held) " held, " (report.skipped) " skipped in "
+                (report.duration_ms) " ms"
+            },
+            None => p.muted { "No update round has completed yet" },
+        }
+
+        h2 { "Recent errors" }
+        @if errors.is_empty() {
+            p.muted { "None" }
+        } @else {
+            table {
+                tr { th { "When" } th { "Where" } th { "Error" } }
+                @for error in &errors {
+                    tr {
+                        td { @if let Some(at) = error.at { (at.format("%Y-%m-%d %H:%M:%S")) } }
+                        td { (error.origin) }
+                        td { (error.message) }
+                    }
+                }
+            }
+        }
+    };
+    (StatusCode::OK, page("Kanari Oracle status", body))
+}
+
+// Public summary of component health and incidents, for downstream users to
+// check programmatically whether the service is degraded
+pub async fn status_summary(
+    State(state): State<AppState>,
+) -> Json<ApiResponse<StatusSummaryResponse>> {
+    let oracle_lock = state.oracle.read().await;
+    let mut components = components(&state, &oracle_lock);
+    let incidents = state.incidents.recent();
+
+    // An active incident marks its component, or the whole service, at least
+    // as bad as its impact
+    let mut status = ServiceStatus::Operational;
+    for incident in incidents.iter().filter(|incident| incident.is_active()) {
+        let impact = ServiceStatus::from(incident.impact);
+        let component = components
+            .iter_mut()
+            .find(|component| incident.component.as_deref() == Some(component.name.as_str()));
+        match component {
+            Some(component) if impact > component.status => {
+                component.status = impact;
+                component.detail = Some(incident.title.clone());
+            }
+            Some(_) => {}
+            None => status = status.max(impact),
+        }
+    }
+    let status = components
+        .iter()
+        .map(|component| component.status)
+        .fold(status, ServiceStatus::max);
+
+    Json(ApiResponse::success(StatusSummaryResponse {
+        status,
+        components,
+        incidents,
+        last_update: oracle_lock.get_last_update().to_rfc3339(),
+    }))
+}
+
+/// Health of the sources, the updater and, when there is one, the database
+fn components(state: &AppState, oracle: &Oracle) -> Vec<ComponentStatus> {
+    let sources = oracle.registry().health();
+    let unhealthy: Vec<&str> = sources
+        .iter()
+        .filter(|source| !source.health.healthy)
+        .map(|source| source.name.as_str())
+        .collect();
+    let mut components = vec![ComponentStatus {
+        name: "sources".to_string(),
+        status: match unhealthy.len() {
+            0 => ServiceStatus::Operational,
+            n if n == sources.len() => ServiceStatus::MajorOutage,
+            _ => ServiceStatus::Degraded,
+        },
+        detail: (!unhealthy.is_empty()).then(|| format!("Unhealthy: {}", unhealthy.join(", "))),
+    }];
+
+    let interval = state
+        .updates
+        .interval()
+        .unwrap_or(oracle.config().general.update_interval) as i64;
+    let since_update = (Utc::now() - oracle.get_last_update()).num_seconds();
+    let (status, detail) = if state.drain.is_draining() {
+        (ServiceStatus::Degraded, Some("Draining".to_string()))
+    } else if state.updates.is_paused() {
+        (ServiceStatus::Degraded, Some("Updates paused".to_string()))
+    } else if since_update > interval * MISSED_ROUNDS {
+        (
+            ServiceStatus::Degraded,
+            Some(format!("No update for {}", format_age(since_update))),
+        )
+    } else {
+        (ServiceStatus::Operational, None)
+    };
+    components.push(ComponentStatus {
+        name: "updater".to_string(),
+        status,
+        detail,
+    });
+
+    if let Some(health) = &state.db_health {
+        let up = health.is_up();
+        components.push(ComponentStatus {
+            name: "database".to_string(),
+            // Prices are still served, but accounts and tokens issued since
+            // the outage are not
+            status: if up {
+                ServiceStatus::Operational
+            } else {
+                ServiceStatus::MajorOutage
+            },
+            detail: (!up).then(|| "Unreachable".to_string()),
+        });
+    }
+    components
+}
+
+fn page(title: &str, body: Markup) -> Markup {
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                meta http-equiv="refresh" content=(REFRESH_SECS);
+                title { (title) }
+                style { (STYLE) }
+            }
+            body {
+                h1 { (title) }
+                (body)
+            }
+        }
+    }
+}
+
+/// "42s", "5m 12s" or "3h 7m"
+fn format_age(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}h {}m", s / 3600, s % 3600 / 60),
+    }
+}
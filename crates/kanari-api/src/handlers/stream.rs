@@ -0,0 +1,131 @@
+use axum::{
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Snapshot;
+use kanari_oracle::subscriber::PriceUpdate;
+use std::collections::HashMap;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::models::ApiResponse;
+use crate::tenancy::Namespace;
+
+// Stream every price the oracle publishes over a WebSocket (authenticated). Each
+// text message is one update, `{"round_id": ..., "asset_type": ..., "prices": [...]}`;
+// the current round is sent first, and again whenever the client falls behind.
+pub async fn stream_prices(
+    ws: WebSocketUpgrade,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    let token = extract_token_from_request(&headers, &query);
+
+    let namespace = match state.auth.check(token.as_deref()).await {
+        Ok(namespace) => namespace,
+        Err(message) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error(message)),
+            )
+                .into_response();
+        }
+    };
+
+    ws.on_upgrade(move |socket| send_updates(socket, state, namespace))
+}
+
+async fn send_updates(mut socket: WebSocket, state: AppState, namespace: Namespace) {
+    // Subscribed before the snapshot is taken, so no update falls in between
+    let (mut updates, snapshot) = {
+        let oracle_lock = state.oracle.read().await;
+        (oracle_lock.subscribe(), oracle_lock.snapshot())
+    };
+    if send_snapshot(&mut socket, &state, &namespace, &snapshot)
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let sent = match update {
+                    Ok(update) => send(&mut socket, &state, &namespace, update).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Price stream client fell behind by {} updates", skipped);
+                        let snapshot = state.oracle.read().await.snapshot();
+                        send_snapshot(&mut socket, &state, &namespace, &snapshot).await
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if sent.is_err() {
+                    break;
+                }
+            }
+            // Anything but a close from the client is ignored
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send_snapshot(
+    socket: &mut WebSocket,
+    state: &AppState,
+    namespace: &Namespace,
+    snapshot: &Snapshot,
+) -> Result<(), axum::Error> {
+    for asset_type in [
+        AssetType::Crypto,
+        AssetType::Stock,
+        AssetType::Basket,
+        AssetType::Tvl,
+    ] {
+        let Some(feed) = snapshot.feed(asset_type) else {
+            continue;
+        };
+        if feed.prices.is_empty() {
+            continue;
+        }
+        let update = PriceUpdate {
+            round_id: snapshot.id,
+            asset_type,
+            prices: feed.prices.values().cloned().collect(),
+        };
+        send(socket, state, namespace, update).await?;
+    }
+    Ok(())
+}
+
+/// Send `update` with only the prices served to `namespace`; nothing is sent
+/// when none are left
+async fn send(
+    socket: &mut WebSocket,
+    state: &AppState,
+    namespace: &Namespace,
+    mut update: PriceUpdate,
+) -> Result<(), axum::Error> {
+    if *namespace != Namespace::All {
+        let oracle_lock = state.oracle.read().await;
+        let config = oracle_lock.config();
+        update
+            .prices
+            .retain(|price| namespace.allows(config, update.asset_type, &price.symbol));
+        if update.prices.is_empty() {
+            return Ok(());
+        }
+    }
+    let json = serde_json::to_string(&update).map_err(axum::Error::new)?;
+    socket.send(Message::Text(json.into())).await
+}
@@ -0,0 +1,295 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, header::CONTENT_TYPE},
+    response::Json,
+};
+use futures::stream::{self, StreamExt};
+use kanari_oracle::config::{SymbolAddition, add_symbols_to_file};
+use kanari_oracle::errors::OracleError;
+use kanari_oracle::models::{AssetType, PriceData};
+use kanari_oracle::oracle::Oracle;
+use std::collections::{HashMap, HashSet};
+
+use crate::api::AppState;
+use crate::auth::extract_token_from_request;
+use crate::models::{
+    ApiResponse, SymbolImportEntry, SymbolImportRequest, SymbolImportResponse, SymbolImportResult,
+    SymbolImportStatus,
+};
+
+/// Entries accepted in one import
+const MAX_IMPORT_ENTRIES: usize = 1000;
+
+/// Entries validated against their providers at the same time
+const VALIDATION_CONCURRENCY: usize = 8;
+
+// Parse `asset_type,symbol,sources` lines, with the sources separated by `;`.
+// A header line starting with "asset_type" is skipped.
+fn parse_csv(body: &str) -> Result<Vec<SymbolImportEntry>, String> {
+    let mut entries = Vec::new();
+    for (number, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (number == 0 && line.to_lowercase().starts_with("asset_type")) {
+            continue;
+        }
+        let mut fields = line.split(',').map(str::trim);
+        let (Some(asset_type), Some(symbol)) = (fields.next(), fields.next()) else {
+            return Err(format!(
+                "Line {}: expected asset_type,symbol[,sources]",
+                number + 1
+            ));
+        };
+        let sources = fields
+            .next()
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|source| !source.is_empty())
+            .map(str::to_string)
+            .collect();
+        entries.push(SymbolImportEntry {
+            asset_type: asset_type.to_string(),
+            symbol: symbol.to_string(),
+            sources,
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_body(headers: &HeaderMap, body: &str) -> Result<Vec<SymbolImportEntry>, String> {
+    let is_csv = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("csv"));
+    if is_csv {
+        return parse_csv(body);
+    }
+    serde_json::from_str::<SymbolImportRequest>(body)
+        .map(|request| request.symbols)
+        .map_err(|e| format!("Invalid JSON body: {}", e))
+}
+
+// Price a symbol from its preferred sources in order, or through the usual
+// fallback chain when none are given
+async fn validate_entry(
+    oracle: &Oracle,
+    asset_type: AssetType,
+    symbol: &str,
+    sources: &[String],
+) -> Result<PriceData, OracleError> {
+    if sources.is_empty() {
+        return match asset_type {
+            AssetType::Crypto => oracle.get_crypto_price(symbol).await,
+            AssetType::Stock => oracle.get_stock_price(symbol).await,
+            AssetType::Basket => oracle.get_basket_price(symbol).await,
+            AssetType::Tvl => oracle.get_tvl(symbol).await,
+        };
+    }
+
+    let mut last_error = None;
+    for source in sources {
+        let result = match asset_type {
+            AssetType::Crypto => oracle.get_crypto_price_from(symbol, source).await,
+            AssetType::Stock => oracle.get_stock_price_from(symbol, source).await,
+            AssetType::Basket => oracle.get_basket_price(symbol).await,
+            AssetType::Tvl => oracle.get_tvl_from(symbol, source).await,
+        };
+        match result {
+            Ok(price_data) => return Ok(price_data),
+            // A misspelled source would silently never be used; reject the entry
+            Err(e @ OracleError::UnknownSource { .. }) => return Err(e),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| OracleError::PriceNotFound(symbol.to_string())))
+}
+
+fn result(
+    entry: &SymbolImportEntry,
+    status: SymbolImportStatus,
+    error: Option<String>,
+) -> SymbolImportResult {
+    SymbolImportResult {
+        asset_type: entry.asset_type.to_lowercase(),
+        symbol: entry.symbol.clone(),
+        sources: entry.sources.clone(),
+        status,
+        price: None,
+        source: None,
+        error,
+    }
+}
+
+// Add many symbols at once (admin only). Each entry is priced from its
+// preferred sources, or every source for its asset type, and only entries that
+// price successfully are added. The running oracle picks them up immediately;
+// they are also written to the config file, unless `?dry_run=true`.
+pub async fn import_symbols(
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    body: String,
+) -> Json<ApiResponse<SymbolImportResponse>> {
+    let token = extract_token_from_request(&headers, &query);
+
+    if let Err(message) = state.auth.check_admin(&state.admin, token.as_deref()).await {
+        return Json(ApiResponse::error(message));
+    }
+
+    let entries = match parse_body(&headers, &body) {
+        Ok(entries) => entries,
+        Err(message) => return Json(ApiResponse::error(message)),
+    };
+    if entries.is_empty() {
+        return Json(ApiResponse::error("No symbols to import".to_string()));
+    }
+    if entries.len() > MAX_IMPORT_ENTRIES {
+        return Json(ApiResponse::error(format!(
+            "At most {} symbols can be imported at once",
+            MAX_IMPORT_ENTRIES
+        )));
+    }
+    let dry_run = query.get("dry_run").is_some_and(|v| v == "true");
+
+    let oracle = state.oracle.read().await.clone();
+
+    // Reject malformed entries, duplicates and symbols there is nothing to do for
+    // before any provider is queried
+    let mut results: Vec<Option<SymbolImportResult>> = Vec::with_capacity(entries.len());
+    let mut pending = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let asset_type = match entry.asset_type.to_lowercase().as_str() {
+            "crypto" => AssetType::Crypto,
+            "stock" => AssetType::Stock,
+            _ => {
+                results.push(Some(result(
+                    entry,
+                    SymbolImportStatus::Invalid,
+                    Some("asset_type must be 'crypto' or 'stock'".to_string()),
+                )));
+                continue;
+            }
+        };
+        let symbol = entry.symbol.trim();
+        if symbol.is_empty() {
+            results.push(Some(result(
+                entry,
+                SymbolImportStatus::Invalid,
+                Some("symbol must not be empty".to_string()),
+            )));
+            continue;
+        }
+
+        let canonical = oracle.config().canonical_symbol(asset_type, symbol);
+        if !seen.insert(format!("{}:{}", asset_type.as_str(), canonical)) {
+            results.push(Some(result(entry, SymbolImportStatus::Duplicate, None)));
+            continue;
+        }
+        let configured = oracle
+            .config()
+            .canonical_symbols(asset_type)
+            .contains(&canonical);
+        if configured && entry.sources.is_empty() {
+            results.push(Some(result(
+                entry,
+                SymbolImportStatus::AlreadyConfigured,
+                None,
+            )));
+            continue;
+        }
+
+        results.push(None);
+        pending.push((index, asset_type, symbol.to_string()));
+    }
+
+    let validated: Vec<(usize, AssetType, String, Result<PriceData, OracleError>)> =
+        stream::iter(pending)
+            .map(|(index, asset_type, symbol)| {
+                let oracle = &oracle;
+                let sources = &entries[index].sources;
+                async move {
+                    let outcome = validate_entry(oracle, asset_type, &symbol, sources).await;
+                    (index, asset_type, symbol, outcome)
+                }
+            })
+            .buffered(VALIDATION_CONCURRENCY)
+            .collect()
+            .await;
+
+    let mut additions = Vec::new();
+    for (index, asset_type, symbol, outcome) in validated {
+        let entry = &entries[index];
+        results[index] = Some(match outcome {
+            Ok(price_data) => {
+                additions.push(SymbolAddition {
+                    asset_type,
+                    symbol,
+                    sources: entry.sources.clone(),
+                });
+                let status = if dry_run {
+                    SymbolImportStatus::Validated
+                } else {
+                    SymbolImportStatus::Activated
+                };
+                SymbolImportResult {
+                    price: Some(price_data.price),
+                    source: Some(price_data.source),
+                    ..result(entry, status, None)
+                }
+            }
+            Err(e @ OracleError::UnknownSource { .. }) => {
+                result(entry, SymbolImportStatus::Invalid, Some(e.to_string()))
+            }
+            Err(e) => result(entry, SymbolImportStatus::Unpriced, Some(e.to_string())),
+        });
+    }
+
+    let mut persisted = false;
+    if !dry_run && !additions.is_empty() {
+        // Built from the current config under the write lock, so a reload that
+        // ran during validation is not undone
+        let mut oracle_lock = state.oracle.write().await;
+        let mut config = oracle_lock.config().clone();
+        config.add_symbols(&additions);
+        if let Err(e) = config.validate() {
+            return Json(ApiResponse::error(format!(
+                "Imported symbols would make the config invalid: {}",
+                e
+            )));
+        }
+
+        // Written first so the file watcher does not undo the change
+        if let Some(path) = &state.config_path {
+            if let Err(e) = add_symbols_to_file(path, &additions).await {
+                return Json(ApiResponse::error(format!(
+                    "Failed to update config file: {}",
+                    e
+                )));
+            }
+            persisted = true;
+        }
+        if let Err(e) = oracle_lock.reload_config(config) {
+            return Json(ApiResponse::error(format!(
+                "Failed to apply imported symbols: {}",
+                e
+            )));
+        }
+        log::info!(
+            "Imported {} symbols{}",
+            additions.len(),
+            if persisted {
+                " into the config file"
+            } else {
+                ""
+            }
+        );
+    }
+
+    Json(ApiResponse::success(SymbolImportResponse {
+        dry_run,
+        activated: if dry_run { 0 } else { additions.len() },
+        persisted,
+        results: results.into_iter().flatten().collect(),
+    }))
+}
@@ -10,7 +10,7 @@ use chrono::{DateTime, Utc};
 use rand::rngs::OsRng;
 use sqlx::Row;
 
-use crate::api::AppState;
+use crate::api::UserState;
 use crate::auth::{create_monthly_token, validate_token};
 use crate::models::ChangeEmailRequest;
 use crate::models::{
@@ -22,7 +22,7 @@ use crate::models::{CreateTokenRequest, TokenInfo, TokenListResponse};
 
 // Register a new user and return an API token
 pub async fn register_user(
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<ApiResponse<TokenResponse>>, StatusCode> {
     // hash password using Argon2id with default params
@@ -84,7 +84,7 @@ pub async fn register_user(
 // Change user email (requires current password confirmation)
 pub async fn change_user_email(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(payload): Json<ChangeEmailRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
     let token = headers
@@ -180,7 +180,7 @@ pub async fn change_user_email(
 // List API tokens for the authenticated user
 pub async fn list_user_tokens(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
 ) -> Result<Json<ApiResponse<TokenListResponse>>, StatusCode> {
     let token = headers
         .get(AUTHORIZATION)
@@ -270,7 +270,7 @@ pub async fn list_user_tokens(
 // Create a new API token for the authenticated user
 pub async fn create_user_token(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(_payload): Json<CreateTokenRequest>,
 ) -> Result<Json<ApiResponse<TokenResponse>>, StatusCode> {
     let token = headers
@@ -345,7 +345,7 @@ pub async fn create_user_token(
 // Delete (revoke) a specific token for the authenticated user's account
 pub async fn delete_user_token(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(payload): Json<crate::models::RevokeTokenRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
     let token = headers
@@ -422,7 +422,7 @@ pub async fn delete_user_token(
 
 // Login: validate credentials and return existing/new token
 pub async fn login_user(
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(payload): Json<LoginRequest>,
 ) -> Result<Json<ApiResponse<TokenResponse>>, StatusCode> {
     let row = match sqlx::query("SELECT password_hash FROM users WHERE username = $1")
@@ -507,7 +507,7 @@ pub async fn login_user(
 // List all users (admin endpoint - requires valid token)
 pub async fn list_users(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
 ) -> Result<Json<ApiResponse<UserListResponse>>, StatusCode> {
     let token = headers
         .get(AUTHORIZATION)
@@ -624,7 +624,7 @@ pub async fn list_users(
 // Get current user profile
 pub async fn get_user_profile(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
 ) -> Result<Json<ApiResponse<UserProfile>>, StatusCode> {
     let token = headers
         .get(AUTHORIZATION)
@@ -712,7 +712,7 @@ pub async fn get_user_profile(
 // Delete user account (requires password confirmation)
 pub async fn delete_user_account(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(payload): Json<DeleteAccountRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
     let token = headers
@@ -805,7 +805,7 @@ pub async fn delete_user_account(
 // Change user password (requires current password confirmation)
 pub async fn change_user_password(
     headers: HeaderMap,
-    State(state): State<AppState>,
+    State(state): State<UserState>,
     Json(payload): Json<ChangePasswordRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
     let token = headers
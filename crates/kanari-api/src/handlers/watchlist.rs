@@ -0,0 +1,365 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Oracle;
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+
+use crate::api::UserState;
+use crate::handlers::custom_symbol::registered_symbols;
+use crate::handlers::grafana::parse_target;
+use crate::handlers::webhook::authenticated_owner;
+use crate::models::{
+    ApiResponse, CreateWatchlistRequest, PriceResponse, WatchlistInfo, WatchlistListResponse,
+    WatchlistPricesResponse, WatchlistSymbolsRequest,
+};
+use crate::tenancy::Namespace;
+
+/// Entries a single watchlist may hold
+const MAX_WATCHLIST_SYMBOLS: usize = 200;
+
+fn watchlist_info(row: &PgRow) -> Result<WatchlistInfo, sqlx::Error> {
+    let created: DateTime<Utc> = row.try_get("created_at")?;
+    Ok(WatchlistInfo {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        symbols: row.try_get("symbols")?,
+        created_at: created.to_rfc3339(),
+    })
+}
+
+// Normalize entries to lowercase without duplicates, rejecting any that are
+// neither configured and served to `namespace` nor among the owner's
+// `registered` symbols
+fn validate_symbols(
+    oracle: &Oracle,
+    namespace: &Namespace,
+    symbols: &[String],
+    registered: &[String],
+) -> Result<Vec<String>, String> {
+    let mut entries: Vec<String> = Vec::new();
+    for symbol in symbols {
+        let entry = symbol.trim().to_lowercase();
+        if entry.is_empty() || entries.contains(&entry) {
+            continue;
+        }
+        let configured = parse_target(oracle, &entry).is_some_and(|(asset_type, symbol)| {
+            let canonical = oracle.config().canonical_symbol(asset_type, &symbol);
+            let configured = match asset_type {
+                AssetType::Crypto => oracle.get_crypto_symbols().contains(&canonical),
+                AssetType::Stock => oracle.get_stock_symbols().contains(&canonical),
+                AssetType::Basket => oracle.config().basket(&canonical).is_some(),
+                AssetType::Tvl => oracle.get_tvl_symbols().contains(&canonical),
+            };
+            configured && namespace.allows(oracle.config(), asset_type, &symbol)
+        });
+        if !configured && !registered.contains(&entry) {
+            return Err(format!(
+                "Unknown or ambiguous symbol '{}'; use a configured symbol, e.g. crypto:btc",
+                symbol
+            ));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// Create a named watchlist for the authenticated user
+pub async fn create_watchlist(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+    Json(payload): Json<CreateWatchlistRequest>,
+) -> Result<Json<ApiResponse<WatchlistInfo>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Ok(Json(ApiResponse::error(
+            "name must not be empty".to_string(),
+        )));
+    }
+    let registered = match registered_symbols(&state, &owner).await {
+        Ok(registered) => registered,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    let symbols = {
+        let oracle = state.oracle.read().await;
+        match validate_symbols(
+            &oracle,
+            &Namespace::User(owner.clone()),
+            &payload.symbols,
+            &registered,
+        ) {
+            Ok(symbols) => symbols,
+            Err(message) => return Ok(Json(ApiResponse::error(message))),
+        }
+    };
+    if symbols.len() > MAX_WATCHLIST_SYMBOLS {
+        return Ok(Json(ApiResponse::error(format!(
+            "A watchlist holds at most {} symbols",
+            MAX_WATCHLIST_SYMBOLS
+        ))));
+    }
+
+    match sqlx::query(
+        "INSERT INTO watchlists (owner, name, symbols) VALUES ($1, $2, $3) ON CONFLICT (owner, name) DO NOTHING RETURNING id, name, symbols, created_at",
+    )
+    .bind(&owner)
+    .bind(name)
+    .bind(&symbols)
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(Some(row)) => match watchlist_info(&row) {
+            Ok(watchlist) => Ok(Json(ApiResponse::success(watchlist))),
+            Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        },
+        Ok(None) => Ok(Json(ApiResponse::error(format!(
+            "You already have a watchlist named '{}'",
+            name
+        )))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// List the authenticated user's watchlists
+pub async fn list_watchlists(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<WatchlistListResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let rows = match sqlx::query(
+        "SELECT id, name, symbols, created_at FROM watchlists WHERE owner = $1 ORDER BY name",
+    )
+    .bind(&owner)
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+
+    match rows
+        .iter()
+        .map(watchlist_info)
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+    {
+        Ok(watchlists) => Ok(Json(ApiResponse::success(WatchlistListResponse {
+            watchlists,
+        }))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Delete one of the authenticated user's watchlists
+pub async fn delete_watchlist(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    match sqlx::query("DELETE FROM watchlists WHERE id = $1 AND owner = $2")
+        .bind(id)
+        .bind(&owner)
+        .execute(&state.db)
+        .await
+    {
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(Json(ApiResponse::error("Watchlist not found".to_string())))
+        }
+        Ok(_) => Ok(Json(ApiResponse::success("Watchlist deleted".to_string()))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Append symbols to one of the authenticated user's watchlists; symbols already
+// on the list are skipped
+pub async fn add_watchlist_symbols(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<UserState>,
+    Json(payload): Json<WatchlistSymbolsRequest>,
+) -> Result<Json<ApiResponse<WatchlistInfo>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let registered = match registered_symbols(&state, &owner).await {
+        Ok(registered) => registered,
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+    let symbols = {
+        let oracle = state.oracle.read().await;
+        match validate_symbols(
+            &oracle,
+            &Namespace::User(owner.clone()),
+            &payload.symbols,
+            &registered,
+        ) {
+            Ok(symbols) => symbols,
+            Err(message) => return Ok(Json(ApiResponse::error(message))),
+        }
+    };
+
+    // The row is locked while the new list is built, so concurrent additions
+    // neither drop each other's symbols nor push the list past the limit
+    match sqlx::query(
+        "WITH merged AS (SELECT id, array_cat(symbols, ARRAY(SELECT s FROM unnest($3::text[]) WITH ORDINALITY AS t(s, n) WHERE s <> ALL(symbols) ORDER BY n)) AS symbols FROM watchlists WHERE id = $1 AND owner = $2 FOR UPDATE) UPDATE watchlists SET symbols = merged.symbols FROM merged WHERE watchlists.id = merged.id AND cardinality(merged.symbols) <= $4 RETURNING watchlists.id, watchlists.name, watchlists.symbols, watchlists.created_at",
+    )
+    .bind(id)
+    .bind(&owner)
+    .bind(&symbols)
+    .bind(MAX_WATCHLIST_SYMBOLS as i32)
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(Some(row)) => match watchlist_info(&row) {
+            Ok(watchlist) => Ok(Json(ApiResponse::success(watchlist))),
+            Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        },
+        Ok(None) => Ok(Json(ApiResponse::error(format!(
+            "Watchlist not found, or it would exceed {} symbols",
+            MAX_WATCHLIST_SYMBOLS
+        )))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Remove a symbol from one of the authenticated user's watchlists
+pub async fn remove_watchlist_symbol(
+    headers: HeaderMap,
+    Path((id, symbol)): Path<(i32, String)>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<WatchlistInfo>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    match sqlx::query(
+        "UPDATE watchlists SET symbols = array_remove(symbols, $3) WHERE id = $1 AND owner = $2 RETURNING id, name, symbols, created_at",
+    )
+    .bind(id)
+    .bind(&owner)
+    .bind(symbol.trim().to_lowercase())
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(Some(row)) => match watchlist_info(&row) {
+            Ok(watchlist) => Ok(Json(ApiResponse::success(watchlist))),
+            Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+        },
+        Ok(None) => Ok(Json(ApiResponse::error("Watchlist not found".to_string()))),
+        Err(e) => Ok(Json(ApiResponse::error(e.to_string()))),
+    }
+}
+
+// Current quotes for every symbol on one of the authenticated user's
+// watchlists, in the list's order
+pub async fn get_watchlist_prices(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<WatchlistPricesResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let watchlist = match sqlx::query(
+        "SELECT id, name, symbols, created_at FROM watchlists WHERE id = $1 AND owner = $2",
+    )
+    .bind(id)
+    .bind(&owner)
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(Some(row)) => match watchlist_info(&row) {
+            Ok(watchlist) => watchlist,
+            Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+        },
+        Ok(None) => return Ok(Json(ApiResponse::error("Watchlist not found".to_string()))),
+        Err(e) => return Ok(Json(ApiResponse::error(e.to_string()))),
+    };
+
+    let oracle = state.oracle.read().await.clone();
+    let (prices, unavailable) =
+        quote_entries(&oracle, &Namespace::User(owner), watchlist.symbols).await;
+
+    Ok(Json(ApiResponse::success(WatchlistPricesResponse {
+        id: watchlist.id,
+        name: watchlist.name,
+        prices,
+        unavailable,
+    })))
+}
+
+/// Current quotes for entries such as "btc" or "crypto:btc", in order, and the
+/// entries that could not be priced. Configured symbols not served to
+/// `namespace` count as unavailable.
+pub(crate) async fn quote_entries(
+    oracle: &Oracle,
+    namespace: &Namespace,
+    entries: Vec<String>,
+) -> (Vec<PriceResponse>, Vec<String>) {
+    let mut prices = Vec::with_capacity(entries.len());
+    let mut unavailable = Vec::new();
+    for entry in entries {
+        let Some((asset_type, symbol)) = parse_target(oracle, &entry) else {
+            unavailable.push(entry);
+            continue;
+        };
+        let configured = oracle
+            .config()
+            .canonical_symbols(asset_type)
+            .contains(&oracle.config().canonical_symbol(asset_type, &symbol));
+        if configured && !namespace.allows(oracle.config(), asset_type, &symbol) {
+            unavailable.push(entry);
+            continue;
+        }
+        let result = match asset_type {
+            AssetType::Crypto => oracle.get_crypto_price(&symbol).await,
+            AssetType::Stock => oracle.get_stock_price(&symbol).await,
+            AssetType::Basket => oracle.get_basket_price(&symbol).await,
+            AssetType::Tvl => oracle.get_tvl(&symbol).await,
+        };
+        match result {
+            Ok(price_data) => prices.push(PriceResponse {
+                symbol: symbol.to_uppercase(),
+                price: oracle
+                    .config()
+                    .api_price(asset_type, &symbol, price_data.price),
+                currency: oracle.config().quote_currency(asset_type),
+                timestamp: price_data.timestamp.to_rfc3339(),
+                source: price_data.source.clone(),
+                asset_type: asset_type.as_str().to_string(),
+                age_seconds: price_data.age_seconds,
+                is_stale: price_data.is_stale,
+                market_state: price_data.market_state,
+                session: price_data.session,
+                market_cap: price_data.market_cap,
+                circulating_supply: price_data.circulating_supply,
+                total_supply: price_data.total_supply,
+                depth: price_data.depth.clone(),
+                fx: None,
+            }),
+            Err(_) => unavailable.push(entry),
+        }
+    }
+    (prices, unavailable)
+}
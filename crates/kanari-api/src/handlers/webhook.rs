@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+};
+
+use crate::api::UserState;
+use crate::auth::token_owner;
+use crate::models::{
+    ApiResponse, CreateWebhookRequest, DeliveryQuery, WebhookDeliveryListResponse, WebhookInfo,
+    WebhookListResponse,
+};
+use crate::webhooks;
+
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 500;
+
+// Username of the bearer token's owner
+pub(crate) async fn authenticated_owner(
+    headers: &HeaderMap,
+    state: &UserState,
+) -> Result<String, String> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .map(|s| s.trim())
+        .ok_or_else(|| "Missing Authorization header".to_string())?;
+
+    token_owner(&state.db, token)
+        .await
+        .ok_or_else(|| "Invalid or expired token".to_string())
+}
+
+// Register a webhook for the authenticated user; the response is the only time
+// its signing secret is returned
+pub async fn create_webhook(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<Json<ApiResponse<WebhookInfo>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let webhook = match webhooks::register(&state.db, &owner, &payload).await {
+        Ok(webhook) => webhook,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    if let Err(e) = state.webhooks.reload().await {
+        log::warn!("Failed to reload webhooks: {}", e);
+    }
+    Ok(Json(ApiResponse::success(webhook)))
+}
+
+// List the authenticated user's webhooks
+pub async fn list_webhooks(
+    headers: HeaderMap,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<WebhookListResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    match webhooks::registered(&state.db, &owner).await {
+        Ok(webhooks) => Ok(Json(ApiResponse::success(WebhookListResponse { webhooks }))),
+        Err(message) => Ok(Json(ApiResponse::error(message))),
+    }
+}
+
+// Delete one of the authenticated user's webhooks, with its delivery log
+pub async fn delete_webhook(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    match webhooks::unregister(&state.db, &owner, id).await {
+        Ok(true) => {}
+        Ok(false) => return Ok(Json(ApiResponse::error("Webhook not found".to_string()))),
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    }
+
+    if let Err(e) = state.webhooks.reload().await {
+        log::warn!("Failed to reload webhooks: {}", e);
+    }
+    Ok(Json(ApiResponse::success("Webhook deleted".to_string())))
+}
+
+// Most recent delivery attempts of one of the authenticated user's webhooks
+pub async fn list_webhook_deliveries(
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    Query(query): Query<DeliveryQuery>,
+    State(state): State<UserState>,
+) -> Result<Json<ApiResponse<WebhookDeliveryListResponse>>, StatusCode> {
+    let owner = match authenticated_owner(&headers, &state).await {
+        Ok(owner) => owner,
+        Err(message) => return Ok(Json(ApiResponse::error(message))),
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, MAX_DELIVERY_LIMIT);
+    match webhooks::deliveries(&state.db, &owner, id, limit).await {
+        Ok(Some(deliveries)) => Ok(Json(ApiResponse::success(WebhookDeliveryListResponse {
+            deliveries,
+        }))),
+        Ok(None) => Ok(Json(ApiResponse::error("Webhook not found".to_string()))),
+        Err(message) => Ok(Json(ApiResponse::error(message))),
+    }
+}
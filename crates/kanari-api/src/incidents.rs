@@ -0,0 +1,323 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::database::{DbHealth, DbPool};
+
+/// How often incidents are reloaded, so ones recorded through another replica
+/// are picked up
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Resolved incidents stay listed on `/status.json` this long
+const RESOLVED_RETENTION_DAYS: i64 = 7;
+
+/// Incidents kept in memory and listed, newest first
+const MAX_INCIDENTS: usize = 50;
+
+/// State of the service or one of its components on `/status.json`, from
+/// best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceStatus {
+    Operational,
+    Degraded,
+    MajorOutage,
+}
+
+impl From<Impact> for ServiceStatus {
+    fn from(impact: Impact) -> Self {
+        match impact {
+            Impact::Degraded => ServiceStatus::Degraded,
+            Impact::MajorOutage => ServiceStatus::MajorOutage,
+        }
+    }
+}
+
+/// How badly an incident affects the service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    Degraded,
+    MajorOutage,
+}
+
+impl Impact {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Impact::Degraded => "degraded",
+            Impact::MajorOutage => "major_outage",
+        }
+    }
+}
+
+impl FromStr for Impact {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "degraded" => Ok(Self::Degraded),
+            "major_outage" => Ok(Self::MajorOutage),
+            _ => Err(format!(
+                "unknown incident impact '{}', expected degraded or major_outage",
+                s
+            )),
+        }
+    }
+}
+
+/// Progress of an incident, as in most status pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentStatus {
+    Investigating,
+    Identified,
+    Monitoring,
+    Resolved,
+}
+
+impl IncidentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentStatus::Investigating => "investigating",
+            IncidentStatus::Identified => "identified",
+            IncidentStatus::Monitoring => "monitoring",
+            IncidentStatus::Resolved => "resolved",
+        }
+    }
+}
+
+impl FromStr for IncidentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "investigating" => Ok(Self::Investigating),
+            "identified" => Ok(Self::Identified),
+            "monitoring" => Ok(Self::Monitoring),
+            "resolved" => Ok(Self::Resolved),
+            _ => Err(format!(
+                "unknown incident status '{}', expected investigating, identified, monitoring or resolved",
+                s
+            )),
+        }
+    }
+}
+
+/// An incident recorded through `/admin/incidents`
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub id: i64,
+    pub title: String,
+    pub impact: Impact,
+    pub status: IncidentStatus,
+    /// Component affected, e.g. "sources" or "database"; None for the whole service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    /// Latest update for readers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl Incident {
+    pub fn is_active(&self) -> bool {
+        self.status != IncidentStatus::Resolved
+    }
+
+    fn from_row(row: &PgRow) -> anyhow::Result<Self> {
+        let impact: String = row.try_get("impact")?;
+        let status: String = row.try_get("status")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            title: row.try_get("title")?,
+            impact: impact.parse().map_err(anyhow::Error::msg)?,
+            status: status.parse().map_err(anyhow::Error::msg)?,
+            component: row.try_get("component")?,
+            message: row.try_get("message")?,
+            started_at: row.try_get("started_at")?,
+            updated_at: row.try_get("updated_at")?,
+            resolved_at: row.try_get("resolved_at")?,
+        })
+    }
+}
+
+/// A new incident
+pub struct NewIncident {
+    pub title: String,
+    pub impact: Impact,
+    pub component: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Incidents operators record through `/admin/incidents`, served on
+/// `/status.json`. With a database they are stored in the `incidents` table and
+/// shared by every replica; without one they live in memory until restart.
+#[derive(Clone)]
+pub struct Incidents {
+    db: Option<DbPool>,
+    /// Active incidents, and those resolved in the last
+    /// [`RESOLVED_RETENTION_DAYS`], newest first
+    recent: Arc<RwLock<Vec<Incident>>>,
+}
+
+impl Incidents {
+    pub fn new(db: Option<DbPool>) -> Self {
+        Self {
+            db,
+            recent: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Incidents to list, newest first
+    pub fn recent(&self) -> Vec<Incident> {
+        self.recent.read().unwrap().clone()
+    }
+
+    /// Read the recent incidents from the database, if there is one
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+        let rows = sqlx::query(
+            "SELECT id, title, impact, status, component, message, started_at, updated_at, resolved_at \
+             FROM incidents \
+             WHERE resolved_at IS NULL OR resolved_at > NOW() - make_interval(days => $1) \
+             ORDER BY started_at DESC LIMIT $2",
+        )
+        .bind(RESOLVED_RETENTION_DAYS as i32)
+        .bind(MAX_INCIDENTS as i64)
+        .fetch_all(db)
+        .await?;
+
+        let incidents = rows
+            .iter()
+            .map(Incident::from_row)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        *self.recent.write().unwrap() = incidents;
+        Ok(())
+    }
+
+    /// Reload incidents every [`RELOAD_INTERVAL`] while the database is up,
+    /// until the task is aborted
+    pub fn spawn(self, health: DbHealth) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if health.is_up()
+                    && let Err(e) = self.reload().await
+                {
+                    log::warn!("Failed to reload incidents: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Record a new incident, under investigation from now
+    pub async fn open(&self, incident: NewIncident) -> anyhow::Result<Incident> {
+        let now = Utc::now();
+        let mut opened = Incident {
+            id: 0,
+            title: incident.title,
+            impact: incident.impact,
+            status: IncidentStatus::Investigating,
+            component: incident.component,
+            message: incident.message,
+            started_at: now,
+            updated_at: now,
+            resolved_at: None,
+        };
+        match &self.db {
+            Some(db) => {
+                opened.id = sqlx::query_scalar(
+                    "INSERT INTO incidents (title, impact, status, component, message, started_at, updated_at) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $6) RETURNING id",
+                )
+                .bind(&opened.title)
+                .bind(opened.impact.as_str())
+                .bind(opened.status.as_str())
+                .bind(&opened.component)
+                .bind(&opened.message)
+                .bind(now)
+                .fetch_one(db)
+                .await?;
+            }
+            None => {
+                let recent = self.recent.read().unwrap();
+                opened.id = recent.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+            }
+        }
+
+        let mut recent = self.recent.write().unwrap();
+        recent.insert(0, opened.clone());
+        recent.truncate(MAX_INCIDENTS);
+        Ok(opened)
+    }
+
+    /// Move an incident to `status`, replacing its message when one is given.
+    /// Resolving stamps `resolved_at`; None when there is no such incident.
+    pub async fn update(
+        &self,
+        id: i64,
+        status: IncidentStatus,
+        message: Option<String>,
+    ) -> anyhow::Result<Option<Incident>> {
+        let now = Utc::now();
+        let resolved_at = (status == IncidentStatus::Resolved).then_some(now);
+        let updated = match &self.db {
+            Some(db) => {
+                let row = sqlx::query(
+                    "UPDATE incidents SET status = $2, message = COALESCE($3, message), \
+                     updated_at = $4, resolved_at = $5 WHERE id = $1 \
+                     RETURNING id, title, impact, status, component, message, started_at, updated_at, resolved_at",
+                )
+                .bind(id)
+                .bind(status.as_str())
+                .bind(&message)
+                .bind(now)
+                .bind(resolved_at)
+                .fetch_optional(db)
+                .await?;
+                match row {
+                    Some(row) => Incident::from_row(&row)?,
+                    None => return Ok(None),
+                }
+            }
+            None => {
+                let recent = self.recent.read().unwrap();
+                let Some(incident) = recent.iter().find(|incident| incident.id == id) else {
+                    return Ok(None);
+                };
+                Incident {
+                    status,
+                    message: message.or_else(|| incident.message.clone()),
+                    updated_at: now,
+                    resolved_at,
+                    ..incident.clone()
+                }
+            }
+        };
+
+        let mut recent = self.recent.write().unwrap();
+        match recent.iter_mut().find(|incident| incident.id == id) {
+            Some(incident) => *incident = updated.clone(),
+            None => {
+                recent.push(updated.clone());
+                recent.sort_by_key(|incident| std::cmp::Reverse(incident.started_at));
+            }
+        }
+        // Without a database nothing else prunes resolved incidents
+        let cutoff = now - ChronoDuration::days(RESOLVED_RETENTION_DAYS);
+        recent.retain(|incident| incident.resolved_at.is_none_or(|at| at > cutoff));
+        Ok(Some(updated))
+    }
+}
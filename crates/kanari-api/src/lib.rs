@@ -1,5 +1,16 @@
 pub mod api;
 pub mod auth;
+pub mod billing;
+pub mod cache;
+pub mod control;
 pub mod database;
+pub mod drain;
+pub mod errors;
 pub mod handlers;
+pub mod incidents;
 pub mod models;
+pub mod refresh_limit;
+pub mod reports;
+pub mod signing;
+pub mod tenancy;
+pub mod webhooks;
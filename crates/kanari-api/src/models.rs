@@ -1,10 +1,22 @@
+use chrono::{DateTime, Utc};
+use kanari_oracle::config::Basket;
+use kanari_oracle::fx::Conversion;
+use kanari_oracle::history::HistoryPoint;
+use kanari_oracle::market::MarketState;
+use kanari_oracle::models::OrderBookDepth;
+use kanari_oracle::sources::SourceStatus;
 use serde::{Deserialize, Serialize};
 
+use crate::incidents::{Incident, ServiceStatus};
+
 #[derive(Serialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// Machine-readable error class, e.g. "rate_limited" or "invalid_symbol"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
 }
 
 impl<T> ApiResponse<T> {
@@ -13,6 +25,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
         }
     }
 
@@ -21,6 +34,16 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message),
+            code: None,
+        }
+    }
+
+    pub fn error_with_code(message: String, code: &'static str) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(message),
+            code: Some(code),
         }
     }
 }
@@ -29,8 +52,45 @@ impl<T> ApiResponse<T> {
 pub struct PriceResponse {
     pub symbol: String,
     pub price: f64,
+    /// Lowercase code of the currency `price` is in
+    pub currency: String,
     pub timestamp: String,
+    /// Provider the price was fetched from
+    pub source: String,
+    pub asset_type: String,
+    pub age_seconds: i64,
+    pub is_stale: bool,
+    /// US market session for stock prices ("pre", "open", "post", "closed")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_state: Option<MarketState>,
+    /// Session the stock price was traded in ("pre", "open", "post")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<MarketState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap: Option<f64>,
+    /// Coins in circulation, for crypto prices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circulating_supply: Option<f64>,
+    /// Coins in existence, including locked ones, for crypto prices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_supply: Option<f64>,
+    /// Order book liquidity within ±2% of the mid price, for crypto prices
+    /// when `crypto.order_book_depth` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<OrderBookDepth>,
+    /// Rate the price was converted at, when `?currency=` asked for a currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fx: Option<Conversion>,
+}
+
+/// Price history of one symbol, as returned by `/history/{asset_type}/{symbol}`
+#[derive(Serialize, Deserialize)]
+pub struct HistoryResponse {
+    pub symbol: String,
     pub asset_type: String,
+    /// Whether prices before splits and dividends were adjusted for them
+    pub adjusted: bool,
+    pub points: Vec<HistoryPoint>,
 }
 
 #[derive(Serialize)]
@@ -38,6 +98,92 @@ pub struct HealthResponse {
     pub status: String,
     pub last_update: String,
     pub total_symbols: usize,
+    pub sources: Vec<SourceStatus>,
+    /// "up" or "down"; absent when the server runs without a database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+}
+
+/// One component on `/status.json`
+#[derive(Serialize)]
+pub struct ComponentStatus {
+    /// "sources", "updater" or "database"
+    pub name: String,
+    pub status: ServiceStatus,
+    /// Why the component is not operational
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct StatusSummaryResponse {
+    /// Worst of the components and active incidents
+    pub status: ServiceStatus,
+    pub components: Vec<ComponentStatus>,
+    /// Active incidents, and those resolved in the last 7 days, newest first
+    pub incidents: Vec<Incident>,
+    pub last_update: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateIncidentRequest {
+    pub title: String,
+    /// "degraded" (default) or "major_outage"
+    pub impact: Option<String>,
+    /// Affected component, e.g. "sources"; omitted for the whole service
+    pub component: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateIncidentRequest {
+    /// "investigating", "identified", "monitoring" or "resolved"
+    pub status: String,
+    /// Replaces the incident's message when given
+    pub message: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// "ready", "warming" (no prices yet) or "draining"
+    pub status: String,
+}
+
+#[derive(Serialize)]
+pub struct DrainResponse {
+    /// False when the server was already draining
+    pub started: bool,
+}
+
+/// State of background updates, as changed through `/admin/updates`
+#[derive(Serialize)]
+pub struct UpdateControlResponse {
+    pub paused: bool,
+    /// Interval set at runtime, in seconds; absent while the configured one applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_override: Option<u64>,
+    /// `general.update_interval` of the loaded config
+    pub config_interval: u64,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateIntervalRequest {
+    /// New interval in seconds, or null to go back to the configured one
+    pub seconds: Option<u64>,
+}
+
+/// Build identification for deployment checks
+#[derive(Serialize)]
+pub struct VersionResponse {
+    pub version: String,
+    pub git_sha: String,
+    /// RFC 3339
+    pub build_time: String,
+    /// Optional functionality enabled in this process, e.g. "store:redis" or "ha"
+    pub features: Vec<String>,
+    /// Hex Ed25519 public key price responses are signed with, when signing is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -135,3 +281,288 @@ pub struct ChangeEmailRequest {
     pub current_password: String,
     pub new_email: Option<String>,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Symbols to notify about; omitted or empty for every symbol
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// "update", "threshold" and/or "staleness"
+    pub events: Vec<String>,
+    /// Required with "threshold": percent move that triggers an event
+    pub threshold_percent: Option<f64>,
+    /// Required with "staleness": age in seconds after which a price is stale
+    pub stale_after_secs: Option<i64>,
+    /// Signing secret; generated when omitted
+    pub secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebhookInfo {
+    pub id: i32,
+    pub url: String,
+    pub symbols: Vec<String>,
+    pub events: Vec<String>,
+    pub threshold_percent: Option<f64>,
+    pub stale_after_secs: Option<i64>,
+    /// Only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebhookListResponse {
+    pub webhooks: Vec<WebhookInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct DeliveryQuery {
+    /// Most recent deliveries to return (default 50, at most 500)
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub event: String,
+    /// Absent for summary reports
+    pub asset_type: Option<String>,
+    pub symbol: Option<String>,
+    /// HTTP status of the callback's response; absent when none arrived
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub delivered_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebhookDeliveryListResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateReportRequest {
+    /// "daily" or "weekly"
+    pub frequency: String,
+    /// Symbols to summarize; omitted or empty for every configured symbol
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Deliver to one of your webhooks instead of by email
+    pub webhook_id: Option<i32>,
+    /// Email address; defaults to the account's email
+    pub email: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReportInfo {
+    pub id: i32,
+    pub frequency: String,
+    pub symbols: Vec<String>,
+    pub email: Option<String>,
+    pub webhook_id: Option<i32>,
+    pub last_sent_at: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct ReportListResponse {
+    pub reports: Vec<ReportInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct SymbolImportEntry {
+    /// "crypto" or "stock"
+    pub asset_type: String,
+    pub symbol: String,
+    /// Sources to try first, in order; validated and written to `source_preferences`
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SymbolImportRequest {
+    pub symbols: Vec<SymbolImportEntry>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolImportStatus {
+    /// Priced and added to the config
+    Activated,
+    /// Priced; not added because of `dry_run`
+    Validated,
+    /// Already configured, and no sources were given to change
+    AlreadyConfigured,
+    /// Same symbol as an earlier entry of the import
+    Duplicate,
+    /// Unknown asset type or source, or an empty symbol
+    Invalid,
+    /// No source could price the symbol
+    Unpriced,
+}
+
+#[derive(Serialize)]
+pub struct SymbolImportResult {
+    pub asset_type: String,
+    pub symbol: String,
+    pub sources: Vec<String>,
+    pub status: SymbolImportStatus,
+    /// Price fetched while validating
+    pub price: Option<f64>,
+    pub source: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SymbolImportResponse {
+    pub dry_run: bool,
+    /// Symbols added to the running config
+    pub activated: usize,
+    /// Whether the config file was updated too
+    pub persisted: bool,
+    /// One result per entry, in the order given
+    pub results: Vec<SymbolImportResult>,
+}
+
+#[derive(Serialize)]
+pub struct BasketUpdateResponse {
+    /// Every basket after the change
+    pub baskets: Vec<Basket>,
+    /// Whether the config file was updated too
+    pub persisted: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CreateCustomSymbolRequest {
+    /// "crypto" or "stock"
+    pub asset_type: String,
+    pub symbol: String,
+}
+
+#[derive(Serialize)]
+pub struct CustomSymbolInfo {
+    pub id: i32,
+    pub asset_type: String,
+    pub symbol: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct CustomSymbolListResponse {
+    pub symbols: Vec<CustomSymbolInfo>,
+    /// Symbols each user may register
+    pub limit: i64,
+}
+
+#[derive(Serialize)]
+pub struct CustomSymbolPricesResponse {
+    pub prices: Vec<PriceResponse>,
+    /// Registered symbols no source could price
+    pub unavailable: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BillingStatusResponse {
+    /// Plan of the active subscription; null on the free quota
+    pub plan: Option<String>,
+    /// Stripe status of the latest subscription, e.g. "active" or "canceled"
+    pub status: Option<String>,
+    pub price_id: Option<String>,
+    pub current_period_end: Option<String>,
+    /// Requests per UTC day; null is unlimited
+    pub daily_quota: Option<u64>,
+    pub used_today: u64,
+}
+
+#[derive(Deserialize)]
+pub struct CreateWatchlistRequest {
+    pub name: String,
+    /// Initial entries such as "btc", "crypto:btc" or "stock:AAPL"
+    #[serde(default)]
+    pub symbols: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WatchlistSymbolsRequest {
+    pub symbols: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct WatchlistInfo {
+    pub id: i32,
+    pub name: String,
+    pub symbols: Vec<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct WatchlistListResponse {
+    pub watchlists: Vec<WatchlistInfo>,
+}
+
+#[derive(Serialize)]
+pub struct WatchlistPricesResponse {
+    pub id: i32,
+    pub name: String,
+    /// Quotes in the watchlist's order
+    pub prices: Vec<PriceResponse>,
+    /// Entries that are not configured or could not be priced
+    pub unavailable: Vec<String>,
+}
+
+// Grafana SimpleJSON datasource requests and responses
+
+#[derive(Deserialize)]
+pub struct GrafanaRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct GrafanaSearchRequest {
+    #[serde(default)]
+    pub target: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrafanaQueryRequest {
+    pub range: GrafanaRange,
+    #[serde(default)]
+    pub targets: Vec<GrafanaTarget>,
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct GrafanaTarget {
+    /// "crypto:btc", "stock:AAPL" or a bare configured symbol
+    #[serde(default)]
+    pub target: String,
+}
+
+/// One series; each datapoint is `[value, unix milliseconds]`
+#[derive(Serialize)]
+pub struct GrafanaTimeSeries {
+    pub target: String,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+#[derive(Deserialize)]
+pub struct GrafanaAnnotationRequest {
+    pub range: GrafanaRange,
+    pub annotation: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct GrafanaAnnotation {
+    /// The annotation definition from the request, echoed back
+    pub annotation: serde_json::Value,
+    /// Unix milliseconds
+    pub time: i64,
+    pub title: String,
+    pub text: String,
+    pub tags: Vec<String>,
+}
@@ -0,0 +1,67 @@
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Forced refreshes each token may request per window unless SYMBOL_REFRESH_LIMIT is set
+const DEFAULT_REFRESH_LIMIT: u32 = 10;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Tokens tracked before windows that have ended are swept out
+const SWEEP_THRESHOLD: usize = 1024;
+
+/// Per-token budget of `POST /update/{asset_type}/{symbol}` calls, so one client
+/// cannot hammer the upstream providers through forced refreshes
+#[derive(Clone)]
+pub struct RefreshLimiter {
+    limit: u32,
+    /// Start of each token's current window and the refreshes made in it
+    windows: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+}
+
+impl RefreshLimiter {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            windows: Arc::default(),
+        }
+    }
+
+    /// Limit per minute from SYMBOL_REFRESH_LIMIT; 0 disables forced refreshes
+    pub fn from_env() -> anyhow::Result<Self> {
+        let limit = match std::env::var("SYMBOL_REFRESH_LIMIT") {
+            Ok(value) => value
+                .parse::<u32>()
+                .map_err(|_| anyhow!("Invalid SYMBOL_REFRESH_LIMIT '{}'", value))?,
+            Err(_) => DEFAULT_REFRESH_LIMIT,
+        };
+        Ok(Self::new(limit))
+    }
+
+    /// Count a refresh for `token`, or return how long until its budget resets
+    pub fn check(&self, token: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        if windows.len() >= SWEEP_THRESHOLD {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        }
+
+        let (start, count) = windows.entry(token.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            return Err(WINDOW.saturating_sub(now.duration_since(*start)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+impl Default for RefreshLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_REFRESH_LIMIT)
+    }
+}
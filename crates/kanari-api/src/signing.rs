@@ -0,0 +1,221 @@
+use anyhow::anyhow;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use std::sync::Arc;
+
+use crate::api::AppState;
+
+/// Header carrying `round=<id>,expires=<unix seconds>,ed25519=<hex signature>`,
+/// made with the server's signing key
+pub const SIGNATURE_HEADER: &str = "X-Kanari-Response-Signature";
+
+/// Seconds a signed response stays valid when KANARI_SIGNATURE_TTL is unset
+pub const DEFAULT_SIGNATURE_TTL: u64 = 60;
+
+/// Response extension naming the round a handler served its prices from, so
+/// the signature attests that round rather than whichever is current once the
+/// body is signed
+#[derive(Debug, Clone, Copy)]
+pub struct ServedRound(pub u64);
+
+/// Signs response bodies with the Ed25519 key in KANARI_SIGNING_KEY, so clients
+/// holding the public key can check a price was not altered on the way, even
+/// through proxies that terminate TLS. Each signature also covers the oracle's
+/// round id and an expiry, so a replayed or stale response fails verification.
+#[derive(Clone)]
+pub struct ResponseSigner {
+    key: Arc<SigningKey>,
+    /// Seconds from signing until a signature expires
+    ttl: u64,
+}
+
+impl ResponseSigner {
+    /// Signer for a 32-byte Ed25519 seed written as 64 hex characters, whose
+    /// signatures expire `ttl` seconds after they are made
+    pub fn new(seed: &str, ttl: u64) -> anyhow::Result<Self> {
+        let seed: [u8; 32] = hex::decode(seed.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow!("The signing key must be 64 hex characters (32 bytes)"))?;
+        if ttl == 0 {
+            return Err(anyhow!("The signature TTL must be at least one second"));
+        }
+        Ok(Self {
+            key: Arc::new(SigningKey::from_bytes(&seed)),
+            ttl,
+        })
+    }
+
+    /// Signer from KANARI_SIGNING_KEY, or the file named by
+    /// KANARI_SIGNING_KEY_FILE; None when neither is set. KANARI_SIGNATURE_TTL
+    /// sets how many seconds signatures stay valid.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let seed = match std::env::var("KANARI_SIGNING_KEY") {
+            Ok(seed) => seed,
+            Err(_) => match std::env::var("KANARI_SIGNING_KEY_FILE") {
+                Ok(path) => std::fs::read_to_string(&path).map_err(|e| {
+                    anyhow!("Failed to read KANARI_SIGNING_KEY_FILE '{}': {}", path, e)
+                })?,
+                Err(_) => return Ok(None),
+            },
+        };
+        let ttl = match std::env::var("KANARI_SIGNATURE_TTL") {
+            Ok(ttl) => ttl.trim().parse().map_err(|_| {
+                anyhow!(
+                    "KANARI_SIGNATURE_TTL must be a number of seconds, got '{}'",
+                    ttl
+                )
+            })?,
+            Err(_) => DEFAULT_SIGNATURE_TTL,
+        };
+        Self::new(&seed, ttl).map(Some)
+    }
+
+    /// Hex public key clients verify signatures with
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// Seconds a signature stays valid
+    pub fn ttl(&self) -> u64 {
+        self.ttl
+    }
+
+    /// Signature header value for `body` served at oracle round `round_id`,
+    /// expiring `ttl` seconds from now
+    pub fn sign(&self, round_id: u64, body: &[u8]) -> String {
+        let expires = Utc::now().timestamp().max(0) as u64 + self.ttl;
+        let signature = self.key.sign(&signed_message(round_id, expires, body));
+        format!(
+            "round={},expires={},ed25519={}",
+            round_id,
+            expires,
+            hex::encode(signature.to_bytes())
+        )
+    }
+}
+
+/// Bytes a signature is made over: the round id and expiry in decimal, each
+/// followed by a dot, then the body
+pub fn signed_message(round_id: u64, expires: u64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}.{}.", round_id, expires).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// `body` re-serialized with object keys sorted and no whitespace, the form
+/// responses are signed in; None when it is not JSON
+pub fn canonicalize(body: &[u8]) -> Option<Vec<u8>> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    serde_json::to_vec(&value).ok()
+}
+
+/// Sign JSON responses when a signer is configured. The body is replaced by its
+/// canonical form, so the signature covers exactly the bytes sent and clients
+/// verify them before parsing. Handlers that served one round name it with
+/// [`ServedRound`]; otherwise the round id is read after the handler ran, so it
+/// is never older than the prices in the body.
+pub async fn sign_responses(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    let Some(signer) = &state.signer else {
+        return response;
+    };
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json || response.status() == StatusCode::NOT_MODIFIED {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("Failed to read response body for signing: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let body = canonicalize(&body).map_or(body, Into::into);
+    let round_id = match parts.extensions.get::<ServedRound>() {
+        Some(ServedRound(round_id)) => *round_id,
+        None => state.oracle.read().await.round_id(),
+    };
+    if let Ok(signature) = HeaderValue::from_str(&signer.sign(round_id, &body)) {
+        parts.headers.insert(SIGNATURE_HEADER, signature);
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kanari_client::signature::verify_attestation;
+
+    const SEED: &str = "0707070707070707070707070707070707070707070707070707070707070707";
+
+    fn signer() -> ResponseSigner {
+        ResponseSigner::new(SEED, DEFAULT_SIGNATURE_TTL).unwrap()
+    }
+
+    #[test]
+    fn signatures_verify_with_the_client() {
+        let signer = signer();
+        let body = canonicalize(br#"{"symbol":"btc","price":64000.5}"#).unwrap();
+        let attestation =
+            verify_attestation(&signer.public_key(), &body, &signer.sign(42, &body)).unwrap();
+        assert_eq!(attestation.round_id, 42);
+        let now = Utc::now().timestamp() as u64;
+        assert!(attestation.expires > now && attestation.expires <= now + DEFAULT_SIGNATURE_TTL);
+    }
+
+    #[test]
+    fn tampered_bodies_fail_verification() {
+        let signer = signer();
+        let body = canonicalize(br#"{"symbol":"btc","price":64000.5}"#).unwrap();
+        let signature = signer.sign(42, &body);
+        let tampered = canonicalize(br#"{"symbol":"btc","price":1.0}"#).unwrap();
+        assert!(verify_attestation(&signer.public_key(), &tampered, &signature).is_err());
+    }
+
+    #[test]
+    fn canonical_form_ignores_key_order_and_whitespace() {
+        let canonical = canonicalize(br#"{"b":1,"a":{"d":[2,1],"c":null}}"#).unwrap();
+        let reordered = canonicalize(
+            br#"{
+                "a": { "c": null, "d": [2, 1] },
+                "b": 1
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(canonical, reordered);
+        assert_eq!(canonical, br#"{"a":{"c":null,"d":[2,1]},"b":1}"#);
+        // Canonicalizing again changes nothing, so signed bytes verify as sent
+        assert_eq!(canonicalize(&canonical).unwrap(), canonical);
+    }
+
+    #[test]
+    fn non_json_bodies_have_no_canonical_form() {
+        assert_eq!(canonicalize(b"not json"), None);
+    }
+
+    #[test]
+    fn rejects_bad_seeds_and_ttls() {
+        assert!(ResponseSigner::new("abcd", 60).is_err());
+        assert!(ResponseSigner::new(&SEED.replace('0', "g"), 60).is_err());
+        assert!(ResponseSigner::new(SEED, 0).is_err());
+    }
+}
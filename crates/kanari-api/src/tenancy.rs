@@ -0,0 +1,68 @@
+use kanari_oracle::config::{Config, TenantConfig};
+use kanari_oracle::models::AssetType;
+
+/// Whose token a request carries, which decides the symbols it is served.
+/// Tenants are looked up in the current config on every use, so tenants and
+/// their symbols follow config reloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Namespace {
+    /// Operator tokens and deployments without authentication
+    All,
+    /// A static token listed as `<tenant>:<token>`
+    Tenant(String),
+    /// A database token; served everything unless a tenant lists its owner
+    User(String),
+}
+
+/// Symbols a namespace resolves to in one config
+enum Scope<'a> {
+    All,
+    /// None for a tenant the config does not define, which is served nothing
+    Tenant(Option<&'a TenantConfig>),
+}
+
+impl Namespace {
+    fn scope<'a>(&self, config: &'a Config) -> Scope<'a> {
+        match self {
+            Namespace::All => Scope::All,
+            Namespace::Tenant(name) => Scope::Tenant(config.tenant(name)),
+            Namespace::User(username) => match config.tenant_of_user(username) {
+                Some(tenant) => Scope::Tenant(Some(tenant)),
+                None => Scope::All,
+            },
+        }
+    }
+
+    /// Name of the tenant served, or None when every symbol is
+    pub fn tenant<'a>(&'a self, config: &'a Config) -> Option<&'a str> {
+        match self.scope(config) {
+            Scope::All => None,
+            Scope::Tenant(Some(tenant)) => Some(&tenant.name),
+            Scope::Tenant(None) => match self {
+                Namespace::Tenant(name) => Some(name),
+                _ => None,
+            },
+        }
+    }
+
+    /// Whether `symbol`, in any spelling, is served to this namespace
+    pub fn allows(&self, config: &Config, asset_type: AssetType, symbol: &str) -> bool {
+        match self.scope(config) {
+            Scope::All => true,
+            Scope::Tenant(Some(tenant)) => config
+                .tenant_symbols(tenant, asset_type)
+                .contains(&config.canonical_symbol(asset_type, symbol)),
+            Scope::Tenant(None) => false,
+        }
+    }
+
+    /// Configured symbols of an asset type served to this namespace, in
+    /// canonical form
+    pub fn symbols(&self, config: &Config, asset_type: AssetType) -> Vec<String> {
+        match self.scope(config) {
+            Scope::All => config.canonical_symbols(asset_type),
+            Scope::Tenant(Some(tenant)) => config.tenant_symbols(tenant, asset_type),
+            Scope::Tenant(None) => Vec::new(),
+        }
+    }
+}
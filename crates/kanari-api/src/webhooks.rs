@@ -0,0 +1,891 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use kanari_oracle::config::Config;
+use kanari_oracle::models::{AssetType, PriceData};
+use kanari_oracle::oracle::Oracle;
+use kanari_oracle::subscriber::PriceUpdate;
+use kanari_oracle::symbols;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use crate::api::SharedOracle;
+use crate::database::{DbHealth, DbPool};
+use crate::models::{CreateWebhookRequest, WebhookDelivery, WebhookInfo};
+use crate::tenancy::Namespace;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>`, keyed with the
+/// webhook's secret
+pub const SIGNATURE_HEADER: &str = "X-Kanari-Signature";
+
+/// Header carrying the event name, e.g. "threshold"
+pub const EVENT_HEADER: &str = "X-Kanari-Event";
+
+/// Event name of summary reports sent through [`Webhooks::send_report`]
+pub const REPORT_EVENT: &str = "report";
+
+/// A callback that hasn't answered by then is logged as a failed delivery
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often prices are checked for staleness and webhooks are reloaded, so
+/// changes made through another replica are picked up
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Delivery logs older than this are deleted
+const DELIVERY_RETENTION_DAYS: i32 = 7;
+
+/// Secrets shorter than this are rejected; generated secrets are 64 hex digits
+const MIN_SECRET_LEN: usize = 16;
+
+/// Comma-separated hosts webhooks may reach even though they resolve to a
+/// loopback, private or link-local address, e.g. "alerts.internal,10.0.0.7"
+const ALLOWED_HOSTS_ENV: &str = "KANARI_WEBHOOK_ALLOWED_HOSTS";
+
+/// What a webhook is notified about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    /// Every published price
+    Update,
+    /// A move of at least `threshold_percent` since the last threshold event
+    Threshold,
+    /// A price older than `stale_after_secs`, sent once until it is fresh again
+    Staleness,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Update => "update",
+            WebhookEvent::Threshold => "threshold",
+            WebhookEvent::Staleness => "staleness",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "update" => Ok(Self::Update),
+            "threshold" => Ok(Self::Threshold),
+            "staleness" => Ok(Self::Staleness),
+            _ => Err(format!(
+                "unknown webhook event '{}', expected update, threshold or staleness",
+                s
+            )),
+        }
+    }
+}
+
+/// A row of the `webhooks` table
+#[derive(Debug, Clone)]
+struct Webhook {
+    id: i32,
+    url: String,
+    secret: String,
+    /// Symbols as registered; empty matches every symbol
+    symbols: Vec<String>,
+    /// Symbols the owner's tenant is served; others are never delivered
+    namespace: Namespace,
+    events: HashSet<WebhookEvent>,
+    threshold_percent: Option<f64>,
+    stale_after_secs: Option<i64>,
+}
+
+impl Webhook {
+    /// Whether the symbol with feed key `key` passes the symbol filter and is
+    /// served to the owner. Filters are canonicalized like lookups, so "BTC"
+    /// matches the "bitcoin" feed.
+    fn matches(&self, config: &Config, asset_type: AssetType, key: &str) -> bool {
+        let selected = self.symbols.is_empty()
+            || self.symbols.iter().any(|symbol| {
+                symbols::feed_key(asset_type, &config.canonical_symbol(asset_type, symbol)) == key
+            });
+        selected && self.namespace.allows(config, asset_type, key)
+    }
+}
+
+/// Body POSTed to a webhook's URL
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    webhook_id: i32,
+    event: WebhookEvent,
+    asset_type: AssetType,
+    symbol: &'a str,
+    price: f64,
+    /// Price of the previous threshold event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    change_percent: Option<f64>,
+    source: &'a str,
+    /// When the price was observed
+    timestamp: DateTime<Utc>,
+    sent_at: DateTime<Utc>,
+}
+
+/// (webhook id, asset type, feed key)
+type SymbolState = (i32, AssetType, String);
+
+/// Delivers price events to the webhooks users registered through `/webhooks`
+/// and logs each attempt in `webhook_deliveries`. Deliveries are not retried.
+#[derive(Clone)]
+pub struct Webhooks {
+    db: DbPool,
+    client: reqwest::Client,
+    allowed_hosts: Arc<Vec<String>>,
+    hooks: Arc<RwLock<Vec<Webhook>>>,
+    /// Price of the last threshold event, or the first price seen
+    baselines: Arc<Mutex<HashMap<SymbolState, f64>>>,
+    /// Symbols a staleness event was sent for that haven't been updated since
+    stale: Arc<Mutex<HashSet<SymbolState>>>,
+}
+
+impl Webhooks {
+    pub fn new(db: DbPool) -> anyhow::Result<Self> {
+        // Redirects and proxies would reach hosts the resolver never sees
+        let allowed_hosts = Arc::new(allowed_hosts());
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(Policy::none())
+            .no_proxy()
+            .dns_resolver(Arc::new(PublicResolver {
+                allowed_hosts: allowed_hosts.clone(),
+            }))
+            .build()?;
+        Ok(Self {
+            db,
+            client,
+            allowed_hosts,
+            hooks: Arc::new(RwLock::new(Vec::new())),
+            baselines: Arc::new(Mutex::new(HashMap::new())),
+            stale: Arc::new(Mutex::new(HashSet::new())),
+        })
+    }
+
+    /// Read the registered webhooks from the database
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let rows = sqlx::query(
+            "SELECT id, owner, url, secret, symbols, events, threshold_percent, stale_after_secs FROM webhooks",
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut hooks = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let events: Vec<String> = row.try_get("events")?;
+            hooks.push(Webhook {
+                id: row.try_get("id")?,
+                url: row.try_get("url")?,
+                secret: row.try_get("secret")?,
+                symbols: row.try_get("symbols")?,
+                namespace: Namespace::User(row.try_get("owner")?),
+                events: events
+                    .iter()
+                    .filter_map(|event| event.parse().ok())
+                    .collect(),
+                threshold_percent: row.try_get("threshold_percent")?,
+                stale_after_secs: row.try_get("stale_after_secs")?,
+            });
+        }
+
+        let ids: HashSet<i32> = hooks.iter().map(|hook| hook.id).collect();
+        self.baselines
+            .lock()
+            .unwrap()
+            .retain(|(id, _, _), _| ids.contains(id));
+        self.stale
+            .lock()
+            .unwrap()
+            .retain(|(id, _, _)| ids.contains(id));
+        *self.hooks.write().unwrap() = hooks;
+        Ok(())
+    }
+
+    /// Deliver events for prices the oracle publishes, and check for stale prices
+    /// every [`SWEEP_INTERVAL`], until the task is aborted
+    pub fn spawn(self, oracle: SharedOracle, health: DbHealth) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut updates = oracle.read().await.subscribe();
+            let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+            sweep.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    received = updates.recv() => match received {
+                        Ok(update) => self.on_update(oracle.read().await.config(), &update),
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::warn!("Webhooks missed {} price updates", skipped);
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = sweep.tick() => {
+                        if health.is_up() {
+                            if let Err(e) = self.reload().await {
+                                log::warn!("Failed to reload webhooks: {}", e);
+                            }
+                            self.prune_deliveries().await;
+                        }
+                        let current = oracle.read().await.clone();
+                        self.check_staleness(&current);
+                    }
+                }
+            }
+        })
+    }
+
+    fn on_update(&self, config: &Config, update: &PriceUpdate) {
+        let hooks = self.hooks.read().unwrap();
+        for price in &update.prices {
+            let key = price.symbol.to_lowercase();
+            for hook in hooks.iter() {
+                if !hook.matches(config, update.asset_type, &key) {
+                    continue;
+                }
+                if hook.events.contains(&WebhookEvent::Update) {
+                    self.deliver(hook, WebhookEvent::Update, update.asset_type, price, None);
+                }
+                let threshold = hook
+                    .threshold_percent
+                    .filter(|_| hook.events.contains(&WebhookEvent::Threshold));
+                if let Some(threshold) = threshold {
+                    self.check_threshold(hook, threshold, update.asset_type, &key, price);
+                }
+            }
+        }
+    }
+
+    fn check_threshold(
+        &self,
+        hook: &Webhook,
+        threshold: f64,
+        asset_type: AssetType,
+        key: &str,
+        price: &PriceData,
+    ) {
+        let state = (hook.id, asset_type, key.to_string());
+        let previous = {
+            let mut baselines = self.baselines.lock().unwrap();
+            match baselines.get(&state).copied() {
+                // The first price seen is the baseline
+                None => {
+                    baselines.insert(state, price.price);
+                    return;
+                }
+                Some(baseline) if baseline <= 0.0 => return,
+                Some(baseline) => {
+                    let change = (price.price - baseline) / baseline * 100.0;
+                    if change.abs() < threshold {
+                        return;
+                    }
+                    baselines.insert(state, price.price);
+                    baseline
+                }
+            }
+        };
+        self.deliver(
+            hook,
+            WebhookEvent::Threshold,
+            asset_type,
+            price,
+            Some(previous),
+        );
+    }
+
+    /// Send a staleness event for each matching price older than the webhook's
+    /// `stale_after_secs`. Symbols that never had a price are not reported.
+    fn check_staleness(&self, oracle: &Oracle) {
+        let snapshot = oracle.snapshot();
+        let now = Utc::now();
+        let hooks = self.hooks.read().unwrap();
+
+        for asset_type in [
+            AssetType::Crypto,
+            AssetType::Stock,
+            AssetType::Basket,
+            AssetType::Tvl,
+        ] {
+            let Some(feed) = snapshot.feed(asset_type) else {
+                continue;
+            };
+            for (key, price) in &feed.prices {
+                for hook in hooks.iter() {
+                    let Some(stale_after) = hook.stale_after_secs else {
+                        continue;
+                    };
+                    if !hook.events.contains(&WebhookEvent::Staleness)
+                        || !hook.matches(oracle.config(), asset_type, key)
+                    {
+                        continue;
+                    }
+
+                    let state = (hook.id, asset_type, key.clone());
+                    let stale = (now - price.timestamp).num_seconds() > stale_after;
+                    let newly_stale = {
+                        let mut notified = self.stale.lock().unwrap();
+                        if stale {
+                            notified.insert(state)
+                        } else {
+                            notified.remove(&state);
+                            false
+                        }
+                    };
+                    if newly_stale {
+                        self.deliver(hook, WebhookEvent::Staleness, asset_type, price, None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// POST the event to the webhook in the background and log the outcome
+    fn deliver(
+        &self,
+        hook: &Webhook,
+        event: WebhookEvent,
+        asset_type: AssetType,
+        price: &PriceData,
+        previous_price: Option<f64>,
+    ) {
+        let payload = WebhookPayload {
+            webhook_id: hook.id,
+            event,
+            asset_type,
+            symbol: &price.symbol,
+            price: price.price,
+            previous_price,
+            change_percent: previous_price
+                .map(|previous| (price.price - previous) / previous * 100.0),
+            source: &price.source,
+            timestamp: price.timestamp,
+            sent_at: Utc::now(),
+        };
+        match serde_json::to_vec(&payload) {
+            Ok(body) => self.post(
+                hook,
+                event.as_str(),
+                Some((asset_type, price.symbol.clone())),
+                body,
+            ),
+            Err(e) => log::error!("Failed to serialize webhook {} event: {}", hook.id, e),
+        }
+    }
+
+    /// Send a summary report to a webhook, signed like price events
+    pub fn send_report(&self, webhook_id: i32, body: Vec<u8>) -> anyhow::Result<()> {
+        let hooks = self.hooks.read().unwrap();
+        let hook = hooks
+            .iter()
+            .find(|hook| hook.id == webhook_id)
+            .ok_or_else(|| anyhow!("Webhook {} not found", webhook_id))?;
+        self.post(hook, REPORT_EVENT, None, body);
+        Ok(())
+    }
+
+    /// POST a signed `body` to the webhook in the background and log the
+    /// outcome, with the price's asset type and symbol if it is about one
+    fn post(
+        &self,
+        hook: &Webhook,
+        event: &'static str,
+        subject: Option<(AssetType, String)>,
+        body: Vec<u8>,
+    ) {
+        let signature = sign(&hook.secret, &body);
+        // The resolver is not asked about IP addresses, so those are checked here
+        let refused = literal_address(&hook.url)
+            .filter(|ip| !is_public(*ip) && !allowed(&self.allowed_hosts, &ip.to_string()))
+            .map(|ip| format!("{} is not a public address", ip));
+        let request = self
+            .client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body);
+
+        let db = self.db.clone();
+        let webhook_id = hook.id;
+        let (asset_type, symbol) = subject.unzip();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let sent = match refused {
+                Some(refused) => Err(refused),
+                None => request.send().await.map_err(|e| e.to_string()),
+            };
+            let (status_code, error) = match sent {
+                Ok(response) if response.status().is_success() => {
+                    (Some(response.status().as_u16() as i32), None)
+                }
+                Ok(response) => (
+                    Some(response.status().as_u16() as i32),
+                    Some(format!("HTTP {}", response.status())),
+                ),
+                Err(e) => (None, Some(e)),
+            };
+            if let Some(error) = &error {
+                log::warn!("Webhook {} delivery failed: {}", webhook_id, error);
+            }
+
+            let logged = sqlx::query(
+                "INSERT INTO webhook_deliveries (webhook_id, event, asset_type, symbol, status_code, error, duration_ms) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(webhook_id)
+            .bind(event)
+            .bind(asset_type.map(|asset_type| asset_type.as_str()))
+            .bind(symbol)
+            .bind(status_code)
+            .bind(error.as_deref())
+            .bind(started.elapsed().as_millis() as i64)
+            .execute(&db)
+            .await;
+            if let Err(e) = logged {
+                log::warn!("Failed to log webhook {} delivery: {}", webhook_id, e);
+            }
+        });
+    }
+
+    async fn prune_deliveries(&self) {
+        let pruned = sqlx::query(
+            "DELETE FROM webhook_deliveries WHERE delivered_at < NOW() - make_interval(days => $1)",
+        )
+        .bind(DELIVERY_RETENTION_DAYS)
+        .execute(&self.db)
+        .await;
+        if let Err(e) = pruned {
+            log::warn!("Failed to prune webhook deliveries: {}", e);
+        }
+    }
+}
+
+fn webhook_info(row: &PgRow) -> Result<WebhookInfo, sqlx::Error> {
+    let created: DateTime<Utc> = row.try_get("created_at")?;
+    Ok(WebhookInfo {
+        id: row.try_get("id")?,
+        url: row.try_get("url")?,
+        symbols: row.try_get("symbols")?,
+        events: row.try_get("events")?,
+        threshold_percent: row.try_get("threshold_percent")?,
+        stale_after_secs: row.try_get("stale_after_secs")?,
+        secret: None,
+        created_at: created.to_rfc3339(),
+    })
+}
+
+fn webhook_delivery(row: &PgRow) -> Result<WebhookDelivery, sqlx::Error> {
+    let delivered: DateTime<Utc> = row.try_get("delivered_at")?;
+    Ok(WebhookDelivery {
+        id: row.try_get("id")?,
+        event: row.try_get("event")?,
+        asset_type: row.try_get("asset_type")?,
+        symbol: row.try_get("symbol")?,
+        status_code: row.try_get("status_code")?,
+        error: row.try_get("error")?,
+        duration_ms: row.try_get("duration_ms")?,
+        delivered_at: delivered.to_rfc3339(),
+    })
+}
+
+fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    let mut secret = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(secret, "{:02x}", byte);
+    }
+    secret
+}
+
+/// Resolves webhook hosts to their public addresses only, so a webhook cannot
+/// reach the server itself or its internal network
+struct PublicResolver {
+    allowed_hosts: Arc<Vec<String>>,
+}
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let allowed_hosts = self.allowed_hosts.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| is_public(address.ip()) || allowed(&allowed_hosts, &host))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            let addresses: Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+/// Hosts of KANARI_WEBHOOK_ALLOWED_HOSTS, lowercased
+fn allowed_hosts() -> Vec<String> {
+    std::env::var(ALLOWED_HOSTS_ENV)
+        .unwrap_or_default()
+        .split(',')
+        .map(|host| host.trim().to_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+fn allowed(allowed_hosts: &[String], host: &str) -> bool {
+    allowed_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+}
+
+/// The address of a URL whose host is an IP address rather than a name
+fn literal_address(url: &str) -> Option<IpAddr> {
+    host(url)?.parse().ok()
+}
+
+/// Host of a URL, without the brackets of an IPv6 address
+fn host(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    Some(
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string(),
+    )
+}
+
+/// Whether `ip` is reachable on the internet: not loopback, private, link-local,
+/// shared (100.64.0.0/10), "this network" (0.0.0.0/8), broadcast, multicast or
+/// documentation. IPv6 addresses carrying an IPv4 address are judged by it.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            let shared = first == 100 && (second & 0xc0) == 64;
+            !(first == 0
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(ip) => match embedded_ipv4(ip) {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// The IPv4 address inside an IPv4-mapped (::ffff:0:0/96), NAT64 (64:ff9b::/96)
+/// or 6to4 (2002::/16) address, which routes to that IPv4 address
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = ip.octets();
+    match ip.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(Ipv4Addr::new(
+            octets[12], octets[13], octets[14], octets[15],
+        )),
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => ip.to_ipv4_mapped(),
+    }
+}
+
+/// Refuse a webhook URL whose host is, or resolves to, an address that is not
+/// public, unless the host is in KANARI_WEBHOOK_ALLOWED_HOSTS
+async fn check_destination(url: &str) -> Result<(), String> {
+    let host = host(url).ok_or_else(|| "url must have a host".to_string())?;
+    if allowed(&allowed_hosts(), &host) {
+        return Ok(());
+    }
+
+    let addresses: Vec<IpAddr> = match literal_address(url) {
+        Some(ip) => vec![ip],
+        None => tokio::net::lookup_host((host.as_str(), 0))
+            .await
+            .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+            .map(|address| address.ip())
+            .collect(),
+    };
+    match addresses.iter().find(|ip| !is_public(**ip)) {
+        Some(ip) => Err(format!(
+            "url must point to a public address; {} resolves to {}",
+            host, ip
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Check a registration and return its events in canonical form
+fn validate(request: &CreateWebhookRequest) -> Result<Vec<String>, String> {
+    match reqwest::Url::parse(&request.url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+        _ => return Err("url must be an http(s) URL".to_string()),
+    }
+
+    let events = request
+        .events
+        .iter()
+        .map(|event| event.parse::<WebhookEvent>())
+        .collect::<Result<Vec<_>, _>>()?;
+    if events.is_empty() {
+        return Err("At least one event is required".to_string());
+    }
+    if events.contains(&WebhookEvent::Threshold)
+        && request
+            .threshold_percent
+            .is_none_or(|percent| percent.is_nan() || percent <= 0.0)
+    {
+        return Err("threshold events need a positive threshold_percent".to_string());
+    }
+    if events.contains(&WebhookEvent::Staleness)
+        && request.stale_after_secs.is_none_or(|secs| secs <= 0)
+    {
+        return Err("staleness events need a positive stale_after_secs".to_string());
+    }
+    if request
+        .secret
+        .as_ref()
+        .is_some_and(|secret| secret.len() < MIN_SECRET_LEN)
+    {
+        return Err(format!(
+            "secret must be at least {} characters",
+            MIN_SECRET_LEN
+        ));
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for event in events {
+        let name = event.as_str().to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Register a webhook for `owner`. The returned info is the only place its
+/// signing secret is given out. Running servers pick the webhook up at their
+/// next reload. Errors are meant for the user.
+pub async fn register(
+    db: &DbPool,
+    owner: &str,
+    request: &CreateWebhookRequest,
+) -> Result<WebhookInfo, String> {
+    let events = validate(request)?;
+    check_destination(&request.url).await?;
+    let symbols: Vec<String> = request
+        .symbols
+        .iter()
+        .map(|symbol| symbol.trim().to_string())
+        .filter(|symbol| !symbol.is_empty())
+        .collect();
+    let secret = request.secret.clone().unwrap_or_else(generate_secret);
+
+    let row = sqlx::query(
+        "INSERT INTO webhooks (owner, url, secret, symbols, events, threshold_percent, stale_after_secs) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id, url, symbols, events, threshold_percent, stale_after_secs, created_at",
+    )
+    .bind(owner)
+    .bind(&request.url)
+    .bind(&secret)
+    .bind(&symbols)
+    .bind(&events)
+    .bind(request.threshold_percent)
+    .bind(request.stale_after_secs)
+    .fetch_one(db)
+    .await
+    .map_err(|e| match e.as_database_error() {
+        Some(db_error) if db_error.is_foreign_key_violation() => {
+            format!("Unknown user '{}'", owner)
+        }
+        _ => e.to_string(),
+    })?;
+
+    let mut webhook = webhook_info(&row).map_err(|e| e.to_string())?;
+    webhook.secret = Some(secret);
+    Ok(webhook)
+}
+
+/// Webhooks of `owner`, newest first
+pub async fn registered(db: &DbPool, owner: &str) -> Result<Vec<WebhookInfo>, String> {
+    let rows = sqlx::query(
+        "SELECT id, url, symbols, events, threshold_percent, stale_after_secs, created_at FROM webhooks WHERE owner = $1 ORDER BY created_at DESC",
+    )
+    .bind(owner)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    rows.iter()
+        .map(webhook_info)
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Delete one of `owner`'s webhooks with its delivery log; false when `owner`
+/// has no such webhook
+pub async fn unregister(db: &DbPool, owner: &str, id: i32) -> Result<bool, String> {
+    let deleted = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND owner = $2")
+        .bind(id)
+        .bind(owner)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(deleted.rows_affected() > 0)
+}
+
+/// The `limit` most recent delivery attempts of one of `owner`'s webhooks,
+/// newest first; None when `owner` has no such webhook
+pub async fn deliveries(
+    db: &DbPool,
+    owner: &str,
+    id: i32,
+    limit: i64,
+) -> Result<Option<Vec<WebhookDelivery>>, String> {
+    let owned = sqlx::query("SELECT 1 FROM webhooks WHERE id = $1 AND owner = $2")
+        .bind(id)
+        .bind(owner)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    if owned.is_none() {
+        return Ok(None);
+    }
+
+    let rows = sqlx::query(
+        "SELECT id, event, asset_type, symbol, status_code, error, duration_ms, delivered_at FROM webhook_deliveries WHERE webhook_id = $1 ORDER BY delivered_at DESC LIMIT $2",
+    )
+    .bind(id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    rows.iter()
+        .map(webhook_delivery)
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map(Some)
+        .map_err(|e| format!("Failed to read delivery: {}", e))
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body`
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+
+    let mut signature = String::from("sha256=");
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(signature, "{:02x}", byte);
+    }
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn internet_addresses_are_public() {
+        for ip in [
+            "93.184.216.34",
+            "1.1.1.1",
+            "2606:4700::1111",
+            "::ffff:1.1.1.1",
+        ] {
+            assert!(public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn internal_ipv4_addresses_are_refused() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "0.1.2.3",
+            "255.255.255.255",
+            "224.0.0.1",
+            "192.0.2.1",
+        ] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn internal_ipv6_addresses_are_refused() {
+        for ip in ["::1", "::", "fc00::1", "fe80::1", "ff02::1"] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn embedded_ipv4_addresses_are_judged_by_the_ipv4_address() {
+        // Mapped, NAT64 and 6to4 forms of 127.0.0.1 and 169.254.169.254
+        for ip in [
+            "::ffff:127.0.0.1",
+            "64:ff9b::7f00:1",
+            "64:ff9b::a9fe:a9fe",
+            "2002:7f00:1::",
+            "2002:a9fe:a9fe::1",
+        ] {
+            assert!(!public(ip), "{}", ip);
+        }
+        assert!(public("64:ff9b::101:101"));
+        assert!(public("2002:101:101::1"));
+    }
+
+    #[test]
+    fn literal_addresses_are_read_from_urls() {
+        assert_eq!(
+            literal_address("http://[::1]:8080/hook"),
+            Some("::1".parse().unwrap())
+        );
+        assert_eq!(
+            literal_address("https://10.0.0.7/hook"),
+            Some("10.0.0.7".parse().unwrap())
+        );
+        assert_eq!(literal_address("https://example.com/hook"), None);
+    }
+
+    #[tokio::test]
+    async fn destinations_on_internal_addresses_are_refused() {
+        assert!(check_destination("http://127.0.0.1/hook").await.is_err());
+        assert!(
+            check_destination("http://[64:ff9b::a9fe:a9fe]/")
+                .await
+                .is_err()
+        );
+        assert!(check_destination("http://localhost:9/hook").await.is_err());
+        assert!(
+            check_destination("https://93.184.216.34/hook")
+                .await
+                .is_ok()
+        );
+    }
+}
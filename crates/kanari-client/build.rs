@@ -0,0 +1,5 @@
+// The Node.js addon needs napi's linker setup; other builds need nothing.
+fn main() {
+    #[cfg(feature = "node")]
+    napi_build::setup();
+}
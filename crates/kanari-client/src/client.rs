@@ -0,0 +1,155 @@
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+use crate::errors::{ClientError, Result};
+use crate::models::{ApiResponse, AssetType, Price};
+use crate::signature::{self, RESPONSE_SIGNATURE_HEADER, ReplayGuard};
+
+/// REST client for a kanari API server
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    token: Option<String>,
+    /// Hex key every response must be signed with, when set
+    public_key: Option<String>,
+    /// Newest round a signed response came from, shared by clones
+    replay: Arc<ReplayGuard>,
+}
+
+impl Client {
+    /// `base_url` is the server root, e.g. `https://oracle.example.com`
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self> {
+        let base_url = Url::parse(base_url.trim_end_matches('/'))
+            .map_err(|e| ClientError::InvalidUrl(format!("{}: {}", base_url, e)))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(ClientError::InvalidUrl(format!(
+                "{}: expected http:// or https://",
+                base_url
+            )));
+        }
+
+        // Browsers apply their own timeouts to fetch()
+        #[cfg(not(target_arch = "wasm32"))]
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+        #[cfg(target_arch = "wasm32")]
+        let http = reqwest::Client::new();
+
+        Ok(Self {
+            http,
+            base_url,
+            token,
+            public_key: None,
+            replay: Arc::new(ReplayGuard::new()),
+        })
+    }
+
+    /// Reject responses not signed with `public_key`, the server's hex Ed25519
+    /// key, along with expired ones and ones from an older round than a
+    /// response already received. Pin the key from a trusted channel rather
+    /// than from the server itself.
+    pub fn with_public_key(mut self, public_key: &str) -> Result<Self> {
+        if signature::parse_public_key(public_key).is_none() {
+            return Err(ClientError::InvalidPublicKey(
+                "expected 64 hex characters".to_string(),
+            ));
+        }
+        self.public_key = Some(public_key.trim().to_string());
+        Ok(self)
+    }
+
+    pub async fn price(&self, asset_type: AssetType, symbol: &str) -> Result<Price> {
+        self.get(&["price", asset_type.as_str(), symbol], &[]).await
+    }
+
+    /// Price converted by the server to `currency` ("eur") through its FX table
+    pub async fn price_in(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        currency: &str,
+    ) -> Result<Price> {
+        self.get(
+            &["price", asset_type.as_str(), symbol],
+            &[("currency", currency)],
+        )
+        .await
+    }
+
+    pub async fn prices(&self, asset_type: AssetType) -> Result<Vec<Price>> {
+        self.get(&["prices", asset_type.as_str()], &[]).await
+    }
+
+    /// Prices converted by the server to `currency` through its FX table
+    pub async fn prices_in(&self, asset_type: AssetType, currency: &str) -> Result<Vec<Price>> {
+        self.get(&["prices", asset_type.as_str()], &[("currency", currency)])
+            .await
+    }
+
+    /// `ws://` or `wss://` URL of the server's `/stream`. The token goes in the
+    /// query, since browsers cannot set headers on a WebSocket.
+    pub fn stream_url(&self) -> String {
+        let mut url = self.url(&["stream"]);
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        // Both are special schemes, so the switch cannot fail
+        let _ = url.set_scheme(scheme);
+        if let Some(token) = &self.token {
+            url.query_pairs_mut().append_pair("token", token);
+        }
+        url.into()
+    }
+
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> Result<T> {
+        let mut request = self.http.get(self.url(segments)).query(query);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let signature = response
+            .headers()
+            .get(RESPONSE_SIGNATURE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await?;
+        if let Some(public_key) = &self.public_key {
+            let Some(signature) = signature else {
+                return Err(ClientError::InvalidSignature(
+                    "response is not signed".to_string(),
+                ));
+            };
+            let attestation = signature::verify_attestation(public_key, &body, &signature)?;
+            self.replay.accept(&attestation)?;
+        }
+        let body: ApiResponse<T> = match serde_json::from_slice(&body) {
+            Ok(body) => body,
+            Err(_) if !status.is_success() => return Err(ClientError::HttpStatus(status.as_u16())),
+            Err(e) => return Err(e.into()),
+        };
+
+        match (body.data, body.error) {
+            (Some(data), _) => Ok(data),
+            (None, error) => Err(ClientError::ApiError {
+                message: error
+                    .unwrap_or_else(|| format!("Server returned {} without data", status)),
+                code: body.code,
+            }),
+        }
+    }
+}
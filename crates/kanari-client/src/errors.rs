@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("HTTP request failed: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("JSON parsing error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// An error reported by the server, with its machine-readable class when given
+    #[error("{message}")]
+    ApiError {
+        message: String,
+        code: Option<String>,
+    },
+
+    #[error("Server returned HTTP {0}")]
+    HttpStatus(u16),
+
+    #[error("Invalid base URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Invalid signing key: {0}")]
+    InvalidPublicKey(String),
+
+    /// A response missing its signature, or signed with another key
+    #[error("Response signature check failed: {0}")]
+    InvalidSignature(String),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
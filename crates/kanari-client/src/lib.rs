@@ -0,0 +1,13 @@
+//! Client for a kanari API server: the response models, webhook signature
+//! verification and a REST client, with optional browser (`wasm`) and Node.js
+//! (`node`) bindings so JavaScript consumers share the same parsing.
+
+pub mod client;
+pub mod errors;
+pub mod models;
+pub mod signature;
+
+#[cfg(feature = "node")]
+mod node;
+#[cfg(feature = "wasm")]
+mod wasm;
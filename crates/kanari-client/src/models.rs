@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Envelope of every REST response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>,
+    /// Machine-readable error class, e.g. "rate_limited" or "invalid_symbol"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetType {
+    Crypto,
+    Stock,
+    /// Weighted combination of other symbols, configured on the server
+    Basket,
+    /// Total value locked of a DeFi protocol or chain, in USD
+    Tvl,
+}
+
+impl AssetType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetType::Crypto => "crypto",
+            AssetType::Stock => "stock",
+            AssetType::Basket => "basket",
+            AssetType::Tvl => "tvl",
+        }
+    }
+}
+
+impl FromStr for AssetType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "crypto" => Ok(Self::Crypto),
+            "stock" => Ok(Self::Stock),
+            "basket" => Ok(Self::Basket),
+            "tvl" => Ok(Self::Tvl),
+            _ => Err(format!(
+                "Invalid asset type '{}'. Use 'crypto', 'stock', 'basket' or 'tvl'",
+                s
+            )),
+        }
+    }
+}
+
+/// US market session of a stock price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketState {
+    Pre,
+    Open,
+    Post,
+    Closed,
+}
+
+/// A price as returned by `/price/{asset_type}/{symbol}` and `/prices/{asset_type}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Price {
+    pub symbol: String,
+    pub price: f64,
+    /// Lowercase code of the currency `price` is in; absent from older servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    /// Provider the price was fetched from
+    pub source: String,
+    pub asset_type: AssetType,
+    #[serde(default)]
+    pub age_seconds: i64,
+    #[serde(default)]
+    pub is_stale: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_state: Option<MarketState>,
+    /// Session a stock price was traded in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<MarketState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_cap: Option<f64>,
+    /// Coins in circulation, for crypto prices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circulating_supply: Option<f64>,
+    /// Coins in existence, including locked ones, for crypto prices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_supply: Option<f64>,
+    /// Order book liquidity within ±2% of the mid price, for crypto prices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<OrderBookDepth>,
+    /// Rate the price was converted at, for prices requested in another currency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fx: Option<Conversion>,
+}
+
+/// Rate between two currencies, triangulated by the server through the US dollar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conversion {
+    pub from: String,
+    pub to: String,
+    /// Units of `to` one unit of `from` buys
+    pub rate: f64,
+    /// Day or time of the older of the two rates the conversion went through
+    pub as_of: DateTime<Utc>,
+}
+
+/// Notional resting in an exchange's order book within ±2% of the mid price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBookDepth {
+    pub bids: f64,
+    pub asks: f64,
+    pub exchange: String,
+}
+
+/// A price as published on `/stream`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceData {
+    pub symbol: String,
+    pub price: f64,
+    pub change_24h: Option<f64>,
+    pub change_24h_percent: Option<f64>,
+    pub volume_24h: Option<f64>,
+    pub market_cap: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circulating_supply: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_supply: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+    pub source: String,
+    #[serde(default)]
+    pub age_seconds: i64,
+    #[serde(default)]
+    pub is_stale: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_state: Option<MarketState>,
+    /// Session a stock price was traded in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<MarketState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<OrderBookDepth>,
+}
+
+/// One `/stream` message: prices of one asset type published together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    /// Round the prices were published in; 0 from servers that predate rounds
+    #[serde(default)]
+    pub round_id: u64,
+    pub asset_type: AssetType,
+    pub prices: Vec<PriceData>,
+}
+
+impl PriceUpdate {
+    /// Parse a text message received from `/stream`
+    pub fn from_message(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+}
+
+/// Body of a price webhook; check it with [`crate::signature::verify`] first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub webhook_id: i32,
+    /// "update", "threshold" or "staleness"
+    pub event: String,
+    pub asset_type: AssetType,
+    pub symbol: String,
+    pub price: f64,
+    /// Price of the previous threshold event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_percent: Option<f64>,
+    pub source: String,
+    /// When the price was observed
+    pub timestamp: DateTime<Utc>,
+    pub sent_at: DateTime<Utc>,
+}
@@ -0,0 +1,174 @@
+use futures::StreamExt;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{
+    ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::{JsFunction, JsUnknown};
+use napi_derive::napi;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::client::Client;
+use crate::errors::ClientError;
+use crate::models::{AssetType, PriceUpdate};
+use crate::signature;
+
+/// Node.js client; prices are plain objects shaped like the REST responses
+#[napi(js_name = "KanariClient")]
+pub struct NodeClient {
+    inner: Client,
+}
+
+#[napi]
+impl NodeClient {
+    #[napi(constructor)]
+    pub fn new(base_url: String, token: Option<String>) -> Result<Self> {
+        Ok(Self {
+            inner: Client::new(&base_url, token).map_err(napi_error)?,
+        })
+    }
+
+    #[napi]
+    pub async fn price(&self, asset_type: String, symbol: String) -> Result<serde_json::Value> {
+        let asset_type = parse_asset_type(&asset_type)?;
+        let price = self
+            .inner
+            .price(asset_type, &symbol)
+            .await
+            .map_err(napi_error)?;
+        Ok(serde_json::to_value(price)?)
+    }
+
+    #[napi]
+    pub async fn prices(&self, asset_type: String) -> Result<serde_json::Value> {
+        let asset_type = parse_asset_type(&asset_type)?;
+        let prices = self.inner.prices(asset_type).await.map_err(napi_error)?;
+        Ok(serde_json::to_value(prices)?)
+    }
+
+    /// Open `/stream` and call `onUpdate(err, update)` with each
+    /// `{asset_type, prices}` update; `err` is set once when the stream ends
+    /// abnormally. Call `close()` on the returned handle to stop.
+    #[napi(
+        ts_args_type = "onUpdate: (err: Error | null, update: { asset_type: string, prices: object[] }) => void"
+    )]
+    pub fn stream(&self, on_update: JsFunction) -> Result<PriceStream> {
+        let on_update: ThreadsafeFunction<serde_json::Value, ErrorStrategy::CalleeHandled> =
+            on_update.create_threadsafe_function(
+                0,
+                |ctx: ThreadSafeCallContext<serde_json::Value>| {
+                    ctx.env.to_js_value(&ctx.value).map(|value| vec![value])
+                },
+            )?;
+        let url = self.inner.stream_url();
+
+        let task = napi::bindgen_prelude::spawn(async move {
+            if let Err(e) = follow(&url, &on_update).await {
+                on_update.call(Err(e), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        });
+        Ok(PriceStream { task })
+    }
+
+    #[napi(getter)]
+    pub fn stream_url(&self) -> String {
+        self.inner.stream_url()
+    }
+}
+
+/// An open `/stream` subscription
+#[napi]
+pub struct PriceStream {
+    task: JoinHandle<()>,
+}
+
+#[napi]
+impl PriceStream {
+    #[napi]
+    pub fn close(&self) {
+        self.task.abort();
+    }
+}
+
+async fn follow(
+    url: &str,
+    on_update: &ThreadsafeFunction<serde_json::Value, ErrorStrategy::CalleeHandled>,
+) -> Result<()> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to open stream: {}", e)))?;
+
+    while let Some(message) = socket.next().await {
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(e) => return Err(Error::from_reason(format!("Stream failed: {}", e))),
+        };
+        // Skip anything that isn't an update rather than dropping the stream
+        let Ok(update) = PriceUpdate::from_message(&text) else {
+            continue;
+        };
+        let update = serde_json::to_value(update)?;
+        on_update.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+    Ok(())
+}
+
+/// Check a webhook's `X-Kanari-Signature` against the raw request body
+#[napi]
+pub fn verify_signature(secret: String, body: Buffer, signature: String) -> bool {
+    signature::verify(&secret, &body, &signature)
+}
+
+/// Check a price response's `X-Kanari-Response-Signature` against its raw body
+/// and the server's hex public key
+#[napi]
+pub fn verify_response(public_key: String, body: Buffer, signature: String) -> bool {
+    signature::verify_response(&public_key, &body, &signature)
+}
+
+/// Verify a price response like `verifyResponse`, and return the
+/// `{round_id, expires}` it was signed with. Throws when the signature is
+/// invalid or expired, or its round is older than `lastRound`.
+#[napi]
+pub fn verify_attestation(
+    public_key: String,
+    body: Buffer,
+    signature: String,
+    last_round: Option<i64>,
+) -> Result<serde_json::Value> {
+    let attestation =
+        signature::verify_attestation(&public_key, &body, &signature).map_err(napi_error)?;
+    if let Some(last_round) = last_round
+        && attestation.round_id < last_round.max(0) as u64
+    {
+        return Err(Error::from_reason(format!(
+            "Round {} is older than round {}",
+            attestation.round_id, last_round
+        )));
+    }
+    Ok(serde_json::to_value(attestation)?)
+}
+
+/// Parse a `/stream` message into a plain object
+#[napi]
+pub fn parse_update(text: String) -> Result<serde_json::Value> {
+    let update = PriceUpdate::from_message(&text)?;
+    Ok(serde_json::to_value(update)?)
+}
+
+fn parse_asset_type(asset_type: &str) -> Result<AssetType> {
+    asset_type.parse().map_err(Error::from_reason)
+}
+
+/// Server errors are prefixed with their machine-readable code, when given
+fn napi_error(error: ClientError) -> Error {
+    match error {
+        ClientError::ApiError {
+            message,
+            code: Some(code),
+        } => Error::from_reason(format!("{}: {}", code, message)),
+        error => Error::from_reason(error.to_string()),
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::Utc;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::errors::{ClientError, Result};
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>`, keyed with the
+/// webhook's secret
+pub const SIGNATURE_HEADER: &str = "X-Kanari-Signature";
+
+/// Header carrying the event name, e.g. "threshold"
+pub const EVENT_HEADER: &str = "X-Kanari-Event";
+
+/// Header carrying `round=<id>,expires=<unix seconds>,ed25519=<hex signature>`
+/// on price responses from a server with a signing key
+pub const RESPONSE_SIGNATURE_HEADER: &str = "X-Kanari-Response-Signature";
+
+/// Whether `signature` (the `X-Kanari-Signature` value) was made for the raw
+/// request `body` with `secret`. Compared in constant time; check the bytes as
+/// received, before parsing them.
+pub fn verify(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(expected) = signature
+        .trim()
+        .strip_prefix("sha256=")
+        .and_then(|hex| hex::decode(hex).ok())
+    else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// What a valid `X-Kanari-Response-Signature` vouches for besides the body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Attestation {
+    /// Oracle round the response was served at. The server gives every write to
+    /// its prices a strictly greater id, so a lower id means older data.
+    pub round_id: u64,
+    /// Unix seconds after which the response must not be trusted
+    pub expires: u64,
+}
+
+/// Bytes the server signs: the round id and expiry in decimal, each followed by
+/// a dot, then the body. On-chain verifiers rebuild this message to check the
+/// Ed25519 signature.
+pub fn signed_message(round_id: u64, expires: u64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}.{}.", round_id, expires).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// Check that `signature` (the `X-Kanari-Response-Signature` value) was made
+/// for the raw response `body` by the server whose hex Ed25519 public key is
+/// `public_key` (the `signing_key` in its `/version`), and has not expired.
+/// Check the bytes as received, before parsing them; pass the result to a
+/// [`ReplayGuard`] to also reject responses older than ones already seen.
+pub fn verify_attestation(public_key: &str, body: &[u8], signature: &str) -> Result<Attestation> {
+    let key = parse_public_key(public_key)
+        .ok_or_else(|| ClientError::InvalidPublicKey("expected 64 hex characters".to_string()))?;
+    let malformed =
+        || ClientError::InvalidSignature(format!("malformed signature '{}'", signature));
+
+    let (mut round_id, mut expires, mut bytes) = (None, None, None);
+    for field in signature.trim().split(',') {
+        match field.trim().split_once('=').ok_or_else(malformed)? {
+            ("round", value) => round_id = value.parse::<u64>().ok(),
+            ("expires", value) => expires = value.parse::<u64>().ok(),
+            ("ed25519", value) => bytes = hex::decode(value).ok(),
+            _ => {}
+        }
+    }
+    let (Some(round_id), Some(expires), Some(bytes)) = (round_id, expires, bytes) else {
+        return Err(malformed());
+    };
+    let signature = Signature::from_slice(&bytes).map_err(|_| malformed())?;
+    if key
+        .verify(&signed_message(round_id, expires, body), &signature)
+        .is_err()
+    {
+        return Err(ClientError::InvalidSignature(
+            "signature does not match the body".to_string(),
+        ));
+    }
+
+    let now = Utc::now().timestamp().max(0) as u64;
+    if now > expires {
+        return Err(ClientError::InvalidSignature(format!(
+            "signature of round {} expired {}s ago",
+            round_id,
+            now - expires
+        )));
+    }
+    Ok(Attestation { round_id, expires })
+}
+
+/// Whether `signature` is a valid, unexpired signature of `body` by
+/// `public_key`; see [`verify_attestation`]
+pub fn verify_response(public_key: &str, body: &[u8], signature: &str) -> bool {
+    verify_attestation(public_key, body, signature).is_ok()
+}
+
+/// Remembers the newest round accepted and rejects attestations from older
+/// rounds, so a replayed response cannot roll a verifier back to stale prices.
+/// Responses from the same round are accepted again, since they carry the same
+/// prices.
+#[derive(Debug, Default)]
+pub struct ReplayGuard {
+    last_round: AtomicU64,
+}
+
+impl ReplayGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Newest round accepted so far, 0 before the first
+    pub fn last_round(&self) -> u64 {
+        self.last_round.load(Ordering::SeqCst)
+    }
+
+    /// Accept `attestation` unless one from a later round already was
+    pub fn accept(&self, attestation: &Attestation) -> Result<()> {
+        let previous = self
+            .last_round
+            .fetch_max(attestation.round_id, Ordering::SeqCst);
+        if attestation.round_id < previous {
+            return Err(ClientError::InvalidSignature(format!(
+                "round {} is older than round {} already seen",
+                attestation.round_id, previous
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Ed25519 public key written as 64 hex characters
+pub(crate) fn parse_public_key(public_key: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(public_key.trim()).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const SEED: [u8; 32] = [7; 32];
+    const BODY: &[u8] = br#"{"price":64000.5,"symbol":"btc"}"#;
+
+    fn public_key() -> String {
+        hex::encode(SigningKey::from_bytes(&SEED).verifying_key().as_bytes())
+    }
+
+    /// Header value as the server writes it
+    fn sign(round_id: u64, expires: u64, body: &[u8]) -> String {
+        let signature =
+            SigningKey::from_bytes(&SEED).sign(&signed_message(round_id, expires, body));
+        format!(
+            "round={},expires={},ed25519={}",
+            round_id,
+            expires,
+            hex::encode(signature.to_bytes())
+        )
+    }
+
+    fn in_a_minute() -> u64 {
+        Utc::now().timestamp() as u64 + 60
+    }
+
+    #[test]
+    fn accepts_a_valid_signature() {
+        let expires = in_a_minute();
+        let attestation =
+            verify_attestation(&public_key(), BODY, &sign(42, expires, BODY)).unwrap();
+        assert_eq!(
+            attestation,
+            Attestation {
+                round_id: 42,
+                expires
+            }
+        );
+        assert!(verify_response(
+            &public_key(),
+            BODY,
+            &sign(42, expires, BODY)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_tampered_body() {
+        let signature = sign(42, in_a_minute(), BODY);
+        let tampered = br#"{"price":1.0,"symbol":"btc"}"#;
+        assert!(verify_attestation(&public_key(), tampered, &signature).is_err());
+    }
+
+    #[test]
+    fn rejects_a_tampered_round_or_expiry() {
+        let expires = in_a_minute();
+        let signature = sign(42, expires, BODY);
+        let later_round = signature.replace("round=42", "round=43");
+        assert!(verify_attestation(&public_key(), BODY, &later_round).is_err());
+        let extended = signature.replace(
+            &format!("expires={}", expires),
+            &format!("expires={}", expires + 3600),
+        );
+        assert!(verify_attestation(&public_key(), BODY, &extended).is_err());
+    }
+
+    #[test]
+    fn rejects_an_expired_signature() {
+        let expired = Utc::now().timestamp() as u64 - 1;
+        let error = verify_attestation(&public_key(), BODY, &sign(42, expired, BODY)).unwrap_err();
+        assert!(error.to_string().contains("expired"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_another_servers_key() {
+        let other = hex::encode(SigningKey::from_bytes(&[8; 32]).verifying_key().as_bytes());
+        assert!(verify_attestation(&other, BODY, &sign(42, in_a_minute(), BODY)).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_signatures_and_keys() {
+        let key = public_key();
+        assert!(verify_attestation(&key, BODY, "").is_err());
+        assert!(verify_attestation(&key, BODY, "round=1,expires=2").is_err());
+        assert!(verify_attestation(&key, BODY, "round=1,expires=2,ed25519=zz").is_err());
+        assert!(matches!(
+            verify_attestation("abc", BODY, &sign(42, in_a_minute(), BODY)),
+            Err(ClientError::InvalidPublicKey(_))
+        ));
+    }
+
+    #[test]
+    fn replay_guard_rejects_older_rounds() {
+        let guard = ReplayGuard::new();
+        let round = |round_id| Attestation {
+            round_id,
+            expires: in_a_minute(),
+        };
+        assert!(guard.accept(&round(5)).is_ok());
+        assert!(guard.accept(&round(5)).is_ok());
+        assert!(guard.accept(&round(4)).is_err());
+        assert!(guard.accept(&round(6)).is_ok());
+        assert!(guard.accept(&round(5)).is_err());
+        assert_eq!(guard.last_round(), 6);
+    }
+
+    #[test]
+    fn verifies_webhook_signatures() {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"webhook-secret-123").unwrap();
+        mac.update(BODY);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(verify("webhook-secret-123", BODY, &signature));
+        assert!(!verify("another-secret-456", BODY, &signature));
+        assert!(!verify("webhook-secret-123", b"{}", &signature));
+        assert!(!verify(
+            "webhook-secret-123",
+            BODY,
+            signature.trim_start_matches("sha256=")
+        ));
+    }
+}
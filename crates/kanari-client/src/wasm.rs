@@ -0,0 +1,153 @@
+use js_sys::{Function, Promise};
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+use web_sys::{MessageEvent, WebSocket};
+
+use crate::client::Client;
+use crate::errors::ClientError;
+use crate::models::{AssetType, PriceUpdate};
+use crate::signature;
+
+/// Browser client; prices are plain objects shaped like the REST responses
+#[wasm_bindgen(js_name = KanariClient)]
+pub struct WasmClient {
+    inner: Client,
+}
+
+#[wasm_bindgen(js_class = KanariClient)]
+impl WasmClient {
+    #[wasm_bindgen(constructor)]
+    pub fn new(base_url: &str, token: Option<String>) -> Result<WasmClient, JsError> {
+        Ok(Self {
+            inner: Client::new(base_url, token).map_err(js_error)?,
+        })
+    }
+
+    /// Resolves to one price, or rejects with the server's error
+    pub fn price(&self, asset_type: &str, symbol: String) -> Result<Promise, JsError> {
+        let asset_type = parse_asset_type(asset_type)?;
+        let client = self.inner.clone();
+        Ok(future_to_promise(async move {
+            let price = client.price(asset_type, &symbol).await.map_err(js_value)?;
+            to_js(&price)
+        }))
+    }
+
+    /// Resolves to every price of `asset_type`
+    pub fn prices(&self, asset_type: &str) -> Result<Promise, JsError> {
+        let asset_type = parse_asset_type(asset_type)?;
+        let client = self.inner.clone();
+        Ok(future_to_promise(async move {
+            let prices = client.prices(asset_type).await.map_err(js_value)?;
+            to_js(&prices)
+        }))
+    }
+
+    /// Open `/stream` and call `on_update` with each `{asset_type, prices}`
+    /// update. Close the returned WebSocket to stop.
+    pub fn stream(&self, on_update: Function) -> Result<WebSocket, JsError> {
+        let socket = WebSocket::new(&self.inner.stream_url())
+            .map_err(|e| JsError::new(&format!("Failed to open stream: {:?}", e)))?;
+
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+            let update = PriceUpdate::from_message(&text)
+                .map_err(|e| JsValue::from(JsError::new(&e.to_string())))
+                .and_then(|update| to_js(&update));
+            match update {
+                Ok(update) => {
+                    let _ = on_update.call1(&JsValue::NULL, &update);
+                }
+                Err(e) => {
+                    web_sys::console::warn_2(&"Ignoring unreadable stream message".into(), &e)
+                }
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        // Lives as long as the socket may deliver messages
+        on_message.forget();
+
+        Ok(socket)
+    }
+
+    #[wasm_bindgen(getter, js_name = streamUrl)]
+    pub fn stream_url(&self) -> String {
+        self.inner.stream_url()
+    }
+}
+
+/// Check a webhook's `X-Kanari-Signature` against the raw request body
+#[wasm_bindgen(js_name = verifySignature)]
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    signature::verify(secret, body, signature)
+}
+
+/// Check a price response's `X-Kanari-Response-Signature` against its raw body
+/// and the server's hex public key
+#[wasm_bindgen(js_name = verifyResponse)]
+pub fn verify_response(public_key: &str, body: &[u8], signature: &str) -> bool {
+    signature::verify_response(public_key, body, signature)
+}
+
+/// Verify a price response like `verifyResponse`, and return the
+/// `{round_id, expires}` it was signed with. Throws when the signature is
+/// invalid or expired, or its round is older than `lastRound`.
+#[wasm_bindgen(js_name = verifyAttestation)]
+pub fn verify_attestation(
+    public_key: &str,
+    body: &[u8],
+    signature: &str,
+    last_round: Option<f64>,
+) -> Result<JsValue, JsError> {
+    let attestation =
+        signature::verify_attestation(public_key, body, signature).map_err(js_error)?;
+    if let Some(last_round) = last_round
+        && (attestation.round_id as f64) < last_round
+    {
+        return Err(JsError::new(&format!(
+            "Round {} is older than round {}",
+            attestation.round_id, last_round
+        )));
+    }
+    to_js(&attestation).map_err(|e| JsError::new(&format!("{:?}", e)))
+}
+
+/// Parse a `/stream` message into a plain object
+#[wasm_bindgen(js_name = parseUpdate)]
+pub fn parse_update(text: &str) -> Result<JsValue, JsError> {
+    let update = PriceUpdate::from_message(text).map_err(|e| JsError::new(&e.to_string()))?;
+    to_js(&update).map_err(|e| JsError::new(&format!("{:?}", e)))
+}
+
+fn parse_asset_type(asset_type: &str) -> Result<AssetType, JsError> {
+    asset_type.parse().map_err(|e: String| JsError::new(&e))
+}
+
+/// Objects rather than Maps, and null for missing fields, as in the JSON
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
+fn js_error(error: ClientError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Rejection value carrying the server's error `code`, when it gave one
+fn js_value(error: ClientError) -> JsValue {
+    let code = match &error {
+        ClientError::ApiError { code, .. } => code.clone(),
+        _ => None,
+    };
+    let value = JsValue::from(js_error(error));
+    if let Some(code) = code {
+        let _ = js_sys::Reflect::set(&value, &"code".into(), &code.into());
+    }
+    value
+}
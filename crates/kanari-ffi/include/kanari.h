@@ -0,0 +1,100 @@
+/*
+ * C API of the kanari price oracle, implemented by libkanari_ffi.
+ *
+ * Every function may be called from any thread. Calls that fetch prices block
+ * the calling thread until the fetch finishes. After a failed call,
+ * kanari_last_error() describes the failure on the calling thread.
+ */
+#ifndef KANARI_H
+#define KANARI_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes */
+#define KANARI_OK 0
+#define KANARI_ERR_INVALID_ARGUMENT (-1)
+#define KANARI_ERR_NOT_FOUND (-2)
+/* Every source failed, timed out, rate limited us or returned a stale price */
+#define KANARI_ERR_UNAVAILABLE (-3)
+#define KANARI_ERR_CONFIG (-4)
+#define KANARI_ERR_INTERNAL (-5)
+
+/* Asset types */
+#define KANARI_ASSET_CRYPTO 0
+#define KANARI_ASSET_STOCK 1
+/* A basket from the config's `baskets` */
+#define KANARI_ASSET_BASKET 2
+/* Total value locked of a protocol or chain ("chain:ethereum"), in USD */
+#define KANARI_ASSET_TVL 3
+
+typedef struct KanariOracle KanariOracle;
+typedef struct KanariSubscription KanariSubscription;
+
+/* A price. Optional values the source did not report are NaN. */
+typedef struct KanariPrice {
+    char *symbol;
+    char *source;
+    double price;
+    double change_24h;
+    double change_24h_percent;
+    double volume_24h;
+    double market_cap;
+    /* Milliseconds since the Unix epoch */
+    int64_t timestamp_ms;
+    int64_t age_seconds;
+    bool is_stale;
+} KanariPrice;
+
+/* Called with each published price; `price` is only valid during the call */
+typedef void (*KanariPriceCallback)(const KanariPrice *price, void *user_data);
+
+/*
+ * Start an oracle from the config file at `config_path` (created with the
+ * defaults when missing), or from the defaults and KANARI_* environment
+ * variables when NULL. Update rounds run in the background every
+ * general.update_interval seconds. Returns NULL on failure.
+ */
+KanariOracle *kanari_oracle_new(const char *config_path);
+
+/* Stop the background updates and free the oracle. NULL is ignored. */
+void kanari_oracle_free(KanariOracle *oracle);
+
+/*
+ * Look up the price of `symbol`, fetching it when the cached one has expired.
+ * On KANARI_OK `out` is filled in; release it with kanari_price_free().
+ */
+int kanari_get_price(const KanariOracle *oracle, int asset_type, const char *symbol,
+                     KanariPrice *out);
+
+/* Free the strings of a price filled in by kanari_get_price(). NULL is ignored. */
+void kanari_price_free(KanariPrice *price);
+
+/* Run an update round now. Returns the number of prices updated, or a status < 0. */
+int kanari_update(const KanariOracle *oracle);
+
+/*
+ * Call `callback` with every price the oracle publishes, on one of the
+ * oracle's threads. Returns NULL on failure.
+ */
+KanariSubscription *kanari_subscribe(const KanariOracle *oracle, KanariPriceCallback callback,
+                                     void *user_data);
+
+/*
+ * Stop a subscription and free it. No callback starts after this returns.
+ * Must not be called from inside the subscription's own callback.
+ */
+void kanari_unsubscribe(KanariSubscription *subscription);
+
+/* Message of the last failed call on this thread, or NULL. Valid until the next failure. */
+const char *kanari_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* KANARI_H */
@@ -0,0 +1,71 @@
+use std::cell::RefCell;
+use std::ffi::{CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use kanari_oracle::errors::OracleError;
+
+pub const KANARI_OK: i32 = 0;
+pub const KANARI_ERR_INVALID_ARGUMENT: i32 = -1;
+pub const KANARI_ERR_NOT_FOUND: i32 = -2;
+pub const KANARI_ERR_UNAVAILABLE: i32 = -3;
+pub const KANARI_ERR_CONFIG: i32 = -4;
+pub const KANARI_ERR_INTERNAL: i32 = -5;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Remember `message` as this thread's last error
+pub fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+}
+
+/// Record an oracle error and return its status code
+pub fn report(error: &OracleError) -> i32 {
+    set_last_error(error.to_string());
+    status(error)
+}
+
+fn status(error: &OracleError) -> i32 {
+    match error {
+        OracleError::PriceNotFound(_)
+        | OracleError::InvalidSymbol(_)
+        | OracleError::UnknownSource { .. } => KANARI_ERR_NOT_FOUND,
+        OracleError::AmbiguousSymbol(_) | OracleError::UnknownCurrency(_) => {
+            KANARI_ERR_INVALID_ARGUMENT
+        }
+        OracleError::ConfigError(_) | OracleError::JsonError(_) => KANARI_ERR_CONFIG,
+        OracleError::HttpError(_)
+        | OracleError::HttpStatus { .. }
+        | OracleError::RateLimited { .. }
+        | OracleError::Timeout(_)
+        | OracleError::SourceUnavailable(_)
+        | OracleError::StaleData { .. }
+        | OracleError::AnomalousPrice { .. }
+        | OracleError::ApiError(_)
+        | OracleError::StoreError(_) => KANARI_ERR_UNAVAILABLE,
+        OracleError::IoError(_) | OracleError::IoOperationFailed(_) => KANARI_ERR_INTERNAL,
+    }
+}
+
+/// Run an entry point, turning a panic into `KANARI_ERR_INTERNAL` instead of
+/// unwinding into C
+pub fn guard(f: impl FnOnce() -> i32) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        set_last_error("internal error: the oracle panicked");
+        KANARI_ERR_INTERNAL
+    })
+}
+
+/// Message of the last failed call on this thread, or NULL. The string stays
+/// valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn kanari_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
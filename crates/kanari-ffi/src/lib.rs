@@ -0,0 +1,328 @@
+//! C ABI for embedding the oracle in-process, declared in `include/kanari.h`.
+//! Each handle owns a Tokio runtime that runs the background update rounds and
+//! subscription callbacks; calls block the calling thread until they finish.
+
+use log::warn;
+use std::ffi::{CStr, c_char, c_int, c_void};
+use std::ptr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use kanari_oracle::config::Config;
+use kanari_oracle::errors::{OracleError, Result};
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Oracle;
+
+mod error;
+mod price;
+
+use error::{KANARI_ERR_INVALID_ARGUMENT, KANARI_OK, guard, report, set_last_error};
+use price::KanariPrice;
+
+pub const KANARI_ASSET_CRYPTO: c_int = 0;
+pub const KANARI_ASSET_STOCK: c_int = 1;
+pub const KANARI_ASSET_BASKET: c_int = 2;
+pub const KANARI_ASSET_TVL: c_int = 3;
+
+/// Oracle handle returned by `kanari_oracle_new`
+pub struct KanariOracle {
+    oracle: Oracle,
+    /// Background update rounds at `general.update_interval`
+    updater: JoinHandle<()>,
+    runtime: Runtime,
+}
+
+impl Drop for KanariOracle {
+    fn drop(&mut self) {
+        self.updater.abort();
+    }
+}
+
+/// Called with each published price and the `user_data` given to `kanari_subscribe`
+pub type KanariPriceCallback = extern "C" fn(price: *const KanariPrice, user_data: *mut c_void);
+
+struct Callback {
+    function: KanariPriceCallback,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the caller of `kanari_subscribe` promises `user_data` may be used
+// from the runtime's threads
+unsafe impl Send for Callback {}
+
+/// Subscription handle returned by `kanari_subscribe`
+pub struct KanariSubscription {
+    /// Cleared by `kanari_unsubscribe`, so no call starts after it returns
+    callback: Arc<Mutex<Option<Callback>>>,
+    task: JoinHandle<()>,
+}
+
+fn asset_type(value: c_int) -> Option<AssetType> {
+    match value {
+        KANARI_ASSET_CRYPTO => Some(AssetType::Crypto),
+        KANARI_ASSET_STOCK => Some(AssetType::Stock),
+        KANARI_ASSET_BASKET => Some(AssetType::Basket),
+        KANARI_ASSET_TVL => Some(AssetType::Tvl),
+        _ => None,
+    }
+}
+
+/// Borrow a C string argument, recording an error when it is NULL or not UTF-8
+///
+/// # Safety
+///
+/// `value` must be NULL or a NUL-terminated string.
+unsafe fn str_arg<'a>(name: &str, value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        set_last_error(format!("{} must not be NULL", name));
+        return None;
+    }
+    // SAFETY: guaranteed by the caller
+    match unsafe { CStr::from_ptr(value) }.to_str() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            set_last_error(format!("{} is not valid UTF-8", name));
+            None
+        }
+    }
+}
+
+async fn load_config(path: Option<&str>) -> Result<Config> {
+    let config = match path {
+        Some(path) => Config::from_file(path).await?,
+        None => {
+            let mut config = Config::default().with_env_overrides()?;
+            config.resolve_secrets().await?;
+            config
+        }
+    };
+    config.validate()?;
+    Ok(config)
+}
+
+/// Run an update round every `general.update_interval` seconds
+async fn update_rounds(oracle: Oracle) {
+    let period = Duration::from_secs(oracle.config().general.update_interval.max(1));
+    let mut interval = time::interval(period);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if let Err(e) = oracle.update_all_prices().await {
+            warn!("Update round failed: {}", e);
+        }
+    }
+}
+
+fn new_oracle(config_path: Option<&str>) -> std::result::Result<KanariOracle, OracleError> {
+    let runtime = Runtime::new()?;
+    let oracle = runtime.block_on(async {
+        let config = load_config(config_path).await?;
+        Oracle::new(config).await
+    })?;
+    let updater = runtime.spawn(update_rounds(oracle.clone()));
+    Ok(KanariOracle {
+        oracle,
+        updater,
+        runtime,
+    })
+}
+
+/// Start an oracle from the config file at `config_path`, or from the defaults
+/// and `KANARI_*` variables when it is NULL. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `config_path` must be NULL or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_oracle_new(config_path: *const c_char) -> *mut KanariOracle {
+    let mut handle = ptr::null_mut();
+    guard(|| {
+        let path = if config_path.is_null() {
+            None
+        } else {
+            // SAFETY: guaranteed by the caller
+            match unsafe { str_arg("config_path", config_path) } {
+                Some(path) => Some(path),
+                None => return KANARI_ERR_INVALID_ARGUMENT,
+            }
+        };
+        match new_oracle(path) {
+            Ok(oracle) => {
+                handle = Box::into_raw(Box::new(oracle));
+                KANARI_OK
+            }
+            Err(e) => report(&e),
+        }
+    });
+    handle
+}
+
+/// Stop the background updates and free the oracle. Free its subscriptions
+/// first; callbacks stop once the oracle is gone.
+///
+/// # Safety
+///
+/// `oracle` must be NULL or a handle from `kanari_oracle_new` not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_oracle_free(oracle: *mut KanariOracle) {
+    if !oracle.is_null() {
+        // SAFETY: guaranteed by the caller
+        drop(unsafe { Box::from_raw(oracle) });
+    }
+}
+
+/// Look up the price of `symbol`, fetching it when the cached one has expired.
+/// On success `out` is filled in and must be released with `kanari_price_free`.
+///
+/// # Safety
+///
+/// `oracle` must be a live handle, `symbol` a NUL-terminated string and `out`
+/// writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_get_price(
+    oracle: *const KanariOracle,
+    asset_type: c_int,
+    symbol: *const c_char,
+    out: *mut KanariPrice,
+) -> c_int {
+    guard(|| {
+        // SAFETY: guaranteed by the caller
+        let Some(handle) = (unsafe { oracle.as_ref() }) else {
+            set_last_error("oracle must not be NULL");
+            return KANARI_ERR_INVALID_ARGUMENT;
+        };
+        if out.is_null() {
+            set_last_error("out must not be NULL");
+            return KANARI_ERR_INVALID_ARGUMENT;
+        }
+        let Some(asset_type) = self::asset_type(asset_type) else {
+            set_last_error(format!("Invalid asset type {}", asset_type));
+            return KANARI_ERR_INVALID_ARGUMENT;
+        };
+        // SAFETY: guaranteed by the caller
+        let Some(symbol) = (unsafe { str_arg("symbol", symbol) }) else {
+            return KANARI_ERR_INVALID_ARGUMENT;
+        };
+
+        let result = handle.runtime.block_on(async {
+            match asset_type {
+                AssetType::Crypto => handle.oracle.get_crypto_price(symbol).await,
+                AssetType::Stock => handle.oracle.get_stock_price(symbol).await,
+                AssetType::Basket => handle.oracle.get_basket_price(symbol).await,
+                AssetType::Tvl => handle.oracle.get_tvl(symbol).await,
+            }
+        });
+        match result {
+            Ok(data) => {
+                // SAFETY: `out` is writable; it may hold garbage, so it is not dropped
+                unsafe { out.write(KanariPrice::new(&data)) };
+                KANARI_OK
+            }
+            Err(e) => report(&e),
+        }
+    })
+}
+
+/// Run an update round now. Returns the number of prices updated, or a negative
+/// status on failure.
+///
+/// # Safety
+///
+/// `oracle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_update(oracle: *const KanariOracle) -> c_int {
+    guard(|| {
+        // SAFETY: guaranteed by the caller
+        let Some(handle) = (unsafe { oracle.as_ref() }) else {
+            set_last_error("oracle must not be NULL");
+            return KANARI_ERR_INVALID_ARGUMENT;
+        };
+        match handle.runtime.block_on(handle.oracle.update_all_prices()) {
+            Ok(round) => c_int::try_from(round.updated).unwrap_or(c_int::MAX),
+            Err(e) => report(&e),
+        }
+    })
+}
+
+/// Call `callback` with every price the oracle publishes, on one of its runtime
+/// threads. The price passed in is only valid during the call. Returns NULL on
+/// failure.
+///
+/// # Safety
+///
+/// `oracle` must be a live handle, and `callback` must be safe to call with
+/// `user_data` from another thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_subscribe(
+    oracle: *const KanariOracle,
+    callback: Option<KanariPriceCallback>,
+    user_data: *mut c_void,
+) -> *mut KanariSubscription {
+    let mut subscription = ptr::null_mut();
+    guard(|| {
+        // SAFETY: guaranteed by the caller
+        let Some(handle) = (unsafe { oracle.as_ref() }) else {
+            set_last_error("oracle must not be NULL");
+            return KANARI_ERR_INVALID_ARGUMENT;
+        };
+        let Some(function) = callback else {
+            set_last_error("callback must not be NULL");
+            return KANARI_ERR_INVALID_ARGUMENT;
+        };
+
+        let callback = Arc::new(Mutex::new(Some(Callback {
+            function,
+            user_data,
+        })));
+        let mut updates = handle.oracle.subscribe();
+        let shared = callback.clone();
+        let task = handle.runtime.spawn(async move {
+            loop {
+                let update = match updates.recv().await {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Subscriber fell behind, skipped {} updates", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                let callback = shared.lock().unwrap_or_else(PoisonError::into_inner);
+                let Some(callback) = callback.as_ref() else {
+                    return;
+                };
+                for data in &update.prices {
+                    let price = KanariPrice::new(data);
+                    (callback.function)(&price, callback.user_data);
+                }
+            }
+        });
+
+        subscription = Box::into_raw(Box::new(KanariSubscription { callback, task }));
+        KANARI_OK
+    });
+    subscription
+}
+
+/// Stop a subscription and free it. No callback starts after this returns; it
+/// must not be called from inside the subscription's own callback.
+///
+/// # Safety
+///
+/// `subscription` must be NULL or a handle from `kanari_subscribe` not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_unsubscribe(subscription: *mut KanariSubscription) {
+    if subscription.is_null() {
+        return;
+    }
+    // SAFETY: guaranteed by the caller
+    let subscription = unsafe { Box::from_raw(subscription) };
+    subscription.task.abort();
+    *subscription
+        .callback
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = None;
+}
@@ -0,0 +1,73 @@
+use std::ffi::{CString, c_char};
+use std::ptr;
+
+use kanari_oracle::models::PriceData;
+
+/// C view of a price. Missing optional values are NaN.
+#[repr(C)]
+pub struct KanariPrice {
+    pub symbol: *mut c_char,
+    pub source: *mut c_char,
+    pub price: f64,
+    pub change_24h: f64,
+    pub change_24h_percent: f64,
+    pub volume_24h: f64,
+    pub market_cap: f64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: i64,
+    pub age_seconds: i64,
+    pub is_stale: bool,
+}
+
+impl KanariPrice {
+    pub fn new(data: &PriceData) -> Self {
+        Self {
+            symbol: c_string(&data.symbol),
+            source: c_string(&data.source),
+            price: data.price,
+            change_24h: data.change_24h.unwrap_or(f64::NAN),
+            change_24h_percent: data.change_24h_percent.unwrap_or(f64::NAN),
+            volume_24h: data.volume_24h.unwrap_or(f64::NAN),
+            market_cap: data.market_cap.unwrap_or(f64::NAN),
+            timestamp_ms: data.timestamp.timestamp_millis(),
+            age_seconds: data.age_seconds,
+            is_stale: data.is_stale,
+        }
+    }
+
+    /// Free the strings and reset them to NULL, so freeing twice is harmless
+    fn release(&mut self) {
+        for field in [&mut self.symbol, &mut self.source] {
+            if !field.is_null() {
+                // SAFETY: non-null strings were allocated by `c_string`
+                drop(unsafe { CString::from_raw(*field) });
+                *field = ptr::null_mut();
+            }
+        }
+    }
+}
+
+impl Drop for KanariPrice {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn c_string(value: &str) -> *mut c_char {
+    CString::new(value.replace('\0', ""))
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Free the strings of a price filled in by `kanari_get_price`
+///
+/// # Safety
+///
+/// `price` must be NULL or point to a price filled in by `kanari_get_price`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn kanari_price_free(price: *mut KanariPrice) {
+    // SAFETY: guaranteed by the caller
+    if let Some(price) = unsafe { price.as_mut() } {
+        price.release();
+    }
+}
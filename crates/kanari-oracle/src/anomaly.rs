@@ -0,0 +1,202 @@
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::config::AnomalyConfig;
+use crate::errors::OracleError;
+use crate::history::PriceHistory;
+use crate::models::{AssetType, PriceData};
+use crate::price_math;
+use crate::symbols;
+
+/// Anomalies kept for `Oracle::anomalies`
+const RECENT_ANOMALIES: usize = 100;
+
+/// Scale making the median absolute deviation comparable to a standard
+/// deviation, for the modified z-score
+const MAD_SCALE: f64 = 0.6745;
+
+/// A price that stood out from its symbol's recent history
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomaly {
+    pub asset_type: AssetType,
+    pub symbol: String,
+    pub source: String,
+    pub price: f64,
+    /// Median of the history the price was compared against
+    pub median: f64,
+    /// Modified z-score, or plain z-score when the window has no spread
+    pub score: f64,
+    pub detected_at: DateTime<Utc>,
+    /// False while the price is held back; true once it persisted for
+    /// `max_quarantine` rounds and was published as the new level
+    pub accepted: bool,
+}
+
+impl Anomaly {
+    /// Error returned to callers that asked for the held price
+    pub fn to_error(&self) -> OracleError {
+        OracleError::AnomalousPrice {
+            symbol: self.symbol.clone(),
+            source_name: self.source.clone(),
+            price: self.price,
+            median: self.median,
+        }
+    }
+}
+
+/// Screens prices before they are published. A price far outside its symbol's
+/// recent window (`anomaly.window` seconds of history) is held back and the feed
+/// keeps the previous value. If the move persists for more than
+/// `anomaly.max_quarantine` consecutive prices it is accepted as a real move.
+#[derive(Debug)]
+pub struct AnomalyDetector {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    config: Option<AnomalyConfig>,
+    /// Consecutive held prices, keyed "asset_type:feed_key"
+    held: HashMap<String, u32>,
+    recent: VecDeque<Anomaly>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: Option<AnomalyConfig>) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                config,
+                held: HashMap::new(),
+                recent: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Apply new settings; `None` turns screening off
+    pub fn reconfigure(&self, config: Option<AnomalyConfig>) {
+        let mut inner = self.inner.lock().unwrap();
+        if config.is_none() {
+            inner.held.clear();
+        }
+        inner.config = config;
+    }
+
+    /// The anomaly if `price_data` should be held back instead of published
+    pub fn screen(
+        &self,
+        asset_type: AssetType,
+        price_data: &PriceData,
+        history: &PriceHistory,
+    ) -> Option<Anomaly> {
+        let mut inner = self.inner.lock().unwrap();
+        let config = inner.config.clone()?;
+        let key = format!(
+            "{}:{}",
+            asset_type.as_str(),
+            symbols::feed_key(asset_type, &price_data.symbol)
+        );
+
+        let now = Utc::now();
+        let from = now - chrono::Duration::seconds(config.window as i64);
+        let window: Vec<f64> = history
+            .range(asset_type, &price_data.symbol, from, now)
+            .iter()
+            .map(|point| point.price)
+            .collect();
+
+        let Some((median, score)) = outlier_score(&window, price_data.price, &config) else {
+            inner.held.remove(&key);
+            return None;
+        };
+        if score <= config.threshold {
+            inner.held.remove(&key);
+            return None;
+        }
+
+        let held = inner.held.entry(key.clone()).or_insert(0);
+        *held += 1;
+        let accepted = *held > config.max_quarantine;
+        if accepted {
+            inner.held.remove(&key);
+        }
+
+        let anomaly = Anomaly {
+            asset_type,
+            symbol: price_data.symbol.clone(),
+            source: price_data.source.clone(),
+            price: price_data.price,
+            median,
+            score,
+            detected_at: now,
+            accepted,
+        };
+        if inner.recent.len() == RECENT_ANOMALIES {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(anomaly.clone());
+
+        if accepted {
+            info!(
+                "Accepting {} {} at {} from {} after {} held prices (median was {})",
+                asset_type.as_str(),
+                price_data.symbol,
+                price_data.price,
+                price_data.source,
+                config.max_quarantine,
+                median
+            );
+            None
+        } else {
+            warn!(
+                "Holding anomalous {} {} price {} from {} (median {}, score {:.1})",
+                asset_type.as_str(),
+                price_data.symbol,
+                price_data.price,
+                price_data.source,
+                median,
+                score
+            );
+            Some(anomaly)
+        }
+    }
+
+    /// Recently detected anomalies, oldest first
+    pub fn recent(&self) -> Vec<Anomaly> {
+        self.inner.lock().unwrap().recent.iter().cloned().collect()
+    }
+}
+
+/// Median of `window` and how far `price` lies outside it, or `None` when the
+/// window is too short or the move is below `min_deviation_percent`
+fn outlier_score(window: &[f64], price: f64, config: &AnomalyConfig) -> Option<(f64, f64)> {
+    if window.len() < config.min_samples {
+        return None;
+    }
+    let median = price_math::median(window.iter().copied())?;
+    let deviation = price_math::deviation_percent(price, median)?;
+    if deviation.abs() < config.min_deviation_percent {
+        return None;
+    }
+
+    let mad = price_math::median(window.iter().map(|value| (value - median).abs()))?;
+    if mad > 0.0 {
+        return Some((median, MAD_SCALE * (price - median).abs() / mad));
+    }
+
+    // Over half the window is one value; fall back to the standard z-score
+    let mean = price_math::mean(window.iter().copied())?;
+    let variance = window
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / window.len() as f64;
+    let score = if variance > 0.0 {
+        (price - mean).abs() / variance.sqrt()
+    } else {
+        f64::INFINITY
+    };
+    Some((median, score))
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use crate::config::AuditConfig;
+use crate::models::AssetType;
+use crate::provenance::{AttemptOutcome, SourceAttempt};
+
+/// One upstream request for one symbol, as written to the audit log
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub asset_type: AssetType,
+    pub source: String,
+    pub symbol: String,
+    pub outcome: AttemptOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Appends every upstream fetch attempt to a JSON-lines file, rotated by size.
+/// Writes happen on a background task, so fetching never waits on the disk.
+#[derive(Clone)]
+pub struct AuditLog {
+    sender: mpsc::UnboundedSender<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Start the writer task; it runs until every clone of the log is dropped
+    pub fn open(config: &AuditConfig) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<AuditEntry>();
+        let mut file = RollingFile {
+            path: PathBuf::from(&config.path),
+            max_bytes: config.max_size.saturating_mul(1024 * 1024),
+            max_files: config.max_files,
+            file: None,
+            size: 0,
+        };
+
+        tokio::spawn(async move {
+            while let Some(entry) = receiver.recv().await {
+                // Write whatever has queued up in one go
+                let mut lines = Vec::new();
+                for entry in
+                    std::iter::once(entry).chain(std::iter::from_fn(|| receiver.try_recv().ok()))
+                {
+                    match serde_json::to_vec(&entry) {
+                        Ok(mut line) => {
+                            line.push(b'\n');
+                            lines.push(line);
+                        }
+                        Err(e) => warn!("Failed to serialize audit entry: {}", e),
+                    }
+                }
+                if let Err(e) = file.write_lines(&lines).await {
+                    warn!(
+                        "Failed to write {} entries to audit log {}: {}",
+                        lines.len(),
+                        file.path.display(),
+                        e
+                    );
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    /// Log an attempt; attempts skipped by a circuit breaker sent no request and
+    /// are left out
+    pub fn record(&self, asset_type: AssetType, symbol: &str, attempt: &SourceAttempt) {
+        if attempt.outcome == AttemptOutcome::Skipped {
+            return;
+        }
+        let _ = self.sender.send(AuditEntry {
+            timestamp: Utc::now(),
+            asset_type,
+            source: attempt.source.clone(),
+            symbol: symbol.to_string(),
+            outcome: attempt.outcome,
+            latency_ms: attempt.latency_ms,
+            http_status: attempt.http_status,
+            detail: attempt.detail.clone(),
+        });
+    }
+}
+
+/// `path` plus up to `max_files` older generations named `path.1` (newest)
+/// through `path.{max_files}`
+struct RollingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RollingFile {
+    async fn write_lines(&mut self, lines: &[Vec<u8>]) -> std::io::Result<()> {
+        for line in lines {
+            if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+                self.rotate().await?;
+            }
+            if self.file.is_none() {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+                    .await?;
+                self.size = file.metadata().await?.len();
+                self.file = Some(file);
+            }
+            if let Some(file) = &mut self.file {
+                file.write_all(line).await?;
+            }
+            self.size += line.len() as u64;
+        }
+        if let Some(file) = &mut self.file {
+            file.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn rotate(&mut self) -> std::io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush().await?;
+        }
+        if self.max_files == 0 {
+            fs::remove_file(&self.path).await?;
+        } else {
+            for generation in (1..self.max_files).rev() {
+                let from = generation_path(&self.path, generation);
+                if fs::try_exists(&from).await? {
+                    fs::rename(&from, generation_path(&self.path, generation + 1)).await?;
+                }
+            }
+            fs::rename(&self.path, generation_path(&self.path, 1)).await?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn generation_path(path: &Path, generation: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", generation));
+    PathBuf::from(name)
+}
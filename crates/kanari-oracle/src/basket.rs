@@ -0,0 +1,49 @@
+use chrono::Utc;
+
+use crate::config::Basket;
+use crate::models::PriceData;
+use crate::price_math;
+use crate::symbols;
+
+/// Source reported for basket prices
+pub const BASKET_SOURCE: &str = "basket";
+
+/// Price of `basket` from the prices of its components, given in the order of
+/// `basket.components`. The timestamp is that of the oldest component, so a
+/// basket goes stale with any of them. The 24h change is only set when every
+/// component reports one.
+pub fn price(basket: &Basket, components: &[PriceData]) -> Option<PriceData> {
+    if components.len() != basket.components.len() || components.is_empty() {
+        return None;
+    }
+    let weights = || basket.components.iter().map(|component| component.weight);
+
+    let value = price_math::weighted_sum(
+        components
+            .iter()
+            .map(|price_data| price_data.price)
+            .zip(weights()),
+    )?;
+    let change_24h = components
+        .iter()
+        .map(|price_data| price_data.change_24h)
+        .collect::<Option<Vec<f64>>>()
+        .and_then(|changes| price_math::weighted_sum(changes.into_iter().zip(weights())));
+
+    let mut price_data = PriceData::new(
+        symbols::normalize_basket(&basket.name),
+        value,
+        BASKET_SOURCE.to_string(),
+    );
+    price_data.timestamp = components
+        .iter()
+        .map(|price_data| price_data.timestamp)
+        .min()
+        .unwrap_or_else(Utc::now);
+    if let Some(change) = change_24h {
+        price_data.change_24h = Some(change);
+        price_data.change_24h_percent =
+            price_math::change(value, value - change).map(|(_, percent)| percent);
+    }
+    Some(price_data)
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::errors::Result;
+use crate::oracle::Oracle;
+use crate::sources::PriceSource;
+use crate::store::PriceStore;
+use crate::subscriber::PriceSubscriber;
+
+/// Fluent construction of an [`Oracle`] for embedding in other services
+///
+/// Starts from [`Config::default`] (or a given config); every setter overrides
+/// one part of it.
+///
+/// ```no_run
+/// # async fn run() -> kanari_oracle::errors::Result<()> {
+/// use std::time::Duration;
+/// use kanari_oracle::oracle::Oracle;
+///
+/// let oracle = Oracle::builder()
+///     .crypto_symbols(["bitcoin", "solana"])
+///     .stock_symbols(["AAPL"])
+///     .cache_ttl(Duration::from_secs(10))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct OracleBuilder {
+    config: Config,
+    sources: Vec<Arc<dyn PriceSource>>,
+    subscribers: Vec<Arc<dyn PriceSubscriber>>,
+    store: Option<Arc<dyn PriceStore>>,
+}
+
+impl OracleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the whole configuration, e.g. one loaded with `Config::from_file`
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn crypto_symbols<I, S>(mut self, symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.crypto.symbols = symbols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn stock_symbols<I, S>(mut self, symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.stocks.symbols = symbols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Interval between background update rounds
+    pub fn update_interval(mut self, interval: Duration) -> Self {
+        self.config.general.update_interval = interval.as_secs();
+        self
+    }
+
+    /// How long a cached price is served without refreshing it
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.config.general.cache_ttl = ttl.as_secs();
+        self
+    }
+
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.config.general.request_timeout = timeout.as_secs();
+        self
+    }
+
+    /// Add a source implemented outside this crate. It is tried after the
+    /// configured sources and kept across config reloads.
+    pub fn source(mut self, source: Arc<dyn PriceSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Call `subscriber` whenever prices are written into the feed
+    pub fn subscriber(mut self, subscriber: impl PriceSubscriber + 'static) -> Self {
+        self.subscribers.push(Arc::new(subscriber));
+        self
+    }
+
+    /// Share published prices through `store` instead of the one in `config.store`
+    pub fn store(mut self, store: Arc<dyn PriceStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Validate the configuration, connect the store if one is configured and
+    /// build the oracle
+    pub async fn build(self) -> Result<Oracle> {
+        Oracle::from_parts(self.config, self.sources, self.subscribers, self.store).await
+    }
+}
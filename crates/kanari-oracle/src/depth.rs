@@ -0,0 +1,140 @@
+use log::{debug, warn};
+
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::models::OrderBookDepth;
+use crate::price_math;
+use crate::symbols;
+
+/// Distance from the mid price, in percent, within which resting orders count
+/// towards a symbol's depth
+pub const DEPTH_BAND_PERCENT: f64 = 2.0;
+
+/// Exchanges `crypto.order_book_depth` may list
+pub const DEPTH_EXCHANGES: &[&str] = &["binance", "coinbase"];
+
+/// Levels requested per side from Binance; enough to reach ±2% on liquid pairs
+const BINANCE_BOOK_LIMIT: usize = 500;
+
+/// Depth of `symbol`'s order book from the first of `exchanges` that answers,
+/// or None when none does. Best effort: each exchange is asked once and
+/// failures are only logged, so a missing book never holds back a price.
+pub async fn fetch(
+    fetcher: &PriceFetcher,
+    exchanges: &[String],
+    symbol: &str,
+) -> Option<OrderBookDepth> {
+    let ticker = fetcher
+        .config()
+        .crypto
+        .ticker_for(symbol)
+        .unwrap_or_else(|| symbols::normalize_crypto(symbol));
+
+    for exchange in exchanges {
+        let result = match exchange.as_str() {
+            "binance" => fetch_binance(fetcher, &ticker).await,
+            "coinbase" => fetch_coinbase(fetcher, &ticker).await,
+            other => Err(OracleError::ConfigError(format!(
+                "Unknown order book exchange '{}'",
+                other
+            ))),
+        };
+        match result {
+            Ok(depth) => return Some(depth),
+            Err(e) => warn!("{} order book unavailable for {}: {}", exchange, symbol, e),
+        }
+    }
+    None
+}
+
+async fn fetch_binance(fetcher: &PriceFetcher, ticker: &str) -> Result<OrderBookDepth> {
+    let pair = symbols::binance_pair(ticker);
+    let url = format!(
+        "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
+        pair, BINANCE_BOOK_LIMIT
+    );
+    fetcher.throttle("binance").await;
+    let response = fetcher.send("binance", fetcher.client().get(&url)).await?;
+    if !response.status().is_success() {
+        return Err(status_error(
+            &format!("Binance order book error for {}", pair),
+            &response,
+        ));
+    }
+    book_depth("binance", &pair, &response.json()?)
+}
+
+async fn fetch_coinbase(fetcher: &PriceFetcher, ticker: &str) -> Result<OrderBookDepth> {
+    let product = format!("{}-USD", ticker.to_uppercase());
+    let url = format!(
+        "https://api.exchange.coinbase.com/products/{}/book?level=2",
+        product
+    );
+    fetcher.throttle("coinbase").await;
+    // Coinbase rejects requests without a User-Agent
+    let request = fetcher
+        .client()
+        .get(&url)
+        .header("User-Agent", "kanari-oracle");
+    let response = fetcher.send("coinbase", request).await?;
+    if !response.status().is_success() {
+        return Err(status_error(
+            &format!("Coinbase order book error for {}", product),
+            &response,
+        ));
+    }
+    book_depth("coinbase", &product, &response.json()?)
+}
+
+/// Depth of a `{"bids": [[price, size, ...]], "asks": [...]}` book, the shape
+/// both Binance and Coinbase answer with
+fn book_depth(exchange: &str, market: &str, book: &serde_json::Value) -> Result<OrderBookDepth> {
+    let invalid =
+        || OracleError::ApiError(format!("Invalid {} order book for {}", exchange, market));
+    let bids = levels(&book["bids"]).ok_or_else(invalid)?;
+    let asks = levels(&book["asks"]).ok_or_else(invalid)?;
+    debug!(
+        "{} order book for {}: {} bids, {} asks",
+        exchange,
+        market,
+        bids.len(),
+        asks.len()
+    );
+
+    let (bids, asks) = within_band(&bids, &asks).ok_or_else(|| {
+        OracleError::ApiError(format!("Empty {} order book for {}", exchange, market))
+    })?;
+    Ok(OrderBookDepth {
+        bids,
+        asks,
+        exchange: exchange.to_string(),
+    })
+}
+
+/// `(price, size)` of each level; prices and sizes arrive as strings
+fn levels(side: &serde_json::Value) -> Option<Vec<(f64, f64)>> {
+    side.as_array()?
+        .iter()
+        .map(|level| {
+            let price = level.get(0)?.as_str()?.parse().ok()?;
+            let size = level.get(1)?.as_str()?.parse().ok()?;
+            Some((price, size))
+        })
+        .collect()
+}
+
+/// Notional of the bids at or above `mid - 2%` and of the asks at or below
+/// `mid + 2%`, where `mid` is halfway between the best bid and the best ask.
+/// None when either side is empty.
+pub fn within_band(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let best_bid = bids.iter().map(|(price, _)| *price).reduce(f64::max)?;
+    let best_ask = asks.iter().map(|(price, _)| *price).reduce(f64::min)?;
+    let mid = price_math::mean([best_bid, best_ask])?;
+    let floor = mid - price_math::percent_of(mid, DEPTH_BAND_PERCENT)?;
+    let ceiling = mid + price_math::percent_of(mid, DEPTH_BAND_PERCENT)?;
+
+    let bids = price_math::weighted_sum(bids.iter().copied().filter(|(price, _)| *price >= floor))?;
+    let asks =
+        price_math::weighted_sum(asks.iter().copied().filter(|(price, _)| *price <= ceiling))?;
+    Some((bids, asks))
+}
@@ -1,9 +1,10 @@
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum OracleError {
     #[error("HTTP request failed: {0}")]
-    HttpError(#[from] reqwest::Error),
+    HttpError(#[source] reqwest::Error),
 
     #[error("JSON parsing error: {0}")]
     JsonError(#[from] serde_json::Error),
@@ -14,9 +15,57 @@ pub enum OracleError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    #[error("{message}")]
+    HttpStatus { status: u16, message: String },
+
+    #[error("{message}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    #[error("Source unavailable: {0}")]
+    SourceUnavailable(String),
+
+    #[error("Invalid symbol: {0}")]
+    InvalidSymbol(String),
+
+    #[error("Price for '{symbol}' is stale ({age_secs}s old) and could not be refreshed")]
+    StaleData { symbol: String, age_secs: i64 },
+
+    #[error(
+        "Price {price} for '{symbol}' from {source_name} was held back as an anomaly (recent median {median})"
+    )]
+    AnomalousPrice {
+        symbol: String,
+        source_name: String,
+        price: f64,
+        median: f64,
+    },
+
     #[error("Price not found for symbol: {0}")]
     PriceNotFound(String),
 
+    #[error(
+        "Symbol '{0}' is configured as both crypto and stock; specify the asset type explicitly"
+    )]
+    AmbiguousSymbol(String),
+
+    #[error("Unknown {asset_type} source: {source_name}")]
+    UnknownSource {
+        asset_type: String,
+        source_name: String,
+    },
+
+    #[error("No exchange rate for currency: {0}")]
+    UnknownCurrency(String),
+
+    #[error("Price store error: {0}")]
+    StoreError(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -24,4 +73,102 @@ pub enum OracleError {
     IoOperationFailed(String),
 }
 
+impl OracleError {
+    /// HTTP status of the upstream response that caused the error, if known
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            OracleError::HttpError(e) => e.status().map(|status| status.as_u16()),
+            OracleError::HttpStatus { status, .. } => Some(*status),
+            OracleError::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Whether repeating the same request might succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            OracleError::HttpError(e) => !e.is_builder() && !e.is_decode(),
+            OracleError::HttpStatus { status, .. } => *status == 408,
+            OracleError::RateLimited { .. }
+            | OracleError::Timeout(_)
+            | OracleError::SourceUnavailable(_) => true,
+            // Malformed upstream payloads are often transient
+            OracleError::ApiError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Copy of the error, for sharing one failure between several callers.
+    /// reqwest, JSON and IO errors cannot be cloned and keep only their message,
+    /// in a variant of the same class.
+    pub fn duplicate(&self) -> OracleError {
+        match self {
+            OracleError::HttpError(e) => OracleError::ApiError(e.to_string()),
+            OracleError::JsonError(e) => OracleError::ApiError(e.to_string()),
+            OracleError::ConfigError(msg) => OracleError::ConfigError(msg.clone()),
+            OracleError::ApiError(msg) => OracleError::ApiError(msg.clone()),
+            OracleError::HttpStatus { status, message } => OracleError::HttpStatus {
+                status: *status,
+                message: message.clone(),
+            },
+            OracleError::RateLimited {
+                retry_after,
+                message,
+            } => OracleError::RateLimited {
+                retry_after: *retry_after,
+                message: message.clone(),
+            },
+            OracleError::Timeout(msg) => OracleError::Timeout(msg.clone()),
+            OracleError::SourceUnavailable(msg) => OracleError::SourceUnavailable(msg.clone()),
+            OracleError::InvalidSymbol(msg) => OracleError::InvalidSymbol(msg.clone()),
+            OracleError::StaleData { symbol, age_secs } => OracleError::StaleData {
+                symbol: symbol.clone(),
+                age_secs: *age_secs,
+            },
+            OracleError::AnomalousPrice {
+                symbol,
+                source_name,
+                price,
+                median,
+            } => OracleError::AnomalousPrice {
+                symbol: symbol.clone(),
+                source_name: source_name.clone(),
+                price: *price,
+                median: *median,
+            },
+            OracleError::PriceNotFound(msg) => OracleError::PriceNotFound(msg.clone()),
+            OracleError::AmbiguousSymbol(msg) => OracleError::AmbiguousSymbol(msg.clone()),
+            OracleError::UnknownSource {
+                asset_type,
+                source_name,
+            } => OracleError::UnknownSource {
+                asset_type: asset_type.clone(),
+                source_name: source_name.clone(),
+            },
+            OracleError::UnknownCurrency(msg) => OracleError::UnknownCurrency(msg.clone()),
+            OracleError::StoreError(msg) => OracleError::StoreError(msg.clone()),
+            OracleError::IoError(e) => OracleError::IoOperationFailed(e.to_string()),
+            OracleError::IoOperationFailed(msg) => OracleError::IoOperationFailed(msg.clone()),
+        }
+    }
+
+    /// Delay requested by the upstream, e.g. from a `Retry-After` header
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            OracleError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for OracleError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            OracleError::Timeout(e.to_string())
+        } else {
+            OracleError::HttpError(e)
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, OracleError>;
@@ -0,0 +1,191 @@
+use log::warn;
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::FetchResponse;
+use crate::config::{CassetteConfig, CassetteMode};
+use crate::errors::{OracleError, Result};
+
+/// Query parameters whose names contain one of these are redacted before a URL
+/// is written to or looked up in a cassette
+const SECRET_PARAMS: &[&str] = &["key", "token", "secret", "password"];
+
+/// One upstream request and the response it got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    /// Request URL with secret query parameters redacted
+    pub url: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<String>,
+    pub body: String,
+}
+
+impl Interaction {
+    fn key(&self) -> String {
+        format!("{} {}", self.method, self.url)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    #[serde(default)]
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Interactions already replayed, keyed "METHOD url"
+    played: HashMap<String, usize>,
+}
+
+/// Upstream responses captured to a JSON file and replayed from it, so provider
+/// parsing and fallback can be exercised without the network. Request headers
+/// are not recorded; API keys in query parameters are redacted.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+    /// Held while the file is written, so a slower write never replaces a newer one
+    writing: tokio::sync::Mutex<()>,
+}
+
+impl Cassette {
+    /// Cassette in the configured mode. Recording starts from an empty file;
+    /// replaying reads the recorded interactions up front.
+    pub fn open(config: &CassetteConfig) -> Result<Self> {
+        match config.mode {
+            CassetteMode::Record => Ok(Self::record(&config.path)),
+            CassetteMode::Replay => Self::replay(&config.path),
+        }
+    }
+
+    /// Send requests upstream and write every response to `path`
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self::with_interactions(path, CassetteMode::Record, Vec::new())
+    }
+
+    /// Answer requests from the interactions recorded in `path`
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let display = path.as_ref().display().to_string();
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            OracleError::ConfigError(format!("Failed to read cassette '{}': {}", display, e))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&content).map_err(|e| {
+            OracleError::ConfigError(format!("Failed to parse cassette '{}': {}", display, e))
+        })?;
+        Ok(Self::with_interactions(
+            path,
+            CassetteMode::Replay,
+            file.interactions,
+        ))
+    }
+
+    fn with_interactions(
+        path: impl AsRef<Path>,
+        mode: CassetteMode,
+        interactions: Vec<Interaction>,
+    ) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode,
+            state: Mutex::new(CassetteState {
+                interactions,
+                played: HashMap::new(),
+            }),
+            writing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether this cassette was opened from `config`
+    pub fn matches(&self, config: &CassetteConfig) -> bool {
+        self.mode == config.mode && self.path == Path::new(&config.path)
+    }
+
+    /// Next recorded response for the request. Repeated requests get the
+    /// responses recorded for them in order, and the last one once those run out.
+    pub fn play(&self, method: &Method, url: &Url) -> Option<FetchResponse> {
+        let key = format!("{} {}", method, redact_url(url));
+        let mut state = self.state.lock().unwrap();
+        let recorded: Vec<&Interaction> = state
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.key() == key)
+            .collect();
+        let played = state.played.get(&key).copied().unwrap_or(0);
+        let interaction = recorded.get(played).or(recorded.last())?;
+        let response = FetchResponse::recorded(
+            interaction.status,
+            interaction.retry_after.clone(),
+            interaction.body.clone(),
+        );
+        state.played.insert(key, played + 1);
+        Some(response)
+    }
+
+    /// Append a live response and rewrite the cassette file. Failures to write
+    /// are logged; the response is still used.
+    pub async fn store(&self, method: &Method, url: &Url, response: &FetchResponse) {
+        let _writing = self.writing.lock().await;
+        let file = {
+            let mut state = self.state.lock().unwrap();
+            state.interactions.push(Interaction {
+                method: method.to_string(),
+                url: redact_url(url),
+                status: response.status().as_u16(),
+                retry_after: response.retry_after().map(str::to_string),
+                body: response.text().to_string(),
+            });
+            CassetteFile {
+                interactions: state.interactions.clone(),
+            }
+        };
+
+        let content = match serde_json::to_string_pretty(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Failed to serialize cassette: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = tokio::fs::write(&self.path, content).await {
+            warn!("Failed to write cassette {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// URL with the values of secret-looking query parameters replaced
+pub fn redact_url(url: &Url) -> String {
+    if url.query().is_none() {
+        return url.to_string();
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let lower = name.to_lowercase();
+            let value = if SECRET_PARAMS.iter().any(|secret| lower.contains(secret)) {
+                "REDACTED".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    let mut redacted = url.clone();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Externally visible state of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are skipped until the cooldown elapses
+    Open,
+    /// One trial request is allowed to decide whether to close again
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Closed { failures: u32 },
+    Open { since: Instant },
+    HalfOpen { probe_started: Instant },
+}
+
+/// Stops calling a provider after `failure_threshold` consecutive failures,
+/// then lets a single request through once `cooldown` has passed
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(Inner::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether a request may be sent now; moves an expired open circuit to half-open
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            Inner::Closed { .. } => true,
+            Inner::Open { since } if since.elapsed() >= self.cooldown => {
+                *state = Inner::HalfOpen {
+                    probe_started: Instant::now(),
+                };
+                true
+            }
+            Inner::Open { .. } => false,
+            // A probe that never reported back must not keep the circuit stuck
+            Inner::HalfOpen { probe_started } if probe_started.elapsed() >= self.cooldown => {
+                *state = Inner::HalfOpen {
+                    probe_started: Instant::now(),
+                };
+                true
+            }
+            Inner::HalfOpen { .. } => false,
+        }
+    }
+
+    pub fn record_success(&self) {
+        *self.state.lock().unwrap() = Inner::Closed { failures: 0 };
+    }
+
+    /// Count a failure; returns true when this failure opened the circuit
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            Inner::Closed { failures } if failures + 1 < self.failure_threshold => {
+                *state = Inner::Closed {
+                    failures: failures + 1,
+                };
+                false
+            }
+            Inner::Open { .. } => false,
+            _ => {
+                *state = Inner::Open {
+                    since: Instant::now(),
+                };
+                true
+            }
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            Inner::Closed { .. } => CircuitState::Closed,
+            Inner::Open { since } if since.elapsed() >= self.cooldown => CircuitState::HalfOpen,
+            Inner::Open { .. } => CircuitState::Open,
+            Inner::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Consecutive failures while closed
+    pub fn failures(&self) -> u32 {
+        match *self.state.lock().unwrap() {
+            Inner::Closed { failures } => failures,
+            _ => self.failure_threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(60);
+
+    /// Breaker that has just opened after `threshold` failures
+    fn opened(threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        let breaker = CircuitBreaker::new(threshold, cooldown);
+        for _ in 1..threshold {
+            assert!(!breaker.record_failure());
+        }
+        assert!(breaker.record_failure());
+        breaker
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.failures(), 2);
+        assert!(breaker.allow());
+
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allow());
+        // Further failures while open do not reopen it
+        assert!(!breaker.record_failure());
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        assert_eq!(breaker.failures(), 0);
+        assert!(!breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn expired_cooldown_lets_one_probe_through() {
+        let breaker = opened(2, Duration::ZERO);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.allow());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn only_one_probe_is_in_flight_at_a_time() {
+        let breaker = opened(1, COOLDOWN);
+        *breaker.state.lock().unwrap() = Inner::Open {
+            since: Instant::now() - COOLDOWN,
+        };
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn successful_probe_closes_the_circuit() {
+        let breaker = opened(2, Duration::ZERO);
+        assert!(breaker.allow());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.failures(), 0);
+    }
+
+    #[test]
+    fn failed_probe_opens_the_circuit_again() {
+        let breaker = opened(3, COOLDOWN);
+        *breaker.state.lock().unwrap() = Inner::Open {
+            since: Instant::now() - COOLDOWN,
+        };
+        assert!(breaker.allow());
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn stuck_probe_is_replaced_after_the_cooldown() {
+        let breaker = opened(1, Duration::ZERO);
+        assert!(breaker.allow());
+        // The first probe never reported back
+        assert!(breaker.allow());
+    }
+}
@@ -1,19 +1,38 @@
-use crate::config::Config;
-use crate::errors::Result;
-use log::warn;
-use reqwest::Client;
+use crate::config::{CassetteMode, Config};
+use crate::errors::{OracleError, Result};
+use log::{info, warn};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 
-pub mod crypto;
-pub mod stock;
+pub mod cassette;
+pub mod circuit_breaker;
+pub mod rate_limit;
 
-pub use crypto::CryptoFetcher;
-pub use stock::StockFetcher;
+use cassette::{Cassette, redact_url};
+use rate_limit::{Quota, RateLimiter};
 
-#[derive(Debug, Clone)]
+/// Providers whose responses report the quota they have left. They always get
+/// a limiter, so a spent quota holds their requests back even without a
+/// configured `requests_per_minute`.
+const QUOTA_SOURCES: &[&str] = &["coingecko", "binance", "finnhub"];
+
+/// HTTP client, config and rate limits shared by every source through an `Arc`,
+/// so all requests go through one connection pool
+#[derive(Debug)]
 pub struct PriceFetcher {
     client: Client,
     config: Config,
+    rate_limiters: HashMap<String, RateLimiter>,
+    /// Caps live requests in flight at `general.max_concurrent_requests`, so a
+    /// large round queues instead of opening hundreds of connections at once
+    in_flight: Option<Arc<Semaphore>>,
+    /// Records or replays every request sent through `send`
+    cassette: Option<Arc<Cassette>>,
 }
 
 impl PriceFetcher {
@@ -22,7 +41,98 @@ impl PriceFetcher {
             .timeout(Duration::from_secs(config.general.request_timeout))
             .build()?;
 
-        Ok(Self { client, config })
+        let cassette = match &config.cassette {
+            Some(cassette_config) => {
+                match cassette_config.mode {
+                    CassetteMode::Record => {
+                        info!("Recording upstream responses to {}", cassette_config.path)
+                    }
+                    CassetteMode::Replay => {
+                        info!("Replaying upstream responses from {}", cassette_config.path)
+                    }
+                }
+                Some(Arc::new(Cassette::open(cassette_config)?))
+            }
+            None => None,
+        };
+
+        let in_flight = concurrency_limit(&config);
+        Ok(Self::with_client(client, config, in_flight, cassette))
+    }
+
+    /// Fetcher for a reloaded config. The HTTP client and its open connections are
+    /// kept unless `request_timeout` changed, and so is the cassette unless the
+    /// `cassette` section changed. Requests in flight keep counting against the
+    /// concurrency limit unless `max_concurrent_requests` changed.
+    pub fn reconfigure(&self, config: Config) -> Result<Self> {
+        let same_cassette = match (&self.cassette, &config.cassette) {
+            (Some(cassette), Some(cassette_config)) => cassette.matches(cassette_config),
+            (None, None) => true,
+            _ => false,
+        };
+        if config.general.request_timeout != self.config.general.request_timeout || !same_cassette {
+            return Self::new(config);
+        }
+        let in_flight = if config.general.max_concurrent_requests
+            == self.config.general.max_concurrent_requests
+        {
+            self.in_flight.clone()
+        } else {
+            concurrency_limit(&config)
+        };
+        Ok(Self::with_client(
+            self.client.clone(),
+            config,
+            in_flight,
+            self.cassette.clone(),
+        ))
+    }
+
+    fn with_client(
+        client: Client,
+        config: Config,
+        in_flight: Option<Arc<Semaphore>>,
+        cassette: Option<Arc<Cassette>>,
+    ) -> Self {
+        let mut rate_limiters: HashMap<String, RateLimiter> = config
+            .sources
+            .iter()
+            .filter_map(|(name, source)| {
+                source
+                    .requests_per_minute
+                    .map(|rpm| (name.clone(), RateLimiter::per_minute(rpm)))
+            })
+            .collect();
+        for name in QUOTA_SOURCES {
+            rate_limiters
+                .entry(name.to_string())
+                .or_insert_with(RateLimiter::unmetered);
+        }
+
+        Self {
+            client,
+            config,
+            rate_limiters,
+            in_flight,
+            cassette,
+        }
+    }
+
+    fn replaying(&self) -> bool {
+        self.cassette
+            .as_ref()
+            .is_some_and(|cassette| cassette.mode() == CassetteMode::Replay)
+    }
+
+    /// Wait for the source's rate limit, if one is configured. Replayed requests
+    /// never wait.
+    pub async fn throttle(&self, source: &str) {
+        if self.replaying() {
+            return;
+        }
+        if let Some(limiter) = self.rate_limiters.get(source) {
+            limiter.acquire().await;
+        }
     }
 
     pub fn client(&self) -> &reqwest::Client {
@@ -33,37 +143,360 @@ impl PriceFetcher {
         &self.config
     }
 
-    pub async fn retry_with_backoff<T, E, F, Fut>(
-        &self,
-        mut operation: F,
-    ) -> std::result::Result<T, E>
+    /// Send a request on behalf of `source` and read its response. The quota the
+    /// upstream reports is fed into the source's rate limit. With a cassette
+    /// configured, the response is replayed from it, or recorded into it after
+    /// the live request.
+    pub async fn send(&self, source: &str, request: RequestBuilder) -> Result<FetchResponse> {
+        let request = request.build()?;
+
+        let replay = self
+            .cassette
+            .as_ref()
+            .filter(|cassette| cassette.mode() == CassetteMode::Replay);
+        if let Some(cassette) = replay {
+            return cassette
+                .play(request.method(), request.url())
+                .ok_or_else(|| {
+                    OracleError::ConfigError(format!(
+                        "No response recorded in cassette {} for {} {}",
+                        cassette.path().display(),
+                        request.method(),
+                        redact_url(request.url())
+                    ))
+                });
+        }
+
+        let method = request.method().clone();
+        let url = request.url().clone();
+        // Held until the body is read. The semaphore is never closed, so
+        // acquiring cannot fail.
+        let _permit = match &self.in_flight {
+            Some(in_flight) => in_flight.acquire().await.ok(),
+            None => None,
+        };
+        let response = FetchResponse::read(self.client.execute(request).await?).await?;
+        if let Some(limiter) = self.rate_limiters.get(source) {
+            limiter.observe(source, &response.quota).await;
+        }
+        if let Some(cassette) = &self.cassette {
+            cassette.store(&method, &url, &response).await;
+        }
+        Ok(response)
+    }
+
+    /// Run `operation` up to `max_retries` times with exponential backoff and jitter.
+    /// Non-retryable errors return immediately; a `Retry-After` from the upstream
+    /// replaces the computed delay.
+    pub async fn retry_with_backoff<T, F, Fut>(&self, mut operation: F) -> Result<T>
     where
         F: FnMut() -> Fut,
-        Fut: std::future::Future<Output = std::result::Result<T, E>>,
-        E: std::fmt::Display,
+        Fut: std::future::Future<Output = Result<T>>,
     {
-        let mut last_error = None;
+        let max_retries = self.config.general.max_retries;
+        let mut attempt = 1;
 
-        for attempt in 1..=self.config.general.max_retries {
-            match operation().await {
+        loop {
+            let error = match operation().await {
                 Ok(result) => return Ok(result),
-                Err(error) => {
-                    warn!(
-                        "Attempt {}/{} failed: {}",
-                        attempt, self.config.general.max_retries, error
-                    );
-                    last_error = Some(error);
-
-                    if attempt < self.config.general.max_retries {
-                        tokio::time::sleep(Duration::from_millis(
-                            self.config.general.retry_delay * attempt as u64,
-                        ))
-                        .await;
-                    }
-                }
+                Err(error) => error,
+            };
+
+            if !error.is_retryable() || attempt >= max_retries {
+                return Err(error);
             }
+
+            warn!("Attempt {}/{} failed: {}", attempt, max_retries, error);
+
+            let delay = match error.retry_after() {
+                Some(retry_after) => retry_after.min(MAX_RETRY_AFTER),
+                None => jittered(max_backoff(self.config.general.retry_delay, attempt)),
+            };
+            tokio::time::sleep(delay).await;
+
+            attempt += 1;
+        }
+    }
+}
+
+/// Semaphore for `general.max_concurrent_requests`, or None when unlimited
+fn concurrency_limit(config: &Config) -> Option<Arc<Semaphore>> {
+    match config.general.max_concurrent_requests {
+        0 => None,
+        permits => Some(Arc::new(Semaphore::new(permits))),
+    }
+}
+
+/// Longest `Retry-After` honoured before retrying
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Upper bound for a single backoff delay
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Largest delay after the given failed attempt: `retry_delay * 2^(attempt - 1)`, capped
+pub fn max_backoff(retry_delay_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_millis(retry_delay_ms.saturating_mul(factor)).min(MAX_BACKOFF)
+}
+
+/// Pick a delay between half and all of `ceiling` so concurrent retries spread out
+fn jittered(ceiling: Duration) -> Duration {
+    let half = ceiling / 2;
+    half + half.mul_f64(rand::random::<f64>())
+}
+
+/// Upstream response with its body read, received live or replayed from a cassette
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    status: StatusCode,
+    retry_after: Option<String>,
+    /// Quota reported by a live response; replayed responses report none
+    quota: Quota,
+    body: String,
+}
+
+impl FetchResponse {
+    pub(crate) async fn read(response: Response) -> Result<Self> {
+        let status = response.status();
+        let retry_after = header(response.headers(), RETRY_AFTER.as_str()).map(str::to_string);
+        let quota = read_quota(response.headers());
+        let body = response.text().await?;
+        Ok(Self {
+            status,
+            retry_after,
+            quota,
+            body,
+        })
+    }
+
+    fn recorded(status: u16, retry_after: Option<String>, body: String) -> Self {
+        Self {
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            retry_after,
+            quota: Quota::default(),
+            body,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Raw `Retry-After` header
+    pub fn retry_after(&self) -> Option<&str> {
+        self.retry_after.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.body
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
+
+    pub fn quota(&self) -> &Quota {
+        &self.quota
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Quota headers sent by CoinGecko and Finnhub (`x-ratelimit-*`), Binance
+/// (`x-mbx-used-weight-1m`) and any upstream asking us to back off (`Retry-After`)
+fn read_quota(headers: &HeaderMap) -> Quota {
+    let number = |name: &str| {
+        header(headers, name)
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|value| value.is_finite() && *value >= 0.0)
+    };
+
+    // Finnhub sends the reset as a Unix timestamp, others as seconds from now
+    let reset = number("x-ratelimit-reset").map(|reset| {
+        if reset > 1e9 {
+            let now = chrono::Utc::now().timestamp() as f64;
+            Duration::from_secs_f64((reset - now).max(0.0))
+        } else {
+            Duration::from_secs_f64(reset)
         }
+    });
 
-        Err(last_error.unwrap())
+    Quota {
+        remaining: number("x-ratelimit-remaining"),
+        used: number("x-mbx-used-weight-1m"),
+        reset,
+        retry_after: number(RETRY_AFTER.as_str()).map(Duration::from_secs_f64),
+    }
+}
+
+/// Classify a non-success response, reading `Retry-After` on HTTP 429 and on the
+/// HTTP 418 Binance answers with once an IP is banned for ignoring it
+pub fn status_error(context: &str, response: &FetchResponse) -> OracleError {
+    let status = response.status();
+    let message = format!("{}: {}", context, status);
+
+    if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::IM_A_TEAPOT {
+        let retry_after = response
+            .retry_after()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return OracleError::RateLimited {
+            retry_after,
+            message,
+        };
+    }
+
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND => OracleError::InvalidSymbol(message),
+        _ if status.is_server_error() => OracleError::SourceUnavailable(message),
+        _ => OracleError::HttpStatus {
+            status: status.as_u16(),
+            message,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn response(status: u16, retry_after: Option<&str>) -> FetchResponse {
+        FetchResponse::recorded(status, retry_after.map(str::to_string), String::new())
+    }
+
+    fn fetcher(max_retries: u32) -> PriceFetcher {
+        let mut config = Config::default();
+        config.general.max_retries = max_retries;
+        config.general.retry_delay = 1;
+        PriceFetcher::new(config).unwrap()
+    }
+
+    #[test]
+    fn backoff_doubles_per_attempt() {
+        assert_eq!(max_backoff(500, 1), Duration::from_millis(500));
+        assert_eq!(max_backoff(500, 2), Duration::from_secs(1));
+        assert_eq!(max_backoff(500, 3), Duration::from_secs(2));
+        assert_eq!(max_backoff(500, 0), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(max_backoff(500, 7), MAX_BACKOFF);
+        assert_eq!(max_backoff(500, u32::MAX), MAX_BACKOFF);
+        assert_eq!(max_backoff(u64::MAX, 2), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn jitter_stays_between_half_and_the_ceiling() {
+        let ceiling = Duration::from_secs(10);
+        for _ in 0..100 {
+            let delay = jittered(ceiling);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn too_many_requests_reads_retry_after() {
+        let error = status_error("quote", &response(429, Some(" 7 ")));
+        assert!(matches!(error, OracleError::RateLimited { .. }));
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn unreadable_retry_after_is_ignored() {
+        let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+        for retry_after in [None, Some(date)] {
+            let error = status_error("quote", &response(429, retry_after));
+            assert!(error.is_retryable());
+            assert_eq!(error.retry_after(), None);
+        }
+    }
+
+    #[test]
+    fn retry_after_is_only_read_on_too_many_requests() {
+        let error = status_error("quote", &response(503, Some("7")));
+        assert!(matches!(error, OracleError::SourceUnavailable(_)));
+        assert_eq!(error.retry_after(), None);
+    }
+
+    #[test]
+    fn server_errors_and_timeouts_are_retried_client_errors_are_not() {
+        for status in [500, 502, 503, 504, 408] {
+            assert!(
+                status_error("quote", &response(status, None)).is_retryable(),
+                "{} not retried",
+                status
+            );
+        }
+        for status in [400, 401, 403, 404, 422] {
+            assert!(
+                !status_error("quote", &response(status, None)).is_retryable(),
+                "{} retried",
+                status
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn retryable_errors_are_retried_up_to_max_retries() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = fetcher(3)
+            .retry_with_backoff(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(OracleError::SourceUnavailable("unavailable".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn non_retryable_errors_return_at_once() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = fetcher(3)
+            .retry_with_backoff(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(OracleError::HttpStatus {
+                    status: 404,
+                    message: "not found".to_string(),
+                })
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(OracleError::HttpStatus { status: 404, .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_after_replaces_the_backoff() {
+        let mut config = Config::default();
+        config.general.max_retries = 2;
+        // Without Retry-After the retry would wait at least 10 seconds
+        config.general.retry_delay = 20_000;
+        let fetcher = PriceFetcher::new(config).unwrap();
+        let calls = AtomicU32::new(0);
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            fetcher.retry_with_backoff(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(OracleError::RateLimited {
+                        retry_after: Some(Duration::ZERO),
+                        message: "slow down".to_string(),
+                    })
+                } else {
+                    Ok(42)
+                }
+            }),
+        )
+        .await
+        .expect("Retry-After was not honoured");
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
     }
 }
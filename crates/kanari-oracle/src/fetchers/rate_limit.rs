@@ -0,0 +1,203 @@
+use log::info;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Longest an upstream's quota headers may hold back its requests
+const MAX_PAUSE: Duration = Duration::from_secs(300);
+
+/// Window assumed for a spent quota that does not say when it resets
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Remaining request quota reported by an upstream response
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quota {
+    /// Requests left in the current window (`x-ratelimit-remaining`)
+    pub remaining: Option<f64>,
+    /// Request weight used in the current minute (Binance `x-mbx-used-weight-1m`),
+    /// counted against the configured `requests_per_minute`
+    pub used: Option<f64>,
+    /// Time until the window resets (`x-ratelimit-reset`)
+    pub reset: Option<Duration>,
+    /// Time to wait before the next request (`Retry-After`)
+    pub retry_after: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    /// Set when the upstream asked us to stop for a while
+    paused_until: Option<Instant>,
+}
+
+/// Token-bucket limiter allowing `requests_per_minute` with bursts up to the same
+/// amount. The quota reported by the upstream can only slow it down further.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Requests per minute; `None` when only the upstream's quota limits requests
+    capacity: Option<f64>,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self::with_capacity(Some(requests_per_minute.max(1) as f64))
+    }
+
+    /// Limiter that lets every request through until the upstream reports its
+    /// quota spent
+    pub fn unmetered() -> Self {
+        Self::with_capacity(None)
+    }
+
+    fn with_capacity(capacity: Option<f64>) -> Self {
+        Self {
+            capacity,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity.unwrap_or(0.0),
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent, then consume one token
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+
+                match bucket.paused_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        bucket.paused_until = None;
+                        let Some(capacity) = self.capacity else {
+                            return;
+                        };
+                        self.refill(&mut bucket, capacity, now);
+                        if bucket.tokens >= 1.0 {
+                            bucket.tokens -= 1.0;
+                            return;
+                        }
+                        Duration::from_secs_f64((1.0 - bucket.tokens) * 60.0 / capacity)
+                    }
+                }
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take the quota reported by an upstream response into account: never send
+    /// more than it has left, and hold off entirely once it is spent or the
+    /// upstream sent `Retry-After`
+    pub async fn observe(&self, source: &str, quota: &Quota) {
+        let mut bucket = self.bucket.lock().await;
+        let now = Instant::now();
+
+        let mut remaining = quota.remaining;
+        if let Some(capacity) = self.capacity {
+            self.refill(&mut bucket, capacity, now);
+            if let Some(used) = quota.used {
+                let left = (capacity - used).max(0.0);
+                remaining = Some(remaining.map_or(left, |r| r.min(left)));
+            }
+            if let Some(remaining) = remaining {
+                bucket.tokens = bucket.tokens.min(remaining);
+            }
+        }
+
+        let pause = match (quota.retry_after, remaining) {
+            (Some(retry_after), _) => retry_after,
+            (None, Some(remaining)) if remaining < 1.0 => quota.reset.unwrap_or(DEFAULT_WINDOW),
+            _ => return,
+        };
+        let until = now + pause.min(MAX_PAUSE);
+        if bucket.paused_until.is_none_or(|paused| paused < until) {
+            info!(
+                "{} quota exhausted; holding requests for {}s",
+                source,
+                pause.min(MAX_PAUSE).as_secs()
+            );
+            bucket.paused_until = Some(until);
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket, capacity: f64, now: Instant) {
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity / 60.0).min(capacity);
+        bucket.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPTLY: Duration = Duration::from_millis(100);
+
+    async fn tokens(limiter: &RateLimiter) -> f64 {
+        limiter.bucket.lock().await.tokens
+    }
+
+    /// Empty the bucket as if the last token was taken `ago`
+    async fn drain(limiter: &RateLimiter, ago: Duration) {
+        let mut bucket = limiter.bucket.lock().await;
+        bucket.tokens = 0.0;
+        bucket.last_refill = Instant::now() - ago;
+    }
+
+    #[tokio::test]
+    async fn full_bucket_allows_a_burst_of_the_whole_minute() {
+        let limiter = RateLimiter::per_minute(5);
+        for _ in 0..5 {
+            tokio::time::timeout(PROMPTLY, limiter.acquire())
+                .await
+                .expect("burst request was delayed");
+        }
+        assert!(
+            tokio::time::timeout(PROMPTLY, limiter.acquire())
+                .await
+                .is_err(),
+            "request beyond the burst was not delayed"
+        );
+    }
+
+    #[tokio::test]
+    async fn tokens_refill_at_the_per_minute_rate() {
+        let limiter = RateLimiter::per_minute(600);
+        drain(&limiter, Duration::from_secs(1)).await;
+        tokio::time::timeout(PROMPTLY, limiter.acquire())
+            .await
+            .expect("refilled token was not granted");
+        // One second at ten tokens per second, less the one just taken
+        let left = tokens(&limiter).await;
+        assert!((9.0..10.0).contains(&left), "{} tokens left", left);
+    }
+
+    #[tokio::test]
+    async fn refill_is_capped_at_the_burst_size() {
+        let limiter = RateLimiter::per_minute(600);
+        drain(&limiter, Duration::from_secs(120)).await;
+        limiter.acquire().await;
+        assert_eq!(tokens(&limiter).await, 599.0);
+    }
+
+    #[tokio::test]
+    async fn empty_bucket_waits_for_the_next_token() {
+        let limiter = RateLimiter::per_minute(600);
+        drain(&limiter, Duration::ZERO).await;
+        let started = Instant::now();
+        limiter.acquire().await;
+        // A token takes 100ms to accrue at ten per second
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn zero_rate_is_treated_as_one_per_minute() {
+        let limiter = RateLimiter::per_minute(0);
+        assert_eq!(limiter.capacity, Some(1.0));
+    }
+}
@@ -0,0 +1,812 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::future::join_all;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{FetchResponse, status_error};
+use crate::models::{OrderBookDepth, PriceData};
+use crate::price_math;
+
+/// Official reference rates `fx.reference` may list
+pub const FX_REFERENCE_SOURCES: &[&str] = &["ecb", "fed"];
+
+/// Intraday providers `fx.intraday` may list
+pub const FX_INTRADAY_SOURCES: &[&str] = &["coinbase", "finnhub"];
+
+/// Limit on each FX request; a slow upstream is retried at the next fetch
+pub const FX_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Euro foreign exchange reference rates, published by the ECB around 16:00 CET
+/// on TARGET business days
+const ECB_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+
+/// Observations of a FRED series; the Fed's H.10 noon buying rates are
+/// published there once a week with daily values
+const FRED_URL: &str = "https://api.stlouisfed.org/fred/series/observations";
+
+/// FRED series of the H.10 release per currency, and whether the series is
+/// quoted in USD per unit of the currency rather than units per USD
+const FED_SERIES: &[(&str, &str, bool)] = &[
+    ("aud", "DEXUSAL", true),
+    ("brl", "DEXBZUS", false),
+    ("cad", "DEXCAUS", false),
+    ("chf", "DEXSZUS", false),
+    ("cny", "DEXCHUS", false),
+    ("dkk", "DEXDNUS", false),
+    ("eur", "DEXUSEU", true),
+    ("gbp", "DEXUSUK", true),
+    ("hkd", "DEXHKUS", false),
+    ("inr", "DEXINUS", false),
+    ("jpy", "DEXJPUS", false),
+    ("krw", "DEXKOUS", false),
+    ("lkr", "DEXSLUS", false),
+    ("mxn", "DEXMXUS", false),
+    ("myr", "DEXMAUS", false),
+    ("nok", "DEXNOUS", false),
+    ("nzd", "DEXUSNZ", true),
+    ("sek", "DEXSDUS", false),
+    ("sgd", "DEXSIUS", false),
+    ("thb", "DEXTHUS", false),
+    ("twd", "DEXTAUS", false),
+    ("zar", "DEXSFUS", false),
+];
+
+/// Exchange rate of one currency against the US dollar
+#[derive(Debug, Clone, Serialize)]
+pub struct FxRate {
+    /// Units of the currency one US dollar buys
+    pub per_usd: f64,
+    pub source: String,
+    /// Whether the rate is an official reference rate rather than an intraday quote
+    pub reference: bool,
+    /// Day a reference rate was published for, or when an intraday rate was quoted
+    pub as_of: DateTime<Utc>,
+}
+
+/// Exchange rates of the configured currencies, as served at `/fx`
+#[derive(Debug, Clone, Serialize)]
+pub struct FxTable {
+    /// Lowercase currency codes; the US dollar itself is not listed
+    pub rates: BTreeMap<String, FxRate>,
+    pub updated_at: DateTime<Utc>,
+    /// What the last fetch failed to get; earlier rates are kept in its place
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Rate between two currencies, triangulated through the US dollar
+#[derive(Debug, Clone, Serialize)]
+pub struct Conversion {
+    pub from: String,
+    pub to: String,
+    /// Units of `to` one unit of `from` buys
+    pub rate: f64,
+    /// Day or time of the older of the two rates the conversion goes through
+    pub as_of: DateTime<Utc>,
+}
+
+impl Conversion {
+    /// `amount` of `from` in `to`
+    pub fn apply(&self, amount: f64) -> Option<f64> {
+        price_math::product(amount, self.rate)
+    }
+
+    /// `price_data` with its price, 24h change, volume, market cap and depth
+    /// in `to`. Percentages and supplies are left as they are.
+    pub fn apply_price(&self, price_data: &PriceData) -> Option<PriceData> {
+        let optional = |amount: Option<f64>| match amount {
+            Some(amount) => self.apply(amount).map(Some),
+            None => Some(None),
+        };
+        Some(PriceData {
+            price: self.apply(price_data.price)?,
+            change_24h: optional(price_data.change_24h)?,
+            volume_24h: optional(price_data.volume_24h)?,
+            market_cap: optional(price_data.market_cap)?,
+            depth: match &price_data.depth {
+                Some(depth) => Some(OrderBookDepth {
+                    bids: self.apply(depth.bids)?,
+                    asks: self.apply(depth.asks)?,
+                    exchange: depth.exchange.clone(),
+                }),
+                None => None,
+            },
+            ..price_data.clone()
+        })
+    }
+}
+
+/// The `fx` section, the latest rates fetched for it, and conversions between
+/// any two of its currencies
+pub struct CurrencyConverter {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    /// Seconds between fetches; None while the feed is off
+    interval: Option<u64>,
+    settings: Settings,
+    latest: Option<FxTable>,
+}
+
+#[derive(Clone, Default)]
+struct Settings {
+    currencies: Vec<String>,
+    reference: Vec<String>,
+    intraday: Vec<String>,
+    /// Seconds after which a reference rate gives way to an intraday one
+    max_age: u64,
+    fred_api_key: Option<String>,
+    finnhub_api_key: Option<String>,
+}
+
+impl CurrencyConverter {
+    pub fn new(config: &Config) -> Self {
+        let feed = Self {
+            inner: Mutex::new(Inner {
+                interval: None,
+                settings: Settings::default(),
+                latest: None,
+            }),
+        };
+        feed.reconfigure(config);
+        feed
+    }
+
+    /// Apply `config.fx`. Offline and sandbox oracles query no upstream, so the
+    /// feed is off for them; turning it off forgets the latest rates.
+    pub fn reconfigure(&self, config: &Config) {
+        let mut inner = self.inner.lock().unwrap();
+        let fx = config
+            .fx
+            .as_ref()
+            .filter(|_| config.offline.is_none() && config.sandbox.is_none());
+        let Some(fx) = fx else {
+            inner.interval = None;
+            inner.settings = Settings::default();
+            inner.latest = None;
+            return;
+        };
+
+        inner.interval = Some(fx.interval);
+        inner.settings = Settings {
+            currencies: fx.currencies.iter().map(|c| c.to_lowercase()).collect(),
+            reference: fx.reference.clone(),
+            intraday: fx.intraday.clone(),
+            max_age: fx.max_age,
+            fred_api_key: fx.fred_api_key.clone(),
+            finnhub_api_key: config.stocks.finnhub_api_key.clone(),
+        };
+        // Rates of currencies no longer listed are dropped
+        let currencies = inner.settings.currencies.clone();
+        if let Some(latest) = &mut inner.latest {
+            latest
+                .rates
+                .retain(|currency, _| currencies.contains(currency));
+        }
+    }
+
+    /// Seconds between fetches, or None while the feed is off
+    pub fn interval(&self) -> Option<u64> {
+        self.inner.lock().unwrap().interval
+    }
+
+    pub fn latest(&self) -> Option<FxTable> {
+        self.inner.lock().unwrap().latest.clone()
+    }
+
+    /// Whether the table can convert from or to `currency`; the US dollar
+    /// always can
+    pub fn supports(&self, currency: &str) -> bool {
+        let currency = currency.to_lowercase();
+        currency == "usd"
+            || self
+                .inner
+                .lock()
+                .unwrap()
+                .latest
+                .as_ref()
+                .is_some_and(|table| table.rates.contains_key(&currency))
+    }
+
+    /// Rate from `from` to `to`. Neither needs to be the US dollar: the rate is
+    /// triangulated through both currencies' rates against it, and dated by the
+    /// older of the two. Converting a currency to itself is always possible.
+    pub fn rate(&self, from: &str, to: &str) -> Result<Conversion> {
+        let from = from.to_lowercase();
+        let to = to.to_lowercase();
+        if from == to {
+            return Ok(Conversion {
+                from,
+                to,
+                rate: 1.0,
+                as_of: Utc::now(),
+            });
+        }
+
+        let inner = self.inner.lock().unwrap();
+        let table = inner.latest.as_ref();
+        // None for the US dollar, which needs no rate of its own
+        let leg = |currency: &str| -> Result<Option<&FxRate>> {
+            if currency == "usd" {
+                return Ok(None);
+            }
+            table
+                .and_then(|table| table.rates.get(currency))
+                .map(Some)
+                .ok_or_else(|| OracleError::UnknownCurrency(currency.to_string()))
+        };
+        let (from_leg, to_leg) = (leg(&from)?, leg(&to)?);
+
+        let per_usd = |leg: Option<&FxRate>| leg.map_or(1.0, |rate| rate.per_usd);
+        let rate = price_math::ratio(per_usd(to_leg), per_usd(from_leg)).ok_or_else(|| {
+            OracleError::UnknownCurrency(format!("{} (invalid rate against {})", to, from))
+        })?;
+        let as_of = [from_leg, to_leg]
+            .into_iter()
+            .flatten()
+            .map(|rate| rate.as_of)
+            .min()
+            .unwrap_or_else(Utc::now);
+        Ok(Conversion {
+            from,
+            to,
+            rate,
+            as_of,
+        })
+    }
+
+    /// `amount` of `from` in `to`, with the conversion it went through
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Result<(f64, Conversion)> {
+        let conversion = self.rate(from, to)?;
+        let converted = conversion.apply(amount).ok_or_else(|| {
+            OracleError::UnknownCurrency(format!("{} (amount out of range)", conversion.to))
+        })?;
+        Ok((converted, conversion))
+    }
+
+    /// Fetch the reference rates now, falling back to the intraday providers for
+    /// currencies without a reference rate newer than `fx.max_age`. A currency no
+    /// source priced keeps its previous rate, and the failure is listed in `errors`.
+    pub async fn refresh(&self, client: &reqwest::Client) {
+        let (settings, previous) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.settings.clone(), inner.latest.clone())
+        };
+        let mut errors = Vec::new();
+        let mut rates: BTreeMap<String, FxRate> = BTreeMap::new();
+
+        let results = join_all(
+            settings
+                .reference
+                .iter()
+                .map(|source| fetch_reference(client, source, &settings)),
+        )
+        .await;
+        for (source, result) in settings.reference.iter().zip(results) {
+            match result {
+                Ok(fetched) => {
+                    // Earlier sources in `fx.reference` take precedence
+                    for (currency, rate) in fetched {
+                        rates.entry(currency).or_insert(rate);
+                    }
+                }
+                Err(e) => {
+                    warn!("{} reference rates unavailable: {}", source, e);
+                    errors.push(format!("{}: {}", source, e));
+                }
+            }
+        }
+        // A reference rate stays authoritative until a newer one replaces it
+        if let Some(previous) = &previous {
+            for (currency, rate) in &previous.rates {
+                if rate.reference {
+                    rates
+                        .entry(currency.clone())
+                        .or_insert_with(|| rate.clone());
+                }
+            }
+        }
+
+        let now = Utc::now();
+        let outdated: Vec<&String> = settings
+            .currencies
+            .iter()
+            .filter(|currency| match rates.get(*currency) {
+                Some(rate) => (now - rate.as_of).num_seconds() > settings.max_age as i64,
+                None => true,
+            })
+            .collect();
+        // Intraday providers are asked in order until every outdated currency is quoted
+        let mut unquoted = outdated;
+        for source in &settings.intraday {
+            if unquoted.is_empty() {
+                break;
+            }
+            match fetch_intraday(client, source, &settings).await {
+                Ok(quotes) => unquoted.retain(|currency| match quotes.get(*currency) {
+                    Some(rate) => {
+                        rates.insert(currency.to_string(), rate.clone());
+                        false
+                    }
+                    None => true,
+                }),
+                Err(e) => {
+                    warn!("{} FX rates unavailable: {}", source, e);
+                    errors.push(format!("{}: {}", source, e));
+                }
+            }
+        }
+
+        let mut table = BTreeMap::new();
+        for currency in &settings.currencies {
+            let rate = rates
+                .remove(currency)
+                .or_else(|| previous.as_ref()?.rates.get(currency).cloned());
+            match rate {
+                Some(rate) => {
+                    table.insert(currency.clone(), rate);
+                }
+                None => errors.push(format!("{}: no rate from any source", currency)),
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.interval.is_none() {
+            // Turned off while fetching
+            return;
+        }
+        info!("FX table: {} currencies against USD", table.len());
+        inner.latest = Some(FxTable {
+            rates: table,
+            updated_at: now,
+            errors,
+        });
+    }
+}
+
+async fn fetch_reference(
+    client: &reqwest::Client,
+    source: &str,
+    settings: &Settings,
+) -> Result<BTreeMap<String, FxRate>> {
+    match source {
+        "ecb" => fetch_ecb(client).await,
+        "fed" => fetch_fed(client, settings).await,
+        other => Err(OracleError::ConfigError(format!(
+            "Unknown FX reference source '{}'",
+            other
+        ))),
+    }
+}
+
+async fn fetch_intraday(
+    client: &reqwest::Client,
+    source: &str,
+    settings: &Settings,
+) -> Result<BTreeMap<String, FxRate>> {
+    match source {
+        "coinbase" => fetch_coinbase(client).await,
+        "finnhub" => fetch_finnhub(client, settings).await,
+        other => Err(OracleError::ConfigError(format!(
+            "Unknown intraday FX source '{}'",
+            other
+        ))),
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<FetchResponse> {
+    FetchResponse::read(request.send().await?).await
+}
+
+/// The ECB's daily table, quoted per euro, rebased on the US dollar
+async fn fetch_ecb(client: &reqwest::Client) -> Result<BTreeMap<String, FxRate>> {
+    let response = send(client.get(ECB_URL)).await?;
+    if !response.status().is_success() {
+        return Err(status_error("ECB reference rate error", &response));
+    }
+    parse_ecb(response.text())
+}
+
+/// Rates of the ECB's daily XML table, rebased from the euro on the US dollar
+fn parse_ecb(body: &str) -> Result<BTreeMap<String, FxRate>> {
+    let invalid = || OracleError::ApiError("Invalid ECB reference rates".to_string());
+    let as_of = attribute(body, "time")
+        .and_then(|day| day.parse::<NaiveDate>().ok())
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .map(|day| day.and_utc())
+        .ok_or_else(invalid)?;
+    let per_eur: BTreeMap<String, f64> = body
+        .split("<Cube ")
+        .filter_map(|cube| {
+            let currency = attribute(cube, "currency")?.to_lowercase();
+            let rate = attribute(cube, "rate")?.parse().ok()?;
+            Some((currency, rate))
+        })
+        .collect();
+    let usd_per_eur = *per_eur.get("usd").ok_or_else(invalid)?;
+
+    let rate = |per_usd: f64| FxRate {
+        per_usd,
+        source: "ecb".to_string(),
+        reference: true,
+        as_of,
+    };
+    let mut rates: BTreeMap<String, FxRate> = per_eur
+        .iter()
+        .filter(|(currency, _)| currency.as_str() != "usd")
+        .map(|(currency, per_eur)| (currency.clone(), rate(per_eur / usd_per_eur)))
+        .collect();
+    rates.insert("eur".to_string(), rate(1.0 / usd_per_eur));
+    Ok(rates)
+}
+
+/// Value of `name='...'` (or `name="..."`) in an XML fragment
+fn attribute<'a>(fragment: &'a str, name: &str) -> Option<&'a str> {
+    for quote in ['\'', '"'] {
+        let prefix = format!("{}={}", name, quote);
+        if let Some(start) = fragment.find(&prefix) {
+            let value = &fragment[start + prefix.len()..];
+            return value.split(quote).next();
+        }
+    }
+    None
+}
+
+/// The latest H.10 rate of each configured currency the Fed publishes
+async fn fetch_fed(
+    client: &reqwest::Client,
+    settings: &Settings,
+) -> Result<BTreeMap<String, FxRate>> {
+    let api_key = settings
+        .fred_api_key
+        .as_deref()
+        .ok_or_else(|| OracleError::ConfigError("fx.fred_api_key is not set".to_string()))?;
+    let series: Vec<_> = FED_SERIES
+        .iter()
+        .filter(|(currency, _, _)| settings.currencies.iter().any(|c| c == currency))
+        .collect();
+
+    let results = join_all(
+        series
+            .iter()
+            .map(|(_, id, _)| fetch_fred_observation(client, api_key, id)),
+    )
+    .await;
+    let mut rates = BTreeMap::new();
+    let mut last_error = None;
+    for ((currency, id, usd_per_unit), result) in series.iter().zip(results) {
+        match result {
+            Ok((as_of, value)) => {
+                let per_usd = if *usd_per_unit { 1.0 / value } else { value };
+                rates.insert(
+                    currency.to_string(),
+                    FxRate {
+                        per_usd,
+                        source: "fed".to_string(),
+                        reference: true,
+                        as_of,
+                    },
+                );
+            }
+            Err(e) => {
+                warn!("FRED series {} unavailable: {}", id, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if rates.is_empty() => Err(e),
+        _ => Ok(rates),
+    }
+}
+
+/// Date and value of the latest observation of a FRED series; days without a
+/// rate (holidays) are reported as "."
+async fn fetch_fred_observation(
+    client: &reqwest::Client,
+    api_key: &str,
+    series: &str,
+) -> Result<(DateTime<Utc>, f64)> {
+    let request = client.get(FRED_URL).query(&[
+        ("series_id", series),
+        ("api_key", api_key),
+        ("file_type", "json"),
+        ("sort_order", "desc"),
+        ("limit", "10"),
+    ]);
+    let response = send(request).await?;
+    if !response.status().is_success() {
+        return Err(status_error(
+            &format!("FRED error for {}", series),
+            &response,
+        ));
+    }
+    let body: serde_json::Value = response.json()?;
+
+    body["observations"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|observation| {
+            let value = observation["value"].as_str()?.parse::<f64>().ok()?;
+            let day = observation["date"].as_str()?.parse::<NaiveDate>().ok()?;
+            Some((day.and_hms_opt(0, 0, 0)?.and_utc(), value))
+        })
+        .ok_or_else(|| OracleError::ApiError(format!("No recent FRED observation for {}", series)))
+}
+
+/// Coinbase's exchange rates against the US dollar, updated through the day
+async fn fetch_coinbase(client: &reqwest::Client) -> Result<BTreeMap<String, FxRate>> {
+    let request = client
+        .get("https://api.coinbase.com/v2/exchange-rates?currency=USD")
+        .header("User-Agent", "kanari-oracle");
+    let response = send(request).await?;
+    if !response.status().is_success() {
+        return Err(status_error("Coinbase exchange rate error", &response));
+    }
+    let body: serde_json::Value = response.json()?;
+
+    let quotes = body["data"]["rates"]
+        .as_object()
+        .ok_or_else(|| OracleError::ApiError("Invalid Coinbase exchange rates".to_string()))?;
+    Ok(intraday_rates(
+        "coinbase",
+        quotes
+            .iter()
+            .filter_map(|(currency, rate)| Some((currency, rate.as_str()?.parse().ok()?))),
+    ))
+}
+
+/// Finnhub's forex rates against the US dollar, with the `stocks.finnhub_api_key`
+async fn fetch_finnhub(
+    client: &reqwest::Client,
+    settings: &Settings,
+) -> Result<BTreeMap<String, FxRate>> {
+    let api_key = settings
+        .finnhub_api_key
+        .as_deref()
+        .ok_or_else(|| OracleError::ConfigError("stocks.finnhub_api_key is not set".to_string()))?;
+    let request = client
+        .get("https://finnhub.io/api/v1/forex/rates")
+        .query(&[("base", "USD"), ("token", api_key)]);
+    let response = send(request).await?;
+    if !response.status().is_success() {
+        return Err(status_error("Finnhub forex error", &response));
+    }
+    let body: serde_json::Value = response.json()?;
+
+    let quotes = body["quote"]
+        .as_object()
+        .ok_or_else(|| OracleError::ApiError("Invalid Finnhub forex rates".to_string()))?;
+    Ok(intraday_rates(
+        "finnhub",
+        quotes
+            .iter()
+            .filter_map(|(currency, rate)| Some((currency, rate.as_f64()?))),
+    ))
+}
+
+fn intraday_rates<'a>(
+    source: &str,
+    quotes: impl Iterator<Item = (&'a String, f64)>,
+) -> BTreeMap<String, FxRate> {
+    let now = Utc::now();
+    quotes
+        .filter(|(_, per_usd)| per_usd.is_finite() && *per_usd > 0.0)
+        .map(|(currency, per_usd)| {
+            (
+                currency.to_lowercase(),
+                FxRate {
+                    per_usd,
+                    source: source.to_string(),
+                    reference: false,
+                    as_of: now,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FxConfig;
+
+    const ECB_TABLE: &str = "<gesmes:Envelope><Cube><Cube time='2024-07-15'>\
+        <Cube currency='USD' rate='1.0900'/>\
+        <Cube currency='JPY' rate='172.20'/>\
+        <Cube currency='GBP' rate='0.8400'/>\
+        </Cube></Cube></gesmes:Envelope>";
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn ecb_rates_are_rebased_on_the_dollar() {
+        let rates = parse_ecb(ECB_TABLE).unwrap();
+        assert!(!rates.contains_key("usd"));
+        assert_close(rates["eur"].per_usd, 1.0 / 1.09);
+        assert_close(rates["jpy"].per_usd, 172.2 / 1.09);
+        assert_close(rates["gbp"].per_usd, 0.84 / 1.09);
+        let rate = &rates["jpy"];
+        assert_eq!(rate.source, "ecb");
+        assert!(rate.reference);
+        assert_eq!(
+            rate.as_of,
+            "2024-07-15T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn ecb_tables_without_a_dollar_rate_or_date_are_invalid() {
+        assert!(parse_ecb(&ECB_TABLE.replace("USD", "CHF")).is_err());
+        assert!(parse_ecb(&ECB_TABLE.replace("time=", "day=")).is_err());
+    }
+
+    #[test]
+    fn xml_attributes_take_either_quote() {
+        assert_eq!(attribute("<Cube rate='1.5'/>", "rate"), Some("1.5"));
+        assert_eq!(attribute("<Cube rate=\"1.5\"/>", "rate"), Some("1.5"));
+        assert_eq!(attribute("<Cube rate='1.5'/>", "currency"), None);
+    }
+
+    #[test]
+    fn intraday_quotes_skip_unusable_rates() {
+        let quotes = [
+            ("THB".to_string(), 36.1),
+            ("jpy".to_string(), 0.0),
+            ("gbp".to_string(), f64::NAN),
+            ("eur".to_string(), -0.9),
+        ];
+        let rates = intraday_rates("coinbase", quotes.iter().map(|(c, r)| (c, *r)));
+        assert_eq!(rates.keys().collect::<Vec<_>>(), ["thb"]);
+        assert!(!rates["thb"].reference);
+        assert_eq!(rates["thb"].source, "coinbase");
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_earlier_rates_when_no_source_answers() {
+        let config = Config {
+            fx: Some(FxConfig {
+                currencies: vec!["EUR".to_string(), "THB".to_string()],
+                reference: Vec::new(),
+                intraday: Vec::new(),
+                ..FxConfig::default()
+            }),
+            ..Config::default()
+        };
+        let converter = CurrencyConverter::new(&config);
+        let reference = parse_ecb(ECB_TABLE).unwrap();
+        converter.inner.lock().unwrap().latest = Some(FxTable {
+            rates: reference.clone(),
+            updated_at: Utc::now(),
+            errors: Vec::new(),
+        });
+
+        converter.refresh(&reqwest::Client::new()).await;
+
+        let table = converter.latest().unwrap();
+        // Only configured currencies are kept, at their earlier rates
+        assert_eq!(table.rates.keys().collect::<Vec<_>>(), ["eur"]);
+        assert_close(table.rates["eur"].per_usd, reference["eur"].per_usd);
+        assert_eq!(table.errors, ["thb: no rate from any source"]);
+    }
+
+    /// Converter whose latest table holds `rates` (units per US dollar)
+    fn converter(rates: &[(&str, f64, &str)]) -> CurrencyConverter {
+        let converter = CurrencyConverter::new(&Config::default());
+        let rates = rates
+            .iter()
+            .map(|(currency, per_usd, as_of)| {
+                let rate = FxRate {
+                    per_usd: *per_usd,
+                    source: "ecb".to_string(),
+                    reference: true,
+                    as_of: as_of.parse().unwrap(),
+                };
+                (currency.to_string(), rate)
+            })
+            .collect();
+        converter.inner.lock().unwrap().latest = Some(FxTable {
+            rates,
+            updated_at: Utc::now(),
+            errors: Vec::new(),
+        });
+        converter
+    }
+
+    fn sample() -> CurrencyConverter {
+        converter(&[
+            ("eur", 0.92, "2024-07-15T00:00:00Z"),
+            ("jpy", 158.0, "2024-07-16T00:00:00Z"),
+        ])
+    }
+
+    #[test]
+    fn converts_to_and_from_the_dollar() {
+        let converter = sample();
+        assert_close(converter.rate("usd", "eur").unwrap().rate, 0.92);
+        assert_close(converter.rate("eur", "usd").unwrap().rate, 1.0 / 0.92);
+    }
+
+    #[test]
+    fn triangulates_through_the_dollar() {
+        let converter = sample();
+        let conversion = converter.rate("EUR", "jpy").unwrap();
+        assert_eq!(
+            (conversion.from.as_str(), conversion.to.as_str()),
+            ("eur", "jpy")
+        );
+        assert_close(conversion.rate, 158.0 / 0.92);
+        // Dated by the older of the two rates
+        assert_eq!(
+            conversion.as_of,
+            "2024-07-15T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        let back = converter.rate("jpy", "eur").unwrap();
+        assert_close(conversion.rate * back.rate, 1.0);
+    }
+
+    #[test]
+    fn converts_amounts_and_prices() {
+        let converter = sample();
+        let (amount, _) = converter.convert(100.0, "eur", "jpy").unwrap();
+        assert_close(amount, 100.0 * 158.0 / 0.92);
+
+        let mut price_data = PriceData::new("btc".to_string(), 60000.0, "binance".to_string());
+        price_data.change_24h = Some(-600.0);
+        price_data.change_24h_percent = Some(-1.0);
+        price_data.volume_24h = None;
+        let converted = converter
+            .rate("usd", "eur")
+            .unwrap()
+            .apply_price(&price_data)
+            .unwrap();
+        assert_close(converted.price, 60000.0 * 0.92);
+        assert_close(converted.change_24h.unwrap(), -600.0 * 0.92);
+        assert_eq!(converted.change_24h_percent, Some(-1.0));
+        assert_eq!(converted.volume_24h, None);
+    }
+
+    #[test]
+    fn a_currency_converts_to_itself_without_a_table() {
+        let converter = CurrencyConverter::new(&Config::default());
+        assert_eq!(converter.rate("thb", "THB").unwrap().rate, 1.0);
+        assert!(converter.supports("usd"));
+        assert!(!converter.supports("thb"));
+    }
+
+    #[test]
+    fn unknown_currencies_are_errors() {
+        let converter = sample();
+        assert!(converter.supports("EUR"));
+        assert!(!converter.supports("thb"));
+        assert!(matches!(
+            converter.rate("eur", "thb"),
+            Err(OracleError::UnknownCurrency(currency)) if currency == "thb"
+        ));
+        assert!(matches!(
+            converter.rate("thb", "usd"),
+            Err(OracleError::UnknownCurrency(currency)) if currency == "thb"
+        ));
+    }
+
+    #[test]
+    fn zero_rates_cannot_be_triangulated() {
+        let converter = converter(&[("eur", 0.0, "2024-07-15T00:00:00Z")]);
+        assert!(converter.rate("eur", "usd").is_err());
+    }
+}
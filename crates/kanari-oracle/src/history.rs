@@ -0,0 +1,188 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::config::{CorporateAction, HistoryConfig};
+use crate::market;
+use crate::models::{AssetType, PriceData};
+use crate::symbols;
+
+/// One published price of a symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPoint {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+    pub source: String,
+    /// Round the price was published in
+    pub round_id: u64,
+}
+
+/// Recently published prices per symbol, oldest first, bounded by
+/// `history.retention` and `history.max_points`
+#[derive(Debug)]
+pub struct PriceHistory {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    config: HistoryConfig,
+    /// Keyed "asset_type:feed_key"
+    series: HashMap<String, VecDeque<HistoryPoint>>,
+}
+
+impl PriceHistory {
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                config,
+                series: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Apply new limits, trimming what they no longer allow
+    pub fn reconfigure(&self, config: HistoryConfig) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.config = config;
+        let config = inner.config.clone();
+        for points in inner.series.values_mut() {
+            trim(points, &config);
+        }
+        inner.series.retain(|_, points| !points.is_empty());
+    }
+
+    /// Drop the series of symbols `keep` rejects
+    pub fn retain(&self, mut keep: impl FnMut(AssetType, &str) -> bool) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.series.retain(|key, _| match key.split_once(':') {
+            Some(("crypto", symbol)) => keep(AssetType::Crypto, symbol),
+            Some(("stock", symbol)) => keep(AssetType::Stock, symbol),
+            Some(("tvl", symbol)) => keep(AssetType::Tvl, symbol),
+            _ => false,
+        });
+    }
+
+    /// Append prices newer than the last recorded point of their symbol
+    pub fn record(&self, round_id: u64, asset_type: AssetType, prices: &[PriceData]) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.config.max_points == 0 {
+            return;
+        }
+        let config = inner.config.clone();
+
+        for price_data in prices {
+            let points = inner
+                .series
+                .entry(series_key(asset_type, &price_data.symbol))
+                .or_default();
+            if points
+                .back()
+                .is_some_and(|last| last.timestamp >= price_data.timestamp)
+            {
+                continue;
+            }
+            points.push_back(HistoryPoint {
+                timestamp: price_data.timestamp,
+                price: price_data.price,
+                source: price_data.source.clone(),
+                round_id,
+            });
+            trim(points, &config);
+        }
+    }
+
+    /// Points of a symbol between `from` and `to`, inclusive, oldest first
+    pub fn range(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<HistoryPoint> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .series
+            .get(&series_key(asset_type, symbol))
+            .map(|points| {
+                points
+                    .iter()
+                    .filter(|point| point.timestamp >= from && point.timestamp <= to)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Like [`PriceHistory::range`], with the prices before each action's ex-date
+    /// multiplied by its adjustment factor
+    pub fn adjusted_range(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        actions: &[&CorporateAction],
+    ) -> Vec<HistoryPoint> {
+        let inner = self.inner.lock().unwrap();
+        let Some(points) = inner.series.get(&series_key(asset_type, symbol)) else {
+            return Vec::new();
+        };
+        let factors: Vec<(NaiveDate, f64)> = actions
+            .iter()
+            .filter_map(|action| Some((action.ex_date, adjustment_factor(action, points)?)))
+            .collect();
+
+        points
+            .iter()
+            .filter(|point| point.timestamp >= from && point.timestamp <= to)
+            .map(|point| {
+                let date = market::eastern_date(point.timestamp);
+                let factor: f64 = factors
+                    .iter()
+                    .filter(|(ex_date, _)| date < *ex_date)
+                    .map(|(_, factor)| factor)
+                    .product();
+                HistoryPoint {
+                    price: point.price * factor,
+                    ..point.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Factor of a corporate action: the inverse of a split ratio, or for a dividend
+/// one minus its share of the last price before the ex-date. None for a dividend
+/// without a recorded price before its ex-date to compare with.
+fn adjustment_factor(action: &CorporateAction, points: &VecDeque<HistoryPoint>) -> Option<f64> {
+    if let Some(ratio) = action.split {
+        return Some(1.0 / ratio);
+    }
+    let dividend = action.dividend?;
+    let close = points
+        .iter()
+        .rev()
+        .find(|point| market::eastern_date(point.timestamp) < action.ex_date)?
+        .price;
+    (dividend < close).then(|| 1.0 - dividend / close)
+}
+
+fn series_key(asset_type: AssetType, symbol: &str) -> String {
+    format!(
+        "{}:{}",
+        asset_type.as_str(),
+        symbols::feed_key(asset_type, symbol)
+    )
+}
+
+fn trim(points: &mut VecDeque<HistoryPoint>, config: &HistoryConfig) {
+    while points.len() > config.max_points {
+        points.pop_front();
+    }
+    let cutoff = Utc::now() - chrono::Duration::seconds(config.retention as i64);
+    while points.front().is_some_and(|point| point.timestamp < cutoff) {
+        points.pop_front();
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+use crate::models::PriceData;
+use crate::price_math;
+
+/// One coin of a [`MarketCapIndex`]
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexConstituent {
+    pub symbol: String,
+    pub price: f64,
+    pub market_cap: f64,
+    /// Share of the index's total market cap, between 0 and 1
+    pub weight: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_24h_percent: Option<f64>,
+}
+
+/// Coins weighted by market cap, as returned by `/index/market-cap`
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketCapIndex {
+    pub total_market_cap: f64,
+    /// 24h change of the index: the constituents' changes weighted by market
+    /// cap. None when no constituent reports a change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_24h_percent: Option<f64>,
+    /// Largest first
+    pub constituents: Vec<IndexConstituent>,
+    /// Symbols left out because their source reports no market cap
+    pub missing: Vec<String>,
+}
+
+/// Weight `prices` by market cap. Prices without a market cap are listed in
+/// `missing` instead of counting with weight 0.
+pub fn market_cap_weighted(prices: &[PriceData]) -> MarketCapIndex {
+    let mut missing = Vec::new();
+    let mut capped = Vec::new();
+    for price_data in prices {
+        match price_data.market_cap.filter(|cap| *cap > 0.0) {
+            Some(market_cap) => capped.push((price_data, market_cap)),
+            None => missing.push(price_data.symbol.clone()),
+        }
+    }
+
+    let total_market_cap = price_math::sum(capped.iter().map(|(_, cap)| *cap)).unwrap_or(0.0);
+    let change_24h_percent = price_math::weighted_mean(
+        capped
+            .iter()
+            .filter_map(|(price_data, cap)| Some((price_data.change_24h_percent?, *cap))),
+    );
+
+    let mut constituents: Vec<IndexConstituent> = capped
+        .into_iter()
+        .map(|(price_data, market_cap)| IndexConstituent {
+            symbol: price_data.symbol.clone(),
+            price: price_data.price,
+            market_cap,
+            weight: market_cap / total_market_cap,
+            change_24h_percent: price_data.change_24h_percent,
+        })
+        .collect();
+    constituents.sort_by(|a, b| b.market_cap.total_cmp(&a.market_cap));
+    missing.sort();
+
+    MarketCapIndex {
+        total_market_cap,
+        change_24h_percent,
+        constituents,
+        missing,
+    }
+}
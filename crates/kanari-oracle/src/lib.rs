@@ -1,5 +1,30 @@
+pub mod anomaly;
+pub mod audit;
+pub mod basket;
+pub mod builder;
 pub mod config;
+pub mod depth;
 pub mod errors;
 pub mod fetchers;
+pub mod fx;
+pub mod history;
+pub mod index;
+pub mod locale;
+pub mod market;
 pub mod models;
+pub mod network;
 pub mod oracle;
+pub mod precision;
+pub mod price_math;
+pub mod provenance;
+pub mod round;
+pub mod scheduler;
+pub mod sentiment;
+pub mod singleflight;
+pub mod sinks;
+pub mod slo;
+pub mod sources;
+pub mod store;
+pub mod subscriber;
+pub mod symbols;
+pub mod table;
@@ -0,0 +1,217 @@
+use crate::fx::Conversion;
+use crate::precision::PrecisionRule;
+
+/// Environment variables naming the user's locale, in the order POSIX
+/// consults them for numbers
+const LOCALE_ENV: [&str; 3] = ["LC_ALL", "LC_NUMERIC", "LANG"];
+
+/// How numbers and currency amounts are written in one language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Separator between groups of three integer digits
+    group: &'static str,
+    decimal: char,
+    /// Whether the currency symbol follows the amount, as in "1.234,56 €"
+    symbol_after: bool,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        ENGLISH
+    }
+}
+
+const ENGLISH: Locale = Locale {
+    group: ",",
+    decimal: '.',
+    symbol_after: false,
+};
+
+const CONTINENTAL: Locale = Locale {
+    group: ".",
+    decimal: ',',
+    symbol_after: true,
+};
+
+const SPACED: Locale = Locale {
+    group: "\u{202f}",
+    decimal: ',',
+    symbol_after: true,
+};
+
+const SWISS: Locale = Locale {
+    group: "’",
+    decimal: '.',
+    symbol_after: false,
+};
+
+/// Locales by language, or language and region where the region differs
+const LOCALES: &[(&str, Locale)] = &[
+    ("de-ch", SWISS),
+    ("en", ENGLISH),
+    ("th", ENGLISH),
+    ("ja", ENGLISH),
+    ("zh", ENGLISH),
+    ("ko", ENGLISH),
+    ("hi", ENGLISH),
+    ("ms", ENGLISH),
+    ("de", CONTINENTAL),
+    ("es", CONTINENTAL),
+    ("it", CONTINENTAL),
+    ("nl", CONTINENTAL),
+    ("pt", CONTINENTAL),
+    ("id", CONTINENTAL),
+    ("tr", CONTINENTAL),
+    ("da", CONTINENTAL),
+    ("vi", CONTINENTAL),
+    ("fr", SPACED),
+    ("ru", SPACED),
+    ("pl", SPACED),
+    ("cs", SPACED),
+    ("sv", SPACED),
+    ("nb", SPACED),
+    ("fi", SPACED),
+    ("uk", SPACED),
+];
+
+/// Symbols of common currencies; others are written with their code
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("usd", "$"),
+    ("eur", "€"),
+    ("gbp", "£"),
+    ("jpy", "¥"),
+    ("cny", "CN¥"),
+    ("thb", "฿"),
+    ("krw", "₩"),
+    ("inr", "₹"),
+    ("vnd", "₫"),
+    ("php", "₱"),
+    ("ils", "₪"),
+    ("try", "₺"),
+    ("rub", "₽"),
+    ("uah", "₴"),
+    ("ngn", "₦"),
+    ("cad", "CA$"),
+    ("aud", "A$"),
+    ("nzd", "NZ$"),
+    ("hkd", "HK$"),
+    ("sgd", "S$"),
+    ("mxn", "MX$"),
+    ("twd", "NT$"),
+    ("brl", "R$"),
+];
+
+impl Locale {
+    /// Locale of a tag such as "th", "de-CH" or "fr_FR.UTF-8"; None when the
+    /// language is not known. "C" and "POSIX" are English.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let tag = tag.trim().to_lowercase().replace('_', "-");
+        if tag == "c" || tag == "posix" {
+            return Some(ENGLISH);
+        }
+        let language = tag.split('-').next().unwrap_or_default();
+        LOCALES
+            .iter()
+            .find(|(name, _)| *name == tag)
+            .or_else(|| LOCALES.iter().find(|(name, _)| *name == language))
+            .map(|(_, locale)| *locale)
+    }
+
+    /// Locale named by the environment, or English when it names none known
+    pub fn from_env() -> Self {
+        LOCALE_ENV
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|tag| !tag.is_empty())
+            .and_then(|tag| Self::parse(&tag))
+            .unwrap_or_default()
+    }
+
+    /// `plain`, a number written with a '.' decimal point such as "-1234.5",
+    /// written this locale's way
+    pub fn number(&self, plain: &str) -> String {
+        let (sign, digits) = match plain.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", plain),
+        };
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let mut out = sign.to_string();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                out.push_str(self.group);
+            }
+            out.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            out.push(self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// `plain` as an amount of `currency`, e.g. "฿2,104,833.10" or "1.234,56 €"
+    pub fn money(&self, plain: &str, currency: &str) -> String {
+        let number = self.number(plain);
+        let currency = currency.to_lowercase();
+        let symbol = CURRENCY_SYMBOLS
+            .iter()
+            .find(|(code, _)| *code == currency)
+            .map(|(_, symbol)| symbol.to_string());
+        match symbol {
+            Some(symbol) if !self.symbol_after => match number.strip_prefix('-') {
+                Some(number) => format!("-{}{}", symbol, number),
+                None => format!("{}{}", symbol, number),
+            },
+            Some(symbol) => format!("{} {}", number, symbol),
+            None => format!("{} {}", number, currency.to_uppercase()),
+        }
+    }
+
+    /// `percent` with two decimals, e.g. "-1,25%"
+    pub fn percent(&self, percent: f64) -> String {
+        format!("{}%", self.number(&format!("{:.2}", percent)))
+    }
+}
+
+/// How the amounts of one asset type are shown in CLI output: converted to the
+/// display currency and written the locale's way
+#[derive(Debug, Clone)]
+pub struct AmountFormat {
+    pub locale: Locale,
+    /// Currency the amounts are shown in
+    pub currency: String,
+    /// Conversion from the asset type's quote currency; None when amounts are
+    /// shown as quoted
+    pub conversion: Option<Conversion>,
+}
+
+impl AmountFormat {
+    /// `amount` in the display currency, or None when it is out of range there
+    pub fn convert(&self, amount: f64) -> Option<f64> {
+        match &self.conversion {
+            Some(conversion) => conversion.apply(amount),
+            None => Some(amount),
+        }
+    }
+
+    /// `amount` converted and rounded by `rule`, with the currency symbol
+    pub fn money(&self, rule: &PrecisionRule, amount: f64) -> String {
+        match self.convert(amount) {
+            Some(amount) => self.locale.money(&rule.format(amount), &self.currency),
+            None => "N/A".to_string(),
+        }
+    }
+
+    /// `amount` converted and rounded by `rule`, without the currency symbol
+    pub fn number(&self, rule: &PrecisionRule, amount: f64) -> String {
+        match self.convert(amount) {
+            Some(amount) => self.locale.number(&rule.format(amount)),
+            None => "N/A".to_string(),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Trading session of the US equity market (NYSE/Nasdaq)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketState {
+    /// Pre-market, 04:00 - 09:30 ET
+    Pre,
+    /// Regular session, 09:30 - 16:00 ET
+    Open,
+    /// After-hours, 16:00 - 20:00 ET
+    Post,
+    /// Overnight, weekends and exchange holidays
+    Closed,
+}
+
+impl MarketState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketState::Pre => "pre",
+            MarketState::Open => "open",
+            MarketState::Post => "post",
+            MarketState::Closed => "closed",
+        }
+    }
+}
+
+/// Extended-hours trading ends at 20:00 ET
+const SESSION_END_HOUR: u32 = 20;
+
+/// State of the US equity market at `now`
+pub fn us_equity_state(now: DateTime<Utc>) -> MarketState {
+    let local = to_eastern(now);
+    if !is_trading_day(local.date()) {
+        return MarketState::Closed;
+    }
+
+    let time = local.time();
+    let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+    if time < at(4, 0) {
+        MarketState::Closed
+    } else if time < at(9, 30) {
+        MarketState::Pre
+    } else if time < at(16, 0) {
+        MarketState::Open
+    } else if time < at(SESSION_END_HOUR, 0) {
+        MarketState::Post
+    } else {
+        MarketState::Closed
+    }
+}
+
+/// Whether a stock price fetched at `fetched` is still the latest one at `now`:
+/// the market is closed and has not traded since the price was fetched
+pub fn is_settled(fetched: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    us_equity_state(now) == MarketState::Closed && fetched >= last_session_end(now)
+}
+
+/// Weekday that is not an NYSE holiday
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !is_us_market_holiday(date)
+}
+
+/// NYSE full-day holidays
+pub fn is_us_market_holiday(date: NaiveDate) -> bool {
+    let year = date.year();
+    let fixed = |month, day| observed(NaiveDate::from_ymd_opt(year, month, day).unwrap());
+    let nth = |month, weekday, n| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n);
+
+    let mut holidays = vec![
+        nth(1, Weekday::Mon, 3),                // Martin Luther King Jr. Day
+        nth(2, Weekday::Mon, 3),                // Washington's Birthday
+        Some(easter(year) - Duration::days(2)), // Good Friday
+        nth(5, Weekday::Mon, 5).or_else(|| nth(5, Weekday::Mon, 4)), // Memorial Day
+        Some(fixed(7, 4)),                      // Independence Day
+        nth(9, Weekday::Mon, 1),                // Labor Day
+        nth(11, Weekday::Thu, 4),               // Thanksgiving
+        Some(fixed(12, 25)),                    // Christmas
+    ];
+    // New Year's Day falling on a Saturday is not observed on the previous Friday
+    let new_year = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+    match new_year.weekday() {
+        Weekday::Sat => {}
+        Weekday::Sun => holidays.push(new_year.succ_opt()),
+        _ => holidays.push(Some(new_year)),
+    }
+    if year >= 2022 {
+        holidays.push(Some(fixed(6, 19))); // Juneteenth
+    }
+
+    holidays.contains(&Some(date))
+}
+
+/// Saturday holidays are observed on Friday, Sunday holidays on Monday
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// Easter Sunday (anonymous Gregorian algorithm)
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+/// End of the most recent trading session (20:00 ET) at or before `now`
+fn last_session_end(now: DateTime<Utc>) -> DateTime<Utc> {
+    let local = to_eastern(now);
+    let mut date = local.date();
+    if local.time() < NaiveTime::from_hms_opt(SESSION_END_HOUR, 0, 0).unwrap() {
+        date = date.pred_opt().unwrap();
+    }
+    // Long weekends and holidays never span more than a few days
+    for _ in 0..10 {
+        if is_trading_day(date) {
+            break;
+        }
+        date = date.pred_opt().unwrap();
+    }
+
+    let end = date.and_hms_opt(SESSION_END_HOUR, 0, 0).unwrap();
+    let offset = eastern_offset(end.and_utc());
+    (end - offset).and_utc()
+}
+
+/// US Eastern calendar date at `at`, the date exchanges trade on
+pub fn eastern_date(at: DateTime<Utc>) -> NaiveDate {
+    to_eastern(at).date()
+}
+
+/// `now` as US Eastern wall-clock time
+fn to_eastern(now: DateTime<Utc>) -> NaiveDateTime {
+    now.naive_utc() + eastern_offset(now)
+}
+
+/// UTC offset of US Eastern time: -4h during daylight saving time, -5h otherwise.
+/// DST runs from 02:00 local on the second Sunday of March to 02:00 local on the
+/// first Sunday of November.
+fn eastern_offset(now: DateTime<Utc>) -> Duration {
+    let year = now.year();
+    let second_sunday_march = NaiveDate::from_weekday_of_month_opt(year, 3, Weekday::Sun, 2)
+        .unwrap()
+        .and_hms_opt(7, 0, 0)
+        .unwrap();
+    let first_sunday_november = NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Sun, 1)
+        .unwrap()
+        .and_hms_opt(6, 0, 0)
+        .unwrap();
+
+    let utc = now.naive_utc();
+    if utc >= second_sunday_march && utc < first_sunday_november {
+        Duration::hours(-4)
+    } else {
+        Duration::hours(-5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn utc(at: &str) -> DateTime<Utc> {
+        at.parse().unwrap()
+    }
+
+    #[test]
+    fn nyse_holidays() {
+        let holidays = [
+            // 2024
+            date(2024, 1, 1),
+            date(2024, 1, 15),
+            date(2024, 2, 19),
+            date(2024, 3, 29),
+            date(2024, 5, 27),
+            date(2024, 6, 19),
+            date(2024, 7, 4),
+            date(2024, 9, 2),
+            date(2024, 11, 28),
+            date(2024, 12, 25),
+            // 2026: Independence Day on a Saturday is observed on Friday
+            date(2026, 1, 1),
+            date(2026, 1, 19),
+            date(2026, 2, 16),
+            date(2026, 4, 3),
+            date(2026, 5, 25),
+            date(2026, 6, 19),
+            date(2026, 7, 3),
+            date(2026, 9, 7),
+            date(2026, 11, 26),
+            date(2026, 12, 25),
+        ];
+        for holiday in holidays {
+            assert!(is_us_market_holiday(holiday), "{} is a holiday", holiday);
+            assert!(!is_trading_day(holiday), "{} is not a trading day", holiday);
+        }
+    }
+
+    #[test]
+    fn weekend_holidays_are_observed() {
+        // New Year's Day on a Sunday moves to Monday
+        assert!(is_us_market_holiday(date(2023, 1, 2)));
+        // Juneteenth on a Sunday moves to Monday
+        assert!(is_us_market_holiday(date(2022, 6, 20)));
+        // Christmas on a Saturday moves to Friday
+        assert!(is_us_market_holiday(date(2021, 12, 24)));
+        // Independence Day on a Sunday moves to Monday
+        assert!(is_us_market_holiday(date(2027, 7, 5)));
+        // New Year's Day on a Saturday is not observed in the old year
+        assert!(is_trading_day(date(2021, 12, 31)));
+    }
+
+    #[test]
+    fn ordinary_days() {
+        // Juneteenth is a holiday from 2022
+        assert!(is_trading_day(date(2021, 6, 18)));
+        assert!(is_trading_day(date(2024, 7, 5)));
+        assert!(is_trading_day(date(2024, 11, 29)));
+        assert!(!is_trading_day(date(2024, 7, 6)));
+        assert!(!is_trading_day(date(2024, 7, 7)));
+    }
+
+    #[test]
+    fn easter_sundays() {
+        assert_eq!(easter(2000), date(2000, 4, 23));
+        assert_eq!(easter(2024), date(2024, 3, 31));
+        assert_eq!(easter(2025), date(2025, 4, 20));
+        assert_eq!(easter(2038), date(2038, 4, 25));
+    }
+
+    #[test]
+    fn daylight_saving_switches_at_two_am_local() {
+        assert_eq!(
+            eastern_offset(utc("2024-03-10T06:59:59Z")),
+            Duration::hours(-5)
+        );
+        assert_eq!(
+            eastern_offset(utc("2024-03-10T07:00:00Z")),
+            Duration::hours(-4)
+        );
+        assert_eq!(
+            eastern_offset(utc("2024-11-03T05:59:59Z")),
+            Duration::hours(-4)
+        );
+        assert_eq!(
+            eastern_offset(utc("2024-11-03T06:00:00Z")),
+            Duration::hours(-5)
+        );
+    }
+
+    #[test]
+    fn sessions_follow_eastern_time() {
+        // Summer, UTC-4
+        assert_eq!(
+            us_equity_state(utc("2024-07-15T07:59:00Z")),
+            MarketState::Closed
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-07-15T08:00:00Z")),
+            MarketState::Pre
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-07-15T13:29:00Z")),
+            MarketState::Pre
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-07-15T13:30:00Z")),
+            MarketState::Open
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-07-15T20:00:00Z")),
+            MarketState::Post
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-07-16T00:00:00Z")),
+            MarketState::Closed
+        );
+        // Winter, UTC-5
+        assert_eq!(
+            us_equity_state(utc("2024-01-16T14:29:00Z")),
+            MarketState::Pre
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-01-16T14:30:00Z")),
+            MarketState::Open
+        );
+        // Weekends and holidays
+        assert_eq!(
+            us_equity_state(utc("2024-07-13T15:00:00Z")),
+            MarketState::Closed
+        );
+        assert_eq!(
+            us_equity_state(utc("2024-07-04T15:00:00Z")),
+            MarketState::Closed
+        );
+    }
+
+    #[test]
+    fn eastern_dates_lag_utc_in_the_evening() {
+        assert_eq!(eastern_date(utc("2024-07-16T03:00:00Z")), date(2024, 7, 15));
+        assert_eq!(eastern_date(utc("2024-07-16T04:00:00Z")), date(2024, 7, 16));
+    }
+
+    #[test]
+    fn prices_settle_once_the_session_ends() {
+        let saturday = utc("2024-07-13T15:00:00Z");
+        // Fetched during Friday's after-hours session, which ends 00:00 UTC
+        assert!(!is_settled(utc("2024-07-12T23:59:00Z"), saturday));
+        assert!(is_settled(utc("2024-07-13T00:00:00Z"), saturday));
+        // Nothing settles while the market is open
+        assert!(!is_settled(
+            utc("2024-07-15T13:35:00Z"),
+            utc("2024-07-15T13:40:00Z")
+        ));
+        // Fetched after Wednesday's session, still the latest on Independence Day
+        assert!(is_settled(
+            utc("2024-07-04T01:00:00Z"),
+            utc("2024-07-04T15:00:00Z")
+        ));
+        // Wednesday's session was the last before the holiday
+        assert_eq!(
+            last_session_end(utc("2024-07-04T15:00:00Z")),
+            utc("2024-07-04T00:00:00Z")
+        );
+    }
+}
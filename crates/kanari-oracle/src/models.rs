@@ -2,6 +2,33 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::market::MarketState;
+use crate::symbols;
+
+/// Asset class a symbol or source belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetType {
+    Crypto,
+    Stock,
+    /// Weighted combination of other symbols, defined in `baskets`
+    Basket,
+    /// Total value locked in a DeFi protocol or chain, in USD
+    Tvl,
+}
+
+impl AssetType {
+    /// Key used for this asset type in `Oracle::price_feeds`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetType::Crypto => "crypto",
+            AssetType::Stock => "stock",
+            AssetType::Basket => "basket",
+            AssetType::Tvl => "tvl",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceData {
     pub symbol: String, // เก็บรูปแบบดั้งเดิม (แต่ key ใน HashMap เป็น lowercase)
@@ -10,8 +37,43 @@ pub struct PriceData {
     pub change_24h_percent: Option<f64>,
     pub volume_24h: Option<f64>,
     pub market_cap: Option<f64>,
+    /// Coins in circulation, for crypto sources that report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circulating_supply: Option<f64>,
+    /// Coins created minus coins burned, for crypto sources that report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_supply: Option<f64>,
     pub timestamp: DateTime<Utc>,
     pub source: String,
+    /// Seconds since `timestamp` when the price was served
+    #[serde(default)]
+    pub age_seconds: i64,
+    /// Whether the price is older than the asset type's `max_age`
+    #[serde(default)]
+    pub is_stale: bool,
+    /// Trading session when the price was served; set for stocks only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_state: Option<MarketState>,
+    /// Session the price was traded in (`pre`, `open` or `post`), for stock
+    /// sources that report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<MarketState>,
+    /// Liquidity within ±2% of the mid price, when `crypto.order_book_depth`
+    /// lists an exchange that answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<OrderBookDepth>,
+}
+
+/// Notional resting in an exchange's order book within ±2% of the mid price,
+/// in the pair's quote currency (USDT on Binance, USD on Coinbase)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBookDepth {
+    /// Bids at or above the mid price minus 2%
+    pub bids: f64,
+    /// Asks at or below the mid price plus 2%
+    pub asks: f64,
+    /// Exchange whose book was read
+    pub exchange: String,
 }
 
 impl PriceData {
@@ -23,10 +85,24 @@ impl PriceData {
             change_24h_percent: None,
             volume_24h: None,
             market_cap: None,
+            circulating_supply: None,
+            total_supply: None,
             timestamp: Utc::now(),
             source,
+            age_seconds: 0,
+            is_stale: false,
+            market_state: None,
+            session: None,
+            depth: None,
         }
     }
+
+    /// Set `age_seconds` and `is_stale` relative to now
+    pub fn with_staleness(mut self, max_age: u64) -> Self {
+        self.age_seconds = (Utc::now() - self.timestamp).num_seconds().max(0);
+        self.is_stale = self.age_seconds as u64 > max_age;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,21 +138,28 @@ impl StockQuote {
             .ok()?;
 
         Some(PriceData {
-            symbol: self.symbol.to_lowercase(), // Use lowercase for consistency
+            symbol: symbols::normalize_stock(&self.symbol),
             price,
             change_24h: Some(change),
             change_24h_percent: Some(change_percent),
             volume_24h: None,
             market_cap: None,
+            circulating_supply: None,
+            total_supply: None,
             timestamp: Utc::now(),
             source: "alphavantage".to_string(),
+            age_seconds: 0,
+            is_stale: false,
+            market_state: None,
+            session: None,
+            depth: None,
         })
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceFeed {
-    pub prices: HashMap<String, PriceData>, // key = symbol.to_lowercase()
+    pub prices: HashMap<String, PriceData>, // key = symbols::feed_key(symbol)
     pub last_update: DateTime<Utc>,
 }
 
@@ -0,0 +1,234 @@
+use chrono::{DateTime, TimeZone, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{FetchResponse, status_error};
+
+/// Limit on each network statistics request; a slow upstream is retried at the
+/// next fetch
+pub const NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fee rates, in sat/vB, recommended for a transaction to confirm within a
+/// given time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimates {
+    /// Next block
+    #[serde(rename(deserialize = "fastestFee"))]
+    pub fastest: f64,
+    /// Within half an hour
+    #[serde(rename(deserialize = "halfHourFee"))]
+    pub half_hour: f64,
+    /// Within an hour
+    #[serde(rename(deserialize = "hourFee"))]
+    pub hour: f64,
+    /// Eventually, at a rate miners still accept
+    #[serde(rename(deserialize = "economyFee"))]
+    pub economy: f64,
+    /// Lowest rate the mempool currently relays
+    #[serde(rename(deserialize = "minimumFee"))]
+    pub minimum: f64,
+}
+
+/// Progress towards the next difficulty retarget
+#[derive(Debug, Clone, Serialize)]
+pub struct DifficultyAdjustment {
+    /// Share of the 2016-block epoch already mined, in percent
+    pub progress_percent: f64,
+    /// Expected difficulty change at the retarget, in percent
+    pub estimated_change_percent: f64,
+    pub remaining_blocks: u64,
+    pub estimated_retarget: DateTime<Utc>,
+}
+
+/// Bitcoin network statistics as served at `/network`
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkStats {
+    pub block_height: Option<u64>,
+    /// Estimated network hashrate, in hashes per second
+    pub hashrate: Option<f64>,
+    pub difficulty: Option<f64>,
+    pub difficulty_adjustment: Option<DifficultyAdjustment>,
+    pub fees: Option<FeeEstimates>,
+    pub updated_at: DateTime<Utc>,
+    /// What the last fetch failed to get; earlier values are kept in its place
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// The `network` section and the latest statistics fetched for it
+pub struct NetworkFeed {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    /// Seconds between fetches; None while the feed is off
+    interval: Option<u64>,
+    /// Base URL of the mempool.space REST API
+    api_url: String,
+    latest: Option<NetworkStats>,
+}
+
+impl NetworkFeed {
+    pub fn new(config: &Config) -> Self {
+        let feed = Self {
+            inner: Mutex::new(Inner {
+                interval: None,
+                api_url: String::new(),
+                latest: None,
+            }),
+        };
+        feed.reconfigure(config);
+        feed
+    }
+
+    /// Apply `config.network`. Offline and sandbox oracles query no upstream,
+    /// so the feed is off for them; turning it off forgets the latest values.
+    pub fn reconfigure(&self, config: &Config) {
+        let mut inner = self.inner.lock().unwrap();
+        let network = config
+            .network
+            .as_ref()
+            .filter(|_| config.offline.is_none() && config.sandbox.is_none());
+        let Some(network) = network else {
+            inner.interval = None;
+            inner.latest = None;
+            return;
+        };
+
+        let api_url = network.api_url.trim_end_matches('/').to_string();
+        if api_url != inner.api_url {
+            // Values from another instance may belong to another network
+            inner.latest = None;
+        }
+        inner.interval = Some(network.interval);
+        inner.api_url = api_url;
+    }
+
+    /// Seconds between fetches, or None while the feed is off
+    pub fn interval(&self) -> Option<u64> {
+        self.inner.lock().unwrap().interval
+    }
+
+    pub fn latest(&self) -> Option<NetworkStats> {
+        self.inner.lock().unwrap().latest.clone()
+    }
+
+    /// Fetch every statistic now and keep the result. Statistics that fail to
+    /// fetch keep their previous value, and the failure is listed in `errors`.
+    pub async fn refresh(&self, client: &reqwest::Client) {
+        let api_url = self.inner.lock().unwrap().api_url.clone();
+        let (height, mining, adjustment, fees) = tokio::join!(
+            fetch_block_height(client, &api_url),
+            fetch_mining(client, &api_url),
+            fetch_difficulty_adjustment(client, &api_url),
+            fetch_fees(client, &api_url),
+        );
+
+        let mut errors = Vec::new();
+        let mut failed = |what: &str, e: OracleError| {
+            warn!("Bitcoin {} unavailable: {}", what, e);
+            errors.push(format!("{}: {}", what, e));
+        };
+        let block_height = height.map_err(|e| failed("block_height", e)).ok();
+        let (hashrate, difficulty) = match mining {
+            Ok((hashrate, difficulty)) => (Some(hashrate), Some(difficulty)),
+            Err(e) => {
+                failed("hashrate", e);
+                (None, None)
+            }
+        };
+        let difficulty_adjustment = adjustment
+            .map_err(|e| failed("difficulty_adjustment", e))
+            .ok();
+        let fees = fees.map_err(|e| failed("fees", e)).ok();
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.interval.is_none() || inner.api_url != api_url {
+            // Turned off or pointed elsewhere while fetching
+            return;
+        }
+        let previous = inner.latest.take();
+        let previous = previous.as_ref();
+        let stats = NetworkStats {
+            block_height: block_height.or_else(|| previous?.block_height),
+            hashrate: hashrate.or_else(|| previous?.hashrate),
+            difficulty: difficulty.or_else(|| previous?.difficulty),
+            difficulty_adjustment: difficulty_adjustment
+                .or_else(|| previous?.difficulty_adjustment.clone()),
+            fees: fees.or_else(|| previous?.fees.clone()),
+            updated_at: Utc::now(),
+            errors,
+        };
+        if let (Some(height), Some(fees)) = (stats.block_height, &stats.fees) {
+            info!(
+                "Bitcoin block {}: next-block fee {} sat/vB",
+                height, fees.fastest
+            );
+        }
+        inner.latest = Some(stats);
+    }
+}
+
+async fn get(client: &reqwest::Client, url: &str, what: &str) -> Result<FetchResponse> {
+    let response = FetchResponse::read(client.get(url).send().await?).await?;
+    if !response.status().is_success() {
+        return Err(status_error(
+            &format!("Bitcoin {} API error", what),
+            &response,
+        ));
+    }
+    Ok(response)
+}
+
+async fn fetch_block_height(client: &reqwest::Client, api_url: &str) -> Result<u64> {
+    let url = format!("{}/blocks/tip/height", api_url);
+    let response = get(client, &url, "block height").await?;
+    let body: serde_json::Value = response.json()?;
+    body.as_u64()
+        .ok_or_else(|| OracleError::ApiError(format!("Invalid block height: {}", body)))
+}
+
+/// Current hashrate and difficulty
+async fn fetch_mining(client: &reqwest::Client, api_url: &str) -> Result<(f64, f64)> {
+    let url = format!("{}/v1/mining/hashrate/3d", api_url);
+    let response = get(client, &url, "hashrate").await?;
+    let body: serde_json::Value = response.json()?;
+
+    let invalid = || OracleError::ApiError("Invalid hashrate response".to_string());
+    let hashrate = body["currentHashrate"].as_f64().ok_or_else(invalid)?;
+    let difficulty = body["currentDifficulty"].as_f64().ok_or_else(invalid)?;
+    Ok((hashrate, difficulty))
+}
+
+async fn fetch_difficulty_adjustment(
+    client: &reqwest::Client,
+    api_url: &str,
+) -> Result<DifficultyAdjustment> {
+    let url = format!("{}/v1/difficulty-adjustment", api_url);
+    let response = get(client, &url, "difficulty adjustment").await?;
+    let body: serde_json::Value = response.json()?;
+
+    let invalid = || OracleError::ApiError(format!("Invalid difficulty adjustment: {}", body));
+    let estimated_retarget = body["estimatedRetargetDate"]
+        .as_i64()
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .ok_or_else(invalid)?;
+    Ok(DifficultyAdjustment {
+        progress_percent: body["progressPercent"].as_f64().ok_or_else(invalid)?,
+        estimated_change_percent: body["difficultyChange"].as_f64().ok_or_else(invalid)?,
+        remaining_blocks: body["remainingBlocks"].as_u64().ok_or_else(invalid)?,
+        estimated_retarget,
+    })
+}
+
+async fn fetch_fees(client: &reqwest::Client, api_url: &str) -> Result<FeeEstimates> {
+    let url = format!("{}/v1/fees/recommended", api_url);
+    let response = get(client, &url, "fee estimates").await?;
+    response
+        .json()
+        .map_err(|e| OracleError::ApiError(format!("Invalid fee estimates: {}", e)))
+}
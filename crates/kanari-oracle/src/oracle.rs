@@ -1,269 +1,1418 @@
+use arc_swap::ArcSwap;
 use chrono::{DateTime, Utc};
+use futures::future::join_all;
 use log::{error, info, warn};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 
-use crate::config::Config;
+use crate::anomaly::{Anomaly, AnomalyDetector};
+use crate::audit::AuditLog;
+use crate::basket;
+use crate::builder::OracleBuilder;
+use crate::config::{Basket, Config, StoreBackend, symbol_sources};
+use crate::depth;
 use crate::errors::{OracleError, Result};
-use crate::fetchers::{CryptoFetcher, PriceFetcher, StockFetcher};
-use crate::models::{PriceData, PriceFeed};
+use crate::fetchers::PriceFetcher;
+use crate::fx::{self, Conversion, CurrencyConverter, FxTable};
+use crate::history::{HistoryPoint, PriceHistory};
+use crate::index::{self, MarketCapIndex};
+use crate::locale::AmountFormat;
+use crate::market;
+use crate::models::{AssetType, PriceData, PriceFeed};
+use crate::network::{self, NetworkFeed, NetworkStats};
+use crate::price_math;
+use crate::provenance::{FetchTrigger, Provenance, SourceAttempt};
+use crate::round::{Round, SymbolOutcome, UpdateReport};
+use crate::scheduler::{self, UpdateBatch};
+use crate::sentiment::{self, Sentiment, SentimentFeed};
+use crate::singleflight::SingleFlight;
+use crate::sinks;
+use crate::slo::{self, SloMonitor, SloNotice, SloReport};
+use crate::sources::mock::FixturePrice;
+use crate::sources::{
+    BatchFetch, Fixture, PriceSource, SimulatedMarket, SourceRegistry, SourceStatus,
+};
+use crate::store::{PriceStore, RedisStore, StoreWriter};
+use crate::subscriber::{PriceSubscriber, PriceUpdate};
+use crate::symbols;
+use crate::table::{self, TableOptions};
 
+/// Immutable view of the price feeds as of the latest round
+pub type Snapshot = Arc<Round>;
+
+/// Price feeds shared with background refreshes, and the subscribers told about
+/// every write. Writers publish a new round, so readers never wait on them.
+struct FeedStore {
+    feeds: ArcSwap<Round>,
+    /// How each published price was obtained, keyed "asset_type:symbol"
+    provenance: Mutex<HashMap<String, Provenance>>,
+    history: PriceHistory,
+    /// Holds back spikes before they reach the feed
+    anomalies: AnomalyDetector,
+    /// Freshness compliance of every configured symbol
+    slo: SloMonitor,
+    /// Fear & Greed index and funding rates, fetched on their own schedule
+    sentiment: SentimentFeed,
+    /// Bitcoin network statistics, fetched on their own schedule
+    network: NetworkFeed,
+    /// Exchange rates against the US dollar, fetched on their own schedule
+    fx: CurrencyConverter,
+    subscribers: Vec<Arc<dyn PriceSubscriber>>,
+    /// Sent every publish while anything holds a receiver from `Oracle::subscribe`
+    updates: broadcast::Sender<PriceUpdate>,
+    /// Outcome of the most recent full update round
+    last_round: Mutex<Option<UpdateReport>>,
+}
+
+/// Updates a receiver from [`Oracle::subscribe`] may fall behind by before it
+/// misses the oldest
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+type Feeds = Arc<FeedStore>;
+
+/// A fetched price with the source attempts behind it, not yet published
+type Fetched = (PriceData, Vec<SourceAttempt>);
+
+/// Seconds between checks for an `slo` section while monitoring is off
+const SLO_IDLE_INTERVAL: u64 = 60;
+
+/// Seconds between checks for a `sentiment` section while the feed is off
+const SENTIMENT_IDLE_INTERVAL: u64 = 60;
+
+/// Seconds between checks for a `network` section while the feed is off
+const NETWORK_IDLE_INTERVAL: u64 = 60;
+
+/// Seconds between checks for an `fx` section while the feed is off
+const FX_IDLE_INTERVAL: u64 = 60;
+
+/// Limit on posting SLO notices to `slo.notify_url`
+const SLO_NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of querying a single upstream source for one symbol
+#[derive(Debug)]
+pub struct SourceQuote {
+    pub source: String,
+    pub latency: Duration,
+    pub result: Result<PriceData>,
+}
+
+async fn timed_quote<F>(source: &str, request: F) -> SourceQuote
+where
+    F: Future<Output = Result<PriceData>>,
+{
+    let started = Instant::now();
+    let result = request.await;
+    SourceQuote {
+        source: source.to_string(),
+        latency: started.elapsed(),
+        result,
+    }
+}
+
+/// In-memory state of an oracle, as returned by [`Oracle::debug_dump`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DebugDump {
+    pub config: serde_json::Value,
+    /// Round the feeds are from
+    pub round_id: u64,
+    pub feeds: HashMap<String, PriceFeed>,
+    pub provenance: Vec<Provenance>,
+    /// Health and circuit breaker state of every source
+    pub sources: Vec<SourceStatus>,
+    pub source_caches: HashMap<String, serde_json::Value>,
+    /// "asset_type:symbol" keys with a background refresh in flight
+    pub refreshing: Vec<String>,
+    /// Batches of the next update round at `general.update_interval`
+    pub schedule: Vec<UpdateBatch>,
+    /// Recently held or accepted anomalous prices
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Price oracle. Clones share the same feeds and sources, so a clone can run an
+/// update without holding a lock on the original.
 #[derive(Clone)]
 pub struct Oracle {
     config: Config,
-    crypto_fetcher: CryptoFetcher,
-    stock_fetcher: StockFetcher,
-    price_feeds: HashMap<String, PriceFeed>,
-    last_update: DateTime<Utc>,
+    /// HTTP client shared by every source, kept across config reloads
+    fetcher: Arc<PriceFetcher>,
+    registry: Arc<SourceRegistry>,
+    /// Sources added through `OracleBuilder::source`, kept across config reloads
+    extra_sources: Arc<Vec<Arc<dyn PriceSource>>>,
+    price_feeds: Feeds,
+    /// "asset_type:symbol" keys with a background refresh in flight
+    refreshing: Arc<Mutex<HashSet<String>>>,
+    /// On-demand fetches in flight, shared by concurrent lookups of the same symbol
+    fetching: Arc<SingleFlight<Arc<Result<PriceData>>>>,
+    last_update: Arc<RwLock<DateTime<Utc>>>,
+    /// Shared store other processes publish into, read by `sync_from_store`
+    store: Option<Arc<dyn PriceStore>>,
+    /// Fetch attempt log, kept across config reloads
+    audit: Option<AuditLog>,
+    /// Simulated prices of sandbox mode, kept across config reloads
+    market: Option<Arc<SimulatedMarket>>,
 }
 
 impl Oracle {
     pub async fn new(config: Config) -> Result<Self> {
-        config.validate()?;
+        Self::from_parts(config, Vec::new(), Vec::new(), None).await
+    }
 
-        let price_fetcher = PriceFetcher::new(config.clone())?;
-        let crypto_fetcher = CryptoFetcher::new(price_fetcher);
+    /// Start building an oracle for use as a library
+    pub fn builder() -> OracleBuilder {
+        OracleBuilder::new()
+    }
 
-        let price_fetcher2 = PriceFetcher::new(config.clone())?;
-        let stock_fetcher = StockFetcher::new(price_fetcher2);
+    /// Build an oracle; without an explicit `store`, one is connected as
+    /// configured in `config.store`. Every published price is written to the store
+    /// and to the sinks in `config.sinks`.
+    pub(crate) async fn from_parts(
+        config: Config,
+        extra_sources: Vec<Arc<dyn PriceSource>>,
+        mut subscribers: Vec<Arc<dyn PriceSubscriber>>,
+        store: Option<Arc<dyn PriceStore>>,
+    ) -> Result<Self> {
+        config.validate()?;
 
-        let mut oracle = Self {
-            config,
-            crypto_fetcher,
-            stock_fetcher,
-            price_feeds: HashMap::new(),
-            last_update: Utc::now(),
+        let store = match (store, config.store.backend, &config.store.redis_url) {
+            (Some(store), _, _) => Some(store),
+            (None, StoreBackend::Redis, Some(url)) => {
+                let store = RedisStore::connect(url, &config.store.key_prefix).await?;
+                info!("Sharing prices through Redis");
+                Some(Arc::new(store) as Arc<dyn PriceStore>)
+            }
+            _ => None,
         };
+        if let Some(store) = &store {
+            subscribers.push(Arc::new(StoreWriter::spawn(store.clone())));
+        }
+        subscribers.extend(sinks::from_config(&config)?);
+
+        let fetcher = Arc::new(PriceFetcher::new(config.clone())?);
+        let audit = config.audit.as_ref().map(AuditLog::open);
+        if let Some(audit_config) = &config.audit {
+            info!("Auditing fetch attempts to {}", audit_config.path);
+        }
+        let market = config
+            .sandbox
+            .as_ref()
+            .map(|_| Arc::new(SimulatedMarket::new(&config)));
+        let registry = build_registry(
+            fetcher.clone(),
+            &extra_sources,
+            audit.clone(),
+            market.as_ref(),
+        )?;
 
         // Initialize price feeds
-        oracle
-            .price_feeds
-            .insert("crypto".to_string(), PriceFeed::new());
-        oracle
-            .price_feeds
-            .insert("stock".to_string(), PriceFeed::new());
+        let mut price_feeds = HashMap::new();
+        price_feeds.insert("crypto".to_string(), PriceFeed::new());
+        price_feeds.insert("stock".to_string(), PriceFeed::new());
+        price_feeds.insert("basket".to_string(), PriceFeed::new());
+        price_feeds.insert("tvl".to_string(), PriceFeed::new());
+
+        let history = PriceHistory::new(config.history.clone());
+        let anomalies = AnomalyDetector::new(config.anomaly.clone());
+        let slo = SloMonitor::new(&config);
+        let sentiment = SentimentFeed::new(&config);
+        let network = NetworkFeed::new(&config);
+        let fx = CurrencyConverter::new(&config);
+
+        let oracle = Self {
+            config,
+            fetcher,
+            registry: Arc::new(registry),
+            extra_sources: Arc::new(extra_sources),
+            price_feeds: Arc::new(FeedStore {
+                feeds: ArcSwap::from_pointee(Round::initial(price_feeds)),
+                provenance: Mutex::new(HashMap::new()),
+                history,
+                anomalies,
+                slo,
+                sentiment,
+                network,
+                fx,
+                subscribers,
+                updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+                last_round: Mutex::new(None),
+            }),
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+            fetching: Arc::new(SingleFlight::default()),
+            last_update: Arc::new(RwLock::new(Utc::now())),
+            store,
+            audit,
+            market,
+        };
 
         info!("Oracle initialized successfully");
         Ok(oracle)
     }
 
-    /// Update all price feeds (crypto and stocks)
-    pub async fn update_all_prices(&mut self) -> Result<usize> {
-        let mut total_updated = 0;
+    /// Apply a new configuration without discarding the warm cache
+    ///
+    /// Cached prices for symbols that are no longer configured are dropped.
+    pub fn reload_config(&mut self, config: Config) -> Result<()> {
+        config.validate()?;
+
+        // Both are built before either is replaced, so a fixture that fails to
+        // load leaves the oracle as it was
+        let fetcher = Arc::new(self.fetcher.reconfigure(config.clone())?);
+        let market = match (&self.market, &config.sandbox) {
+            (Some(market), Some(_)) => Some(market.clone()),
+            (None, Some(_)) => Some(Arc::new(SimulatedMarket::new(&config))),
+            (_, None) => None,
+        };
+        let registry = build_registry(
+            fetcher.clone(),
+            &self.extra_sources,
+            self.audit.clone(),
+            market.as_ref(),
+        )?;
+        if let Some(market) = &market {
+            market.reconfigure(&config);
+        }
+        self.fetcher = fetcher;
+        self.registry = Arc::new(registry);
+        self.market = market;
+
+        let crypto_symbols: HashSet<String> = config
+            .canonical_symbols(AssetType::Crypto)
+            .iter()
+            .map(|s| symbols::feed_key(AssetType::Crypto, s))
+            .collect();
+        let stock_symbols: HashSet<String> = config
+            .canonical_symbols(AssetType::Stock)
+            .iter()
+            .map(|s| symbols::feed_key(AssetType::Stock, s))
+            .collect();
+        let basket_symbols: HashSet<String> = config
+            .canonical_symbols(AssetType::Basket)
+            .iter()
+            .map(|s| symbols::feed_key(AssetType::Basket, s))
+            .collect();
+        let tvl_symbols: HashSet<String> = config
+            .canonical_symbols(AssetType::Tvl)
+            .iter()
+            .map(|s| symbols::feed_key(AssetType::Tvl, s))
+            .collect();
 
-        // Update crypto prices
-        match self.update_crypto_prices().await {
-            Ok(count) => {
-                total_updated += count;
-                info!("Updated {} crypto prices", count);
+        update_feeds(&self.price_feeds, |feeds| {
+            if let Some(feed) = feeds.get_mut("crypto") {
+                feed.prices.retain(|key, _| crypto_symbols.contains(key));
             }
-            Err(e) => {
-                error!("Failed to update crypto prices: {}", e);
+            if let Some(feed) = feeds.get_mut("stock") {
+                feed.prices.retain(|key, _| stock_symbols.contains(key));
+            }
+            if let Some(feed) = feeds.get_mut("basket") {
+                feed.prices.retain(|key, _| basket_symbols.contains(key));
+            }
+            if let Some(feed) = feeds.get_mut("tvl") {
+                feed.prices.retain(|key, _| tvl_symbols.contains(key));
+            }
+        });
+        self.price_feeds
+            .provenance
+            .lock()
+            .unwrap()
+            .retain(|key, _| match key.split_once(':') {
+                Some(("crypto", symbol)) => crypto_symbols.contains(symbol),
+                Some(("stock", symbol)) => stock_symbols.contains(symbol),
+                Some(("basket", symbol)) => basket_symbols.contains(symbol),
+                Some(("tvl", symbol)) => tvl_symbols.contains(symbol),
+                _ => false,
+            });
+        self.price_feeds.history.reconfigure(config.history.clone());
+        self.price_feeds
+            .anomalies
+            .reconfigure(config.anomaly.clone());
+        self.price_feeds.slo.reconfigure(&config);
+        self.price_feeds.sentiment.reconfigure(&config);
+        self.price_feeds.network.reconfigure(&config);
+        self.price_feeds.fx.reconfigure(&config);
+        self.price_feeds
+            .history
+            .retain(|asset_type, symbol| match asset_type {
+                AssetType::Crypto => crypto_symbols.contains(symbol),
+                AssetType::Stock => stock_symbols.contains(symbol),
+                AssetType::Basket => basket_symbols.contains(symbol),
+                AssetType::Tvl => tvl_symbols.contains(symbol),
+            });
+
+        self.config = config;
+        info!("Oracle configuration reloaded");
+        Ok(())
+    }
+
+    fn feeds(&self) -> Snapshot {
+        self.price_feeds.feeds.load_full()
+    }
+
+    /// Current price feeds as an immutable snapshot of the latest round; cheap to
+    /// take and unaffected by later updates, so it can be serialized without
+    /// holding any lock
+    pub fn snapshot(&self) -> Snapshot {
+        self.feeds()
+    }
+
+    /// Receive every batch of prices written into the feed from now on. Unlike a
+    /// [`PriceSubscriber`], this can be attached to a running oracle; a receiver
+    /// that falls behind gets `RecvError::Lagged` and skips the oldest updates.
+    pub fn subscribe(&self) -> broadcast::Receiver<PriceUpdate> {
+        self.price_feeds.updates.subscribe()
+    }
+
+    /// Get the active configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get the sources prices are fetched from
+    pub fn registry(&self) -> &SourceRegistry {
+        &self.registry
+    }
+
+    /// Update all price feeds (crypto, stocks and TVL). The asset types are
+    /// fetched concurrently so a slow stock source does not delay crypto prices,
+    /// then published together, with the baskets they reprice, as one round.
+    /// The report is logged and kept as the last round (see [`Oracle::last_round`]).
+    pub async fn update_all_prices(&self) -> Result<UpdateReport> {
+        let mut report = UpdateReport::start();
+        let (crypto, stock, tvl) = tokio::join!(
+            self.fetch_for_feed(AssetType::Crypto, &self.config.crypto.symbols),
+            self.fetch_for_feed(AssetType::Stock, &self.config.stocks.symbols),
+            self.fetch_for_feed(AssetType::Tvl, &self.config.tvl.symbols)
+        );
+
+        let mut fetched = Vec::new();
+        for (asset_type, result) in [
+            (AssetType::Crypto, crypto),
+            (AssetType::Stock, stock),
+            (AssetType::Tvl, tvl),
+        ] {
+            match result {
+                Ok((part, prices)) => {
+                    report.merge(part);
+                    fetched.push((asset_type, prices));
+                }
+                Err(e) => {
+                    error!("Failed to update {} prices: {}", asset_type.as_str(), e);
+                }
             }
         }
+        report.merge(self.publish_round(fetched));
 
-        // Update stock prices
-        match self.update_stock_prices().await {
-            Ok(count) => {
-                total_updated += count;
-                info!("Updated {} stock prices", count);
+        let report = report.finish();
+        report.log();
+        self.record_round(report.clone());
+        self.touch();
+        Ok(report)
+    }
+
+    /// Update cryptocurrency prices
+    pub async fn update_crypto_prices(&self) -> Result<UpdateReport> {
+        let symbols = &self.config.crypto.symbols;
+        let report = self.update_feed(AssetType::Crypto, symbols).await?;
+
+        if report.updated == 0 && !symbols.is_empty() {
+            return Err(OracleError::ApiError(
+                "All crypto price sources failed".to_string(),
+            ));
+        }
+
+        Ok(report)
+    }
+
+    /// Update stock prices
+    pub async fn update_stock_prices(&self) -> Result<UpdateReport> {
+        self.update_feed(AssetType::Stock, &self.config.stocks.symbols)
+            .await
+    }
+
+    /// Update the total value locked of the configured protocols and chains
+    pub async fn update_tvl(&self) -> Result<UpdateReport> {
+        self.update_feed(AssetType::Tvl, &self.config.tvl.symbols)
+            .await
+    }
+
+    /// Update a subset of the configured symbols, as scheduled by `scheduler::plan_round`
+    pub async fn update_symbols(
+        &self,
+        asset_type: AssetType,
+        symbols: &[String],
+    ) -> Result<UpdateReport> {
+        let report = self.update_feed(asset_type, symbols).await?;
+        self.touch();
+        Ok(report)
+    }
+
+    /// Fetch `symbols` and publish them, with the baskets they reprice, as one round
+    async fn update_feed(&self, asset_type: AssetType, symbols: &[String]) -> Result<UpdateReport> {
+        let (mut report, prices) = self.fetch_for_feed(asset_type, symbols).await?;
+        report.merge(self.publish_round(vec![(asset_type, prices)]));
+        Ok(report.finish())
+    }
+
+    /// Publish the prices of one update cycle as a single round, together with
+    /// every basket with a component among them, and report what became of
+    /// each. Prices held back as anomalies are left out of the round.
+    fn publish_round(&self, fetched: Vec<(AssetType, Vec<Fetched>)>) -> UpdateReport {
+        let mut report = UpdateReport::start();
+        let mut batches = Vec::with_capacity(fetched.len() + 1);
+        for (asset_type, prices) in fetched {
+            let (accepted, held) = screen(&self.price_feeds, asset_type, prices);
+            for (price_data, _) in &accepted {
+                report.push(SymbolOutcome::updated(asset_type, price_data));
             }
-            Err(e) => {
-                error!("Failed to update stock prices: {}", e);
+            for anomaly in &held {
+                report.push(SymbolOutcome::held(anomaly));
             }
+            batches.push((asset_type, accepted));
         }
 
-        self.last_update = Utc::now();
-        Ok(total_updated)
+        let baskets = self.price_baskets(&batches, &mut report);
+        batches.push((AssetType::Basket, baskets));
+        report.round_id = commit(&self.price_feeds, FetchTrigger::Update, batches);
+        report.finish()
     }
 
-    /// Update cryptocurrency prices
-    pub async fn update_crypto_prices(&mut self) -> Result<usize> {
-        let prices = self.crypto_fetcher.fetch_all_crypto_prices().await?;
-        let count = prices.len();
+    /// Reprice every basket with a component in `batches`, from the feeds as
+    /// they will be once `batches` is published. A component missing from
+    /// `batches` counts with its last price until that goes missing.
+    fn price_baskets(
+        &self,
+        batches: &[(AssetType, Vec<Fetched>)],
+        report: &mut UpdateReport,
+    ) -> Vec<Fetched> {
+        let updated: HashSet<String> = batches
+            .iter()
+            .flat_map(|(asset_type, prices)| {
+                prices
+                    .iter()
+                    .map(|(price_data, _)| provenance_key(*asset_type, &price_data.symbol))
+            })
+            .collect();
+        if updated.is_empty() || self.config.baskets.is_empty() {
+            return Vec::new();
+        }
+        let mut staged = self.feeds().feeds.clone();
+        apply(&mut staged, batches);
 
-        let crypto_feed = self
-            .price_feeds
-            .get_mut("crypto")
-            .ok_or_else(|| OracleError::ConfigError("Crypto feed not initialized".to_string()))?;
+        let mut prices = Vec::new();
+        for basket in &self.config.baskets {
+            let affected = basket.components.iter().any(|component| {
+                updated.contains(&provenance_key(
+                    component.asset_type,
+                    &self
+                        .config
+                        .canonical_symbol(component.asset_type, &component.symbol),
+                ))
+            });
+            if !affected {
+                continue;
+            }
+            match self.basket_price(basket, &staged) {
+                Ok(price_data) => prices.push((price_data, Vec::new())),
+                Err(reason) => report.push(SymbolOutcome::unpriced(
+                    AssetType::Basket,
+                    &symbols::normalize_basket(&basket.name),
+                    reason,
+                )),
+            }
+        }
 
-        for price_data in prices {
-            crypto_feed.update_price(price_data);
+        let (accepted, held) = screen(&self.price_feeds, AssetType::Basket, prices);
+        for (price_data, _) in &accepted {
+            report.push(SymbolOutcome::updated(AssetType::Basket, price_data));
         }
+        for anomaly in &held {
+            report.push(SymbolOutcome::held(anomaly));
+        }
+        accepted
+    }
 
-        Ok(count)
+    /// Price of a basket from the component prices in `feeds`; the error names
+    /// the first component without a price
+    fn basket_price(
+        &self,
+        basket: &Basket,
+        feeds: &HashMap<String, PriceFeed>,
+    ) -> std::result::Result<PriceData, String> {
+        let mut components = Vec::with_capacity(basket.components.len());
+        for component in &basket.components {
+            let symbol = self
+                .config
+                .canonical_symbol(component.asset_type, &component.symbol);
+            let price_data = feeds
+                .get(component.asset_type.as_str())
+                .and_then(|feed| feed.get_price(&symbols::feed_key(component.asset_type, &symbol)))
+                .ok_or_else(|| {
+                    format!(
+                        "no {} price for component {}",
+                        component.asset_type.as_str(),
+                        symbol
+                    )
+                })?;
+            components.push(price_data.clone());
+        }
+        basket::price(basket, &components)
+            .ok_or_else(|| "component prices out of range".to_string())
     }
 
-    /// Update stock prices
-    pub async fn update_stock_prices(&mut self) -> Result<usize> {
-        let prices = self.stock_fetcher.fetch_all_stock_prices().await?;
-        let count = prices.len();
+    /// Id of the latest round, 0 before the first. Every write to the price
+    /// feeds publishes a round with a strictly greater id, also across restarts
+    /// unless the clock goes back, so verifiers can tell a newer attestation
+    /// from a replayed one.
+    pub fn round_id(&self) -> u64 {
+        self.feeds().id
+    }
 
-        let stock_feed = self
-            .price_feeds
-            .get_mut("stock")
-            .ok_or_else(|| OracleError::ConfigError("Stock feed not initialized".to_string()))?;
+    /// Outcome of the most recent full update round, if one has run
+    pub fn last_round(&self) -> Option<UpdateReport> {
+        self.price_feeds.last_round.lock().unwrap().clone()
+    }
+
+    /// Keep `report` as the last round, for rounds assembled from several
+    /// `update_symbols` calls
+    pub fn record_round(&self, report: UpdateReport) {
+        *self.price_feeds.last_round.lock().unwrap() = Some(report);
+    }
+
+    /// Fetch the prices of `symbols` without publishing them. The report lists
+    /// the symbols skipped or not priced by any source.
+    async fn fetch_for_feed(
+        &self,
+        asset_type: AssetType,
+        symbols: &[String],
+    ) -> Result<(UpdateReport, Vec<Fetched>)> {
+        let mut report = UpdateReport::start();
+        let preferences = match asset_type {
+            AssetType::Crypto => &self.config.crypto.source_preferences,
+            AssetType::Stock => &self.config.stocks.source_preferences,
+            AssetType::Tvl => &self.config.tvl.source_preferences,
+            // Baskets are priced from their components by `price_baskets`
+            AssetType::Basket => return Ok((report.finish(), Vec::new())),
+        };
+        // Equivalent spellings ("btc", "bitcoin") are fetched once, under one entry
+        let mut seen = HashSet::new();
+        let canonical: Vec<String> = symbols
+            .iter()
+            .map(|symbol| self.config.canonical_symbol(asset_type, symbol))
+            .filter(|symbol| seen.insert(symbol.clone()))
+            .collect();
+        let symbols = self.unsettled(asset_type, &canonical);
+        for symbol in canonical.iter().filter(|symbol| !symbols.contains(symbol)) {
+            report.push(SymbolOutcome::skipped(asset_type, symbol));
+        }
+        if symbols.is_empty() {
+            return Ok((report.finish(), Vec::new()));
+        }
+        let BatchFetch {
+            mut prices,
+            missing,
+        } = self
+            .registry
+            .fetch_all(asset_type, &symbols, preferences)
+            .await;
+        attach_depth(
+            &self.fetcher,
+            &self.depth_exchanges(asset_type),
+            prices
+                .iter_mut()
+                .map(|(price_data, _)| price_data)
+                .collect(),
+        )
+        .await;
 
-        for price_data in prices {
-            stock_feed.update_price(price_data);
+        if self.feeds().feed(asset_type).is_none() {
+            return Err(OracleError::ConfigError(format!(
+                "{} feed not initialized",
+                asset_type.as_str()
+            )));
         }
 
-        Ok(count)
+        for (symbol, attempts) in &missing {
+            report.push(SymbolOutcome::failed(asset_type, symbol, attempts));
+        }
+        Ok((report.finish(), prices))
+    }
+
+    /// Copy prices published by other processes from the shared store into the
+    /// feeds, keeping whichever copy of each price is newer. Returns the number of
+    /// prices taken from the store; subscribers are not notified.
+    pub async fn sync_from_store(&self) -> Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+
+        let mut total = 0;
+        for asset_type in [
+            AssetType::Crypto,
+            AssetType::Stock,
+            AssetType::Basket,
+            AssetType::Tvl,
+        ] {
+            let stored = store.load(asset_type).await?;
+            total += self.merge_newer(asset_type, stored);
+        }
+        Ok(total)
+    }
+
+    /// Copy prices a primary instance published, as received from its stream,
+    /// into the feeds of a standby. Like [`Oracle::sync_from_store`], older copies
+    /// are ignored and subscribers are not notified.
+    pub fn replicate(&self, update: PriceUpdate) -> usize {
+        self.merge_newer(update.asset_type, update.prices)
+    }
+
+    /// Write the prices that are newer than the ones in the feed; returns how many
+    fn merge_newer(&self, asset_type: AssetType, prices: Vec<PriceData>) -> usize {
+        let current = self.feeds();
+        let newer: Vec<PriceData> = prices
+            .into_iter()
+            .filter(|price_data| {
+                current
+                    .feed(asset_type)
+                    .and_then(|feed| {
+                        feed.get_price(&symbols::feed_key(asset_type, &price_data.symbol))
+                    })
+                    .is_none_or(|cached| cached.timestamp < price_data.timestamp)
+            })
+            .collect();
+        if newer.is_empty() {
+            return 0;
+        }
+
+        let round_id = update_feeds(&self.price_feeds, |map| {
+            if let Some(feed) = map.get_mut(asset_type.as_str()) {
+                for price_data in &newer {
+                    let key = symbols::feed_key(asset_type, &price_data.symbol);
+                    let is_newer = feed
+                        .get_price(&key)
+                        .is_none_or(|cached| cached.timestamp < price_data.timestamp);
+                    if is_newer {
+                        feed.update_price(price_data.clone());
+                    }
+                }
+            }
+        });
+        self.price_feeds
+            .history
+            .record(round_id, asset_type, &newer);
+        self.touch();
+        newer.len()
+    }
+
+    /// Symbols whose cached price may still change. With `stocks.market_hours`,
+    /// stocks fetched since the US market last closed are left out until it reopens.
+    fn unsettled(&self, asset_type: AssetType, symbols: &[String]) -> Vec<String> {
+        if asset_type != AssetType::Stock || !self.config.stocks.market_hours {
+            return symbols.to_vec();
+        }
+
+        let now = Utc::now();
+        let feeds = self.feeds();
+        let feed = feeds.feed(asset_type);
+        let unsettled: Vec<String> = symbols
+            .iter()
+            .filter(|symbol| {
+                !feed
+                    .and_then(|f| f.get_price(&symbols::feed_key(asset_type, symbol)))
+                    .is_some_and(|p| market::is_settled(p.timestamp, now))
+            })
+            .cloned()
+            .collect();
+
+        if unsettled.len() < symbols.len() {
+            info!(
+                "Market closed, skipping {} stock symbols",
+                symbols.len() - unsettled.len()
+            );
+        }
+        unsettled
+    }
+
+    /// Whether a cached price is the latest one there will be until the market reopens
+    fn is_settled(&self, asset_type: AssetType, price_data: &PriceData) -> bool {
+        asset_type == AssetType::Stock
+            && self.config.stocks.market_hours
+            && market::is_settled(price_data.timestamp, Utc::now())
+    }
+
+    /// Price as served: age and staleness, plus the market state for stocks.
+    /// Settled stock prices are not flagged as stale while the market is closed.
+    fn present(&self, asset_type: AssetType, price_data: PriceData) -> PriceData {
+        let settled = self.is_settled(asset_type, &price_data);
+        let mut price_data = price_data.with_staleness(self.max_age(asset_type));
+        if asset_type == AssetType::Stock {
+            price_data.market_state = Some(market::us_equity_state(Utc::now()));
+            price_data.is_stale &= !settled;
+        }
+        price_data
+    }
+
+    /// Seconds after which prices of an asset type are flagged as stale
+    fn max_age(&self, asset_type: AssetType) -> u64 {
+        self.config.max_age(asset_type)
     }
 
     /// Get cryptocurrency price by symbol
     pub async fn get_crypto_price(&self, symbol: &str) -> Result<PriceData> {
-        let crypto_feed = self
-            .price_feeds
-            .get("crypto")
-            .ok_or_else(|| OracleError::ConfigError("Crypto feed not initialized".to_string()))?;
+        self.get_price(AssetType::Crypto, symbol).await
+    }
 
-        // Try to get from cache first
-        if let Some(price_data) = crypto_feed.get_price(symbol) {
-            return Ok(price_data.clone());
+    /// Get stock price by symbol
+    pub async fn get_stock_price(&self, symbol: &str) -> Result<PriceData> {
+        self.get_price(AssetType::Stock, symbol).await
+    }
+
+    /// Total value locked of a protocol ("aave") or chain ("chain:ethereum"), in USD
+    pub async fn get_tvl(&self, symbol: &str) -> Result<PriceData> {
+        self.get_price(AssetType::Tvl, symbol).await
+    }
+
+    /// Price of the basket named `name`, from the current prices of its
+    /// components; components missing from the cache are fetched on demand
+    pub async fn get_basket_price(&self, name: &str) -> Result<PriceData> {
+        let basket = self
+            .config
+            .basket(name)
+            .ok_or_else(|| OracleError::PriceNotFound(name.to_string()))?;
+
+        let mut components = Vec::with_capacity(basket.components.len());
+        for component in &basket.components {
+            components.push(
+                self.get_price(component.asset_type, &component.symbol)
+                    .await?,
+            );
         }
+        let price_data = basket::price(basket, &components)
+            .ok_or_else(|| OracleError::PriceNotFound(name.to_string()))?;
+        Ok(self.present(AssetType::Basket, price_data))
+    }
 
-        // Try Binance fallback
-        match self
-            .crypto_fetcher
-            .fetch_binance_prices(&[symbol.to_string()])
-            .await
-        {
-            Ok(prices) if !prices.is_empty() => {
-                if let Some(price_data) = prices.first() {
-                    return Ok(price_data.clone());
-                }
+    /// Get a cryptocurrency price from one named source, bypassing the cache and
+    /// the fallback chain. The result is not written into the feed.
+    pub async fn get_crypto_price_from(&self, symbol: &str, source: &str) -> Result<PriceData> {
+        self.get_price_from(AssetType::Crypto, symbol, source).await
+    }
+
+    /// Get a stock price from one named source, bypassing the cache and the
+    /// fallback chain. The result is not written into the feed.
+    pub async fn get_stock_price_from(&self, symbol: &str, source: &str) -> Result<PriceData> {
+        self.get_price_from(AssetType::Stock, symbol, source).await
+    }
+
+    /// Get a TVL from one named source, bypassing the cache and the fallback
+    /// chain. The result is not written into the feed.
+    pub async fn get_tvl_from(&self, symbol: &str, source: &str) -> Result<PriceData> {
+        self.get_price_from(AssetType::Tvl, symbol, source).await
+    }
+
+    async fn get_price_from(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        source: &str,
+    ) -> Result<PriceData> {
+        let serves_asset = self
+            .registry
+            .get(source)
+            .is_some_and(|s| s.capabilities().asset_type == asset_type);
+        if !serves_asset {
+            return Err(OracleError::UnknownSource {
+                asset_type: asset_type.as_str().to_string(),
+                source_name: source.to_string(),
+            });
+        }
+
+        let symbol = self.config.canonical_symbol(asset_type, symbol);
+        let price_data = self.registry.fetch_from(source, &symbol).await?;
+        Ok(self.present(asset_type, price_data))
+    }
+
+    /// Serve a cached price. Entries older than `cache_ttl` are still served for
+    /// `stale_while_revalidate` seconds while a background refresh runs; missing or
+    /// older entries are fetched on demand and cached into the feed. When that fetch
+    /// fails, the cached price is served flagged as stale.
+    async fn get_price(&self, asset_type: AssetType, symbol: &str) -> Result<PriceData> {
+        let symbol = &self.config.canonical_symbol(asset_type, symbol);
+
+        // Try to get from cache first
+        let cached = self
+            .feeds()
+            .feed(asset_type)
+            .ok_or_else(|| {
+                OracleError::ConfigError(format!("{} feed not initialized", asset_type.as_str()))
+            })?
+            .get_price(&symbols::feed_key(asset_type, symbol))
+            .cloned();
+
+        let general = &self.config.general;
+        if let Some(ref price_data) = cached {
+            let age = (Utc::now() - price_data.timestamp).num_seconds().max(0) as u64;
+            if age <= general.cache_ttl || self.is_settled(asset_type, price_data) {
+                return Ok(self.present(asset_type, price_data.clone()));
             }
-            Ok(_) => {
-                warn!("Binance returned empty results for {}", symbol);
+            if age <= general.cache_ttl + general.stale_while_revalidate {
+                self.spawn_refresh(asset_type, symbol);
+                return Ok(self.present(asset_type, price_data.clone()));
             }
+        }
+
+        // Missing or too old: block on the symbol's preferred sources, or every usable
+        // source. Concurrent misses for the same symbol share one upstream fetch.
+        let key = format!(
+            "{}:{}",
+            asset_type.as_str(),
+            symbols::feed_key(asset_type, symbol)
+        );
+        let registry = self.registry.clone();
+        let feeds = self.price_feeds.clone();
+        let fetcher = self.fetcher.clone();
+        let exchanges = self.depth_exchanges(asset_type);
+        let sources = self.sources_for(asset_type, symbol);
+        let owned_symbol = symbol.to_string();
+        let outcome = self
+            .fetching
+            .run(&key, move || async move {
+                let (mut result, attempts) = registry
+                    .fetch_with_fallback(asset_type, &owned_symbol, &sources)
+                    .await;
+                if let Ok(price_data) = &mut result {
+                    attach_depth(&fetcher, &exchanges, vec![price_data]).await;
+                    let fetched = vec![(price_data.clone(), attempts)];
+                    let held = publish(&feeds, asset_type, FetchTrigger::OnDemand, fetched);
+                    if let Some(anomaly) = held.first() {
+                        return Arc::new(Err(anomaly.to_error()));
+                    }
+                }
+                Arc::new(result)
+            })
+            .await;
+
+        match &*outcome {
+            Ok(price_data) => Ok(self.present(asset_type, price_data.clone())),
+            // The feed kept the previous price, which is served instead
+            Err(e @ OracleError::AnomalousPrice { .. }) => match cached {
+                Some(price_data) => Ok(self.present(asset_type, price_data)),
+                None => Err(e.duplicate()),
+            },
             Err(e) => {
-                warn!("Binance fallback also failed: {}", e);
+                warn!(
+                    "On-demand {} fetch failed for {}: {}",
+                    asset_type.as_str(),
+                    symbol,
+                    e
+                );
+                match cached {
+                    Some(price_data) => Ok(self.present(asset_type, price_data)),
+                    None => Err(e.duplicate()),
+                }
             }
         }
+    }
 
-        Err(OracleError::PriceNotFound(symbol.to_string()))
+    /// Exchanges read for the order book depth of the asset type's prices. Sandbox
+    /// prices are simulated, so no book is read for them.
+    fn depth_exchanges(&self, asset_type: AssetType) -> Vec<String> {
+        if asset_type != AssetType::Crypto || self.market.is_some() {
+            return Vec::new();
+        }
+        self.config.crypto.order_book_depth.clone()
     }
 
-    /// Get stock price by symbol
-    pub async fn get_stock_price(&self, symbol: &str) -> Result<PriceData> {
-        let stock_feed = self
-            .price_feeds
-            .get("stock")
-            .ok_or_else(|| OracleError::ConfigError("Stock feed not initialized".to_string()))?;
+    /// The symbol's preferred sources, or every usable source for its asset type
+    fn sources_for(&self, asset_type: AssetType, symbol: &str) -> Vec<String> {
+        let preferences = match asset_type {
+            AssetType::Crypto => &self.config.crypto.source_preferences,
+            AssetType::Stock => &self.config.stocks.source_preferences,
+            AssetType::Tvl => &self.config.tvl.source_preferences,
+            AssetType::Basket => return Vec::new(),
+        };
+        match symbol_sources(preferences, symbol) {
+            Some(sources) => sources.to_vec(),
+            None => self.registry.names_for(asset_type),
+        }
+    }
 
-        // Try to get from cache first
-        if let Some(price_data) = stock_feed.get_price(symbol) {
-            return Ok(price_data.clone());
+    /// Refresh one cached price in the background, unless a refresh is already running
+    fn spawn_refresh(&self, asset_type: AssetType, symbol: &str) {
+        let key = format!(
+            "{}:{}",
+            asset_type.as_str(),
+            symbols::feed_key(asset_type, symbol)
+        );
+        if !self.refreshing.lock().unwrap().insert(key.clone()) {
+            return;
         }
 
-        // If not in cache, try to fetch directly
-        let price_data = if self.config.stocks.alpha_vantage_api_key.is_some() {
-            self.stock_fetcher.fetch_alpha_vantage_price(symbol).await?
-        } else if self.config.stocks.finnhub_api_key.is_some() {
-            self.stock_fetcher.fetch_finnhub_price(symbol).await?
-        } else {
-            self.stock_fetcher.fetch_free_stock_price(symbol).await?
+        let registry = self.registry.clone();
+        let feeds = self.price_feeds.clone();
+        let refreshing = self.refreshing.clone();
+        let sources = self.sources_for(asset_type, symbol);
+        let symbol = symbol.to_string();
+
+        tokio::spawn(async move {
+            match registry
+                .fetch_with_fallback(asset_type, &symbol, &sources)
+                .await
+            {
+                (Ok(price_data), attempts) => {
+                    publish(
+                        &feeds,
+                        asset_type,
+                        FetchTrigger::Refresh,
+                        vec![(price_data, attempts)],
+                    );
+                }
+                (Err(e), _) => warn!("Background refresh failed for {}: {}", symbol, e),
+            }
+            refreshing.lock().unwrap().remove(&key);
+        });
+    }
+
+    /// Fetch a symbol from every enabled source concurrently, bypassing the cache
+    pub async fn compare_sources(
+        &self,
+        symbol: &str,
+        asset_type: &str,
+    ) -> Result<Vec<SourceQuote>> {
+        let asset_type = match asset_type {
+            "crypto" => AssetType::Crypto,
+            "stock" => AssetType::Stock,
+            "tvl" => AssetType::Tvl,
+            _ => {
+                return Err(OracleError::ConfigError(format!(
+                    "Invalid asset type '{}'. Use 'crypto', 'stock' or 'tvl'",
+                    asset_type
+                )));
+            }
         };
+        let symbol = &self.config.canonical_symbol(asset_type, symbol);
 
-        Ok(price_data)
+        let requests = self
+            .registry
+            .for_asset(asset_type)
+            .into_iter()
+            .map(
+                |source| async move { timed_quote(source.name(), source.fetch_one(symbol)).await },
+            );
+
+        Ok(join_all(requests).await)
+    }
+
+    /// Prices of `asset_type` in `round` as served, keyed by feed key. Pass one
+    /// [`Oracle::snapshot`] to serve several lists from the same round.
+    pub fn round_prices(&self, round: &Round, asset_type: AssetType) -> HashMap<String, PriceData> {
+        round
+            .feed(asset_type)
+            .map(|feed| {
+                feed.get_prices_map()
+                    .iter()
+                    .map(|(key, p)| (key.clone(), self.present(asset_type, p.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     /// Get all current crypto prices
     pub fn get_all_crypto_prices(&self) -> Vec<PriceData> {
-        self.price_feeds
-            .get("crypto")
-            .map(|feed| feed.get_all_prices().into_iter().cloned().collect())
-            .unwrap_or_default()
+        self.round_prices(&self.feeds(), AssetType::Crypto)
+            .into_values()
+            .collect()
     }
 
     /// Get all current stock prices
     pub fn get_all_stock_prices(&self) -> Vec<PriceData> {
+        self.round_prices(&self.feeds(), AssetType::Stock)
+            .into_values()
+            .collect()
+    }
+
+    /// Get all current basket prices
+    pub fn get_all_basket_prices(&self) -> Vec<PriceData> {
+        self.round_prices(&self.feeds(), AssetType::Basket)
+            .into_values()
+            .collect()
+    }
+
+    /// Get the current total value locked of every configured protocol and chain
+    pub fn get_all_tvl(&self) -> Vec<PriceData> {
+        self.round_prices(&self.feeds(), AssetType::Tvl)
+            .into_values()
+            .collect()
+    }
+
+    /// Market-cap-weighted index of the current prices of `symbols`, or of every
+    /// configured crypto symbol when empty
+    pub fn market_cap_index(&self, symbols: &[String]) -> MarketCapIndex {
+        let wanted: HashSet<String> = symbols
+            .iter()
+            .map(|symbol| self.config.canonical_symbol(AssetType::Crypto, symbol))
+            .collect();
+        let prices: Vec<PriceData> = self
+            .get_all_crypto_prices_map()
+            .into_iter()
+            .filter(|(symbol, _)| wanted.is_empty() || wanted.contains(symbol))
+            .map(|(_, price_data)| price_data)
+            .collect();
+        index::market_cap_weighted(&prices)
+    }
+
+    /// Resolve a symbol's asset type from the configured crypto and stock symbol sets
+    pub fn resolve_asset_type(&self, symbol: &str) -> Result<&'static str> {
+        let is_crypto = self
+            .config
+            .canonical_symbols(AssetType::Crypto)
+            .contains(&self.config.canonical_symbol(AssetType::Crypto, symbol));
+        let is_stock = self
+            .config
+            .canonical_symbols(AssetType::Stock)
+            .contains(&self.config.canonical_symbol(AssetType::Stock, symbol));
+
+        match (is_crypto, is_stock) {
+            (true, false) => Ok("crypto"),
+            (false, true) => Ok("stock"),
+            (true, true) => Err(OracleError::AmbiguousSymbol(symbol.to_string())),
+            (false, false) => Err(OracleError::PriceNotFound(symbol.to_string())),
+        }
+    }
+
+    /// How the published price of a symbol was obtained: the source used, the
+    /// sources that failed or lacked it, and what triggered the fetch
+    pub fn provenance(&self, asset_type: AssetType, symbol: &str) -> Option<Provenance> {
+        let symbol = self.config.canonical_symbol(asset_type, symbol);
         self.price_feeds
-            .get("stock")
-            .map(|feed| feed.get_all_prices().into_iter().cloned().collect())
-            .unwrap_or_default()
+            .provenance
+            .lock()
+            .unwrap()
+            .get(&provenance_key(asset_type, &symbol))
+            .cloned()
+    }
+
+    /// Everything the oracle holds in memory, for troubleshooting. Secrets in the
+    /// config are redacted.
+    pub fn debug_dump(&self) -> DebugDump {
+        let mut provenance: Vec<Provenance> = self
+            .price_feeds
+            .provenance
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+        provenance.sort_by(|a, b| {
+            (a.asset_type.as_str(), &a.symbol).cmp(&(b.asset_type.as_str(), &b.symbol))
+        });
+        let mut refreshing: Vec<String> = self.refreshing.lock().unwrap().iter().cloned().collect();
+        refreshing.sort();
+        let interval = Duration::from_secs(self.config.general.update_interval);
+
+        let round = self.feeds();
+        DebugDump {
+            config: self.config.redacted(),
+            round_id: round.id,
+            feeds: round.feeds.clone(),
+            provenance,
+            sources: self.registry.health(),
+            source_caches: self.registry.cache_states(),
+            refreshing,
+            schedule: scheduler::plan_round(&self.config, interval),
+            anomalies: self.anomalies(),
+        }
+    }
+
+    /// Recently held or accepted anomalous prices, oldest first
+    pub fn anomalies(&self) -> Vec<Anomaly> {
+        self.price_feeds.anomalies.recent()
+    }
+
+    /// Freshness compliance of every configured symbol, or None when `slo` is
+    /// not configured
+    pub fn slo_report(&self) -> Option<SloReport> {
+        self.price_feeds.slo.report()
     }
 
-    /// Get available crypto symbols
+    /// Evaluate the freshness objectives in `slo` every `slo.interval` seconds
+    /// until the task is aborted. Changes are logged and posted to
+    /// `slo.notify_url`; while `slo` is unset the task idles, so monitoring can
+    /// be turned on by a config reload.
+    pub fn spawn_slo_evaluator(&self) -> tokio::task::JoinHandle<()> {
+        let feeds = self.price_feeds.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder()
+                .timeout(SLO_NOTIFY_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            loop {
+                let secs = feeds.slo.interval().unwrap_or(SLO_IDLE_INTERVAL);
+                tokio::time::sleep(Duration::from_secs(secs)).await;
+
+                let notices = feeds.slo.evaluate(&feeds.feeds.load().feeds, Utc::now());
+                if notices.is_empty() {
+                    continue;
+                }
+                notices.iter().for_each(slo::log_notice);
+                if let Some(url) = feeds.slo.notify_url() {
+                    notify_slo(&client, &url, &notices).await;
+                }
+            }
+        })
+    }
+
+    /// Latest market sentiment, or None when `sentiment` is not configured or
+    /// nothing was fetched yet
+    pub fn sentiment(&self) -> Option<Sentiment> {
+        self.price_feeds.sentiment.latest()
+    }
+
+    /// Fetch the values in `sentiment` right away and then every
+    /// `sentiment.interval` seconds until the task is aborted. While `sentiment`
+    /// is unset the task idles, so the feed can be turned on by a config reload.
+    pub fn spawn_sentiment_updater(&self) -> tokio::task::JoinHandle<()> {
+        let feeds = self.price_feeds.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder()
+                .timeout(sentiment::SENTIMENT_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            loop {
+                let secs = match feeds.sentiment.interval() {
+                    Some(secs) => {
+                        feeds.sentiment.refresh(&client).await;
+                        secs
+                    }
+                    None => SENTIMENT_IDLE_INTERVAL,
+                };
+                tokio::time::sleep(Duration::from_secs(secs)).await;
+            }
+        })
+    }
+
+    /// Latest Bitcoin network statistics, or None when `network` is not
+    /// configured or nothing was fetched yet
+    pub fn network_stats(&self) -> Option<NetworkStats> {
+        self.price_feeds.network.latest()
+    }
+
+    /// Fetch the statistics in `network` right away and then every
+    /// `network.interval` seconds until the task is aborted. While `network` is
+    /// unset the task idles, so the feed can be turned on by a config reload.
+    pub fn spawn_network_updater(&self) -> tokio::task::JoinHandle<()> {
+        let feeds = self.price_feeds.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder()
+                .timeout(network::NETWORK_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            loop {
+                let secs = match feeds.network.interval() {
+                    Some(secs) => {
+                        feeds.network.refresh(&client).await;
+                        secs
+                    }
+                    None => NETWORK_IDLE_INTERVAL,
+                };
+                tokio::time::sleep(Duration::from_secs(secs)).await;
+            }
+        })
+    }
+
+    /// Latest exchange rates against the US dollar, or None when `fx` is not
+    /// configured or nothing was fetched yet
+    pub fn fx_rates(&self) -> Option<FxTable> {
+        self.price_feeds.fx.latest()
+    }
+
+    /// Conversions between the US dollar and the currencies in `fx`
+    pub fn converter(&self) -> &CurrencyConverter {
+        &self.price_feeds.fx
+    }
+
+    /// `price_data` of an `asset_type` symbol converted from the asset type's
+    /// quote currency to `currency`, with the conversion it went through
+    pub fn price_in(
+        &self,
+        asset_type: AssetType,
+        price_data: &PriceData,
+        currency: &str,
+    ) -> Result<(PriceData, Conversion)> {
+        let from = self.config.quote_currency(asset_type);
+        let conversion = self.price_feeds.fx.rate(&from, currency)?;
+        let converted = conversion.apply_price(price_data).ok_or_else(|| {
+            OracleError::UnknownCurrency(format!("{} (price out of range)", conversion.to))
+        })?;
+        Ok((converted, conversion))
+    }
+
+    /// How amounts of an asset type are shown in CLI output: in
+    /// `display.currency` when a rate to it is known, and in the quote
+    /// currency otherwise
+    pub fn amount_format(&self, asset_type: AssetType) -> AmountFormat {
+        let quote = self.config.quote_currency(asset_type);
+        let conversion = match &self.config.display.currency {
+            Some(currency) if *currency != quote => {
+                match self.price_feeds.fx.rate(&quote, currency) {
+                    Ok(conversion) => Some(conversion),
+                    Err(e) => {
+                        warn!(
+                            "Showing {} prices in {}: {}",
+                            asset_type.as_str(),
+                            quote.to_uppercase(),
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        AmountFormat {
+            locale: self.config.locale(),
+            currency: conversion
+                .as_ref()
+                .map_or(quote, |conversion| conversion.to.clone()),
+            conversion,
+        }
+    }
+
+    /// Fetch the rates in `fx` once, for one-shot commands; does nothing while
+    /// the feed is off
+    pub async fn refresh_fx_rates(&self) {
+        if self.price_feeds.fx.interval().is_some() {
+            let client = reqwest::Client::builder()
+                .timeout(fx::FX_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            self.price_feeds.fx.refresh(&client).await;
+        }
+    }
+
+    /// Fetch the rates in `fx` right away and then every `fx.interval` seconds
+    /// until the task is aborted. While `fx` is unset the task idles, so the
+    /// feed can be turned on by a config reload.
+    pub fn spawn_fx_updater(&self) -> tokio::task::JoinHandle<()> {
+        let feeds = self.price_feeds.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder()
+                .timeout(fx::FX_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            loop {
+                let secs = match feeds.fx.interval() {
+                    Some(secs) => {
+                        feeds.fx.refresh(&client).await;
+                        secs
+                    }
+                    None => FX_IDLE_INTERVAL,
+                };
+                tokio::time::sleep(Duration::from_secs(secs)).await;
+            }
+        })
+    }
+
+    /// Published prices of a symbol between `from` and `to`, oldest first
+    pub fn history(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<HistoryPoint> {
+        let symbol = self.config.canonical_symbol(asset_type, symbol);
+        self.price_feeds
+            .history
+            .range(asset_type, &symbol, from, to)
+    }
+
+    /// Like [`Oracle::history`], with stock prices adjusted for the splits and
+    /// dividends in `stocks.corporate_actions`
+    pub fn adjusted_history(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<HistoryPoint> {
+        let symbol = self.config.canonical_symbol(asset_type, symbol);
+        let actions = match asset_type {
+            AssetType::Crypto | AssetType::Basket | AssetType::Tvl => Vec::new(),
+            AssetType::Stock => self.config.stocks.corporate_actions_for(&symbol),
+        };
+        self.price_feeds
+            .history
+            .adjusted_range(asset_type, &symbol, from, to, &actions)
+    }
+
+    /// Get available crypto symbols, one per feed entry
     pub fn get_crypto_symbols(&self) -> Vec<String> {
-        self.config.crypto.symbols.clone()
+        self.config.canonical_symbols(AssetType::Crypto)
     }
 
-    /// Get available stock symbols
+    /// Get available stock symbols, one per feed entry
     pub fn get_stock_symbols(&self) -> Vec<String> {
-        self.config.stocks.symbols.clone()
+        self.config.canonical_symbols(AssetType::Stock)
     }
 
-    /// Print current prices in a formatted table
-    pub fn print_current_prices(&self) {
+    /// Get the configured TVL protocols and chains, one per feed entry
+    pub fn get_tvl_symbols(&self) -> Vec<String> {
+        self.config.canonical_symbols(AssetType::Tvl)
+    }
+
+    /// Print current crypto and stock prices as tables laid out by `options`
+    pub fn print_current_prices(&self, options: &TableOptions) {
         println!(
             "\n=== Current Prices (Last updated: {}) ===",
-            self.last_update.format("%Y-%m-%d %H:%M:%S UTC")
+            self.get_last_update().format("%Y-%m-%d %H:%M:%S UTC")
         );
 
-        // Print crypto prices
-        let crypto_prices = self.get_all_crypto_prices();
-        let crypto_is_empty = crypto_prices.is_empty();
-        if !crypto_is_empty {
-            println!("\n--- Cryptocurrencies ---");
-            println!(
-                "{:<8} {:<12} {:<12} {:<10} {:<10}",
-                "Symbol", "Price ($)", "Change", "Change %", "Source"
-            );
-            println!("{}", "-".repeat(70));
-
-            for price in &crypto_prices {
-                let change_24h = price
-                    .change_24h
-                    .map(|c| format!("{:.2}", c))
-                    .unwrap_or_else(|| "N/A".to_string());
-                let change_percent = price
-                    .change_24h_percent
-                    .map(|c| format!("{:.2}%", c))
-                    .unwrap_or_else(|| "N/A".to_string());
-
-                println!(
-                    "{:<8} {:<12.2} {:<12} {:<10} {:<10}",
-                    price.symbol, price.price, change_24h, change_percent, price.source
-                );
-            }
+        let sections = [
+            (
+                "Cryptocurrencies",
+                AssetType::Crypto,
+                self.get_all_crypto_prices(),
+            ),
+            ("Stocks", AssetType::Stock, self.get_all_stock_prices()),
+        ];
+        if sections.iter().all(|(_, _, prices)| prices.is_empty()) {
+            println!("No price data available. Run update to fetch prices.");
         }
-
-        // Print stock prices
-        let stock_prices = self.get_all_stock_prices();
-        let stock_is_empty = stock_prices.is_empty();
-        if !stock_is_empty {
-            println!("\n--- Stocks ---");
+        for (title, asset_type, prices) in &sections {
+            if prices.is_empty() {
+                continue;
+            }
+            let amounts = self.amount_format(*asset_type);
+            println!("\n--- {} ---", title);
             println!(
-                "{:<8} {:<12} {:<12} {:<10} {:<10}",
-                "Symbol", "Price ($)", "Change", "Change %", "Source"
+                "{}",
+                table::price_table(&self.config, *asset_type, &amounts, prices, options)
             );
-            println!("{}", "-".repeat(70));
-
-            for price in &stock_prices {
-                let change_24h = price
-                    .change_24h
-                    .map(|c| format!("{:.2}", c))
-                    .unwrap_or_else(|| "N/A".to_string());
-                let change_percent = price
-                    .change_24h_percent
-                    .map(|c| format!("{:.2}%", c))
-                    .unwrap_or_else(|| "N/A".to_string());
-
-                println!(
-                    "{:<8} {:<12.2} {:<12} {:<10} {:<10}",
-                    price.symbol, price.price, change_24h, change_percent, price.source
-                );
-            }
-        }
-
-        if crypto_is_empty && stock_is_empty {
-            println!("No price data available. Run update to fetch prices.");
         }
 
         println!();
     }
 
+    /// The cached prices as a fixture for offline mode, sorted by symbol
+    pub fn record_fixture(&self) -> Fixture {
+        let feeds = self.feeds();
+        let recorded = |asset_type: AssetType| {
+            let mut prices: Vec<FixturePrice> = feeds
+                .feed(asset_type)
+                .map(|feed| feed.prices.values().map(FixturePrice::from).collect())
+                .unwrap_or_default();
+            prices.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+            prices
+        };
+        Fixture {
+            crypto: recorded(AssetType::Crypto),
+            stocks: recorded(AssetType::Stock),
+            tvl: recorded(AssetType::Tvl),
+        }
+    }
+
     /// Get price statistics
     pub fn get_price_statistics(&self) -> HashMap<String, serde_json::Value> {
         let mut stats = HashMap::new();
@@ -281,22 +1430,18 @@ impl Oracle {
         );
         stats.insert(
             "last_update".to_string(),
-            serde_json::Value::String(self.last_update.to_rfc3339()),
+            serde_json::Value::String(self.get_last_update().to_rfc3339()),
         );
 
         // Calculate average prices
-        if !crypto_prices.is_empty() {
-            let avg_crypto_price: f64 =
-                crypto_prices.iter().map(|p| p.price).sum::<f64>() / crypto_prices.len() as f64;
+        if let Some(avg_crypto_price) = price_math::mean(crypto_prices.iter().map(|p| p.price)) {
             stats.insert(
                 "avg_crypto_price".to_string(),
                 serde_json::json!(avg_crypto_price),
             );
         }
 
-        if !stock_prices.is_empty() {
-            let avg_stock_price: f64 =
-                stock_prices.iter().map(|p| p.price).sum::<f64>() / stock_prices.len() as f64;
+        if let Some(avg_stock_price) = price_math::mean(stock_prices.iter().map(|p| p.price)) {
             stats.insert(
                 "avg_stock_price".to_string(),
                 serde_json::json!(avg_stock_price),
@@ -308,22 +1453,209 @@ impl Oracle {
 
     /// Get last update timestamp
     pub fn get_last_update(&self) -> DateTime<Utc> {
-        self.last_update
+        *self.last_update.read().unwrap()
+    }
+
+    fn touch(&self) {
+        *self.last_update.write().unwrap() = Utc::now();
     }
 
     /// Get all crypto prices as HashMap for API
     pub fn get_all_crypto_prices_map(&self) -> HashMap<String, PriceData> {
-        self.price_feeds
-            .get("crypto")
-            .map(|feed| feed.get_prices_map().clone())
-            .unwrap_or_default()
+        self.round_prices(&self.feeds(), AssetType::Crypto)
     }
 
     /// Get all stock prices as HashMap for API
     pub fn get_all_stock_prices_map(&self) -> HashMap<String, PriceData> {
-        self.price_feeds
-            .get("stock")
-            .map(|feed| feed.get_prices_map().clone())
-            .unwrap_or_default()
+        self.round_prices(&self.feeds(), AssetType::Stock)
+    }
+
+    /// Get all basket prices as HashMap for API
+    pub fn get_all_basket_prices_map(&self) -> HashMap<String, PriceData> {
+        self.round_prices(&self.feeds(), AssetType::Basket)
+    }
+
+    /// Get the total value locked of every configured protocol and chain as
+    /// HashMap for API
+    pub fn get_all_tvl_map(&self) -> HashMap<String, PriceData> {
+        self.round_prices(&self.feeds(), AssetType::Tvl)
     }
 }
+
+/// Registry for the fetcher's config, followed by sources added through the builder.
+/// In sandbox mode the configured sources quote `market` instead, and in offline
+/// mode they replay the fixture.
+fn build_registry(
+    fetcher: Arc<PriceFetcher>,
+    extra_sources: &[Arc<dyn PriceSource>],
+    audit: Option<AuditLog>,
+    market: Option<&Arc<SimulatedMarket>>,
+) -> Result<SourceRegistry> {
+    let registry = match (market, &fetcher.config().offline) {
+        (Some(market), _) => {
+            info!(
+                "Sandbox mode: simulating prices with seed {}",
+                fetcher.config().sandbox.as_ref().map_or(0, |s| s.seed)
+            );
+            SourceRegistry::simulated(fetcher.config(), market)
+        }
+        (None, Some(offline)) => {
+            let fixture = Fixture::load(&offline.fixture)?;
+            info!(
+                "Offline mode: replaying {} crypto and {} stock prices from {}",
+                fixture.crypto.len(),
+                fixture.stocks.len(),
+                offline.fixture
+            );
+            SourceRegistry::from_fixture(fetcher.config(), &fixture)
+        }
+        (None, None) => SourceRegistry::from_config(fetcher),
+    };
+
+    let mut registry = registry.with_audit(audit);
+    for source in extra_sources {
+        registry.register(source.clone());
+    }
+    Ok(registry)
+}
+
+/// Fill in the order book depth of freshly fetched prices, reading the books
+/// of all of them at once. Nothing is read when `exchanges` is empty.
+async fn attach_depth(fetcher: &PriceFetcher, exchanges: &[String], prices: Vec<&mut PriceData>) {
+    if exchanges.is_empty() || prices.is_empty() {
+        return;
+    }
+    let depths = join_all(
+        prices
+            .iter()
+            .map(|price_data| depth::fetch(fetcher, exchanges, &price_data.symbol)),
+    )
+    .await;
+    for (price_data, depth) in prices.into_iter().zip(depths) {
+        price_data.depth = depth;
+    }
+}
+
+/// Screen `fetched` for anomalies and publish the rest as a round of its own;
+/// returns the prices held back
+fn publish(
+    feeds: &Feeds,
+    asset_type: AssetType,
+    trigger: FetchTrigger,
+    fetched: Vec<Fetched>,
+) -> Vec<Anomaly> {
+    let (accepted, held) = screen(feeds, asset_type, fetched);
+    commit(feeds, trigger, vec![(asset_type, accepted)]);
+    held
+}
+
+/// Split `fetched` into the prices fit to publish and the anomalies held back
+fn screen(
+    feeds: &Feeds,
+    asset_type: AssetType,
+    fetched: Vec<Fetched>,
+) -> (Vec<Fetched>, Vec<Anomaly>) {
+    let mut held = Vec::new();
+    let mut accepted = Vec::with_capacity(fetched.len());
+    for (price_data, attempts) in fetched {
+        match feeds
+            .anomalies
+            .screen(asset_type, &price_data, &feeds.history)
+        {
+            Some(anomaly) => held.push(anomaly),
+            None => accepted.push((price_data, attempts)),
+        }
+    }
+    (accepted, held)
+}
+
+/// Publish every batch in one round, then hand the prices to the history,
+/// the subscribers and the update stream, tagged with that round. Returns the
+/// round's id, or None when there was nothing to publish.
+fn commit(
+    feeds: &Feeds,
+    trigger: FetchTrigger,
+    batches: Vec<(AssetType, Vec<Fetched>)>,
+) -> Option<u64> {
+    let batches: Vec<(AssetType, Vec<Fetched>)> = batches
+        .into_iter()
+        .filter(|(_, prices)| !prices.is_empty())
+        .collect();
+    if batches.is_empty() {
+        return None;
+    }
+
+    let round_id = update_feeds(feeds, |map| apply(map, &batches));
+
+    for (asset_type, fetched) in batches {
+        let (prices, trails): (Vec<PriceData>, Vec<Vec<SourceAttempt>>) =
+            fetched.into_iter().unzip();
+        {
+            let mut provenance = feeds.provenance.lock().unwrap();
+            for (price_data, attempts) in prices.iter().zip(trails) {
+                provenance.insert(
+                    provenance_key(asset_type, &price_data.symbol),
+                    Provenance::new(asset_type, trigger, price_data, attempts),
+                );
+            }
+        }
+
+        feeds.history.record(round_id, asset_type, &prices);
+
+        for subscriber in &feeds.subscribers {
+            subscriber.on_update(asset_type, &prices);
+        }
+        if feeds.updates.receiver_count() > 0 {
+            let _ = feeds.updates.send(PriceUpdate {
+                round_id,
+                asset_type,
+                prices,
+            });
+        }
+    }
+    Some(round_id)
+}
+
+/// Write the prices of every batch into `map`
+fn apply(map: &mut HashMap<String, PriceFeed>, batches: &[(AssetType, Vec<Fetched>)]) {
+    for (asset_type, prices) in batches {
+        if let Some(feed) = map.get_mut(asset_type.as_str()) {
+            for (price_data, _) in prices {
+                feed.update_price(price_data.clone());
+            }
+        }
+    }
+}
+
+/// Post SLO notices to the operator's endpoint as one JSON array
+async fn notify_slo(client: &reqwest::Client, url: &str, notices: &[SloNotice]) {
+    match client.post(url).json(notices).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!(
+            "SLO notification rejected with status {}",
+            response.status()
+        ),
+        Err(e) => warn!("Failed to send SLO notification: {}", e),
+    }
+}
+
+fn provenance_key(asset_type: AssetType, symbol: &str) -> String {
+    format!(
+        "{}:{}",
+        asset_type.as_str(),
+        symbols::feed_key(asset_type, symbol)
+    )
+}
+
+/// Apply `update` to a copy of the current feeds and publish the result as the
+/// next round; returns its id. The closure may run more than once if another
+/// writer publishes first.
+fn update_feeds(feeds: &Feeds, update: impl Fn(&mut HashMap<String, PriceFeed>)) -> u64 {
+    let now = Utc::now();
+    let previous = feeds.feeds.rcu(|current| {
+        let mut next = current.feeds.clone();
+        update(&mut next);
+        current.next(next, now)
+    });
+    previous.next_id(now)
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Decimal places beyond which an f64 carries no more information
+pub const MAX_DECIMALS: u32 = 17;
+
+/// How a price is rounded for display. A price keeps `decimals` decimal places,
+/// and more when it is too small to show `significant_figures` digits that way,
+/// so sub-cent coins don't render as 0.00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrecisionRule {
+    /// Decimal places shown
+    #[serde(default = "default_decimals")]
+    pub decimals: u32,
+    /// Fewest significant digits shown; 0 keeps exactly `decimals` places
+    #[serde(default = "default_significant_figures")]
+    pub significant_figures: u32,
+}
+
+impl Default for PrecisionRule {
+    fn default() -> Self {
+        Self {
+            decimals: default_decimals(),
+            significant_figures: default_significant_figures(),
+        }
+    }
+}
+
+fn default_decimals() -> u32 {
+    2
+}
+
+fn default_significant_figures() -> u32 {
+    4
+}
+
+impl PrecisionRule {
+    /// Rule showing whole numbers only, e.g. for total value locked
+    pub fn whole() -> Self {
+        Self {
+            decimals: 0,
+            significant_figures: 0,
+        }
+    }
+
+    /// Decimal places `value` is shown with
+    pub fn places(&self, value: f64) -> usize {
+        let mut places = self.decimals;
+        if self.significant_figures > 0 && value.is_finite() && value != 0.0 {
+            // Position of the leading digit: 0 for 1.5, -3 for 0.0012
+            let magnitude = value.abs().log10().floor() as i32;
+            let needed = self.significant_figures as i32 - 1 - magnitude;
+            places = places.max(needed.max(0) as u32);
+        }
+        places.min(MAX_DECIMALS) as usize
+    }
+
+    /// `value` with its display precision, e.g. "64012.51" or "0.00001235"
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.places(value), value)
+    }
+
+    /// `value` rounded to its display precision
+    pub fn round(&self, value: f64) -> f64 {
+        self.format(value).parse().unwrap_or(value)
+    }
+}
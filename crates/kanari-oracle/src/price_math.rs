@@ -0,0 +1,130 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+
+// Prices arrive from upstream JSON as f64 and leave the same way, so these helpers
+// take and return f64 but do every intermediate step in exact decimal arithmetic.
+// Sums and ratios over many prices therefore do not pick up floating-point drift.
+
+/// `value` as a decimal; `None` for NaN and infinities
+pub fn to_decimal(value: f64) -> Option<Decimal> {
+    Decimal::from_f64(value)
+}
+
+/// `value` back as an `f64` for serialization
+pub fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Absolute and percentage change from `previous` to `current`. The percentage
+/// is 0 when `previous` is 0.
+pub fn change(current: f64, previous: f64) -> Option<(f64, f64)> {
+    let current = to_decimal(current)?;
+    let previous = to_decimal(previous)?;
+    let change = current.checked_sub(previous)?;
+    let percent = if previous.is_zero() {
+        Decimal::ZERO
+    } else {
+        change
+            .checked_div(previous)?
+            .checked_mul(Decimal::ONE_HUNDRED)?
+    };
+    Some((to_f64(change), to_f64(percent)))
+}
+
+/// `percent` percent of `value`
+pub fn percent_of(value: f64, percent: f64) -> Option<f64> {
+    let product = to_decimal(value)?.checked_mul(to_decimal(percent)?)?;
+    Some(to_f64(product.checked_div(Decimal::ONE_HUNDRED)?))
+}
+
+/// `value` multiplied by `factor`
+pub fn product(value: f64, factor: f64) -> Option<f64> {
+    Some(to_f64(to_decimal(value)?.checked_mul(to_decimal(factor)?)?))
+}
+
+/// `numerator` divided by `denominator`; `None` when `denominator` is 0
+pub fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    let denominator = to_decimal(denominator)?;
+    if denominator.is_zero() {
+        return None;
+    }
+    Some(to_f64(to_decimal(numerator)?.checked_div(denominator)?))
+}
+
+/// Sum of `values`; 0 for no values
+pub fn sum(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let mut sum = Decimal::ZERO;
+    for value in values {
+        sum = sum.checked_add(to_decimal(value)?)?;
+    }
+    Some(to_f64(sum))
+}
+
+/// Sum of `(value, weight)` pairs multiplied out; 0 for no values
+pub fn weighted_sum(values: impl IntoIterator<Item = (f64, f64)>) -> Option<f64> {
+    let mut sum = Decimal::ZERO;
+    for (value, weight) in values {
+        sum = sum.checked_add(to_decimal(value)?.checked_mul(to_decimal(weight)?)?)?;
+    }
+    Some(to_f64(sum))
+}
+
+/// Arithmetic mean; `None` for no values
+pub fn mean(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let mut sum = Decimal::ZERO;
+    let mut count = 0u32;
+    for value in values {
+        sum = sum.checked_add(to_decimal(value)?)?;
+        count += 1;
+    }
+    if count == 0 {
+        return None;
+    }
+    Some(to_f64(sum.checked_div(Decimal::from(count))?))
+}
+
+/// Median; `None` for no values
+pub fn median(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let mut values: Vec<Decimal> = values.into_iter().map(to_decimal).collect::<Option<_>>()?;
+    values.sort();
+
+    let n = values.len();
+    let median = match n {
+        0 => return None,
+        n if n % 2 == 0 => values[n / 2 - 1]
+            .checked_add(values[n / 2])?
+            .checked_div(Decimal::TWO)?,
+        n => values[n / 2],
+    };
+    Some(to_f64(median))
+}
+
+/// Signed deviation of `value` from `reference`, in percent; `None` when
+/// `reference` is 0
+pub fn deviation_percent(value: f64, reference: f64) -> Option<f64> {
+    let reference = to_decimal(reference)?;
+    if reference.is_zero() {
+        return None;
+    }
+    let deviation = to_decimal(value)?
+        .checked_sub(reference)?
+        .checked_div(reference)?
+        .checked_mul(Decimal::ONE_HUNDRED)?;
+    Some(to_f64(deviation))
+}
+
+/// Mean of `(value, weight)` pairs weighted by `weight`; `None` for no values
+/// or a total weight of 0
+pub fn weighted_mean(values: impl IntoIterator<Item = (f64, f64)>) -> Option<f64> {
+    let mut sum = Decimal::ZERO;
+    let mut total_weight = Decimal::ZERO;
+    for (value, weight) in values {
+        let weight = to_decimal(weight)?;
+        sum = sum.checked_add(to_decimal(value)?.checked_mul(weight)?)?;
+        total_weight = total_weight.checked_add(weight)?;
+    }
+    if total_weight.is_zero() {
+        return None;
+    }
+    Some(to_f64(sum.checked_div(total_weight)?))
+}
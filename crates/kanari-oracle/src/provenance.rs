@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::errors::OracleError;
+use crate::models::{AssetType, PriceData};
+
+/// What a source did for one symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptOutcome {
+    /// Supplied the published price
+    Used,
+    /// The request failed
+    Failed,
+    /// Answered, but without a price for the symbol
+    Missing,
+    /// Not asked because its circuit breaker was open
+    Skipped,
+}
+
+/// One source tried for a symbol
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceAttempt {
+    pub source: String,
+    pub outcome: AttemptOutcome,
+    /// Error message for failed attempts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// HTTP status of a failed request, when the failure was an HTTP error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+}
+
+impl SourceAttempt {
+    pub fn new(source: &str, outcome: AttemptOutcome) -> Self {
+        Self {
+            source: source.to_string(),
+            outcome,
+            detail: None,
+            latency_ms: None,
+            http_status: None,
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = Some(latency.as_millis() as u64);
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Record the error of a failed attempt
+    pub fn with_error(mut self, error: &OracleError) -> Self {
+        self.detail = Some(error.to_string());
+        self.http_status = error.http_status();
+        self
+    }
+}
+
+/// What caused a price to be fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchTrigger {
+    /// Background update round
+    Update,
+    /// Cache miss on lookup
+    OnDemand,
+    /// Stale-while-revalidate refresh after a lookup
+    Refresh,
+}
+
+/// How the currently published price of a symbol was obtained
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    pub symbol: String,
+    pub asset_type: AssetType,
+    pub price: f64,
+    pub source: String,
+    pub trigger: FetchTrigger,
+    pub published_at: DateTime<Utc>,
+    /// Whether the price came from a source other than the first one tried
+    pub fallback: bool,
+    /// Sources in the order they were tried, ending with the one used
+    pub attempts: Vec<SourceAttempt>,
+}
+
+impl Provenance {
+    pub fn new(
+        asset_type: AssetType,
+        trigger: FetchTrigger,
+        price_data: &PriceData,
+        attempts: Vec<SourceAttempt>,
+    ) -> Self {
+        let fallback = attempts
+            .first()
+            .is_some_and(|a| a.outcome != AttemptOutcome::Used);
+        Self {
+            symbol: price_data.symbol.clone(),
+            asset_type,
+            price: price_data.price,
+            source: price_data.source.clone(),
+            trigger,
+            published_at: Utc::now(),
+            fallback,
+            attempts,
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::anomaly::Anomaly;
+use crate::models::{AssetType, PriceData, PriceFeed};
+use crate::provenance::{AttemptOutcome, SourceAttempt};
+
+/// Every price feed as one publish left them, keyed by asset type ("crypto",
+/// "stock"). Rounds are immutable and replaced whole, so a reader holding one
+/// never sees some prices of a later round without the others.
+#[derive(Debug, Clone)]
+pub struct Round {
+    /// Strictly greater than the id of every earlier round. Ids follow the
+    /// clock in milliseconds, so they keep increasing across restarts unless
+    /// the clock goes back; 0 before anything was published.
+    pub id: u64,
+    pub published_at: DateTime<Utc>,
+    pub feeds: HashMap<String, PriceFeed>,
+}
+
+impl Round {
+    /// Round 0, holding the empty feeds an oracle starts with
+    pub fn initial(feeds: HashMap<String, PriceFeed>) -> Self {
+        Self {
+            id: 0,
+            published_at: Utc::now(),
+            feeds,
+        }
+    }
+
+    /// The round after this one, holding `feeds`, published at `now`
+    pub fn next(&self, feeds: HashMap<String, PriceFeed>, now: DateTime<Utc>) -> Self {
+        Self {
+            id: self.next_id(now),
+            published_at: now,
+            feeds,
+        }
+    }
+
+    /// Id of the round published after this one at `now`
+    pub fn next_id(&self, now: DateTime<Utc>) -> u64 {
+        (now.timestamp_millis().max(0) as u64).max(self.id + 1)
+    }
+
+    pub fn feed(&self, asset_type: AssetType) -> Option<&PriceFeed> {
+        self.feeds.get(asset_type.as_str())
+    }
+}
+
+/// What an update round did with one symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolStatus {
+    /// A fresh price was published
+    Updated,
+    /// No source returned a price
+    Failed,
+    /// A price was fetched but held back as an anomaly
+    Held,
+    /// Not fetched, because its price is settled until the market reopens
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolOutcome {
+    pub asset_type: AssetType,
+    pub symbol: String,
+    pub status: SymbolStatus,
+    /// Source of the published or held price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Why the symbol was not updated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Per-symbol outcome of an update round, returned by the `Oracle::update_*`
+/// methods. A round succeeds partially when some symbols fail.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateReport {
+    /// Round the published prices went out in; None when nothing was published
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round_id: Option<u64>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub updated: usize,
+    pub failed: usize,
+    pub held: usize,
+    pub skipped: usize,
+    /// Prices published per source
+    pub sources_used: BTreeMap<String, usize>,
+    pub symbols: Vec<SymbolOutcome>,
+}
+
+impl UpdateReport {
+    /// Empty report of a round starting now
+    pub fn start() -> Self {
+        Self {
+            round_id: None,
+            started_at: Utc::now(),
+            duration_ms: 0,
+            updated: 0,
+            failed: 0,
+            held: 0,
+            skipped: 0,
+            sources_used: BTreeMap::new(),
+            symbols: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, outcome: SymbolOutcome) {
+        match outcome.status {
+            SymbolStatus::Updated => {
+                self.updated += 1;
+                if let Some(source) = &outcome.source {
+                    *self.sources_used.entry(source.clone()).or_default() += 1;
+                }
+            }
+            SymbolStatus::Failed => self.failed += 1,
+            SymbolStatus::Held => self.held += 1,
+            SymbolStatus::Skipped => self.skipped += 1,
+        }
+        self.symbols.push(outcome);
+    }
+
+    /// Add the outcomes of another part of the same round, e.g. another batch
+    pub fn merge(&mut self, other: UpdateReport) {
+        self.started_at = self.started_at.min(other.started_at);
+        self.round_id = self.round_id.max(other.round_id);
+        for outcome in other.symbols {
+            self.push(outcome);
+        }
+    }
+
+    /// This report with only the outcomes `keep` accepts, and counts to match
+    pub fn filtered(self, keep: impl Fn(&SymbolOutcome) -> bool) -> Self {
+        let mut report = Self {
+            updated: 0,
+            failed: 0,
+            held: 0,
+            skipped: 0,
+            sources_used: BTreeMap::new(),
+            symbols: Vec::new(),
+            ..self
+        };
+        for outcome in self.symbols.into_iter().filter(|outcome| keep(outcome)) {
+            report.push(outcome);
+        }
+        report
+    }
+
+    /// Set the duration from `started_at` to now
+    pub fn finish(mut self) -> Self {
+        self.duration_ms = (Utc::now() - self.started_at)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+            .as_millis() as u64;
+        self
+    }
+
+    /// Log a one-line summary, and each symbol that failed or was held back
+    pub fn log(&self) {
+        let id = self
+            .round_id
+            .map_or_else(String::new, |id| format!(" {}", id));
+        info!(
+            "Update round{}: {} updated, {} failed, {} held, {} skipped in {} ms",
+            id, self.updated, self.failed, self.held, self.skipped, self.duration_ms
+        );
+        for outcome in &self.symbols {
+            if matches!(outcome.status, SymbolStatus::Failed | SymbolStatus::Held) {
+                warn!(
+                    "{} {} {:?}: {}",
+                    outcome.asset_type.as_str(),
+                    outcome.symbol,
+                    outcome.status,
+                    outcome.reason.as_deref().unwrap_or("unknown")
+                );
+            }
+        }
+    }
+}
+
+impl SymbolOutcome {
+    pub fn updated(asset_type: AssetType, price_data: &PriceData) -> Self {
+        Self {
+            asset_type,
+            symbol: price_data.symbol.clone(),
+            status: SymbolStatus::Updated,
+            source: Some(price_data.source.clone()),
+            reason: None,
+        }
+    }
+
+    /// A symbol no source priced; the reason is taken from the sources tried
+    pub fn failed(asset_type: AssetType, symbol: &str, attempts: &[SourceAttempt]) -> Self {
+        Self {
+            asset_type,
+            symbol: symbol.to_string(),
+            status: SymbolStatus::Failed,
+            source: None,
+            reason: Some(failure_reason(attempts)),
+        }
+    }
+
+    /// A symbol that could not be priced for a reason other than its sources
+    pub fn unpriced(asset_type: AssetType, symbol: &str, reason: String) -> Self {
+        Self {
+            asset_type,
+            symbol: symbol.to_string(),
+            status: SymbolStatus::Failed,
+            source: None,
+            reason: Some(reason),
+        }
+    }
+
+    pub fn held(anomaly: &Anomaly) -> Self {
+        Self {
+            asset_type: anomaly.asset_type,
+            symbol: anomaly.symbol.clone(),
+            status: SymbolStatus::Held,
+            source: Some(anomaly.source.clone()),
+            reason: Some(anomaly.to_error().to_string()),
+        }
+    }
+
+    pub fn skipped(asset_type: AssetType, symbol: &str) -> Self {
+        Self {
+            asset_type,
+            symbol: symbol.to_string(),
+            status: SymbolStatus::Skipped,
+            source: None,
+            reason: Some("market closed".to_string()),
+        }
+    }
+}
+
+/// Summarize why every source failed a symbol, e.g.
+/// "binance: HTTP 500; coingecko: no price; finnhub: circuit open"
+fn failure_reason(attempts: &[SourceAttempt]) -> String {
+    if attempts.is_empty() {
+        return "no source available".to_string();
+    }
+    attempts
+        .iter()
+        .map(|attempt| {
+            let what = match attempt.outcome {
+                AttemptOutcome::Failed => attempt.detail.as_deref().unwrap_or("failed"),
+                AttemptOutcome::Missing => "no price",
+                AttemptOutcome::Skipped => "circuit open",
+                AttemptOutcome::Used => "used",
+            };
+            format!("{}: {}", attempt.source, what)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,82 @@
+use serde::{Serialize, Serializer};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::models::AssetType;
+
+/// A group of symbols updated together at `offset` into an update round
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateBatch {
+    #[serde(rename = "offset_ms", serialize_with = "as_millis")]
+    pub offset: Duration,
+    pub asset_type: AssetType,
+    pub symbols: Vec<String>,
+}
+
+/// Split one update round into batches of at most `general.symbols_per_batch`
+/// symbols, spread evenly across `interval` with up to `general.update_jitter`
+/// of a slot added at random. A batch size of 0 updates everything at once.
+pub fn plan_round(config: &Config, interval: Duration) -> Vec<UpdateBatch> {
+    let per_batch = config.general.symbols_per_batch;
+
+    let batches: Vec<UpdateBatch> = [
+        (
+            AssetType::Crypto,
+            config.canonical_symbols(AssetType::Crypto),
+        ),
+        (AssetType::Stock, config.canonical_symbols(AssetType::Stock)),
+        (AssetType::Tvl, config.canonical_symbols(AssetType::Tvl)),
+    ]
+    .into_iter()
+    .filter(|(_, symbols)| !symbols.is_empty())
+    .flat_map(|(asset_type, symbols)| {
+        let chunk = if per_batch == 0 {
+            symbols.len()
+        } else {
+            per_batch
+        };
+        symbols
+            .chunks(chunk)
+            .map(|chunk| UpdateBatch {
+                offset: Duration::ZERO,
+                asset_type,
+                symbols: chunk.to_vec(),
+            })
+            .collect::<Vec<_>>()
+    })
+    .collect();
+
+    if per_batch == 0 || batches.len() < 2 {
+        return batches;
+    }
+
+    // Alternate asset types so consecutive batches hit different providers
+    let mut groups: Vec<std::vec::IntoIter<UpdateBatch>> =
+        [AssetType::Crypto, AssetType::Stock, AssetType::Tvl]
+            .into_iter()
+            .map(|asset_type| {
+                batches
+                    .iter()
+                    .filter(|b| b.asset_type == asset_type)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            })
+            .collect();
+    let mut interleaved = Vec::with_capacity(batches.len());
+    while interleaved.len() < batches.len() {
+        interleaved.extend(groups.iter_mut().filter_map(|group| group.next()));
+    }
+
+    let slot = interval / interleaved.len() as u32;
+    let jitter = config.general.update_jitter.clamp(0.0, 1.0);
+    for (index, batch) in interleaved.iter_mut().enumerate() {
+        batch.offset = slot * index as u32 + slot.mul_f64(jitter * rand::random::<f64>());
+    }
+
+    interleaved
+}
+
+fn as_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
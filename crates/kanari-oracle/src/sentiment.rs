@@ -0,0 +1,244 @@
+use chrono::{DateTime, TimeZone, Utc};
+use futures::future::join_all;
+use log::{info, warn};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{FetchResponse, status_error};
+use crate::symbols;
+
+/// Latest Crypto Fear & Greed index published by alternative.me
+const FEAR_GREED_URL: &str = "https://api.alternative.me/fng/?limit=1";
+
+/// Mark price and funding rate of a Binance USDT-margined perpetual
+const FUNDING_URL: &str = "https://fapi.binance.com/fapi/v1/premiumIndex";
+
+/// Limit on each sentiment request; a slow upstream is retried at the next fetch
+pub const SENTIMENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Crypto Fear & Greed index, from 0 (extreme fear) to 100 (extreme greed)
+#[derive(Debug, Clone, Serialize)]
+pub struct FearGreed {
+    pub value: u8,
+    /// "Extreme Fear", "Fear", "Neutral", "Greed" or "Extreme Greed"
+    pub classification: String,
+    /// Day the value was published for
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Funding rate of a perpetual futures contract; positive when longs pay shorts
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingRate {
+    pub symbol: String,
+    /// Rate of the current funding interval, in percent
+    pub rate_percent: f64,
+    pub mark_price: f64,
+    pub next_funding_time: DateTime<Utc>,
+}
+
+/// Market sentiment as served at `/sentiment`
+#[derive(Debug, Clone, Serialize)]
+pub struct Sentiment {
+    /// None until the index was fetched once
+    pub fear_greed: Option<FearGreed>,
+    pub funding_rates: Vec<FundingRate>,
+    pub updated_at: DateTime<Utc>,
+    /// What the last fetch failed to get; earlier values are kept in its place
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// The `sentiment` section and the latest values fetched for it
+pub struct SentimentFeed {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    /// Seconds between fetches; None while the feed is off
+    interval: Option<u64>,
+    /// Binance tickers of `sentiment.funding_symbols`
+    funding_tickers: Vec<String>,
+    latest: Option<Sentiment>,
+}
+
+impl SentimentFeed {
+    pub fn new(config: &Config) -> Self {
+        let feed = Self {
+            inner: Mutex::new(Inner {
+                interval: None,
+                funding_tickers: Vec::new(),
+                latest: None,
+            }),
+        };
+        feed.reconfigure(config);
+        feed
+    }
+
+    /// Apply `config.sentiment`. Offline and sandbox oracles query no upstream,
+    /// so the feed is off for them; turning it off forgets the latest values.
+    pub fn reconfigure(&self, config: &Config) {
+        let mut inner = self.inner.lock().unwrap();
+        let sentiment = config
+            .sentiment
+            .as_ref()
+            .filter(|_| config.offline.is_none() && config.sandbox.is_none());
+        let Some(sentiment) = sentiment else {
+            inner.interval = None;
+            inner.funding_tickers.clear();
+            inner.latest = None;
+            return;
+        };
+
+        inner.interval = Some(sentiment.interval);
+        inner.funding_tickers = sentiment
+            .funding_symbols
+            .iter()
+            .map(|symbol| {
+                config
+                    .crypto
+                    .ticker_for(symbol)
+                    .unwrap_or_else(|| symbols::normalize_crypto(symbol))
+            })
+            .collect();
+        // Funding rates of symbols no longer listed are dropped
+        let tickers: Vec<String> = inner
+            .funding_tickers
+            .iter()
+            .map(|ticker| symbols::binance_pair(ticker))
+            .collect();
+        if let Some(latest) = &mut inner.latest {
+            latest
+                .funding_rates
+                .retain(|rate| tickers.contains(&rate.symbol));
+        }
+    }
+
+    /// Seconds between fetches, or None while the feed is off
+    pub fn interval(&self) -> Option<u64> {
+        self.inner.lock().unwrap().interval
+    }
+
+    pub fn latest(&self) -> Option<Sentiment> {
+        self.inner.lock().unwrap().latest.clone()
+    }
+
+    /// Fetch every value now and keep the result. Values that fail to fetch keep
+    /// their previous reading, and the failure is listed in `errors`.
+    pub async fn refresh(&self, client: &reqwest::Client) {
+        let tickers = self.inner.lock().unwrap().funding_tickers.clone();
+        let mut errors = Vec::new();
+
+        let fear_greed = match fetch_fear_greed(client).await {
+            Ok(fear_greed) => Some(fear_greed),
+            Err(e) => {
+                warn!("Fear & Greed index unavailable: {}", e);
+                errors.push(format!("fear_greed: {}", e));
+                None
+            }
+        };
+        let results = join_all(tickers.iter().map(|ticker| fetch_funding(client, ticker))).await;
+        let mut funding_rates = Vec::new();
+        for (ticker, result) in tickers.iter().zip(results) {
+            match result {
+                Ok(rate) => funding_rates.push(rate),
+                Err(e) => {
+                    warn!("Funding rate unavailable for {}: {}", ticker, e);
+                    errors.push(format!("funding {}: {}", ticker, e));
+                }
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.interval.is_none() {
+            // Turned off while fetching
+            return;
+        }
+        let previous = inner.latest.take();
+        let fear_greed = fear_greed.or_else(|| previous.as_ref()?.fear_greed.clone());
+        if let Some(previous) = previous {
+            for rate in previous.funding_rates {
+                if !funding_rates
+                    .iter()
+                    .any(|fresh| fresh.symbol == rate.symbol)
+                {
+                    funding_rates.push(rate);
+                }
+            }
+        }
+        funding_rates.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        if let Some(fear_greed) = &fear_greed {
+            info!(
+                "Fear & Greed index: {} ({})",
+                fear_greed.value, fear_greed.classification
+            );
+        }
+        inner.latest = Some(Sentiment {
+            fear_greed,
+            funding_rates,
+            updated_at: Utc::now(),
+            errors,
+        });
+    }
+}
+
+async fn fetch_fear_greed(client: &reqwest::Client) -> Result<FearGreed> {
+    let response = FetchResponse::read(client.get(FEAR_GREED_URL).send().await?).await?;
+    if !response.status().is_success() {
+        return Err(status_error("Fear & Greed API error", &response));
+    }
+    let body: serde_json::Value = response.json()?;
+
+    let entry = &body["data"][0];
+    let invalid = || OracleError::ApiError(format!("Invalid Fear & Greed response: {}", body));
+    let value = entry["value"]
+        .as_str()
+        .and_then(|s| s.parse::<u8>().ok())
+        .ok_or_else(invalid)?;
+    let classification = entry["value_classification"]
+        .as_str()
+        .ok_or_else(invalid)?
+        .to_string();
+    let timestamp = entry["timestamp"]
+        .as_str()
+        .and_then(|s| s.parse::<i64>().ok())
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        .ok_or_else(invalid)?;
+
+    Ok(FearGreed {
+        value,
+        classification,
+        timestamp,
+    })
+}
+
+async fn fetch_funding(client: &reqwest::Client, ticker: &str) -> Result<FundingRate> {
+    let pair = symbols::binance_pair(ticker);
+    let request = client.get(FUNDING_URL).query(&[("symbol", pair.as_str())]);
+    let response = FetchResponse::read(request.send().await?).await?;
+    if !response.status().is_success() {
+        return Err(status_error(
+            &format!("Binance funding API error for {}", pair),
+            &response,
+        ));
+    }
+    let body: serde_json::Value = response.json()?;
+
+    let invalid = || OracleError::ApiError(format!("Invalid funding response for {}", pair));
+    let number = |field: &str| body[field].as_str().and_then(|s| s.parse::<f64>().ok());
+    let rate = number("lastFundingRate").ok_or_else(invalid)?;
+    let mark_price = number("markPrice").ok_or_else(invalid)?;
+    let next_funding_time = body["nextFundingTime"]
+        .as_i64()
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .ok_or_else(invalid)?;
+
+    Ok(FundingRate {
+        symbol: pair,
+        rate_percent: rate * 100.0,
+        mark_price,
+        next_funding_time,
+    })
+}
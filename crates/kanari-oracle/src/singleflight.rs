@@ -0,0 +1,48 @@
+use futures::FutureExt;
+use futures::future::{BoxFuture, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+/// Coalesces concurrent calls with the same key into one execution whose output
+/// is shared by every caller
+pub struct SingleFlight<T: Clone> {
+    calls: Mutex<HashMap<String, Shared<BoxFuture<'static, T>>>>,
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    /// Run the future from `make` unless a call for `key` is already in flight,
+    /// in which case wait for that call's output instead
+    pub async fn run<F, Fut>(&self, key: &str, make: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let call = self
+            .calls
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| make().boxed().shared())
+            .clone();
+
+        let output = call.clone().await;
+
+        // Only the first caller to finish removes the entry; a newer call for the
+        // same key may already have replaced it
+        let mut calls = self.calls.lock().unwrap();
+        if calls.get(key).is_some_and(|current| current.ptr_eq(&call)) {
+            calls.remove(key);
+        }
+
+        output
+    }
+}
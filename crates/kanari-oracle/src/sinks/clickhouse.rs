@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+
+use super::{Observation, Sink};
+use crate::config::ClickHouseConfig;
+use crate::errors::Result;
+use crate::fetchers::{FetchResponse, status_error};
+
+/// One row of the observations table; see the README for its schema
+#[derive(Serialize)]
+struct Row<'a> {
+    timestamp: String,
+    asset_type: &'a str,
+    symbol: &'a str,
+    source: &'a str,
+    price: f64,
+    change_24h: Option<f64>,
+    change_24h_percent: Option<f64>,
+    volume_24h: Option<f64>,
+    // Left out unless depth is on, so tables without these columns keep working
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth_bids: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth_asks: Option<f64>,
+}
+
+/// Inserts observations through ClickHouse's HTTP interface as `JSONEachRow`
+pub struct ClickHouseSink {
+    client: reqwest::Client,
+    config: ClickHouseConfig,
+}
+
+impl ClickHouseSink {
+    pub fn new(config: ClickHouseConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl Sink for ClickHouseSink {
+    fn name(&self) -> &str {
+        "clickhouse"
+    }
+
+    async fn write(&self, batch: &[Observation]) -> Result<()> {
+        let mut body = String::new();
+        for observation in batch {
+            let price = &observation.price;
+            let row = Row {
+                timestamp: price.timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                asset_type: observation.asset_type.as_str(),
+                symbol: &price.symbol,
+                source: &price.source,
+                price: price.price,
+                change_24h: price.change_24h,
+                change_24h_percent: price.change_24h_percent,
+                volume_24h: price.volume_24h,
+                depth_bids: price.depth.as_ref().map(|depth| depth.bids),
+                depth_asks: price.depth.as_ref().map(|depth| depth.asks),
+            };
+            body.push_str(&serde_json::to_string(&row)?);
+            body.push('\n');
+        }
+
+        let query = format!(
+            "INSERT INTO {}.{} FORMAT JSONEachRow",
+            self.config.database, self.config.table
+        );
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .query(&[("query", query.as_str())])
+            .body(body);
+        if let Some(user) = &self.config.user {
+            request = request.header("X-ClickHouse-User", user);
+        }
+        if let Some(password) = &self.config.password {
+            request = request.header("X-ClickHouse-Key", password);
+        }
+
+        let response = FetchResponse::read(request.send().await?).await?;
+        if !response.status().is_success() {
+            return Err(status_error("ClickHouse insert failed", &response));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::time::Duration;
+
+use super::{Observation, Sink};
+use crate::config::InfluxConfig;
+use crate::errors::Result;
+use crate::fetchers::{FetchResponse, status_error};
+
+/// Writes observations to InfluxDB v2 (`/api/v2/write`) as line protocol, one
+/// point per price tagged with asset type, symbol and source
+pub struct InfluxSink {
+    client: reqwest::Client,
+    config: InfluxConfig,
+}
+
+impl InfluxSink {
+    pub fn new(config: InfluxConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self { client, config })
+    }
+
+    /// One line-protocol point, e.g.
+    /// `price,asset_type=crypto,symbol=btc,source=binance price=64000.5,volume_24h=1200 1700000000000000000`
+    fn line(&self, observation: &Observation) -> String {
+        let price = &observation.price;
+        let mut line = escape(&self.config.measurement, &[',', ' ']);
+        for (key, value) in [
+            ("asset_type", observation.asset_type.as_str()),
+            ("symbol", price.symbol.as_str()),
+            ("source", price.source.as_str()),
+        ] {
+            let _ = write!(line, ",{}={}", key, escape(value, &[',', '=', ' ']));
+        }
+
+        let _ = write!(line, " price={}", price.price);
+        let optional = [
+            ("change_24h", price.change_24h),
+            ("change_24h_percent", price.change_24h_percent),
+            ("volume_24h", price.volume_24h),
+            ("depth_bids", price.depth.as_ref().map(|depth| depth.bids)),
+            ("depth_asks", price.depth.as_ref().map(|depth| depth.asks)),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value.filter(|v| v.is_finite()) {
+                let _ = write!(line, ",{}={}", key, value);
+            }
+        }
+
+        let timestamp = price.timestamp.timestamp_nanos_opt().unwrap_or_default();
+        let _ = write!(line, " {}", timestamp);
+        line
+    }
+}
+
+/// Backslash-escape the characters line protocol treats as delimiters
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[async_trait]
+impl Sink for InfluxSink {
+    fn name(&self) -> &str {
+        "influxdb"
+    }
+
+    async fn write(&self, batch: &[Observation]) -> Result<()> {
+        let body = batch
+            .iter()
+            .filter(|observation| observation.price.price.is_finite())
+            .map(|observation| self.line(observation))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if body.is_empty() {
+            return Ok(());
+        }
+
+        let url = format!("{}/api/v2/write", self.config.url.trim_end_matches('/'));
+        let mut request = self
+            .client
+            .post(url)
+            .query(&[
+                ("org", self.config.org.as_str()),
+                ("bucket", self.config.bucket.as_str()),
+                ("precision", "ns"),
+            ])
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(body);
+        if let Some(token) = &self.config.token {
+            request = request.header("Authorization", format!("Token {}", token));
+        }
+
+        let response = FetchResponse::read(request.send().await?).await?;
+        if !response.status().is_success() {
+            return Err(status_error("InfluxDB write failed", &response));
+        }
+        Ok(())
+    }
+}
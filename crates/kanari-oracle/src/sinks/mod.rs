@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use log::warn;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time;
+
+use crate::config::Config;
+use crate::errors::Result;
+use crate::models::{AssetType, PriceData};
+use crate::subscriber::PriceSubscriber;
+
+pub mod clickhouse;
+pub mod influx;
+
+pub use clickhouse::ClickHouseSink;
+pub use influx::InfluxSink;
+
+/// A price as written to an analytics sink
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub asset_type: AssetType,
+    pub price: PriceData,
+}
+
+/// Destination for price observations, written in batches
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    async fn write(&self, batch: &[Observation]) -> Result<()>;
+}
+
+/// Subscriber that buffers published prices and hands them to a [`Sink`] in
+/// batches of `batch_size`, or every `flush_interval` when fewer arrive. A failed
+/// batch is logged and dropped, so a slow or unavailable sink never holds up
+/// publishing.
+pub struct BatchWriter {
+    sender: mpsc::UnboundedSender<Observation>,
+}
+
+impl BatchWriter {
+    /// Start the writer task; it flushes what is left and stops when the writer
+    /// is dropped
+    pub fn spawn(sink: Arc<dyn Sink>, batch_size: usize, flush_interval: Duration) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Observation>();
+        let batch_size = batch_size.max(1);
+
+        tokio::spawn(async move {
+            let mut buffer = Vec::with_capacity(batch_size);
+            let mut ticker = time::interval(flush_interval);
+            ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+            loop {
+                let open = tokio::select! {
+                    received = receiver.recv() => match received {
+                        Some(observation) => {
+                            buffer.push(observation);
+                            if buffer.len() < batch_size {
+                                continue;
+                            }
+                            true
+                        }
+                        None => false,
+                    },
+                    _ = ticker.tick() => true,
+                };
+
+                if !buffer.is_empty() {
+                    if let Err(e) = sink.write(&buffer).await {
+                        warn!(
+                            "Dropped {} observations for {}: {}",
+                            buffer.len(),
+                            sink.name(),
+                            e
+                        );
+                    }
+                    buffer.clear();
+                }
+                if !open {
+                    break;
+                }
+            }
+        });
+
+        Self { sender }
+    }
+}
+
+impl PriceSubscriber for BatchWriter {
+    fn on_update(&self, asset_type: AssetType, prices: &[PriceData]) {
+        for price in prices {
+            let _ = self.sender.send(Observation {
+                asset_type,
+                price: price.clone(),
+            });
+        }
+    }
+}
+
+/// Batch writers for every sink enabled in `config.sinks`
+pub fn from_config(config: &Config) -> Result<Vec<Arc<dyn PriceSubscriber>>> {
+    let mut writers: Vec<Arc<dyn PriceSubscriber>> = Vec::new();
+
+    if let Some(clickhouse) = &config.sinks.clickhouse {
+        let sink = ClickHouseSink::new(clickhouse.clone())?;
+        writers.push(Arc::new(BatchWriter::spawn(
+            Arc::new(sink),
+            clickhouse.batch_size,
+            Duration::from_millis(clickhouse.flush_interval),
+        )));
+    }
+
+    if let Some(influxdb) = &config.sinks.influxdb {
+        let sink = InfluxSink::new(influxdb.clone())?;
+        writers.push(Arc::new(BatchWriter::spawn(
+            Arc::new(sink),
+            influxdb.batch_size,
+            Duration::from_millis(influxdb.flush_interval),
+        )));
+    }
+
+    Ok(writers)
+}
@@ -0,0 +1,430 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::config::{Config, SloConfig, SloTarget};
+use crate::market;
+use crate::models::{AssetType, PriceFeed};
+use crate::symbols;
+
+/// Violations kept per symbol; older ones no longer count against compliance
+const MAX_VIOLATIONS: usize = 100;
+
+/// A stretch of time a symbol's price was older than its `max_age`, or missing
+#[derive(Debug, Clone, Serialize)]
+pub struct SloViolation {
+    pub start: DateTime<Utc>,
+    /// None while the violation is ongoing
+    pub end: Option<DateTime<Utc>>,
+    pub duration_seconds: i64,
+}
+
+/// Freshness compliance of one symbol over the SLO window
+#[derive(Debug, Clone, Serialize)]
+pub struct SloStatus {
+    pub asset_type: AssetType,
+    pub symbol: String,
+    pub max_age: u64,
+    /// Percentage of the window the price must be fresh
+    pub target: f64,
+    /// Percentage of the window, since monitoring started, the price was fresh
+    pub compliance: f64,
+    pub met: bool,
+    /// Age of the price at the last evaluation; None when there is no price
+    pub age_seconds: Option<i64>,
+    pub fresh: bool,
+    /// Violations overlapping the window, oldest first
+    pub violations: Vec<SloViolation>,
+}
+
+/// Compliance of every configured symbol, as served at `/stats/slo`
+#[derive(Debug, Clone, Serialize)]
+pub struct SloReport {
+    /// Seconds over which compliance is computed
+    pub window: u64,
+    pub evaluated_at: Option<DateTime<Utc>>,
+    pub symbols: Vec<SloStatus>,
+}
+
+/// What changed for a symbol during an evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SloEvent {
+    /// The price became older than `max_age`, or went missing
+    Stale,
+    /// The price is within `max_age` again
+    Fresh,
+    /// Compliance dropped below the target
+    TargetMissed,
+    /// Compliance is back at or above the target
+    TargetMet,
+}
+
+impl SloEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SloEvent::Stale => "stale",
+            SloEvent::Fresh => "fresh",
+            SloEvent::TargetMissed => "target_missed",
+            SloEvent::TargetMet => "target_met",
+        }
+    }
+}
+
+/// Sent to `slo.notify_url` and logged for every [`SloEvent`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SloNotice {
+    pub event: SloEvent,
+    pub asset_type: AssetType,
+    pub symbol: String,
+    pub age_seconds: Option<i64>,
+    pub max_age: u64,
+    pub compliance: f64,
+    pub target: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// Tracks how long each configured symbol's price stays within its freshness
+/// objective. `evaluate` is called every `slo.interval` seconds; a symbol is
+/// in violation from the moment its price passed `max_age` until a fresher one
+/// is seen, and compliance is the share of the window spent outside violations.
+#[derive(Debug)]
+pub struct SloMonitor {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    config: Option<SloConfig>,
+    /// Keyed "asset_type:feed_key"
+    symbols: HashMap<String, SymbolState>,
+    evaluated_at: Option<DateTime<Utc>>,
+    /// `stocks.market_hours`: settled stock prices count as fresh while the
+    /// market is closed
+    market_hours: bool,
+}
+
+#[derive(Debug)]
+struct SymbolState {
+    asset_type: AssetType,
+    symbol: String,
+    max_age: u64,
+    target: f64,
+    /// First evaluation that covered the symbol; compliance is not computed
+    /// over time before it
+    tracked_since: Option<DateTime<Utc>>,
+    violations: VecDeque<SloViolation>,
+    age_seconds: Option<i64>,
+    met: bool,
+}
+
+impl SymbolState {
+    fn violating(&self) -> bool {
+        self.violations.back().is_some_and(|v| v.end.is_none())
+    }
+
+    fn compliance(&self, now: DateTime<Utc>, window: u64) -> f64 {
+        let Some(tracked_since) = self.tracked_since else {
+            return 100.0;
+        };
+        let from = tracked_since.max(now - Duration::seconds(window as i64));
+        let observed = (now - from).num_milliseconds();
+        if observed <= 0 {
+            return 100.0;
+        }
+        let violated: i64 = self
+            .violations
+            .iter()
+            .map(|v| {
+                let start = v.start.max(from);
+                let end = v.end.unwrap_or(now).min(now);
+                (end - start).num_milliseconds().max(0)
+            })
+            .sum();
+        100.0 * (1.0 - violated as f64 / observed as f64)
+    }
+
+    fn notice(&self, event: SloEvent, compliance: f64, at: DateTime<Utc>) -> SloNotice {
+        SloNotice {
+            event,
+            asset_type: self.asset_type,
+            symbol: self.symbol.clone(),
+            age_seconds: self.age_seconds,
+            max_age: self.max_age,
+            compliance,
+            target: self.target,
+            at,
+        }
+    }
+}
+
+impl SloMonitor {
+    pub fn new(config: &Config) -> Self {
+        let monitor = Self {
+            inner: Mutex::new(Inner {
+                config: None,
+                symbols: HashMap::new(),
+                evaluated_at: None,
+                market_hours: false,
+            }),
+        };
+        monitor.reconfigure(config);
+        monitor
+    }
+
+    /// Apply `config.slo` to the configured symbols. Symbols still configured
+    /// keep their violations; `None` turns monitoring off and forgets them.
+    pub fn reconfigure(&self, config: &Config) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.market_hours = config.stocks.market_hours;
+        let Some(slo) = config.slo.clone() else {
+            inner.config = None;
+            inner.symbols.clear();
+            inner.evaluated_at = None;
+            return;
+        };
+
+        let mut symbols = HashMap::new();
+        for asset_type in [AssetType::Crypto, AssetType::Stock, AssetType::Tvl] {
+            let default_max_age = slo.max_age.unwrap_or(config.max_age(asset_type));
+            for symbol in config.canonical_symbols(asset_type) {
+                let key = slo_key(asset_type, &symbol);
+                let overrides = slo_override(config, &slo, asset_type, &symbol);
+                let max_age = overrides.and_then(|o| o.max_age).unwrap_or(default_max_age);
+                let target = overrides.and_then(|o| o.target).unwrap_or(slo.target);
+
+                let state = match inner.symbols.remove(&key) {
+                    Some(mut state) => {
+                        state.max_age = max_age;
+                        state.target = target;
+                        state
+                    }
+                    None => SymbolState {
+                        asset_type,
+                        symbol,
+                        max_age,
+                        target,
+                        tracked_since: None,
+                        violations: VecDeque::new(),
+                        age_seconds: None,
+                        met: true,
+                    },
+                };
+                symbols.insert(key, state);
+            }
+        }
+
+        inner.symbols = symbols;
+        inner.config = Some(slo);
+    }
+
+    /// Seconds until the next evaluation, or None while monitoring is off
+    pub fn interval(&self) -> Option<u64> {
+        self.inner
+            .lock()
+            .unwrap()
+            .config
+            .as_ref()
+            .map(|slo| slo.interval)
+    }
+
+    /// Where notices are posted, if anywhere
+    pub fn notify_url(&self) -> Option<String> {
+        self.inner
+            .lock()
+            .unwrap()
+            .config
+            .as_ref()
+            .and_then(|slo| slo.notify_url.clone())
+    }
+
+    /// Check every symbol's price age in `feeds` at `now`, record violations
+    /// and return what changed
+    pub fn evaluate(
+        &self,
+        feeds: &HashMap<String, PriceFeed>,
+        now: DateTime<Utc>,
+    ) -> Vec<SloNotice> {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(window) = inner.config.as_ref().map(|slo| slo.window) else {
+            return Vec::new();
+        };
+        let window_start = now - Duration::seconds(window as i64);
+        let market_hours = inner.market_hours;
+
+        let mut notices = Vec::new();
+        for state in inner.symbols.values_mut() {
+            let tracked_since = *state.tracked_since.get_or_insert(now);
+            let price = feeds.get(state.asset_type.as_str()).and_then(|feed| {
+                feed.prices
+                    .get(&symbols::feed_key(state.asset_type, &state.symbol))
+            });
+            state.age_seconds = price.map(|price| (now - price.timestamp).num_seconds());
+
+            let settled = state.asset_type == AssetType::Stock
+                && market_hours
+                && price.is_some_and(|price| market::is_settled(price.timestamp, now));
+            let fresh = settled
+                || state
+                    .age_seconds
+                    .is_some_and(|age| age <= state.max_age as i64);
+            let event = match (fresh, state.violating()) {
+                (false, false) => {
+                    // Count from when the price actually passed max_age
+                    let start = price
+                        .map(|price| price.timestamp + Duration::seconds(state.max_age as i64))
+                        .unwrap_or(now)
+                        .clamp(tracked_since, now);
+                    if state.violations.len() == MAX_VIOLATIONS {
+                        state.violations.pop_front();
+                    }
+                    state.violations.push_back(SloViolation {
+                        start,
+                        end: None,
+                        duration_seconds: 0,
+                    });
+                    Some(SloEvent::Stale)
+                }
+                (true, true) => {
+                    if let Some(violation) = state.violations.back_mut() {
+                        violation.end = Some(now);
+                    }
+                    Some(SloEvent::Fresh)
+                }
+                _ => None,
+            };
+
+            state
+                .violations
+                .retain(|v| v.end.is_none_or(|end| end >= window_start));
+            for violation in state.violations.iter_mut() {
+                violation.duration_seconds =
+                    (violation.end.unwrap_or(now) - violation.start).num_seconds();
+            }
+
+            let compliance = state.compliance(now, window);
+            if let Some(event) = event {
+                notices.push(state.notice(event, compliance, now));
+            }
+            let met = compliance >= state.target;
+            if met != state.met {
+                state.met = met;
+                let event = if met {
+                    SloEvent::TargetMet
+                } else {
+                    SloEvent::TargetMissed
+                };
+                notices.push(state.notice(event, compliance, now));
+            }
+        }
+        inner.evaluated_at = Some(now);
+        notices
+    }
+
+    /// Compliance of every monitored symbol, or None while monitoring is off
+    pub fn report(&self) -> Option<SloReport> {
+        let inner = self.inner.lock().unwrap();
+        let window = inner.config.as_ref()?.window;
+        let now = inner.evaluated_at.unwrap_or_else(Utc::now);
+
+        let mut statuses: Vec<SloStatus> = inner
+            .symbols
+            .values()
+            .map(|state| {
+                let compliance = state.compliance(now, window);
+                SloStatus {
+                    asset_type: state.asset_type,
+                    symbol: state.symbol.clone(),
+                    max_age: state.max_age,
+                    target: state.target,
+                    compliance,
+                    met: compliance >= state.target,
+                    age_seconds: state.age_seconds,
+                    fresh: !state.violating(),
+                    violations: state.violations.iter().cloned().collect(),
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| {
+            (a.asset_type.as_str(), &a.symbol).cmp(&(b.asset_type.as_str(), &b.symbol))
+        });
+
+        Some(SloReport {
+            window,
+            evaluated_at: inner.evaluated_at,
+            symbols: statuses,
+        })
+    }
+}
+
+fn slo_key(asset_type: AssetType, symbol: &str) -> String {
+    format!(
+        "{}:{}",
+        asset_type.as_str(),
+        symbols::feed_key(asset_type, symbol)
+    )
+}
+
+/// Override for a symbol, matched as "asset_type:symbol" first, then by symbol
+/// alone, either way through the configured aliases
+fn slo_override<'a>(
+    config: &Config,
+    slo: &'a SloConfig,
+    asset_type: AssetType,
+    symbol: &str,
+) -> Option<&'a SloTarget> {
+    let wanted = symbols::feed_key(asset_type, symbol);
+    let matches = |name: &str| {
+        symbols::feed_key(asset_type, &config.canonical_symbol(asset_type, name)) == wanted
+    };
+
+    let qualified = slo.symbols.iter().find(|(key, _)| {
+        key.split_once(':')
+            .is_some_and(|(prefix, name)| prefix == asset_type.as_str() && matches(name))
+    });
+    let bare = || {
+        slo.symbols
+            .iter()
+            .find(|(key, _)| !key.contains(':') && matches(key))
+    };
+    qualified.or_else(bare).map(|(_, overrides)| overrides)
+}
+
+/// Log a notice at the level its event deserves
+pub fn log_notice(notice: &SloNotice) {
+    let age = notice
+        .age_seconds
+        .map(|age| format!("{}s old", age))
+        .unwrap_or_else(|| "missing".to_string());
+    match notice.event {
+        SloEvent::Stale => warn!(
+            "SLO: {} {} is stale ({}, max_age {}s)",
+            notice.asset_type.as_str(),
+            notice.symbol,
+            age,
+            notice.max_age
+        ),
+        SloEvent::Fresh => info!(
+            "SLO: {} {} is fresh again ({})",
+            notice.asset_type.as_str(),
+            notice.symbol,
+            age
+        ),
+        SloEvent::TargetMissed => warn!(
+            "SLO: {} {} compliance {:.3}% is below its {}% target",
+            notice.asset_type.as_str(),
+            notice.symbol,
+            notice.compliance,
+            notice.target
+        ),
+        SloEvent::TargetMet => info!(
+            "SLO: {} {} compliance {:.3}% meets its {}% target again",
+            notice.asset_type.as_str(),
+            notice.symbol,
+            notice.compliance,
+            notice.target
+        ),
+    }
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use log::debug;
+use std::sync::Arc;
+
+use super::{PriceSource, SourceCapabilities};
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::market::MarketState;
+use crate::models::{AssetType, PriceData, StockPriceResponse};
+
+/// Alpha Vantage global quote API (requires an API key)
+pub struct AlphaVantageSource {
+    fetcher: Arc<PriceFetcher>,
+}
+
+impl AlphaVantageSource {
+    pub fn new(fetcher: Arc<PriceFetcher>) -> Self {
+        Self { fetcher }
+    }
+}
+
+#[async_trait]
+impl PriceSource for AlphaVantageSource {
+    fn name(&self) -> &str {
+        "alpha_vantage"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: AssetType::Stock,
+            batch: false,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+        let api_key = self
+            .fetcher
+            .config()
+            .stocks
+            .alpha_vantage_api_key
+            .as_ref()
+            .ok_or_else(|| {
+                OracleError::ConfigError("Alpha Vantage API key not configured".to_string())
+            })?;
+
+        let url = format!(
+            "https://www.alphavantage.co/query?function=GLOBAL_QUOTE&symbol={}&apikey={}",
+            symbol, api_key
+        );
+
+        debug!("Fetching Alpha Vantage price for: {}", symbol);
+
+        let client = self.fetcher.client().clone();
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let response = self.fetcher.send(self.name(), client.get(&url)).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error("Alpha Vantage API error", &response));
+                }
+
+                let stock_response: StockPriceResponse = response.json()?;
+                let quote = stock_response.global_quote;
+
+                let price: f64 = quote
+                    .price
+                    .parse()
+                    .map_err(|_| OracleError::ApiError("Invalid price format".to_string()))?;
+
+                let change: f64 = quote.change.parse().unwrap_or(0.0);
+
+                let change_percent_str = quote.change_percent.trim_end_matches('%');
+                let change_percent: f64 = change_percent_str.parse().unwrap_or(0.0);
+
+                let mut price_data = PriceData::new(quote.symbol, price, self.name().to_string());
+
+                price_data.change_24h = Some(change);
+                price_data.change_24h_percent = Some(change_percent);
+                // Global quotes only cover the regular session
+                price_data.session = Some(MarketState::Open);
+
+                Ok(price_data)
+            })
+            .await
+    }
+}
@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use log::info;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::coingecko_ids::CoinIdResolver;
+use super::{PriceSource, SourceCapabilities};
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::models::{AssetType, PriceData};
+use crate::symbols;
+
+/// Most coins `/coins/markets` returns per page
+const MARKETS_PAGE_SIZE: usize = 250;
+
+/// CoinGecko markets API; one request covers up to 250 symbols
+pub struct CoinGeckoSource {
+    fetcher: Arc<PriceFetcher>,
+    ids: CoinIdResolver,
+}
+
+impl CoinGeckoSource {
+    pub fn new(fetcher: Arc<PriceFetcher>) -> Self {
+        Self {
+            ids: CoinIdResolver::new(fetcher.clone()),
+            fetcher,
+        }
+    }
+
+    /// Fetch prices, market caps and supply from the CoinGecko markets endpoint
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<Vec<PriceData>> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Requested symbols keyed by the coin id they resolve to, so results are
+        // reported under the symbol that was asked for
+        let mut requested: HashMap<String, Vec<String>> = HashMap::new();
+        for (symbol, id) in symbols.iter().zip(self.ids.resolve(symbols).await) {
+            requested
+                .entry(id)
+                .or_default()
+                .push(symbols::normalize_crypto(symbol));
+        }
+
+        let ids: Vec<String> = requested.keys().cloned().collect();
+        let vs_currency = self.fetcher.config().crypto.default_vs_currency.clone();
+
+        let mut prices = Vec::new();
+        for page in ids.chunks(MARKETS_PAGE_SIZE) {
+            for coin in self.fetch_markets(page, &vs_currency).await? {
+                let Some(price) = coin["current_price"].as_f64() else {
+                    continue;
+                };
+                let coin_id = coin["id"].as_str().unwrap_or_default().to_lowercase();
+
+                let symbols = requested.get(&coin_id).into_iter().flatten();
+                for symbol in symbols {
+                    let mut price_data =
+                        PriceData::new(symbol.clone(), price, self.name().to_string());
+
+                    price_data.change_24h = coin["price_change_24h"].as_f64();
+                    price_data.change_24h_percent = coin["price_change_percentage_24h"].as_f64();
+                    price_data.volume_24h = coin["total_volume"].as_f64();
+                    price_data.market_cap = coin["market_cap"].as_f64().filter(|cap| *cap > 0.0);
+                    price_data.circulating_supply = coin["circulating_supply"].as_f64();
+                    price_data.total_supply = coin["total_supply"].as_f64();
+
+                    prices.push(price_data);
+                }
+            }
+        }
+
+        info!(
+            "Successfully fetched {} prices from CoinGecko",
+            prices.len()
+        );
+        Ok(prices)
+    }
+}
+
+impl CoinGeckoSource {
+    /// Market data of up to `MARKETS_PAGE_SIZE` coins: price, 24h change and
+    /// volume, market cap and supply
+    async fn fetch_markets(
+        &self,
+        ids: &[String],
+        vs_currency: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        let url = format!(
+            "https://api.coingecko.com/api/v3/coins/markets?ids={}&vs_currency={}&per_page={}&price_change_percentage=24h",
+            ids.join(","),
+            vs_currency,
+            MARKETS_PAGE_SIZE
+        );
+
+        info!("Fetching CoinGecko prices from: {}", url);
+
+        // Clone API key if available
+        let api_key = self.fetcher.config().crypto.coingecko_api_key.clone();
+        let client = self.fetcher.client().clone();
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let mut request = client
+                    .get(&url)
+                    .header(
+                        "User-Agent",
+                        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+                    )
+                    .header("Accept", "application/json");
+
+                // Add API key if available
+                if let Some(ref key) = api_key {
+                    request = request.header("x-cg-demo-api-key", key);
+                }
+
+                let response = self.fetcher.send(self.name(), request).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error("CoinGecko API error", &response));
+                }
+
+                let coins: Vec<serde_json::Value> = response.json()?;
+                info!("CoinGecko returned data for {} coins", coins.len());
+                Ok(coins)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn name(&self) -> &str {
+        "coingecko"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: AssetType::Crypto,
+            batch: true,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        self.fetch_prices(&[symbol.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| OracleError::PriceNotFound(symbol.to_string()))
+    }
+
+    async fn fetch_batch(&self, symbols: &[String]) -> Result<Vec<PriceData>> {
+        self.fetch_prices(symbols).await
+    }
+
+    fn cache_state(&self) -> Option<serde_json::Value> {
+        Some(self.ids.cache_state())
+    }
+}
@@ -0,0 +1,170 @@
+use log::{info, warn};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::errors::Result;
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::symbols;
+
+/// Wait this long before retrying after a failed coin list download
+const RETRY_AFTER: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Deserialize)]
+struct CoinListEntry {
+    id: String,
+    symbol: String,
+}
+
+#[derive(Default)]
+struct CoinList {
+    ids: HashSet<String>,
+    by_ticker: HashMap<String, Vec<String>>,
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+impl CoinList {
+    fn from_entries(entries: Vec<CoinListEntry>) -> Self {
+        let mut list = Self::default();
+        for entry in entries {
+            let id = entry.id.to_lowercase();
+            list.by_ticker
+                .entry(entry.symbol.to_lowercase())
+                .or_default()
+                .push(id.clone());
+            list.ids.insert(id);
+        }
+        list
+    }
+
+    fn is_due(&self, refresh: Duration) -> bool {
+        let fresh = self.fetched_at.is_some_and(|at| at.elapsed() < refresh);
+        let backing_off = self
+            .attempted_at
+            .is_some_and(|at| at.elapsed() < RETRY_AFTER);
+        !fresh && !backing_off
+    }
+
+    /// Symbols that already are coin ids are kept; tickers shared by several
+    /// coins are left alone, since picking one would be a guess
+    fn resolve(&self, symbol: &str) -> String {
+        if self.ids.contains(symbol) {
+            return symbol.to_string();
+        }
+        match self.by_ticker.get(symbol).map(Vec::as_slice) {
+            Some([id]) => id.clone(),
+            Some(ids) if ids.len() > 1 => {
+                warn!(
+                    "Ticker '{}' matches {} CoinGecko coins ({}); add it to crypto.coingecko_ids",
+                    symbol,
+                    ids.len(),
+                    ids.iter().take(5).cloned().collect::<Vec<_>>().join(", ")
+                );
+                symbol.to_string()
+            }
+            _ => symbol.to_string(),
+        }
+    }
+}
+
+/// Maps crypto tickers (e.g. "btc") to CoinGecko coin ids (e.g. "bitcoin")
+///
+/// Uses `crypto.coingecko_ids` overrides first, then CoinGecko's `/coins/list`,
+/// which is downloaded on first use and again every `crypto.coingecko_id_refresh`
+/// seconds.
+pub struct CoinIdResolver {
+    fetcher: Arc<PriceFetcher>,
+    list: Mutex<CoinList>,
+}
+
+impl CoinIdResolver {
+    pub fn new(fetcher: Arc<PriceFetcher>) -> Self {
+        Self {
+            fetcher,
+            list: Mutex::new(CoinList::default()),
+        }
+    }
+
+    /// CoinGecko id for each of `symbols`, in the same order
+    pub async fn resolve(&self, symbols: &[String]) -> Vec<String> {
+        let crypto = &self.fetcher.config().crypto;
+        let overrides: HashMap<String, &String> = crypto
+            .coingecko_ids
+            .iter()
+            .map(|(ticker, id)| (ticker.to_lowercase(), id))
+            .collect();
+        let symbols: Vec<String> = symbols
+            .iter()
+            .map(|s| symbols::normalize_crypto(s))
+            .collect();
+
+        let mut list = self.list.lock().await;
+        let needs_list = symbols.iter().any(|s| !overrides.contains_key(s));
+        if needs_list && list.is_due(Duration::from_secs(crypto.coingecko_id_refresh)) {
+            self.refresh(&mut list).await;
+        }
+
+        symbols
+            .iter()
+            .map(|symbol| match overrides.get(symbol) {
+                Some(id) => id.to_lowercase(),
+                None => list.resolve(symbol),
+            })
+            .collect()
+    }
+
+    /// Size and age of the cached coin list
+    pub fn cache_state(&self) -> serde_json::Value {
+        match self.list.try_lock() {
+            Ok(list) => serde_json::json!({
+                "coins": list.ids.len(),
+                "tickers": list.by_ticker.len(),
+                "fetched_secs_ago": list.fetched_at.map(|at| at.elapsed().as_secs()),
+                "attempted_secs_ago": list.attempted_at.map(|at| at.elapsed().as_secs()),
+            }),
+            // Held by a refresh in progress
+            Err(_) => serde_json::json!({ "refreshing": true }),
+        }
+    }
+
+    /// Replace the coin list; on failure the previous one stays in use
+    async fn refresh(&self, list: &mut CoinList) {
+        let now = Instant::now();
+        list.attempted_at = Some(now);
+        match self.fetch_coin_list().await {
+            Ok(entries) => {
+                info!("Loaded {} coins from CoinGecko coin list", entries.len());
+                *list = CoinList::from_entries(entries);
+                list.fetched_at = Some(now);
+            }
+            Err(e) => warn!("Failed to refresh CoinGecko coin list: {}", e),
+        }
+    }
+
+    async fn fetch_coin_list(&self) -> Result<Vec<CoinListEntry>> {
+        let url = "https://api.coingecko.com/api/v3/coins/list";
+        let api_key = self.fetcher.config().crypto.coingecko_api_key.clone();
+        let client = self.fetcher.client().clone();
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle("coingecko").await;
+
+                let mut request = client.get(url).header("Accept", "application/json");
+                if let Some(ref key) = api_key {
+                    request = request.header("x-cg-demo-api-key", key);
+                }
+
+                let response = self.fetcher.send("coingecko", request).await?;
+                if !response.status().is_success() {
+                    return Err(status_error("CoinGecko coin list error", &response));
+                }
+
+                response.json::<Vec<CoinListEntry>>()
+            })
+            .await
+    }
+}
@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use log::debug;
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::{PriceSource, SourceCapabilities};
+use crate::config::CustomSourceConfig;
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::models::PriceData;
+use crate::symbols;
+
+/// A JSON HTTP API defined in the `custom_sources` config section
+pub struct CustomSource {
+    name: String,
+    config: CustomSourceConfig,
+    fetcher: Arc<PriceFetcher>,
+}
+
+impl CustomSource {
+    pub fn new(name: &str, config: CustomSourceConfig, fetcher: Arc<PriceFetcher>) -> Self {
+        Self {
+            name: name.to_string(),
+            config,
+            fetcher,
+        }
+    }
+
+    /// Substitute `{symbol}`, `{SYMBOL}` and `{vs_currency}` in a template
+    fn expand(&self, template: &str, symbol: &str) -> String {
+        template
+            .replace("{symbol}", symbol)
+            .replace("{SYMBOL}", &symbol.to_uppercase())
+            .replace(
+                "{vs_currency}",
+                &self.fetcher.config().crypto.default_vs_currency,
+            )
+    }
+
+    /// Read a number at a JSON pointer; numeric strings are accepted too
+    fn number_at(&self, body: &Value, pointer: &str, symbol: &str) -> Option<f64> {
+        match body.pointer(&self.expand(pointer, symbol))? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn optional_number_at(
+        &self,
+        body: &Value,
+        pointer: &Option<String>,
+        symbol: &str,
+    ) -> Option<f64> {
+        self.number_at(body, pointer.as_deref()?, symbol)
+    }
+}
+
+#[async_trait]
+impl PriceSource for CustomSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: self.config.asset_type,
+            batch: false,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+
+        let url = self.expand(&self.config.url, symbol);
+        let headers: Vec<(String, String)> = self
+            .config
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), self.expand(value, symbol)))
+            .collect();
+
+        debug!("Fetching {} price for: {}", self.name, symbol);
+
+        let client = self.fetcher.client().clone();
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let mut request = client.get(&url).header("Accept", "application/json");
+                for (name, value) in &headers {
+                    request = request.header(name.as_str(), value.as_str());
+                }
+
+                let response = self.fetcher.send(self.name(), request).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error(&format!("{} API error", self.name), &response));
+                }
+
+                let body: Value = response.json()?;
+
+                let price = self
+                    .number_at(&body, &self.config.price, symbol)
+                    .ok_or_else(|| {
+                        OracleError::ApiError(format!(
+                            "No price at '{}' in {} response for {}",
+                            self.expand(&self.config.price, symbol),
+                            self.name,
+                            symbol
+                        ))
+                    })?;
+
+                let mut price_data = PriceData::new(
+                    symbols::normalize(self.config.asset_type, symbol),
+                    price,
+                    self.name.clone(),
+                );
+
+                price_data.change_24h = self.optional_number_at(&body, &self.config.change, symbol);
+                price_data.change_24h_percent =
+                    self.optional_number_at(&body, &self.config.change_percent, symbol);
+                price_data.volume_24h = self.optional_number_at(&body, &self.config.volume, symbol);
+
+                Ok(price_data)
+            })
+            .await
+    }
+}
@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use log::{debug, info, warn};
+use std::sync::Arc;
+
+use super::{PriceSource, SourceCapabilities};
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::models::{AssetType, PriceData};
+use crate::symbols;
+
+/// Total value locked of DeFi protocols and chains, in USD, from DefiLlama's
+/// free API
+pub struct DefiLlamaSource {
+    fetcher: Arc<PriceFetcher>,
+}
+
+impl DefiLlamaSource {
+    pub fn new(fetcher: Arc<PriceFetcher>) -> Self {
+        Self { fetcher }
+    }
+
+    /// Current TVL of a protocol, answered by DefiLlama as a bare number
+    async fn fetch_protocol(&self, slug: &str) -> Result<PriceData> {
+        let url = format!("https://api.llama.fi/tvl/{}", slug);
+        let client = self.fetcher.client().clone();
+
+        info!("Fetching DefiLlama TVL for protocol: {}", slug);
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let response = self.fetcher.send(self.name(), client.get(&url)).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error(
+                        &format!("DefiLlama TVL API error for {}", slug),
+                        &response,
+                    ));
+                }
+
+                let tvl: serde_json::Value = response.json()?;
+                let tvl = tvl.as_f64().ok_or_else(|| {
+                    OracleError::ApiError(format!(
+                        "Invalid TVL from DefiLlama for {}: {}",
+                        slug, tvl
+                    ))
+                })?;
+
+                Ok(PriceData::new(
+                    slug.to_string(),
+                    tvl,
+                    self.name().to_string(),
+                ))
+            })
+            .await
+    }
+
+    /// Current TVL of every chain DefiLlama tracks, keyed by normalized chain name
+    async fn fetch_chains(&self) -> Result<Vec<(String, f64)>> {
+        let url = "https://api.llama.fi/v2/chains";
+        let client = self.fetcher.client().clone();
+
+        info!("Fetching DefiLlama chain TVL");
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let response = self.fetcher.send(self.name(), client.get(url)).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error("DefiLlama chains API error", &response));
+                }
+
+                let chains: Vec<serde_json::Value> = response.json()?;
+                debug!("DefiLlama returned {} chains", chains.len());
+
+                Ok(chains
+                    .iter()
+                    .filter_map(|chain| {
+                        let name = chain["name"].as_str()?;
+                        let tvl = chain["tvl"].as_f64()?;
+                        Some((symbols::normalize_tvl(name), tvl))
+                    })
+                    .collect())
+            })
+            .await
+    }
+
+    /// Prices of the chain `symbols` ("chain:ethereum") from one chain listing
+    fn chain_prices(&self, chains: &[(String, f64)], symbols: &[&String]) -> Vec<PriceData> {
+        symbols
+            .iter()
+            .filter_map(|symbol| {
+                let name = symbols::tvl_chain(symbol)?;
+                match chains.iter().find(|(chain, _)| chain == name) {
+                    Some((_, tvl)) => Some(PriceData::new(
+                        symbol.to_string(),
+                        *tvl,
+                        self.name().to_string(),
+                    )),
+                    None => {
+                        warn!("DefiLlama does not track chain '{}'", name);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl PriceSource for DefiLlamaSource {
+    fn name(&self) -> &str {
+        "defillama"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: AssetType::Tvl,
+            batch: false,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        let symbol = symbols::normalize_tvl(symbol);
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+        if symbols::tvl_chain(&symbol).is_none() {
+            return self.fetch_protocol(&symbol).await;
+        }
+
+        let chains = self.fetch_chains().await?;
+        self.chain_prices(&chains, &[&symbol])
+            .pop()
+            .ok_or(OracleError::PriceNotFound(symbol))
+    }
+
+    /// Chains come from a single listing; protocols are fetched in parallel
+    async fn fetch_batch(&self, symbols: &[String]) -> Result<Vec<PriceData>> {
+        let normalized: Vec<String> = symbols
+            .iter()
+            .map(|symbol| symbols::normalize_tvl(symbol))
+            .filter(|symbol| !symbol.is_empty())
+            .collect();
+        let (chains, protocols): (Vec<&String>, Vec<&String>) = normalized
+            .iter()
+            .partition(|symbol| symbols::tvl_chain(symbol).is_some());
+
+        let mut prices = Vec::new();
+        let mut last_error = None;
+        if !chains.is_empty() {
+            match self.fetch_chains().await {
+                Ok(listing) => prices.extend(self.chain_prices(&listing, &chains)),
+                Err(e) => {
+                    warn!("{} failed for chains: {}", self.name(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let results = join_all(protocols.iter().map(|slug| self.fetch_protocol(slug))).await;
+        for (slug, result) in protocols.iter().zip(results) {
+            match result {
+                Ok(price_data) => prices.push(price_data),
+                Err(e) => {
+                    warn!("{} failed for {}: {}", self.name(), slug, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if prices.is_empty() => Err(e),
+            _ => Ok(prices),
+        }
+    }
+}
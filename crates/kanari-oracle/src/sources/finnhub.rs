@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use log::debug;
+use std::sync::Arc;
+
+use super::{PriceSource, SourceCapabilities};
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::market::MarketState;
+use crate::models::{AssetType, PriceData};
+use crate::symbols;
+
+/// Finnhub quote API (requires an API key)
+pub struct FinnhubSource {
+    fetcher: Arc<PriceFetcher>,
+}
+
+impl FinnhubSource {
+    pub fn new(fetcher: Arc<PriceFetcher>) -> Self {
+        Self { fetcher }
+    }
+}
+
+#[async_trait]
+impl PriceSource for FinnhubSource {
+    fn name(&self) -> &str {
+        "finnhub"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: AssetType::Stock,
+            batch: false,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+        let api_key = self
+            .fetcher
+            .config()
+            .stocks
+            .finnhub_api_key
+            .as_ref()
+            .ok_or_else(|| {
+                OracleError::ConfigError("Finnhub API key not configured".to_string())
+            })?;
+
+        let url = format!(
+            "https://finnhub.io/api/v1/quote?symbol={}&token={}",
+            symbol, api_key
+        );
+
+        debug!("Fetching Finnhub price for: {}", symbol);
+
+        let client = self.fetcher.client().clone();
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let response = self.fetcher.send(self.name(), client.get(&url)).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error("Finnhub API error", &response));
+                }
+
+                let quote: serde_json::Value = response.json()?;
+
+                let current_price = quote["c"].as_f64().ok_or_else(|| {
+                    OracleError::ApiError("Invalid price data from Finnhub".to_string())
+                })?;
+
+                let change = quote["d"].as_f64().unwrap_or(0.0);
+                let change_percent = quote["dp"].as_f64().unwrap_or(0.0);
+
+                let mut price_data = PriceData::new(
+                    symbols::normalize_stock(symbol),
+                    current_price,
+                    self.name().to_string(),
+                );
+
+                price_data.change_24h = Some(change);
+                price_data.change_24h_percent = Some(change_percent);
+                // The quote endpoint ignores extended-hours trades
+                price_data.session = Some(MarketState::Open);
+
+                Ok(price_data)
+            })
+            .await
+    }
+}
@@ -0,0 +1,193 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::{PriceSource, SourceCapabilities};
+use crate::config::Config;
+use crate::errors::{OracleError, Result};
+use crate::models::{AssetType, PriceData};
+use crate::symbols;
+
+/// One recorded price in a [`Fixture`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixturePrice {
+    pub symbol: String,
+    pub price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_24h: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_24h_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_24h: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_cap: Option<f64>,
+    /// Source the price was recorded from, which is the only one replaying it;
+    /// every source replays it when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl FixturePrice {
+    pub fn new(symbol: &str, price: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            price,
+            change_24h: None,
+            change_24h_percent: None,
+            volume_24h: None,
+            market_cap: None,
+            source: None,
+        }
+    }
+}
+
+impl From<&PriceData> for FixturePrice {
+    fn from(price_data: &PriceData) -> Self {
+        Self {
+            symbol: price_data.symbol.clone(),
+            price: price_data.price,
+            change_24h: price_data.change_24h,
+            change_24h_percent: price_data.change_24h_percent,
+            volume_24h: price_data.volume_24h,
+            market_cap: price_data.market_cap,
+            source: Some(price_data.source.clone()),
+        }
+    }
+}
+
+/// Prices recorded for offline use, as written by `kanari record`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub crypto: Vec<FixturePrice>,
+    #[serde(default)]
+    pub stocks: Vec<FixturePrice>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tvl: Vec<FixturePrice>,
+}
+
+impl Fixture {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            OracleError::ConfigError(format!("Failed to read fixture '{}': {}", path, e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            OracleError::ConfigError(format!("Failed to parse fixture '{}': {}", path, e))
+        })
+    }
+
+    pub fn prices(&self, asset_type: AssetType) -> &[FixturePrice] {
+        match asset_type {
+            AssetType::Crypto => &self.crypto,
+            AssetType::Stock => &self.stocks,
+            AssetType::Tvl => &self.tvl,
+            AssetType::Basket => &[],
+        }
+    }
+}
+
+/// Serves fixed prices without any upstream request, for offline mode and for
+/// tests of code built on the oracle. Prices are timestamped when served, so
+/// they never go stale.
+pub struct MockSource {
+    name: String,
+    asset_type: AssetType,
+    /// Keyed by `symbols::feed_key`
+    prices: RwLock<HashMap<String, FixturePrice>>,
+}
+
+impl MockSource {
+    pub fn new(name: &str, asset_type: AssetType) -> Self {
+        Self {
+            name: name.to_string(),
+            asset_type,
+            prices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Source serving the fixture's prices of `asset_type` that were recorded
+    /// from `name` or from no particular source. Symbols are stored under their
+    /// canonical spelling in `config`, the one the oracle requests.
+    pub fn from_fixture(
+        name: &str,
+        asset_type: AssetType,
+        fixture: &Fixture,
+        config: &Config,
+    ) -> Self {
+        let source = Self::new(name, asset_type);
+        {
+            let mut prices = source.prices.write().unwrap();
+            for price in fixture.prices(asset_type) {
+                if price.source.as_deref().is_some_and(|s| s != name) {
+                    continue;
+                }
+                let canonical = config.canonical_symbol(asset_type, &price.symbol);
+                prices.insert(symbols::feed_key(asset_type, &canonical), price.clone());
+            }
+        }
+        source
+    }
+
+    /// Serve `price` for `symbol` from now on
+    pub fn set_price(&self, symbol: &str, price: f64) {
+        self.prices.write().unwrap().insert(
+            symbols::feed_key(self.asset_type, symbol),
+            FixturePrice::new(symbol, price),
+        );
+    }
+
+    /// Stop serving `symbol`, so requests for it fail as with a real provider
+    pub fn remove_price(&self, symbol: &str) {
+        self.prices
+            .write()
+            .unwrap()
+            .remove(&symbols::feed_key(self.asset_type, symbol));
+    }
+
+    fn quote(&self, symbol: &str) -> Option<PriceData> {
+        let prices = self.prices.read().unwrap();
+        let recorded = prices.get(&symbols::feed_key(self.asset_type, symbol))?;
+
+        let mut price_data = PriceData::new(
+            symbols::normalize(self.asset_type, symbol),
+            recorded.price,
+            self.name.clone(),
+        );
+        price_data.change_24h = recorded.change_24h;
+        price_data.change_24h_percent = recorded.change_24h_percent;
+        price_data.volume_24h = recorded.volume_24h;
+        price_data.market_cap = recorded.market_cap;
+        Some(price_data)
+    }
+}
+
+#[async_trait]
+impl PriceSource for MockSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: self.asset_type,
+            batch: true,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+        self.quote(symbol)
+            .ok_or_else(|| OracleError::PriceNotFound(symbol.to_string()))
+    }
+
+    async fn fetch_batch(&self, symbols: &[String]) -> Result<Vec<PriceData>> {
+        Ok(symbols.iter().filter_map(|s| self.quote(s)).collect())
+    }
+
+    fn cache_state(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({ "prices": self.prices.read().unwrap().len() }))
+    }
+}
@@ -0,0 +1,926 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::audit::AuditLog;
+use crate::config::{Config, FallbackPolicy, symbol_sources};
+use crate::errors::{OracleError, Result};
+use crate::fetchers::PriceFetcher;
+use crate::fetchers::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::market::MarketState;
+use crate::models::{AssetType, PriceData};
+use crate::price_math;
+use crate::provenance::{AttemptOutcome, SourceAttempt};
+use crate::symbols;
+
+pub mod alpha_vantage;
+pub mod binance;
+pub mod coingecko;
+pub mod coingecko_ids;
+pub mod custom;
+pub mod defillama;
+pub mod finnhub;
+pub mod mock;
+pub mod scoring;
+pub mod simulated;
+pub mod yahoo_finance;
+
+pub use alpha_vantage::AlphaVantageSource;
+pub use binance::BinanceSource;
+pub use coingecko::CoinGeckoSource;
+pub use coingecko_ids::CoinIdResolver;
+pub use custom::CustomSource;
+pub use defillama::DefiLlamaSource;
+pub use finnhub::FinnhubSource;
+pub use mock::{Fixture, MockSource};
+pub use simulated::{SimulatedMarket, SimulatedSource};
+pub use yahoo_finance::YahooFinanceSource;
+
+use scoring::{MIN_SAMPLES, SourceScore};
+
+/// Source of prices combined by [`FallbackPolicy::AllAggregate`]
+pub const AGGREGATE_SOURCE: &str = "aggregate";
+
+/// What a source can do, used by the registry to plan requests
+#[derive(Debug, Clone, Copy)]
+pub struct SourceCapabilities {
+    pub asset_type: AssetType,
+    /// Whether `fetch_batch` sends a single upstream request for many symbols
+    pub batch: bool,
+}
+
+/// Point-in-time health of a source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceHealth {
+    pub healthy: bool,
+    pub detail: Option<String>,
+    pub circuit: CircuitState,
+    /// Share of recent requests that succeeded
+    pub success_rate: Option<f64>,
+    /// Mean latency of recent requests
+    pub avg_latency_ms: Option<u64>,
+    /// Routing score in (0, 1]; higher-scoring sources are tried first
+    pub score: Option<f64>,
+}
+
+impl SourceHealth {
+    pub fn healthy() -> Self {
+        Self {
+            healthy: true,
+            detail: None,
+            circuit: CircuitState::Closed,
+            success_rate: None,
+            avg_latency_ms: None,
+            score: None,
+        }
+    }
+}
+
+/// Health of one registered source, as reported by the registry
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStatus {
+    pub name: String,
+    #[serde(flatten)]
+    pub health: SourceHealth,
+}
+
+/// Rolling request statistics of one registered source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStats {
+    pub name: String,
+    pub asset_type: AssetType,
+    pub circuit: CircuitState,
+    /// Requests in the rolling window the rates and latencies below cover
+    pub samples: usize,
+    pub error_rate: f64,
+    pub avg_latency_ms: u64,
+    pub p50_latency_ms: u64,
+    pub p95_latency_ms: u64,
+    /// Requests since start
+    pub requests_total: u64,
+    pub errors_total: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+/// An upstream price provider
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Name used in config and in `PriceData::source` (e.g. "binance")
+    fn name(&self) -> &str;
+
+    fn capabilities(&self) -> SourceCapabilities;
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData>;
+
+    /// Fetch several symbols; symbols that fail are left out of the result.
+    /// The default fans out to `fetch_one`.
+    async fn fetch_batch(&self, symbols: &[String]) -> Result<Vec<PriceData>> {
+        let results = join_all(symbols.iter().map(|s| self.fetch_one(s))).await;
+
+        let mut prices = Vec::new();
+        let mut last_error = None;
+        for (symbol, result) in symbols.iter().zip(results) {
+            match result {
+                Ok(price_data) => prices.push(price_data),
+                Err(e) => {
+                    warn!("{} failed for {}: {}", self.name(), symbol, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if prices.is_empty() => Err(e),
+            _ => Ok(prices),
+        }
+    }
+
+    fn health(&self) -> SourceHealth {
+        SourceHealth::healthy()
+    }
+
+    /// Summary of the source's internal caches, for troubleshooting
+    fn cache_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+/// Names of the enabled sources with their asset type, in priority order
+fn enabled_sources(config: &Config) -> impl Iterator<Item = (&str, AssetType)> {
+    let crypto = config.crypto_source_order().into_iter();
+    let stock = config.stock_source_order().into_iter();
+    let tvl = config.tvl_source_order().into_iter();
+    crypto
+        .map(|name| (name, AssetType::Crypto))
+        .chain(stock.map(|name| (name, AssetType::Stock)))
+        .chain(tvl.map(|name| (name, AssetType::Tvl)))
+}
+
+/// Result of fetching several symbols through their source chains
+#[derive(Debug, Default)]
+pub struct BatchFetch {
+    /// Prices found, each with the sources tried for it
+    pub prices: Vec<(PriceData, Vec<SourceAttempt>)>,
+    /// Symbols no source priced, with the sources tried for each
+    pub missing: Vec<(String, Vec<SourceAttempt>)>,
+}
+
+/// Ordered set of sources the oracle fetches from, each behind a circuit breaker
+pub struct SourceRegistry {
+    sources: Vec<Arc<dyn PriceSource>>,
+    breakers: HashMap<String, CircuitBreaker>,
+    scores: HashMap<String, SourceScore>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    adaptive: bool,
+    fallback: HashMap<AssetType, FallbackPolicy>,
+    audit: Option<AuditLog>,
+}
+
+impl Default for SourceRegistry {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(60))
+    }
+}
+
+impl SourceRegistry {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            sources: Vec::new(),
+            breakers: HashMap::new(),
+            scores: HashMap::new(),
+            failure_threshold,
+            cooldown,
+            adaptive: false,
+            fallback: HashMap::new(),
+            audit: None,
+        }
+    }
+
+    /// Whether the default source order follows recent success rate and latency
+    pub fn with_adaptive_routing(mut self, enabled: bool) -> Self {
+        self.adaptive = enabled;
+        self
+    }
+
+    /// How the sources tried for a symbol of `asset_type` are combined
+    pub fn with_fallback(mut self, asset_type: AssetType, policy: FallbackPolicy) -> Self {
+        self.fallback.insert(asset_type, policy);
+        self
+    }
+
+    pub fn fallback(&self, asset_type: AssetType) -> FallbackPolicy {
+        self.fallback.get(&asset_type).copied().unwrap_or_default()
+    }
+
+    /// Write every upstream request to `audit`
+    pub fn with_audit(mut self, audit: Option<AuditLog>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Built-in and custom sources that are enabled and usable with the fetcher's
+    /// config, in priority order. Key-based providers are skipped without an API key.
+    pub fn from_config(fetcher: Arc<PriceFetcher>) -> Self {
+        let config = fetcher.config();
+        let mut registry = Self::configured(config);
+
+        let names = config
+            .crypto_source_order()
+            .into_iter()
+            .chain(config.stock_source_order())
+            .chain(config.tvl_source_order());
+
+        for name in names {
+            let source: Arc<dyn PriceSource> = match name {
+                "coingecko" => Arc::new(CoinGeckoSource::new(fetcher.clone())),
+                "binance" => Arc::new(BinanceSource::new(fetcher.clone())),
+                "alpha_vantage" if config.stocks.alpha_vantage_api_key.is_some() => {
+                    Arc::new(AlphaVantageSource::new(fetcher.clone()))
+                }
+                "finnhub" if config.stocks.finnhub_api_key.is_some() => {
+                    Arc::new(FinnhubSource::new(fetcher.clone()))
+                }
+                "yahoo_finance" => Arc::new(YahooFinanceSource::new(fetcher.clone())),
+                "defillama" => Arc::new(DefiLlamaSource::new(fetcher.clone())),
+                _ => match config.custom_sources.get(name) {
+                    Some(custom) => {
+                        Arc::new(CustomSource::new(name, custom.clone(), fetcher.clone()))
+                    }
+                    None => continue,
+                },
+            };
+            registry.register(source);
+        }
+
+        registry
+    }
+
+    /// Every enabled source replaced by a [`MockSource`] replaying `fixture`, for
+    /// offline mode. Key-based providers are included even without an API key.
+    pub fn from_fixture(config: &Config, fixture: &Fixture) -> Self {
+        let mut registry = Self::configured(config);
+        for (name, asset_type) in enabled_sources(config) {
+            registry.register(Arc::new(MockSource::from_fixture(
+                name, asset_type, fixture, config,
+            )));
+        }
+        registry
+    }
+
+    /// Every enabled source replaced by a [`SimulatedSource`] quoting `market`,
+    /// for sandbox mode
+    pub fn simulated(config: &Config, market: &Arc<SimulatedMarket>) -> Self {
+        let mut registry = Self::configured(config);
+        for (name, asset_type) in enabled_sources(config) {
+            registry.register(Arc::new(SimulatedSource::new(
+                name,
+                asset_type,
+                market.clone(),
+            )));
+        }
+        registry
+    }
+
+    /// Empty registry with the config's circuit breaker, routing and fallback settings
+    fn configured(config: &Config) -> Self {
+        Self::new(
+            config.general.circuit_failure_threshold,
+            Duration::from_secs(config.general.circuit_cooldown),
+        )
+        .with_adaptive_routing(config.general.adaptive_routing)
+        .with_fallback(AssetType::Crypto, config.crypto.fallback)
+        .with_fallback(AssetType::Stock, config.stocks.fallback)
+        .with_fallback(AssetType::Tvl, config.tvl.fallback)
+    }
+
+    /// Add a source after the existing ones
+    pub fn register(&mut self, source: Arc<dyn PriceSource>) {
+        self.breakers.insert(
+            source.name().to_string(),
+            CircuitBreaker::new(self.failure_threshold, self.cooldown),
+        );
+        self.scores
+            .insert(source.name().to_string(), SourceScore::default());
+        self.sources.push(source);
+    }
+
+    /// Whether the source's circuit lets a request through
+    fn allows(&self, name: &str) -> bool {
+        let allowed = self.breakers.get(name).is_none_or(|b| b.allow());
+        if !allowed {
+            info!("Skipping {}: circuit open", name);
+        }
+        allowed
+    }
+
+    fn audit(&self, asset_type: AssetType, symbol: &str, attempt: &SourceAttempt) {
+        if let Some(audit) = &self.audit {
+            audit.record(asset_type, symbol, attempt);
+        }
+    }
+
+    fn record<T>(&self, name: &str, result: &Result<T>, latency: Duration) {
+        if let Some(score) = self.scores.get(name) {
+            let error = result.as_ref().err().map(|e| e.to_string());
+            score.record(error, latency);
+        }
+
+        let Some(breaker) = self.breakers.get(name) else {
+            return;
+        };
+        match result {
+            Ok(_) => breaker.record_success(),
+            Err(_) => {
+                if breaker.record_failure() {
+                    warn!(
+                        "Circuit opened for {} after repeated failures; skipping it for {}s",
+                        name,
+                        self.cooldown.as_secs()
+                    );
+                }
+            }
+        }
+    }
+
+    /// Health of every registered source, including its circuit state
+    pub fn health(&self) -> Vec<SourceStatus> {
+        self.sources
+            .iter()
+            .map(|source| {
+                let mut health = source.health();
+                if let Some(breaker) = self.breakers.get(source.name()) {
+                    health.circuit = breaker.state();
+                    if health.circuit != CircuitState::Closed {
+                        health.healthy = false;
+                        health.detail = Some(format!(
+                            "Circuit {} after {} consecutive failures",
+                            if health.circuit == CircuitState::Open {
+                                "open"
+                            } else {
+                                "half-open"
+                            },
+                            breaker.failures()
+                        ));
+                    }
+                }
+                if let Some(score) = self.scores.get(source.name()) {
+                    let snapshot = score.snapshot();
+                    if snapshot.samples > 0 {
+                        health.success_rate = Some(snapshot.success_rate);
+                        health.avg_latency_ms = Some(snapshot.avg_latency.as_millis() as u64);
+                        health.score = Some(snapshot.score());
+                    }
+                    if health.healthy
+                        && snapshot.samples >= MIN_SAMPLES
+                        && snapshot.success_rate < 0.5
+                    {
+                        health.healthy = false;
+                        health.detail = Some(format!(
+                            "{:.0}% of the last {} requests failed",
+                            (1.0 - snapshot.success_rate) * 100.0,
+                            snapshot.samples
+                        ));
+                    }
+                }
+                SourceStatus {
+                    name: source.name().to_string(),
+                    health,
+                }
+            })
+            .collect()
+    }
+
+    /// Latency and error statistics of every registered source
+    pub fn stats(&self) -> Vec<SourceStats> {
+        self.sources
+            .iter()
+            .map(|source| {
+                let name = source.name();
+                let snapshot = self
+                    .scores
+                    .get(name)
+                    .map(SourceScore::snapshot)
+                    .unwrap_or_else(|| SourceScore::default().snapshot());
+                let (last_error_at, last_error) = snapshot.last_error.unzip();
+                SourceStats {
+                    name: name.to_string(),
+                    asset_type: source.capabilities().asset_type,
+                    circuit: self
+                        .breakers
+                        .get(name)
+                        .map(CircuitBreaker::state)
+                        .unwrap_or(CircuitState::Closed),
+                    samples: snapshot.samples,
+                    error_rate: 1.0 - snapshot.success_rate,
+                    avg_latency_ms: snapshot.avg_latency.as_millis() as u64,
+                    p50_latency_ms: snapshot.p50_latency.as_millis() as u64,
+                    p95_latency_ms: snapshot.p95_latency.as_millis() as u64,
+                    requests_total: snapshot.requests,
+                    errors_total: snapshot.failures,
+                    last_error,
+                    last_error_at,
+                }
+            })
+            .collect()
+    }
+
+    /// Cache summaries of the sources that keep any, keyed by source name
+    pub fn cache_states(&self) -> HashMap<String, serde_json::Value> {
+        self.sources
+            .iter()
+            .filter_map(|source| Some((source.name().to_string(), source.cache_state()?)))
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn PriceSource>> {
+        self.sources.iter().find(|s| s.name() == name)
+    }
+
+    pub fn sources(&self) -> &[Arc<dyn PriceSource>] {
+        &self.sources
+    }
+
+    /// Sources for an asset type, in priority order
+    pub fn for_asset(&self, asset_type: AssetType) -> Vec<Arc<dyn PriceSource>> {
+        self.sources
+            .iter()
+            .filter(|s| s.capabilities().asset_type == asset_type)
+            .cloned()
+            .collect()
+    }
+
+    /// Sources for an asset type in the order they are tried: by routing score when
+    /// adaptive routing is on, keeping priority order among similar scores
+    pub fn ranked_for(&self, asset_type: AssetType) -> Vec<Arc<dyn PriceSource>> {
+        let mut sources = self.for_asset(asset_type);
+        if self.adaptive {
+            // Scores are compared in tenths so noise does not override configured priority
+            sources.sort_by_key(|source| {
+                let score = self
+                    .scores
+                    .get(source.name())
+                    .map(|s| s.snapshot().score())
+                    .unwrap_or(1.0);
+                std::cmp::Reverse((score * 10.0).round() as i64)
+            });
+        }
+        sources
+    }
+
+    /// Names of the sources for an asset type, in the order they are tried
+    pub fn names_for(&self, asset_type: AssetType) -> Vec<String> {
+        self.ranked_for(asset_type)
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect()
+    }
+
+    /// Fetch a symbol from one named source, even if its circuit is open
+    pub async fn fetch_from(&self, source: &str, symbol: &str) -> Result<PriceData> {
+        match self.get(source) {
+            Some(found) => {
+                let started = Instant::now();
+                let result = found.fetch_one(symbol).await;
+                let latency = started.elapsed();
+                self.record(source, &result, latency);
+
+                let attempt = match &result {
+                    Ok(_) => SourceAttempt::new(source, AttemptOutcome::Used),
+                    Err(e) => SourceAttempt::new(source, AttemptOutcome::Failed).with_error(e),
+                };
+                self.audit(
+                    found.capabilities().asset_type,
+                    symbol,
+                    &attempt.with_latency(latency),
+                );
+                result
+            }
+            None => Err(OracleError::ConfigError(format!(
+                "Source '{}' is not available",
+                source
+            ))),
+        }
+    }
+
+    /// Price a symbol from `sources` as the asset type's fallback policy says,
+    /// along with every source tried on the way
+    pub async fn fetch_with_fallback(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        sources: &[String],
+    ) -> (Result<PriceData>, Vec<SourceAttempt>) {
+        match self.fallback(asset_type) {
+            FallbackPolicy::Ordered => self.fetch_ordered(symbol, sources).await,
+            FallbackPolicy::Race | FallbackPolicy::AllAggregate => {
+                self.fetch_concurrently(asset_type, symbol, sources).await
+            }
+        }
+    }
+
+    /// Try sources in the given order, returning the first successful price
+    async fn fetch_ordered(
+        &self,
+        symbol: &str,
+        sources: &[String],
+    ) -> (Result<PriceData>, Vec<SourceAttempt>) {
+        let mut attempts = Vec::new();
+        let mut last_error = None;
+        let mut skipped = Vec::new();
+
+        for name in sources {
+            let Some(source) = self.get(name) else {
+                continue;
+            };
+            if !self.allows(name) {
+                skipped.push(name.as_str());
+                attempts.push(SourceAttempt::new(name, AttemptOutcome::Skipped));
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = source.fetch_one(symbol).await;
+            let latency = started.elapsed();
+            self.record(name, &result, latency);
+            let asset_type = source.capabilities().asset_type;
+            match result {
+                Ok(price_data) => {
+                    let attempt =
+                        SourceAttempt::new(name, AttemptOutcome::Used).with_latency(latency);
+                    self.audit(asset_type, symbol, &attempt);
+                    attempts.push(attempt);
+                    return (Ok(price_data), attempts);
+                }
+                Err(e) => {
+                    warn!("Source {} failed for {}: {}", name, symbol, e);
+                    let attempt = SourceAttempt::new(name, AttemptOutcome::Failed)
+                        .with_latency(latency)
+                        .with_error(&e);
+                    self.audit(asset_type, symbol, &attempt);
+                    attempts.push(attempt);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        (Err(chain_error(symbol, last_error, &skipped)), attempts)
+    }
+
+    /// Ask every source at once. A race returns the first price to arrive and
+    /// drops the requests still running; an aggregate waits for all of them.
+    async fn fetch_concurrently(
+        &self,
+        asset_type: AssetType,
+        symbol: &str,
+        sources: &[String],
+    ) -> (Result<PriceData>, Vec<SourceAttempt>) {
+        let policy = self.fallback(asset_type);
+        let mut attempts = Vec::new();
+        let mut skipped = Vec::new();
+        let mut pending = FuturesUnordered::new();
+
+        for name in sources {
+            let Some(source) = self.get(name) else {
+                continue;
+            };
+            if !self.allows(name) {
+                skipped.push(name.as_str());
+                attempts.push(SourceAttempt::new(name, AttemptOutcome::Skipped));
+                continue;
+            }
+            pending.push(async move {
+                let started = Instant::now();
+                let result = source.fetch_one(symbol).await;
+                (name, result, started.elapsed())
+            });
+        }
+
+        let mut quotes = Vec::new();
+        let mut last_error = None;
+        while let Some((name, result, latency)) = pending.next().await {
+            self.record(name, &result, latency);
+            let attempt = match result {
+                Ok(price_data) => {
+                    quotes.push(price_data);
+                    SourceAttempt::new(name, AttemptOutcome::Used).with_latency(latency)
+                }
+                Err(e) => {
+                    warn!("Source {} failed for {}: {}", name, symbol, e);
+                    let attempt = SourceAttempt::new(name, AttemptOutcome::Failed)
+                        .with_latency(latency)
+                        .with_error(&e);
+                    last_error = Some(e);
+                    attempt
+                }
+            };
+            self.audit(asset_type, symbol, &attempt);
+            attempts.push(attempt);
+            if policy == FallbackPolicy::Race && !quotes.is_empty() {
+                break;
+            }
+        }
+
+        if policy == FallbackPolicy::AllAggregate {
+            sort_by_chain(&mut attempts, sources, |attempt| &attempt.source);
+            sort_by_chain(&mut quotes, sources, |quote| &quote.source);
+        }
+        match aggregate(quotes) {
+            Some(price_data) => (Ok(price_data), attempts),
+            None => (Err(chain_error(symbol, last_error, &skipped)), attempts),
+        }
+    }
+
+    /// Fetch every symbol of an asset type. Symbols are grouped by the sources they
+    /// are tried from (`ranked_for` order, or their configured source preference) and
+    /// each group walks its sources with `fetch_batch`, so a batch-capable source gets
+    /// one request per group and only per-symbol sources fan out. Symbols no source
+    /// priced are returned as `missing`, with the attempts that failed them.
+    pub async fn fetch_all(
+        &self,
+        asset_type: AssetType,
+        symbols: &[String],
+        preferences: &HashMap<String, Vec<String>>,
+    ) -> BatchFetch {
+        let default_chain = self.names_for(asset_type);
+        let mut groups: Vec<(&[String], Vec<String>)> = Vec::new();
+
+        for symbol in symbols.iter().filter(|s| !s.is_empty()) {
+            let chain = symbol_sources(preferences, symbol).unwrap_or(&default_chain);
+            match groups.iter_mut().find(|(sources, _)| *sources == chain) {
+                Some((_, members)) => members.push(symbol.clone()),
+                None => groups.push((chain, vec![symbol.clone()])),
+            }
+        }
+
+        let results = join_all(groups.into_iter().map(|(chain, members)| async move {
+            match self.fallback(asset_type) {
+                FallbackPolicy::Ordered => self.fetch_chain(asset_type, chain, members).await,
+                FallbackPolicy::Race | FallbackPolicy::AllAggregate => {
+                    self.fetch_chain_concurrently(asset_type, chain, members)
+                        .await
+                }
+            }
+        }))
+        .await;
+
+        let mut batch = BatchFetch::default();
+        for result in results {
+            batch.prices.extend(result.prices);
+            batch.missing.extend(result.missing);
+        }
+        batch
+    }
+
+    /// Walk `chain` in order, each source covering the symbols the previous ones
+    /// missed. Every price comes with the sources tried for its symbol.
+    async fn fetch_chain(
+        &self,
+        asset_type: AssetType,
+        chain: &[String],
+        mut missing: Vec<String>,
+    ) -> BatchFetch {
+        let key = |symbol: &str| symbols::feed_key(asset_type, symbol);
+        let mut prices = Vec::new();
+        let mut attempts: HashMap<String, Vec<SourceAttempt>> = HashMap::new();
+        let mut record_attempt = |symbol: &str, attempt: SourceAttempt| {
+            self.audit(asset_type, symbol, &attempt);
+            attempts.entry(key(symbol)).or_default().push(attempt);
+        };
+
+        for name in chain {
+            if missing.is_empty() {
+                break;
+            }
+            let Some(source) = self.get(name) else {
+                continue;
+            };
+            if !self.allows(name) {
+                for symbol in &missing {
+                    record_attempt(symbol, SourceAttempt::new(name, AttemptOutcome::Skipped));
+                }
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = source.fetch_batch(&missing).await;
+            let latency = started.elapsed();
+            self.record(name, &result, latency);
+            match result {
+                Ok(fetched) => {
+                    info!("Fetched {} prices from {}", fetched.len(), name);
+                    let fetched_symbols: HashSet<String> =
+                        fetched.iter().map(|p| key(&p.symbol)).collect();
+                    for symbol in &missing {
+                        let outcome = if fetched_symbols.contains(&key(symbol)) {
+                            AttemptOutcome::Used
+                        } else {
+                            AttemptOutcome::Missing
+                        };
+                        record_attempt(
+                            symbol,
+                            SourceAttempt::new(name, outcome).with_latency(latency),
+                        );
+                    }
+                    missing.retain(|s| !fetched_symbols.contains(&key(s)));
+                    prices.extend(fetched);
+                }
+                Err(e) => {
+                    warn!("{} failed: {}", name, e);
+                    for symbol in &missing {
+                        record_attempt(
+                            symbol,
+                            SourceAttempt::new(name, AttemptOutcome::Failed)
+                                .with_latency(latency)
+                                .with_error(&e),
+                        );
+                    }
+                }
+            }
+        }
+
+        for symbol in &missing {
+            warn!("All sources failed for {}", symbol);
+        }
+
+        let prices = prices
+            .into_iter()
+            .map(|price_data| {
+                let trail = attempts
+                    .remove(&key(&price_data.symbol))
+                    .unwrap_or_default();
+                (price_data, trail)
+            })
+            .collect();
+        let missing = missing
+            .into_iter()
+            .map(|symbol| {
+                let trail = attempts.remove(&key(&symbol)).unwrap_or_default();
+                (symbol, trail)
+            })
+            .collect();
+        BatchFetch { prices, missing }
+    }
+
+    /// Ask every source of `chain` for all `members` at once. A race takes each
+    /// symbol from the first source to answer with it and stops once every symbol
+    /// has a price; an aggregate publishes the median of every answer.
+    async fn fetch_chain_concurrently(
+        &self,
+        asset_type: AssetType,
+        chain: &[String],
+        members: Vec<String>,
+    ) -> BatchFetch {
+        let policy = self.fallback(asset_type);
+        let key = |symbol: &str| symbols::feed_key(asset_type, symbol);
+        let mut quotes: HashMap<String, Vec<PriceData>> = HashMap::new();
+        let mut attempts: HashMap<String, Vec<SourceAttempt>> = HashMap::new();
+        let mut record_attempt = |symbol: &str, attempt: SourceAttempt| {
+            self.audit(asset_type, symbol, &attempt);
+            attempts.entry(key(symbol)).or_default().push(attempt);
+        };
+
+        let members = &members;
+        let mut pending = FuturesUnordered::new();
+        for name in chain {
+            let Some(source) = self.get(name) else {
+                continue;
+            };
+            if !self.allows(name) {
+                for symbol in members {
+                    record_attempt(symbol, SourceAttempt::new(name, AttemptOutcome::Skipped));
+                }
+                continue;
+            }
+            pending.push(async move {
+                let started = Instant::now();
+                let result = source.fetch_batch(members).await;
+                (name, result, started.elapsed())
+            });
+        }
+
+        // Symbols without a price yet; in a race, later answers for the others
+        // are ignored
+        let mut missing: HashSet<String> = members.iter().map(|s| key(s)).collect();
+        while let Some((name, result, latency)) = pending.next().await {
+            self.record(name, &result, latency);
+            let open = |symbol: &String| {
+                policy == FallbackPolicy::AllAggregate || missing.contains(&key(symbol))
+            };
+            let open: Vec<&String> = members.iter().filter(|s| open(s)).collect();
+            match result {
+                Ok(fetched) => {
+                    info!("Fetched {} prices from {}", fetched.len(), name);
+                    let mut fetched: HashMap<String, PriceData> =
+                        fetched.into_iter().map(|p| (key(&p.symbol), p)).collect();
+                    for symbol in open {
+                        let outcome = match fetched.remove(&key(symbol)) {
+                            Some(price_data) => {
+                                quotes.entry(key(symbol)).or_default().push(price_data);
+                                missing.remove(&key(symbol));
+                                AttemptOutcome::Used
+                            }
+                            None => AttemptOutcome::Missing,
+                        };
+                        record_attempt(
+                            symbol,
+                            SourceAttempt::new(name, outcome).with_latency(latency),
+                        );
+                    }
+                }
+                Err(e) => {
+                    warn!("{} failed: {}", name, e);
+                    for symbol in open {
+                        record_attempt(
+                            symbol,
+                            SourceAttempt::new(name, AttemptOutcome::Failed)
+                                .with_latency(latency)
+                                .with_error(&e),
+                        );
+                    }
+                }
+            }
+            if policy == FallbackPolicy::Race && missing.is_empty() {
+                break;
+            }
+        }
+
+        for symbol in &missing {
+            warn!("All sources failed for {}", symbol);
+        }
+        let missing = members
+            .iter()
+            .filter(|symbol| missing.contains(&key(symbol)))
+            .map(|symbol| {
+                let trail = attempts.remove(&key(symbol)).unwrap_or_default();
+                (symbol.clone(), trail)
+            })
+            .collect();
+
+        let prices = quotes
+            .into_iter()
+            .filter_map(|(symbol_key, mut symbol_quotes)| {
+                let mut trail = attempts.remove(&symbol_key).unwrap_or_default();
+                if policy == FallbackPolicy::AllAggregate {
+                    sort_by_chain(&mut trail, chain, |attempt| &attempt.source);
+                    sort_by_chain(&mut symbol_quotes, chain, |quote| &quote.source);
+                }
+                Some((aggregate(symbol_quotes)?, trail))
+            })
+            .collect();
+        BatchFetch { prices, missing }
+    }
+}
+
+/// Error for a symbol no source of its chain priced
+fn chain_error(symbol: &str, last_error: Option<OracleError>, skipped: &[&str]) -> OracleError {
+    match last_error {
+        Some(e) => e,
+        None if !skipped.is_empty() => {
+            OracleError::SourceUnavailable(format!("circuit open for {}", skipped.join(", ")))
+        }
+        None => OracleError::PriceNotFound(symbol.to_string()),
+    }
+}
+
+/// Order items by the position of their source in `chain`, so an aggregate does
+/// not depend on which source answered first
+fn sort_by_chain<T>(items: &mut [T], chain: &[String], source: impl Fn(&T) -> &String) {
+    items.sort_by_key(|item| chain.iter().position(|name| name == source(item)));
+}
+
+/// One price from the quotes of several sources: a single quote as it is, or the
+/// median price of several, keeping the other fields of the first
+fn aggregate(mut quotes: Vec<PriceData>) -> Option<PriceData> {
+    // An extended-hours trade is newer than the regular close other sources report
+    if let Some(session) = quotes
+        .iter()
+        .filter_map(|quote| quote.session)
+        .find(|session| *session != MarketState::Open)
+    {
+        quotes.retain(|quote| quote.session == Some(session));
+    }
+    if quotes.len() <= 1 {
+        return quotes.pop();
+    }
+    let median = price_math::median(quotes.iter().map(|quote| quote.price))?;
+    let mut price_data = quotes.swap_remove(0);
+    price_data.price = median;
+    price_data.source = AGGREGATE_SOURCE.to_string();
+    // Market cap and supply are only reported by some sources
+    for quote in &quotes {
+        price_data.market_cap = price_data.market_cap.or(quote.market_cap);
+        price_data.circulating_supply = price_data.circulating_supply.or(quote.circulating_supply);
+        price_data.total_supply = price_data.total_supply.or(quote.total_supply);
+    }
+    Some(price_data)
+}
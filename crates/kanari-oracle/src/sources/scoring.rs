@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of recent requests a score is computed from
+const WINDOW: usize = 50;
+
+/// Outcomes older than this no longer count. A demoted source is rarely tried,
+/// so its score expires and it goes back to its configured place for a retry.
+pub const MAX_AGE: Duration = Duration::from_secs(600);
+
+/// Requests needed before a score is trusted; sources with fewer keep their place
+pub const MIN_SAMPLES: usize = 5;
+
+/// Rolling success rate and latency of one source, plus lifetime totals
+#[derive(Debug, Default)]
+pub struct SourceScore {
+    window: Mutex<Window>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    outcomes: VecDeque<(Instant, bool, Duration)>,
+    requests: u64,
+    failures: u64,
+    last_error: Option<(DateTime<Utc>, String)>,
+}
+
+/// Point-in-time view of a `SourceScore`
+#[derive(Debug, Clone)]
+pub struct ScoreSnapshot {
+    pub samples: usize,
+    pub success_rate: f64,
+    pub avg_latency: Duration,
+    pub p50_latency: Duration,
+    pub p95_latency: Duration,
+    /// Requests since start, beyond the rolling window
+    pub requests: u64,
+    pub failures: u64,
+    pub last_error: Option<(DateTime<Utc>, String)>,
+}
+
+impl ScoreSnapshot {
+    /// Routing score in (0, 1]: success rate discounted by average latency.
+    /// Sources without enough samples score 1 so they are not starved.
+    pub fn score(&self) -> f64 {
+        if self.samples < MIN_SAMPLES {
+            return 1.0;
+        }
+        self.success_rate / (1.0 + self.avg_latency.as_secs_f64())
+    }
+}
+
+impl SourceScore {
+    /// Record one request; `error` is the failure message of a failed request
+    pub fn record(&self, error: Option<String>, latency: Duration) {
+        self.record_at(Instant::now(), error, latency);
+    }
+
+    fn record_at(&self, at: Instant, error: Option<String>, latency: Duration) {
+        let mut window = self.window.lock().unwrap();
+        if window.outcomes.len() == WINDOW {
+            window.outcomes.pop_front();
+        }
+        window.outcomes.push_back((at, error.is_none(), latency));
+        window.requests += 1;
+        if let Some(message) = error {
+            window.failures += 1;
+            window.last_error = Some((Utc::now(), message));
+        }
+    }
+
+    pub fn snapshot(&self) -> ScoreSnapshot {
+        let mut window = self.window.lock().unwrap();
+        while window
+            .outcomes
+            .front()
+            .is_some_and(|(at, _, _)| at.elapsed() > MAX_AGE)
+        {
+            window.outcomes.pop_front();
+        }
+        let samples = window.outcomes.len();
+        let mut snapshot = ScoreSnapshot {
+            samples,
+            success_rate: 1.0,
+            avg_latency: Duration::ZERO,
+            p50_latency: Duration::ZERO,
+            p95_latency: Duration::ZERO,
+            requests: window.requests,
+            failures: window.failures,
+            last_error: window.last_error.clone(),
+        };
+        if samples == 0 {
+            return snapshot;
+        }
+
+        let successes = window.outcomes.iter().filter(|(_, ok, _)| *ok).count();
+        let mut latencies: Vec<Duration> = window
+            .outcomes
+            .iter()
+            .map(|(_, _, latency)| *latency)
+            .collect();
+        latencies.sort();
+
+        snapshot.success_rate = successes as f64 / samples as f64;
+        snapshot.avg_latency = latencies.iter().sum::<Duration>() / samples as u32;
+        snapshot.p50_latency = percentile(&latencies, 0.5);
+        snapshot.p95_latency = percentile(&latencies, 0.95);
+        snapshot
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`
+fn percentile(values: &[Duration], p: f64) -> Duration {
+    let rank = (p * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn few_samples_score_as_healthy() {
+        let score = SourceScore::default();
+        for _ in 1..MIN_SAMPLES {
+            score.record(Some("down".to_string()), FAST);
+        }
+        assert_eq!(score.snapshot().score(), 1.0);
+    }
+
+    #[test]
+    fn failures_and_latency_lower_the_score() {
+        let score = SourceScore::default();
+        for i in 0..10 {
+            let error = (i % 2 == 1).then(|| "down".to_string());
+            score.record(error, Duration::from_secs(1));
+        }
+        let snapshot = score.snapshot();
+        assert_eq!(snapshot.samples, 10);
+        assert_eq!(snapshot.success_rate, 0.5);
+        assert_eq!(snapshot.score(), 0.25);
+    }
+
+    #[test]
+    fn only_the_latest_window_counts() {
+        let score = SourceScore::default();
+        for _ in 0..WINDOW {
+            score.record(Some("down".to_string()), FAST);
+        }
+        for _ in 0..WINDOW {
+            score.record(None, FAST);
+        }
+        let snapshot = score.snapshot();
+        assert_eq!(snapshot.samples, WINDOW);
+        assert_eq!(snapshot.success_rate, 1.0);
+    }
+
+    #[test]
+    fn old_outcomes_expire_so_demoted_sources_recover() {
+        let Some(long_ago) = Instant::now().checked_sub(MAX_AGE + Duration::from_secs(1)) else {
+            return;
+        };
+        let score = SourceScore::default();
+        for _ in 0..WINDOW {
+            score.record_at(long_ago, Some("down".to_string()), FAST);
+        }
+        score.record(Some("down".to_string()), FAST);
+        let snapshot = score.snapshot();
+        assert_eq!(snapshot.samples, 1);
+        assert_eq!(snapshot.score(), 1.0);
+    }
+}
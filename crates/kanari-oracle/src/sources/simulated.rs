@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::{PriceSource, SourceCapabilities};
+use crate::config::{Config, SandboxConfig, SandboxSymbol};
+use crate::errors::{OracleError, Result};
+use crate::models::{AssetType, PriceData};
+use crate::symbols;
+
+/// Random-walk state of one symbol
+#[derive(Debug)]
+struct Walk {
+    rng: StdRng,
+    /// Starting price, which `change_24h` is measured from
+    open: f64,
+    price: f64,
+    volume: f64,
+}
+
+#[derive(Debug)]
+struct Simulation {
+    seed: u64,
+    volatility: f64,
+    /// Overrides keyed "asset_type:feed_key", resolved through the configured aliases
+    overrides: HashMap<String, SandboxSymbol>,
+    walks: HashMap<String, Walk>,
+}
+
+/// Simulated prices for sandbox mode, shared by every [`SimulatedSource`] so all
+/// sources agree. Each symbol walks on its own generator seeded from the
+/// configured seed and the symbol, so its prices do not depend on the order in
+/// which symbols are requested. Every request moves the walk one step.
+#[derive(Debug)]
+pub struct SimulatedMarket {
+    simulation: Mutex<Simulation>,
+}
+
+impl SimulatedMarket {
+    pub fn new(config: &Config) -> Self {
+        let sandbox = config.sandbox.clone().unwrap_or_default();
+        Self {
+            simulation: Mutex::new(Simulation {
+                seed: sandbox.seed,
+                volatility: sandbox.volatility,
+                overrides: resolve_overrides(config, &sandbox),
+                walks: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Apply a reloaded config. Walks continue with the new volatility; a new
+    /// seed starts every walk over.
+    pub fn reconfigure(&self, config: &Config) {
+        let sandbox = config.sandbox.clone().unwrap_or_default();
+        let mut simulation = self.simulation.lock().unwrap();
+        if simulation.seed != sandbox.seed {
+            simulation.seed = sandbox.seed;
+            simulation.walks.clear();
+        }
+        simulation.volatility = sandbox.volatility;
+        simulation.overrides = resolve_overrides(config, &sandbox);
+    }
+
+    /// Move the symbol's walk one step and quote the new price
+    pub fn quote(&self, asset_type: AssetType, symbol: &str, source: &str) -> PriceData {
+        let key = walk_key(asset_type, symbol);
+        let mut simulation = self.simulation.lock().unwrap();
+        let overrides = simulation.overrides.get(&key).cloned().unwrap_or_default();
+        let volatility = overrides.volatility.unwrap_or(simulation.volatility) / 100.0;
+        let seed = symbol_seed(simulation.seed, &key);
+
+        let walk = simulation.walks.entry(key).or_insert_with(|| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let price = overrides
+                .price
+                .unwrap_or_else(|| 10f64.powf(rng.gen_range(0.0..4.0)));
+            let volume = rng.gen_range(1e5..1e8);
+            Walk {
+                rng,
+                open: price,
+                price,
+                volume,
+            }
+        });
+
+        // Log-normal step, so prices stay positive and do not drift on average
+        let step = gaussian(&mut walk.rng);
+        walk.price *= (volatility * step - volatility * volatility / 2.0).exp();
+        walk.volume *= (0.1 * gaussian(&mut walk.rng)).exp();
+
+        let mut price_data = PriceData::new(
+            symbols::normalize(asset_type, symbol),
+            walk.price,
+            source.to_string(),
+        );
+        price_data.change_24h = Some(walk.price - walk.open);
+        price_data.change_24h_percent = Some((walk.price / walk.open - 1.0) * 100.0);
+        price_data.volume_24h = Some(walk.volume);
+        price_data
+    }
+}
+
+fn walk_key(asset_type: AssetType, symbol: &str) -> String {
+    format!(
+        "{}:{}",
+        asset_type.as_str(),
+        symbols::feed_key(asset_type, symbol)
+    )
+}
+
+/// Overrides keyed by walk, with "asset_type:symbol" entries taking precedence
+/// over bare symbols
+fn resolve_overrides(config: &Config, sandbox: &SandboxConfig) -> HashMap<String, SandboxSymbol> {
+    let key = |asset_type: AssetType, symbol: &str| {
+        walk_key(asset_type, &config.canonical_symbol(asset_type, symbol))
+    };
+
+    let mut resolved = HashMap::new();
+    for (symbol, overrides) in sandbox.symbols.iter().filter(|(k, _)| !k.contains(':')) {
+        for asset_type in [AssetType::Crypto, AssetType::Stock, AssetType::Tvl] {
+            resolved.insert(key(asset_type, symbol), overrides.clone());
+        }
+    }
+    for (qualified, overrides) in &sandbox.symbols {
+        let (asset_type, symbol) = match qualified.split_once(':') {
+            Some(("crypto", symbol)) => (AssetType::Crypto, symbol),
+            Some(("stock", symbol)) => (AssetType::Stock, symbol),
+            Some(("tvl", symbol)) => (AssetType::Tvl, symbol),
+            _ => continue,
+        };
+        resolved.insert(key(asset_type, symbol), overrides.clone());
+    }
+    resolved
+}
+
+/// Seed of one symbol's generator (FNV-1a over the walk key), stable across runs
+fn symbol_seed(seed: u64, key: &str) -> u64 {
+    key.bytes()
+        .fold(seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Standard normal sample (Box-Muller)
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u1 = 1.0 - rng.r#gen::<f64>();
+    let u2 = rng.r#gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Source answering from a [`SimulatedMarket`] under the name of a configured
+/// source, for sandbox mode
+pub struct SimulatedSource {
+    name: String,
+    asset_type: AssetType,
+    market: Arc<SimulatedMarket>,
+}
+
+impl SimulatedSource {
+    pub fn new(name: &str, asset_type: AssetType, market: Arc<SimulatedMarket>) -> Self {
+        Self {
+            name: name.to_string(),
+            asset_type,
+            market,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for SimulatedSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: self.asset_type,
+            batch: true,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+        Ok(self.market.quote(self.asset_type, symbol, &self.name))
+    }
+
+    async fn fetch_batch(&self, symbols: &[String]) -> Result<Vec<PriceData>> {
+        Ok(symbols
+            .iter()
+            .filter(|symbol| !symbol.is_empty())
+            .map(|symbol| self.market.quote(self.asset_type, symbol, &self.name))
+            .collect())
+    }
+}
@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use log::debug;
+use std::sync::Arc;
+
+use super::{PriceSource, SourceCapabilities};
+use crate::errors::{OracleError, Result};
+use crate::fetchers::{PriceFetcher, status_error};
+use crate::market::MarketState;
+use crate::models::{AssetType, PriceData};
+use crate::price_math;
+use crate::symbols;
+
+/// Yahoo Finance chart API, used when no API-key provider is configured
+///
+/// Note: Free Yahoo endpoints can be rate-limited or blocked. Prefer API-key providers
+/// (e.g., Alpha Vantage; consider adding alternatives like Twelve Data or Polygon with free tiers).
+pub struct YahooFinanceSource {
+    fetcher: Arc<PriceFetcher>,
+}
+
+impl YahooFinanceSource {
+    pub fn new(fetcher: Arc<PriceFetcher>) -> Self {
+        Self { fetcher }
+    }
+}
+
+#[async_trait]
+impl PriceSource for YahooFinanceSource {
+    fn name(&self) -> &str {
+        "yahoo_finance"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            asset_type: AssetType::Stock,
+            batch: false,
+        }
+    }
+
+    async fn fetch_one(&self, symbol: &str) -> Result<PriceData> {
+        if symbol.is_empty() {
+            return Err(OracleError::ApiError("Empty symbol provided".to_string()));
+        }
+        // Using Yahoo Finance alternative API (no API key required). With extended
+        // hours, today's minute candles include pre-market and after-hours trades.
+        let extended_hours = self.fetcher.config().stocks.extended_hours;
+        let url = if extended_hours {
+            format!(
+                "https://query1.finance.yahoo.com/v8/finance/chart/{}?interval=1m&range=1d&includePrePost=true",
+                symbols::yahoo_ticker(symbol)
+            )
+        } else {
+            format!(
+                "https://query1.finance.yahoo.com/v8/finance/chart/{}",
+                symbols::yahoo_ticker(symbol)
+            )
+        };
+
+        debug!("Fetching free stock price for: {}", symbol);
+
+        let client = self.fetcher.client().clone();
+
+        self.fetcher
+            .retry_with_backoff(|| async {
+                self.fetcher.throttle(self.name()).await;
+
+                let request = client.get(&url).header(
+                    "User-Agent",
+                    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+                );
+                let response = self.fetcher.send(self.name(), request).await?;
+
+                if !response.status().is_success() {
+                    return Err(status_error("Yahoo Finance API error", &response));
+                }
+
+                let data: serde_json::Value = response.json()?;
+
+                let result = &data["chart"]["result"][0];
+                let meta = &result["meta"];
+
+                let regular_price = meta["regularMarketPrice"].as_f64().ok_or_else(|| {
+                    OracleError::ApiError("Invalid price data from Yahoo Finance".to_string())
+                })?;
+
+                // An extended-hours trade moves against the regular close, which is
+                // the last regular price both before the open and after the close
+                let (session, current_price, reference) =
+                    match extended_quote(result).filter(|_| extended_hours) {
+                        Some((session, price)) => (session, price, regular_price),
+                        None => {
+                            let previous_close = meta["previousClose"]
+                                .as_f64()
+                                .or_else(|| meta["chartPreviousClose"].as_f64())
+                                .unwrap_or(regular_price);
+                            (MarketState::Open, regular_price, previous_close)
+                        }
+                    };
+                let change = price_math::change(current_price, reference);
+
+                let mut price_data = PriceData::new(
+                    symbols::normalize_stock(symbol),
+                    current_price,
+                    self.name().to_string(),
+                );
+
+                price_data.change_24h = change.map(|(change, _)| change);
+                price_data.change_24h_percent = change.map(|(_, percent)| percent);
+                price_data.session = Some(session);
+
+                Ok(price_data)
+            })
+            .await
+    }
+}
+
+/// Session and price of the latest candle in a chart fetched with
+/// `includePrePost`, when that candle is a pre-market or after-hours trade
+fn extended_quote(result: &serde_json::Value) -> Option<(MarketState, f64)> {
+    let timestamps = result["timestamp"].as_array()?;
+    let closes = result["indicators"]["quote"][0]["close"].as_array()?;
+    let (timestamp, price) = timestamps
+        .iter()
+        .zip(closes)
+        .rev()
+        .find_map(|(timestamp, close)| Some((timestamp.as_i64()?, close.as_f64()?)))?;
+
+    let periods = &result["meta"]["currentTradingPeriod"];
+    let within = |period: &str| {
+        let start = periods[period]["start"].as_i64();
+        let end = periods[period]["end"].as_i64();
+        matches!((start, end), (Some(start), Some(end)) if (start..end).contains(&timestamp))
+    };
+    if within("pre") {
+        Some((MarketState::Pre, price))
+    } else if within("post") {
+        Some((MarketState::Post, price))
+    } else {
+        None
+    }
+}
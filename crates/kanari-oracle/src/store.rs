@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use log::warn;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::errors::{OracleError, Result};
+use crate::models::{AssetType, PriceData};
+use crate::subscriber::PriceSubscriber;
+
+/// Shared storage for published prices, so several oracle processes (an updater
+/// and any number of API replicas) serve the same view
+#[async_trait]
+pub trait PriceStore: Send + Sync {
+    /// Write prices, replacing any stored under the same symbols
+    async fn save(&self, asset_type: AssetType, prices: &[PriceData]) -> Result<()>;
+
+    /// Every stored price of an asset type
+    async fn load(&self, asset_type: AssetType) -> Result<Vec<PriceData>>;
+}
+
+/// Prices kept in one Redis hash per asset type (`{prefix}:prices:{asset_type}`),
+/// field = symbol, value = the price as JSON
+pub struct RedisStore {
+    connection: ConnectionManager,
+    key_prefix: String,
+}
+
+impl RedisStore {
+    pub async fn connect(url: &str, key_prefix: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(store_error)?;
+        let connection = ConnectionManager::new(client).await.map_err(store_error)?;
+        Ok(Self {
+            connection,
+            key_prefix: key_prefix.to_string(),
+        })
+    }
+
+    fn key(&self, asset_type: AssetType) -> String {
+        format!("{}:prices:{}", self.key_prefix, asset_type.as_str())
+    }
+}
+
+#[async_trait]
+impl PriceStore for RedisStore {
+    async fn save(&self, asset_type: AssetType, prices: &[PriceData]) -> Result<()> {
+        let key = self.key(asset_type);
+        let mut pipe = redis::pipe();
+        for price_data in prices {
+            pipe.hset(&key, &price_data.symbol, serde_json::to_string(price_data)?)
+                .ignore();
+        }
+
+        let mut connection = self.connection.clone();
+        pipe.query_async::<()>(&mut connection)
+            .await
+            .map_err(store_error)
+    }
+
+    async fn load(&self, asset_type: AssetType) -> Result<Vec<PriceData>> {
+        let mut connection = self.connection.clone();
+        let stored: HashMap<String, String> = connection
+            .hgetall(self.key(asset_type))
+            .await
+            .map_err(store_error)?;
+
+        Ok(stored
+            .into_iter()
+            .filter_map(|(symbol, json)| match serde_json::from_str(&json) {
+                Ok(price_data) => Some(price_data),
+                Err(e) => {
+                    warn!("Ignoring unreadable stored price for {}: {}", symbol, e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+fn store_error(e: redis::RedisError) -> OracleError {
+    OracleError::StoreError(e.to_string())
+}
+
+/// Subscriber that copies every published price into a [`PriceStore`]. Writes
+/// happen on a background task, in publish order, so publishing never waits on
+/// the store.
+pub struct StoreWriter {
+    sender: mpsc::UnboundedSender<(AssetType, Vec<PriceData>)>,
+}
+
+impl StoreWriter {
+    /// Start the writer task; it runs until the writer is dropped
+    pub fn spawn(store: Arc<dyn PriceStore>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<(AssetType, Vec<PriceData>)>();
+        tokio::spawn(async move {
+            while let Some((asset_type, prices)) = receiver.recv().await {
+                if let Err(e) = store.save(asset_type, &prices).await {
+                    warn!(
+                        "Failed to write {} {} prices to store: {}",
+                        prices.len(),
+                        asset_type.as_str(),
+                        e
+                    );
+                }
+            }
+        });
+        Self { sender }
+    }
+}
+
+impl PriceSubscriber for StoreWriter {
+    fn on_update(&self, asset_type: AssetType, prices: &[PriceData]) {
+        let _ = self.sender.send((asset_type, prices.to_vec()));
+    }
+}
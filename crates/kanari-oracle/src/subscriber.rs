@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{AssetType, PriceData};
+
+/// Receives prices as they are written into the oracle's feed
+///
+/// Called from update rounds, background refreshes and on-demand fetches, after
+/// the prices are visible to readers. Implementations should return quickly;
+/// hand slow work (network, disk) off to a channel or task.
+///
+/// Closures taking `(AssetType, &[PriceData])` implement this trait:
+///
+/// ```no_run
+/// # async fn run() -> kanari_oracle::errors::Result<()> {
+/// use kanari_oracle::models::{AssetType, PriceData};
+/// use kanari_oracle::oracle::Oracle;
+///
+/// let oracle = Oracle::builder()
+///     .crypto_symbols(["bitcoin", "ethereum"])
+///     .subscriber(|asset_type: AssetType, prices: &[PriceData]| {
+///         println!("{} {} prices updated", prices.len(), asset_type.as_str());
+///     })
+///     .build()
+///     .await?;
+///
+/// oracle.update_all_prices().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait PriceSubscriber: Send + Sync {
+    fn on_update(&self, asset_type: AssetType, prices: &[PriceData]);
+}
+
+impl<F> PriceSubscriber for F
+where
+    F: Fn(AssetType, &[PriceData]) + Send + Sync,
+{
+    fn on_update(&self, asset_type: AssetType, prices: &[PriceData]) {
+        self(asset_type, prices)
+    }
+}
+
+/// Prices written into the feed together, as received from [`Oracle::subscribe`]
+///
+/// [`Oracle::subscribe`]: crate::oracle::Oracle::subscribe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    /// Round the prices were published in; one round may send an update per
+    /// asset type
+    #[serde(default)]
+    pub round_id: u64,
+    pub asset_type: AssetType,
+    pub prices: Vec<PriceData>,
+}
@@ -0,0 +1,99 @@
+use crate::models::AssetType;
+
+/// Prefix of TVL symbols naming a chain ("chain:ethereum") rather than a protocol
+pub const TVL_CHAIN_PREFIX: &str = "chain:";
+
+/// Quote currencies dropped from crypto pairs such as "BTC/USDT"
+const QUOTE_CURRENCIES: &[&str] = &["usdt", "usdc", "busd", "fdusd", "usd", "eur"];
+
+/// Canonical form of a symbol: lowercase for crypto, uppercase for stocks
+pub fn normalize(asset_type: AssetType, symbol: &str) -> String {
+    match asset_type {
+        AssetType::Crypto => normalize_crypto(symbol),
+        AssetType::Stock => normalize_stock(symbol),
+        AssetType::Basket => normalize_basket(symbol),
+        AssetType::Tvl => normalize_tvl(symbol),
+    }
+}
+
+/// Key of a symbol in a [`PriceFeed`](crate::models::PriceFeed); equal for every
+/// spelling of the same symbol
+pub fn feed_key(asset_type: AssetType, symbol: &str) -> String {
+    normalize(asset_type, symbol).to_lowercase()
+}
+
+/// Lowercase coin id or ticker. Pairs written as "BTC/USDT", "btc_usdt" or
+/// "BTC-USD" lose their quote currency. A lowercase hyphenated symbol is kept
+/// whole, since CoinGecko ids such as "usd-coin" or "true-usd" look like pairs.
+pub fn normalize_crypto(symbol: &str) -> String {
+    let symbol = symbol.trim();
+    let separators: &[char] = if symbol.chars().any(|c| c.is_ascii_uppercase()) {
+        &['/', '_', '-']
+    } else {
+        &['/', '_']
+    };
+    let symbol = symbol.to_lowercase();
+
+    match symbol.split_once(separators) {
+        Some((base, quote)) if !base.is_empty() && QUOTE_CURRENCIES.contains(&quote) => {
+            base.to_string()
+        }
+        _ => symbol,
+    }
+}
+
+/// Uppercase ticker with share classes written with a dot ("BRK-B" -> "BRK.B")
+pub fn normalize_stock(symbol: &str) -> String {
+    symbol.trim().to_uppercase().replace(['-', '/'], ".")
+}
+
+/// Lowercase basket name
+pub fn normalize_basket(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Lowercase DefiLlama protocol slug ("aave-v3"), or chain name behind
+/// [`TVL_CHAIN_PREFIX`] with spaces written as hyphens ("chain:arbitrum-nova")
+pub fn normalize_tvl(symbol: &str) -> String {
+    symbol.trim().to_lowercase().replace(' ', "-")
+}
+
+/// Chain a normalized TVL symbol names, or None for a protocol
+pub fn tvl_chain(symbol: &str) -> Option<&str> {
+    symbol.strip_prefix(TVL_CHAIN_PREFIX)
+}
+
+/// Whether the built-in sources can look a symbol up: CoinGecko ids and exchange
+/// tickers for crypto ("usd-coin", "btc"), exchange tickers for stocks ("BRK.B"),
+/// including Yahoo Finance indices and currencies ("^GSPC", "EURUSD=X"), and
+/// DefiLlama protocol slugs and chains for TVL ("aave-v3", "chain:ethereum")
+pub fn is_well_formed(asset_type: AssetType, symbol: &str) -> bool {
+    let normalized = normalize(asset_type, symbol);
+    let name = match asset_type {
+        AssetType::Tvl => tvl_chain(&normalized).unwrap_or(&normalized),
+        _ => &normalized,
+    };
+    !name.is_empty()
+        && name.chars().all(|c| match asset_type {
+            AssetType::Crypto => c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-',
+            AssetType::Stock => {
+                c.is_ascii_uppercase() || c.is_ascii_digit() || matches!(c, '.' | '^' | '=')
+            }
+            AssetType::Basket => {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_')
+            }
+            AssetType::Tvl => {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.')
+            }
+        })
+}
+
+/// Binance spot pair against USDT, e.g. "BTCUSDT"
+pub fn binance_pair(symbol: &str) -> String {
+    format!("{}USDT", normalize_crypto(symbol).to_uppercase())
+}
+
+/// Yahoo Finance ticker; share classes use a hyphen ("BRK.B" -> "BRK-B")
+pub fn yahoo_ticker(symbol: &str) -> String {
+    normalize_stock(symbol).replace('.', "-")
+}
@@ -0,0 +1,283 @@
+use comfy_table::{Cell, CellAlignment, Color, ContentArrangement, Table, presets};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::locale::AmountFormat;
+use crate::models::{AssetType, PriceData};
+use crate::oracle::Oracle;
+use crate::precision::PrecisionRule;
+
+/// Order of the rows of a price table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Alphabetical
+    #[default]
+    Symbol,
+    /// Highest price first
+    Price,
+    /// Biggest 24h gain in percent first; symbols without a change last
+    Change,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "symbol" => Ok(Self::Symbol),
+            "price" => Ok(Self::Price),
+            "change" => Ok(Self::Change),
+            _ => Err(format!(
+                "unknown sort key '{}', expected symbol, price or change",
+                s
+            )),
+        }
+    }
+}
+
+/// A column of a price table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Symbol,
+    Price,
+    /// 24h change in the display currency
+    Change,
+    /// 24h change in percent
+    ChangePercent,
+    Volume,
+    MarketCap,
+    Source,
+    /// Time since the price was fetched
+    Age,
+}
+
+impl Column {
+    /// Columns shown when none are chosen
+    pub const DEFAULT: [Column; 5] = [
+        Column::Symbol,
+        Column::Price,
+        Column::Change,
+        Column::ChangePercent,
+        Column::Source,
+    ];
+
+    fn header(&self, currency: &str) -> String {
+        match self {
+            Column::Symbol => "Symbol".to_string(),
+            Column::Price => format!("Price ({})", currency.to_uppercase()),
+            Column::Change => "Change".to_string(),
+            Column::ChangePercent => "Change %".to_string(),
+            Column::Volume => "Volume".to_string(),
+            Column::MarketCap => "Market cap".to_string(),
+            Column::Source => "Source".to_string(),
+            Column::Age => "Age".to_string(),
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Column::Symbol | Column::Source)
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "symbol" => Ok(Self::Symbol),
+            "price" => Ok(Self::Price),
+            "change" => Ok(Self::Change),
+            "change%" | "change_percent" => Ok(Self::ChangePercent),
+            "volume" => Ok(Self::Volume),
+            "market_cap" => Ok(Self::MarketCap),
+            "source" => Ok(Self::Source),
+            "age" => Ok(Self::Age),
+            _ => Err(format!(
+                "unknown column '{}', expected symbol, price, change, change%, volume, market_cap, source or age",
+                s
+            )),
+        }
+    }
+}
+
+/// How price tables are laid out
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    pub sort: SortKey,
+    /// Columns in order; [`Column::DEFAULT`] when empty
+    pub columns: Vec<Column>,
+    /// Color gains green and losses red
+    pub color: bool,
+}
+
+/// Sorted by symbol with the default columns, colored when stdout is a
+/// terminal and NO_COLOR is unset
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            sort: SortKey::default(),
+            columns: Vec::new(),
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
+
+/// Table of `prices` of one asset type, shown as `amounts` says
+pub fn price_table(
+    config: &Config,
+    asset_type: AssetType,
+    amounts: &AmountFormat,
+    prices: &[PriceData],
+    options: &TableOptions,
+) -> Table {
+    let columns: &[Column] = if options.columns.is_empty() {
+        &Column::DEFAULT
+    } else {
+        &options.columns
+    };
+
+    let mut rows: Vec<&PriceData> = prices.iter().collect();
+    match options.sort {
+        SortKey::Symbol => rows.sort_by(|a, b| a.symbol.cmp(&b.symbol)),
+        SortKey::Price => rows.sort_by(|a, b| b.price.total_cmp(&a.price)),
+        SortKey::Change => rows.sort_by(|a, b| {
+            let change = |price: &PriceData| price.change_24h_percent.unwrap_or(f64::NEG_INFINITY);
+            change(b).total_cmp(&change(a))
+        }),
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(
+            columns
+                .iter()
+                .map(|column| column.header(&amounts.currency)),
+        );
+    if options.color {
+        table.enforce_styling();
+    } else {
+        table.force_no_tty();
+    }
+
+    for price in rows {
+        let precision = config.precision(asset_type, &price.symbol);
+        table.add_row(
+            columns
+                .iter()
+                .map(|column| cell(*column, price, &precision, amounts)),
+        );
+    }
+    for (i, column) in columns.iter().enumerate() {
+        if column.is_numeric()
+            && let Some(table_column) = table.column_mut(i)
+        {
+            table_column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    table
+}
+
+/// Outcome of looking up one requested symbol
+pub struct Lookup {
+    /// Symbol as requested
+    pub symbol: String,
+    pub result: Result<(AssetType, PriceData), String>,
+}
+
+/// Table of looked-up symbols of any asset type, in the order requested, with
+/// the error of each symbol that failed
+pub fn lookup_table(oracle: &Oracle, lookups: &[Lookup], color: bool) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["Symbol", "Type", "Price", "Change %", "Source", "Status"]);
+    if color {
+        table.enforce_styling();
+    } else {
+        table.force_no_tty();
+    }
+
+    // One format per asset type, so a missing exchange rate is reported once
+    let mut formats: HashMap<AssetType, AmountFormat> = HashMap::new();
+    for lookup in lookups {
+        let symbol = lookup.symbol.to_uppercase();
+        match &lookup.result {
+            Ok((asset_type, price)) => {
+                let amounts = formats
+                    .entry(*asset_type)
+                    .or_insert_with(|| oracle.amount_format(*asset_type));
+                let precision = oracle.config().precision(*asset_type, &lookup.symbol);
+                let status = if price.is_stale {
+                    Cell::new("stale").fg(Color::Yellow)
+                } else {
+                    Cell::new("ok")
+                };
+                table.add_row([
+                    Cell::new(symbol),
+                    Cell::new(asset_type.as_str()),
+                    Cell::new(amounts.money(&precision, price.price)),
+                    cell(Column::ChangePercent, price, &precision, amounts),
+                    Cell::new(&price.source),
+                    status,
+                ]);
+            }
+            Err(e) => {
+                table.add_row([
+                    Cell::new(symbol),
+                    Cell::new(""),
+                    Cell::new(""),
+                    Cell::new(""),
+                    Cell::new(""),
+                    Cell::new(e).fg(Color::Red),
+                ]);
+            }
+        }
+    }
+    for i in [2, 3] {
+        if let Some(column) = table.column_mut(i) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    table
+}
+
+fn cell(
+    column: Column,
+    price: &PriceData,
+    precision: &PrecisionRule,
+    amounts: &AmountFormat,
+) -> Cell {
+    let optional = |value: Option<f64>, rule: &PrecisionRule| {
+        value.map_or_else(|| "N/A".to_string(), |value| amounts.number(rule, value))
+    };
+    let colored = |cell: Cell, value: Option<f64>| match value {
+        Some(value) if value > 0.0 => cell.fg(Color::Green),
+        Some(value) if value < 0.0 => cell.fg(Color::Red),
+        _ => cell,
+    };
+
+    match column {
+        Column::Symbol => Cell::new(&price.symbol),
+        Column::Price => Cell::new(amounts.number(precision, price.price)),
+        Column::Change => colored(
+            Cell::new(optional(price.change_24h, precision)),
+            price.change_24h,
+        ),
+        Column::ChangePercent => colored(
+            Cell::new(price.change_24h_percent.map_or_else(
+                || "N/A".to_string(),
+                |percent| amounts.locale.percent(percent),
+            )),
+            price.change_24h_percent,
+        ),
+        Column::Volume => Cell::new(optional(price.volume_24h, &PrecisionRule::whole())),
+        Column::MarketCap => Cell::new(optional(price.market_cap, &PrecisionRule::whole())),
+        Column::Source => Cell::new(&price.source),
+        Column::Age => Cell::new(format!("{}s", price.age_seconds)),
+    }
+}
@@ -0,0 +1,89 @@
+use pyo3::prelude::*;
+
+use kanari_oracle::config::Config;
+
+use crate::{block_on, to_py_err};
+
+/// Oracle configuration, in the same JSON format as `config.json`
+#[pyclass(name = "Config", module = "kanari")]
+#[derive(Clone)]
+pub struct PyConfig {
+    pub(crate) inner: Config,
+}
+
+#[pymethods]
+impl PyConfig {
+    /// Default configuration: the built-in symbols and keyless sources
+    #[new]
+    pub(crate) fn new() -> PyResult<Self> {
+        let inner = Config::default().with_env_overrides().map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Load a config file, with `KANARI_*` overrides and secrets resolved
+    #[staticmethod]
+    fn load(py: Python<'_>, path: &str) -> PyResult<Self> {
+        let inner = block_on(py, Config::from_file(path)).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Parse a config from a JSON string
+    #[staticmethod]
+    fn from_json(py: Python<'_>, json: &str) -> PyResult<Self> {
+        let config: Config = serde_json::from_str(json)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let mut inner = config.with_env_overrides().map_err(to_py_err)?;
+        block_on(py, inner.resolve_secrets()).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// The config as JSON, with secrets redacted
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&self.inner.redacted())
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    /// Raise if the config has problems that would stop the oracle
+    fn validate(&self) -> PyResult<()> {
+        self.inner.validate().map_err(to_py_err)
+    }
+
+    #[getter]
+    fn crypto_symbols(&self) -> Vec<String> {
+        self.inner.crypto.symbols.clone()
+    }
+
+    #[setter]
+    fn set_crypto_symbols(&mut self, symbols: Vec<String>) {
+        self.inner.crypto.symbols = symbols;
+    }
+
+    #[getter]
+    fn stock_symbols(&self) -> Vec<String> {
+        self.inner.stocks.symbols.clone()
+    }
+
+    #[setter]
+    fn set_stock_symbols(&mut self, symbols: Vec<String>) {
+        self.inner.stocks.symbols = symbols;
+    }
+
+    /// Seconds a cached price is served without refreshing it
+    #[getter]
+    fn cache_ttl(&self) -> u64 {
+        self.inner.general.cache_ttl
+    }
+
+    #[setter]
+    fn set_cache_ttl(&mut self, seconds: u64) {
+        self.inner.general.cache_ttl = seconds;
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Config(crypto_symbols={}, stock_symbols={})",
+            self.inner.crypto.symbols.len(),
+            self.inner.stocks.symbols.len()
+        )
+    }
+}
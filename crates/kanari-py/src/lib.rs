@@ -0,0 +1,67 @@
+//! Python bindings for `kanari-oracle`, built with maturin into the `kanari`
+//! module. Calls block on a shared Tokio runtime with the GIL released, so they
+//! work the same from scripts and notebooks.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+use kanari_oracle::errors::OracleError;
+use kanari_oracle::models::AssetType;
+
+mod config;
+mod models;
+mod oracle;
+
+create_exception!(
+    kanari,
+    KanariError,
+    PyException,
+    "Error raised by the oracle"
+);
+
+/// Runtime shared by every call, also running the oracle's background tasks
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("Failed to start the Tokio runtime"))
+}
+
+/// Run `future` to completion without holding the GIL
+fn block_on<F>(py: Python<'_>, future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    py.allow_threads(|| runtime().block_on(future))
+}
+
+fn to_py_err(error: OracleError) -> PyErr {
+    KanariError::new_err(error.to_string())
+}
+
+fn parse_asset_type(asset_type: &str) -> PyResult<AssetType> {
+    match asset_type {
+        "crypto" => Ok(AssetType::Crypto),
+        "stock" => Ok(AssetType::Stock),
+        "basket" => Ok(AssetType::Basket),
+        "tvl" => Ok(AssetType::Tvl),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid asset type '{}'. Use 'crypto', 'stock', 'basket' or 'tvl'",
+            asset_type
+        ))),
+    }
+}
+
+#[pymodule]
+fn kanari(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<config::PyConfig>()?;
+    m.add_class::<oracle::PyOracle>()?;
+    m.add_class::<models::Price>()?;
+    m.add_class::<models::SourceQuote>()?;
+    m.add_class::<models::HistoryPoint>()?;
+    m.add("KanariError", m.py().get_type::<KanariError>())?;
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use kanari_oracle::history;
+use kanari_oracle::models::PriceData;
+use kanari_oracle::oracle;
+
+use crate::to_py_err;
+
+/// Aggregated price of one symbol
+#[pyclass(module = "kanari", frozen, get_all)]
+#[derive(Clone)]
+pub struct Price {
+    pub symbol: String,
+    pub price: f64,
+    pub change_24h: Option<f64>,
+    pub change_24h_percent: Option<f64>,
+    pub volume_24h: Option<f64>,
+    pub market_cap: Option<f64>,
+    pub circulating_supply: Option<f64>,
+    pub total_supply: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+    pub source: String,
+    pub age_seconds: i64,
+    pub is_stale: bool,
+    /// "pre", "open", "post" or "closed"; set for stocks only
+    pub market_state: Option<&'static str>,
+    /// Session a stock price was traded in: "pre", "open" or "post"
+    pub session: Option<&'static str>,
+    /// Order book notional within 2% below the mid price, when depth is on
+    pub depth_bids: Option<f64>,
+    /// Order book notional within 2% above the mid price, when depth is on
+    pub depth_asks: Option<f64>,
+}
+
+impl From<PriceData> for Price {
+    fn from(data: PriceData) -> Self {
+        Self {
+            symbol: data.symbol,
+            price: data.price,
+            change_24h: data.change_24h,
+            change_24h_percent: data.change_24h_percent,
+            volume_24h: data.volume_24h,
+            market_cap: data.market_cap,
+            circulating_supply: data.circulating_supply,
+            total_supply: data.total_supply,
+            timestamp: data.timestamp,
+            source: data.source,
+            age_seconds: data.age_seconds,
+            is_stale: data.is_stale,
+            market_state: data.market_state.map(|state| state.as_str()),
+            session: data.session.map(|session| session.as_str()),
+            depth_bids: data.depth.as_ref().map(|depth| depth.bids),
+            depth_asks: data.depth.as_ref().map(|depth| depth.asks),
+        }
+    }
+}
+
+#[pymethods]
+impl Price {
+    /// The price as a dict, ready for `pandas.DataFrame`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("symbol", &self.symbol)?;
+        dict.set_item("price", self.price)?;
+        dict.set_item("change_24h", self.change_24h)?;
+        dict.set_item("change_24h_percent", self.change_24h_percent)?;
+        dict.set_item("volume_24h", self.volume_24h)?;
+        dict.set_item("market_cap", self.market_cap)?;
+        dict.set_item("circulating_supply", self.circulating_supply)?;
+        dict.set_item("total_supply", self.total_supply)?;
+        dict.set_item("timestamp", self.timestamp)?;
+        dict.set_item("source", &self.source)?;
+        dict.set_item("age_seconds", self.age_seconds)?;
+        dict.set_item("is_stale", self.is_stale)?;
+        dict.set_item("market_state", self.market_state)?;
+        dict.set_item("session", self.session)?;
+        dict.set_item("depth_bids", self.depth_bids)?;
+        dict.set_item("depth_asks", self.depth_asks)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Price(symbol='{}', price={}, source='{}')",
+            self.symbol, self.price, self.source
+        )
+    }
+}
+
+/// Answer of a single source, as returned by `Oracle.compare_sources`
+#[pyclass(module = "kanari", frozen, get_all)]
+pub struct SourceQuote {
+    pub source: String,
+    pub latency_ms: f64,
+    /// None when the source failed; see `error`
+    pub price: Option<Price>,
+    pub error: Option<String>,
+}
+
+impl From<oracle::SourceQuote> for SourceQuote {
+    fn from(quote: oracle::SourceQuote) -> Self {
+        let (price, error) = match quote.result {
+            Ok(data) => (Some(data.into()), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        Self {
+            source: quote.source,
+            latency_ms: quote.latency.as_secs_f64() * 1000.0,
+            price,
+            error,
+        }
+    }
+}
+
+#[pymethods]
+impl SourceQuote {
+    /// Return the price, raising `KanariError` if the source failed
+    fn unwrap(&self) -> PyResult<Price> {
+        match (&self.price, &self.error) {
+            (Some(price), _) => Ok(price.clone()),
+            (None, error) => Err(crate::KanariError::new_err(
+                error.clone().unwrap_or_default(),
+            )),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match (&self.price, &self.error) {
+            (Some(price), _) => format!(
+                "SourceQuote(source='{}', price={}, latency_ms={:.1})",
+                self.source, price.price, self.latency_ms
+            ),
+            (None, error) => format!(
+                "SourceQuote(source='{}', error='{}')",
+                self.source,
+                error.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// A published price, as returned by `Oracle.history`
+#[pyclass(module = "kanari", frozen, get_all)]
+pub struct HistoryPoint {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+    pub source: String,
+}
+
+impl From<history::HistoryPoint> for HistoryPoint {
+    fn from(point: history::HistoryPoint) -> Self {
+        Self {
+            timestamp: point.timestamp,
+            price: point.price,
+            source: point.source,
+        }
+    }
+}
+
+#[pymethods]
+impl HistoryPoint {
+    fn __repr__(&self) -> String {
+        format!(
+            "HistoryPoint(timestamp='{}', price={}, source='{}')",
+            self.timestamp.to_rfc3339(),
+            self.price,
+            self.source
+        )
+    }
+}
+
+/// Convert an oracle result into a Python price
+pub(crate) fn price(result: kanari_oracle::errors::Result<PriceData>) -> PyResult<Price> {
+    result.map(Price::from).map_err(to_py_err)
+}
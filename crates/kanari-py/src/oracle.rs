@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+
+use kanari_oracle::errors::OracleError;
+use kanari_oracle::models::AssetType;
+use kanari_oracle::oracle::Oracle;
+
+use crate::config::PyConfig;
+use crate::models::{self, HistoryPoint, Price, SourceQuote};
+use crate::{block_on, parse_asset_type, to_py_err};
+
+/// Price oracle fetching and aggregating prices in-process. Prices are fetched
+/// on demand and cached for `Config.cache_ttl` seconds; call `update()` to
+/// refresh every configured symbol at once.
+#[pyclass(name = "Oracle", module = "kanari", frozen)]
+pub struct PyOracle {
+    inner: Oracle,
+}
+
+impl PyOracle {
+    /// The symbol's asset type, looked up in the configured symbols when not given
+    fn asset_type(&self, symbol: &str, asset_type: Option<&str>) -> PyResult<AssetType> {
+        match asset_type {
+            Some(asset_type) => parse_asset_type(asset_type),
+            None => parse_asset_type(self.inner.resolve_asset_type(symbol).map_err(to_py_err)?),
+        }
+    }
+}
+
+#[pymethods]
+impl PyOracle {
+    #[new]
+    #[pyo3(signature = (config=None))]
+    fn new(py: Python<'_>, config: Option<PyConfig>) -> PyResult<Self> {
+        let config = match config {
+            Some(config) => config.inner,
+            None => PyConfig::new()?.inner,
+        };
+        let inner = block_on(py, Oracle::new(config)).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Refresh every configured symbol, or only those of `asset_type`, and
+    /// return how many prices were updated
+    #[pyo3(signature = (asset_type=None))]
+    fn update(&self, py: Python<'_>, asset_type: Option<&str>) -> PyResult<usize> {
+        let asset_type = asset_type.map(parse_asset_type).transpose()?;
+        block_on(py, async {
+            match asset_type {
+                None => self.inner.update_all_prices().await,
+                Some(AssetType::Crypto) => self.inner.update_crypto_prices().await,
+                Some(AssetType::Stock) => self.inner.update_stock_prices().await,
+                Some(AssetType::Tvl) => self.inner.update_tvl().await,
+                // Baskets are repriced from their components after any update
+                Some(AssetType::Basket) => self.inner.update_all_prices().await,
+            }
+        })
+        .map(|report| report.updated)
+        .map_err(to_py_err)
+    }
+
+    /// Refresh only the given symbols
+    fn update_symbols(
+        &self,
+        py: Python<'_>,
+        asset_type: &str,
+        symbols: Vec<String>,
+    ) -> PyResult<usize> {
+        let asset_type = parse_asset_type(asset_type)?;
+        block_on(py, self.inner.update_symbols(asset_type, &symbols))
+            .map(|report| report.updated)
+            .map_err(to_py_err)
+    }
+
+    /// Aggregated price of a symbol. `asset_type` may be omitted for symbols
+    /// configured as only crypto or only stock.
+    #[pyo3(signature = (symbol, asset_type=None))]
+    fn price(&self, py: Python<'_>, symbol: &str, asset_type: Option<&str>) -> PyResult<Price> {
+        let result = match self.asset_type(symbol, asset_type)? {
+            AssetType::Crypto => block_on(py, self.inner.get_crypto_price(symbol)),
+            AssetType::Stock => block_on(py, self.inner.get_stock_price(symbol)),
+            AssetType::Basket => block_on(py, self.inner.get_basket_price(symbol)),
+            AssetType::Tvl => block_on(py, self.inner.get_tvl(symbol)),
+        };
+        models::price(result)
+    }
+
+    /// Price of a symbol from one named source, bypassing the cache and the
+    /// fallback chain
+    #[pyo3(signature = (symbol, source, asset_type=None))]
+    fn price_from(
+        &self,
+        py: Python<'_>,
+        symbol: &str,
+        source: &str,
+        asset_type: Option<&str>,
+    ) -> PyResult<Price> {
+        let result = match self.asset_type(symbol, asset_type)? {
+            AssetType::Crypto => block_on(py, self.inner.get_crypto_price_from(symbol, source)),
+            AssetType::Stock => block_on(py, self.inner.get_stock_price_from(symbol, source)),
+            AssetType::Tvl => block_on(py, self.inner.get_tvl_from(symbol, source)),
+            AssetType::Basket => Err(OracleError::UnknownSource {
+                asset_type: AssetType::Basket.as_str().to_string(),
+                source_name: source.to_string(),
+            }),
+        };
+        models::price(result)
+    }
+
+    /// Query every enabled source for a symbol concurrently
+    #[pyo3(signature = (symbol, asset_type=None))]
+    fn compare_sources(
+        &self,
+        py: Python<'_>,
+        symbol: &str,
+        asset_type: Option<&str>,
+    ) -> PyResult<Vec<SourceQuote>> {
+        let asset_type = self.asset_type(symbol, asset_type)?;
+        let quotes = block_on(py, self.inner.compare_sources(symbol, asset_type.as_str()))
+            .map_err(to_py_err)?;
+        Ok(quotes.into_iter().map(SourceQuote::from).collect())
+    }
+
+    /// Every cached price, or only those of `asset_type`
+    #[pyo3(signature = (asset_type=None))]
+    fn prices(&self, asset_type: Option<&str>) -> PyResult<Vec<Price>> {
+        let prices = match asset_type.map(parse_asset_type).transpose()? {
+            Some(AssetType::Crypto) => self.inner.get_all_crypto_prices(),
+            Some(AssetType::Stock) => self.inner.get_all_stock_prices(),
+            Some(AssetType::Basket) => self.inner.get_all_basket_prices(),
+            Some(AssetType::Tvl) => self.inner.get_all_tvl(),
+            None => {
+                let mut prices = self.inner.get_all_crypto_prices();
+                prices.extend(self.inner.get_all_stock_prices());
+                prices.extend(self.inner.get_all_basket_prices());
+                prices.extend(self.inner.get_all_tvl());
+                prices
+            }
+        };
+        Ok(prices.into_iter().map(Price::from).collect())
+    }
+
+    /// Published prices of a symbol between `start` and `end` (default: now),
+    /// oldest first. With `adjusted`, stock prices are corrected for the splits
+    /// and dividends in `stocks.corporate_actions`.
+    #[pyo3(signature = (symbol, start, end=None, asset_type=None, adjusted=false))]
+    fn history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        asset_type: Option<&str>,
+        adjusted: bool,
+    ) -> PyResult<Vec<HistoryPoint>> {
+        let asset_type = self.asset_type(symbol, asset_type)?;
+        let end = end.unwrap_or_else(Utc::now);
+        let points = if adjusted {
+            self.inner.adjusted_history(asset_type, symbol, start, end)
+        } else {
+            self.inner.history(asset_type, symbol, start, end)
+        };
+        Ok(points.into_iter().map(HistoryPoint::from).collect())
+    }
+
+    #[getter]
+    fn crypto_symbols(&self) -> Vec<String> {
+        self.inner.get_crypto_symbols()
+    }
+
+    #[getter]
+    fn stock_symbols(&self) -> Vec<String> {
+        self.inner.get_stock_symbols()
+    }
+
+    /// Time of the last completed update round
+    #[getter]
+    fn last_update(&self) -> DateTime<Utc> {
+        self.inner.get_last_update()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Oracle(crypto_symbols={}, stock_symbols={})",
+            self.inner.get_crypto_symbols().len(),
+            self.inner.get_stock_symbols().len()
+        )
+    }
+}
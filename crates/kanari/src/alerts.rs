@@ -0,0 +1,310 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Subcommand;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use tokio::{signal, time};
+
+use kanari_api::database::{self, DbPool};
+use kanari_api::models::{CreateWebhookRequest, WebhookDelivery, WebhookInfo};
+use kanari_api::webhooks;
+use kanari_oracle::config::{Config, DatabaseConfig};
+
+use crate::remote::RemoteClient;
+
+/// Pause between polls of the delivery logs in `alerts list --follow`
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Deliveries read per alert and poll; more firings than this between two
+/// polls are not all printed
+const FOLLOW_LIMIT: i64 = 100;
+
+/// Alerts are webhooks: the oracle POSTs to their URL when a price crosses a
+/// threshold or goes stale
+#[derive(Subcommand)]
+pub enum AlertsCommand {
+    /// Register an alert
+    Add {
+        /// Callback URL the alert is POSTed to
+        url: String,
+        /// Symbols to watch, comma separated (e.g., BTC,ETH); every symbol
+        /// when omitted
+        #[arg(short, long, value_delimiter = ',')]
+        symbols: Vec<String>,
+        /// Fire when a price moves by this many percent
+        #[arg(long)]
+        threshold: Option<f64>,
+        /// Fire when a price is older than this many seconds
+        #[arg(long)]
+        stale_after: Option<i64>,
+        /// Also fire on every price update
+        #[arg(long)]
+        every_update: bool,
+        /// Signing secret of the deliveries (at least 16 characters);
+        /// generated when omitted
+        #[arg(long)]
+        secret: Option<String>,
+    },
+    /// List alerts
+    List {
+        /// Keep running and print each alert firing as it is delivered
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Remove an alert and its delivery log
+    Rm {
+        /// Alert id, as shown by `alerts list`
+        id: i32,
+    },
+}
+
+/// Where alerts are kept: a running API server, or the database directly
+enum Backend {
+    Api(RemoteClient),
+    Database { db: DbPool, owner: String },
+}
+
+impl Backend {
+    async fn add(&self, request: &CreateWebhookRequest) -> Result<WebhookInfo> {
+        match self {
+            Backend::Api(client) => client.create_webhook(request).await,
+            Backend::Database { db, owner } => webhooks::register(db, owner, request)
+                .await
+                .map_err(|e| anyhow!(e)),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<WebhookInfo>> {
+        match self {
+            Backend::Api(client) => client.webhooks().await,
+            Backend::Database { db, owner } => webhooks::registered(db, owner)
+                .await
+                .map_err(|e| anyhow!(e)),
+        }
+    }
+
+    async fn remove(&self, id: i32) -> Result<()> {
+        match self {
+            Backend::Api(client) => client.delete_webhook(id).await,
+            Backend::Database { db, owner } => match webhooks::unregister(db, owner, id).await {
+                Ok(true) => Ok(()),
+                Ok(false) => bail!("Alert {} not found", id),
+                Err(e) => Err(anyhow!(e)),
+            },
+        }
+    }
+
+    async fn deliveries(&self, id: i32) -> Result<Vec<WebhookDelivery>> {
+        match self {
+            Backend::Api(client) => client.webhook_deliveries(id, FOLLOW_LIMIT).await,
+            Backend::Database { db, owner } => {
+                match webhooks::deliveries(db, owner, id, FOLLOW_LIMIT).await {
+                    Ok(Some(deliveries)) => Ok(deliveries),
+                    Ok(None) => bail!("Alert {} not found", id),
+                    Err(e) => Err(anyhow!(e)),
+                }
+            }
+        }
+    }
+}
+
+/// Run an `alerts` subcommand against the server at `remote`, authenticated
+/// with `token`, or else against the database of `config_path` on behalf of
+/// `owner`
+pub async fn run(
+    command: AlertsCommand,
+    remote: Option<String>,
+    token: Option<String>,
+    owner: Option<String>,
+    config_path: &str,
+) -> Result<()> {
+    let backend = match (remote, owner) {
+        (Some(_), Some(_)) => bail!("--owner is for local mode; --remote uses the token's owner"),
+        (Some(url), None) => {
+            if token.is_none() {
+                bail!("--remote needs a --token to manage alerts");
+            }
+            Backend::Api(RemoteClient::new(&url, token)?)
+        }
+        (None, Some(owner)) => Backend::Database {
+            db: connect(config_path).await?,
+            owner,
+        },
+        (None, None) => bail!("Pass --remote and --token, or --owner to use the database"),
+    };
+    let local = matches!(backend, Backend::Database { .. });
+
+    match command {
+        AlertsCommand::Add {
+            url,
+            symbols,
+            threshold,
+            stale_after,
+            every_update,
+            secret,
+        } => {
+            let mut events = Vec::new();
+            if every_update {
+                events.push("update".to_string());
+            }
+            if threshold.is_some() {
+                events.push("threshold".to_string());
+            }
+            if stale_after.is_some() {
+                events.push("staleness".to_string());
+            }
+            if events.is_empty() {
+                bail!("Pass --threshold, --stale-after and/or --every-update");
+            }
+
+            let alert = backend
+                .add(&CreateWebhookRequest {
+                    url,
+                    symbols,
+                    events,
+                    threshold_percent: threshold,
+                    stale_after_secs: stale_after,
+                    secret,
+                })
+                .await?;
+            println!("Added alert {}", describe(&alert));
+            if let Some(secret) = &alert.secret {
+                println!("Signing secret: {}", secret);
+                println!("Store it now; it is not shown again.");
+            }
+            if local {
+                println!("Running servers pick it up within 30 seconds.");
+            }
+            Ok(())
+        }
+        AlertsCommand::List { follow } => {
+            let alerts = backend.list().await?;
+            if alerts.is_empty() {
+                println!("No alerts");
+            }
+            for alert in &alerts {
+                println!("{}", describe(alert));
+            }
+            if follow {
+                follow_firings(&backend, &alerts).await?;
+            }
+            Ok(())
+        }
+        AlertsCommand::Rm { id } => {
+            backend.remove(id).await?;
+            println!("Removed alert {}", id);
+            Ok(())
+        }
+    }
+}
+
+/// Pool of the database in the config at `config_path`, or of DATABASE_URL
+/// when there is no such file, once it answers
+async fn connect(config_path: &str) -> Result<DbPool> {
+    let database = if Path::new(config_path).is_file() {
+        Config::from_file(config_path).await?.database
+    } else {
+        DatabaseConfig::default()
+    };
+    let pool = database::create_db_pool(&database)?;
+    database::check_connection(&pool, &database).await?;
+    Ok(pool)
+}
+
+/// Print the deliveries of every alert as they happen, until Ctrl+C. Firings
+/// logged before `alerts` were listed are skipped; alerts added meanwhile are
+/// followed from their first firing.
+async fn follow_firings(backend: &Backend, alerts: &[WebhookInfo]) -> Result<()> {
+    let mut seen: HashMap<i32, i64> = HashMap::new();
+    for alert in alerts {
+        let last = backend
+            .deliveries(alert.id)
+            .await?
+            .first()
+            .map_or(0, |delivery| delivery.id);
+        seen.insert(alert.id, last);
+    }
+    println!("Following alert firings (Ctrl+C to stop)");
+
+    let mut interval = time::interval(FOLLOW_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = signal::ctrl_c() => return Ok(()),
+        }
+
+        let alerts = match backend.list().await {
+            Ok(alerts) => alerts,
+            Err(e) => {
+                eprintln!("Failed to list alerts: {}", e);
+                continue;
+            }
+        };
+        seen.retain(|id, _| alerts.iter().any(|alert| alert.id == *id));
+        for alert in &alerts {
+            let deliveries = match backend.deliveries(alert.id).await {
+                Ok(deliveries) => deliveries,
+                Err(e) => {
+                    eprintln!("Failed to read firings of alert {}: {}", alert.id, e);
+                    continue;
+                }
+            };
+            let last = seen.entry(alert.id).or_insert(0);
+            for delivery in deliveries.iter().rev().filter(|d| d.id > *last) {
+                println!("{}", firing(alert.id, delivery));
+            }
+            if let Some(newest) = deliveries.first() {
+                *last = (*last).max(newest.id);
+            }
+        }
+    }
+}
+
+/// One line per alert, e.g. "#7 threshold 5% on BTC, ETH -> https://..."
+fn describe(alert: &WebhookInfo) -> String {
+    let events: Vec<String> = alert
+        .events
+        .iter()
+        .map(|event| match event.as_str() {
+            "threshold" => match alert.threshold_percent {
+                Some(percent) => format!("threshold {}%", percent),
+                None => event.clone(),
+            },
+            "staleness" => match alert.stale_after_secs {
+                Some(secs) => format!("stale after {}s", secs),
+                None => event.clone(),
+            },
+            _ => event.clone(),
+        })
+        .collect();
+    let symbols = if alert.symbols.is_empty() {
+        "every symbol".to_string()
+    } else {
+        alert.symbols.join(", ")
+    };
+    format!(
+        "#{} {} on {} -> {}",
+        alert.id,
+        events.join(", "),
+        symbols,
+        alert.url
+    )
+}
+
+/// One line per delivery, e.g. "2026-10-16T09:12:03Z #7 threshold crypto:BTC 200 (41 ms)"
+fn firing(alert: i32, delivery: &WebhookDelivery) -> String {
+    let subject = match (&delivery.asset_type, &delivery.symbol) {
+        (Some(asset_type), Some(symbol)) => format!("{}:{}", asset_type, symbol),
+        (None, Some(symbol)) => symbol.clone(),
+        _ => "-".to_string(),
+    };
+    let outcome = match (delivery.status_code, &delivery.error) {
+        (_, Some(error)) => format!("failed: {}", error),
+        (Some(status), None) => status.to_string(),
+        (None, None) => "no response".to_string(),
+    };
+    format!(
+        "{} #{} {} {} {} ({} ms)",
+        delivery.delivered_at, alert, delivery.event, subject, outcome, delivery.duration_ms
+    )
+}
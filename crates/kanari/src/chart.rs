@@ -0,0 +1,309 @@
+use anyhow::{Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use kanari_oracle::history::HistoryPoint;
+use kanari_oracle::locale::Locale;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::remote::{self, RemoteClient};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Fewest rows a chart is drawn with
+const MIN_HEIGHT: usize = 4;
+
+/// Length of one bar of a chart, e.g. "15m", "1h" or "1d"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    seconds: i64,
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let unit_at = lower
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(lower.len());
+        let (count, unit) = lower.split_at(unit_at);
+        let unit = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => 0,
+        };
+        match count.parse::<i64>() {
+            Ok(count) if count > 0 && unit > 0 => Ok(Self {
+                seconds: count * unit,
+            }),
+            _ => Err(format!(
+                "unknown interval '{}', expected a number followed by s, m, h or d, e.g. 15m or 1h",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.seconds {
+            s if s % 86400 == 0 => write!(f, "{}d", s / 86400),
+            s if s % 3600 == 0 => write!(f, "{}h", s / 3600),
+            s if s % 60 == 0 => write!(f, "{}m", s / 60),
+            s => write!(f, "{}s", s),
+        }
+    }
+}
+
+/// How a chart is drawn
+pub struct ChartOptions {
+    pub interval: Interval,
+    /// Bars ending with the current interval
+    pub bars: usize,
+    /// Rows of the price axis
+    pub height: usize,
+    /// Draw each bar's close as a line instead of candles
+    pub line: bool,
+    /// Color rising bars green and falling bars red
+    pub color: bool,
+    pub locale: Locale,
+}
+
+/// Open, high, low and close of the prices published in one interval
+#[derive(Debug, Clone, Copy)]
+struct Candle {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+}
+
+/// Print a chart of the prices the server at `client` kept for `symbol` over
+/// the last `options.bars` intervals. Intervals are aligned to the Unix epoch,
+/// so hourly bars start on the hour; leading intervals without a price are
+/// left out.
+pub async fn print_chart(
+    client: &RemoteClient,
+    symbol: &str,
+    asset_type: &str,
+    options: &ChartOptions,
+) -> Result<()> {
+    if options.bars == 0 {
+        bail!("--bars must be at least 1");
+    }
+    if options.height < MIN_HEIGHT {
+        bail!("--height must be at least {}", MIN_HEIGHT);
+    }
+
+    let width = TimeDelta::seconds(options.interval.seconds);
+    let now = Utc::now();
+    let end = DateTime::from_timestamp(
+        (now.timestamp() / options.interval.seconds + 1) * options.interval.seconds,
+        0,
+    )
+    .unwrap_or(now);
+    let start = end - width * options.bars as i32;
+
+    let history = client.history(symbol, asset_type, start).await?;
+    let mut candles = candles(&history.points, start, options.interval, options.bars);
+    let Some(first) = candles.iter().position(Option::is_some) else {
+        bail!(
+            "No history for {} in the last {} bars of {}; the server keeps `history.retention` seconds",
+            symbol.to_uppercase(),
+            options.bars,
+            options.interval
+        );
+    };
+    candles.drain(..first);
+    let start = start + width * first as i32;
+
+    let rule = remote::precision(&history.asset_type);
+    let label = |price: f64| options.locale.number(&rule.format(price));
+    let bars: Vec<Candle> = candles.iter().flatten().copied().collect();
+    let (open, close) = (bars[0].open, bars[bars.len() - 1].close);
+    println!(
+        "{} ({}) · {} bars · last {} ({})",
+        history.symbol.to_uppercase(),
+        history.asset_type,
+        options.interval,
+        label(close),
+        options.locale.percent((close - open) / open * 100.0)
+    );
+    for row in render(&candles, options, &label) {
+        println!("{}", row);
+    }
+
+    let time_format = if options.interval.seconds % 86400 == 0 {
+        "%Y-%m-%d"
+    } else {
+        "%m-%d %H:%M"
+    };
+    let margin = axis_width(&candles, options.height, &label) + 2;
+    let from = start.format(time_format).to_string();
+    let to = format!("{} UTC", end.format(time_format));
+    let gap = (candles.len() * 2).saturating_sub(from.len() + to.len());
+    if gap > 0 {
+        println!("{}{}{}{}", " ".repeat(margin), from, " ".repeat(gap), to);
+    } else {
+        println!("{}{} UTC", " ".repeat(margin), from);
+    }
+    Ok(())
+}
+
+/// One candle per interval from `start`, None for intervals without a price.
+/// `points` are oldest first.
+fn candles(
+    points: &[HistoryPoint],
+    start: DateTime<Utc>,
+    interval: Interval,
+    bars: usize,
+) -> Vec<Option<Candle>> {
+    let mut candles: Vec<Option<Candle>> = vec![None; bars];
+    for point in points {
+        let offset = (point.timestamp - start).num_seconds();
+        if offset < 0 {
+            continue;
+        }
+        let Some(candle) = candles.get_mut((offset / interval.seconds) as usize) else {
+            continue;
+        };
+        match candle {
+            Some(candle) => {
+                candle.high = candle.high.max(point.price);
+                candle.low = candle.low.min(point.price);
+                candle.close = point.price;
+            }
+            None => {
+                *candle = Some(Candle {
+                    open: point.price,
+                    high: point.price,
+                    low: point.price,
+                    close: point.price,
+                })
+            }
+        }
+    }
+    candles
+}
+
+/// Lowest and highest price drawn; never the same, so a flat series is drawn
+/// mid-chart
+fn price_range(candles: &[Option<Candle>]) -> (f64, f64) {
+    let low = candles
+        .iter()
+        .flatten()
+        .map(|candle| candle.low)
+        .fold(f64::INFINITY, f64::min);
+    let high = candles
+        .iter()
+        .flatten()
+        .map(|candle| candle.high)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if high > low {
+        (low, high)
+    } else {
+        let pad = (low.abs() * 0.01).max(f64::EPSILON);
+        (low - pad, high + pad)
+    }
+}
+
+/// Rows of a chart `height` rows high that carry a price label, bottom first
+fn labelled_rows(height: usize) -> [usize; 3] {
+    [0, (height - 1) / 2, height - 1]
+}
+
+/// Price at the middle of `row`, 0 being the bottom row
+fn price_at(candles: &[Option<Candle>], height: usize, row: usize) -> f64 {
+    let (low, high) = price_range(candles);
+    low + (high - low) * row as f64 / (height - 1) as f64
+}
+
+/// Width of the widest price label
+fn axis_width(candles: &[Option<Candle>], height: usize, label: &impl Fn(f64) -> String) -> usize {
+    labelled_rows(height)
+        .iter()
+        .map(|row| label(price_at(candles, height, *row)).chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Rows of the chart, top first, each with its price label
+fn render(
+    candles: &[Option<Candle>],
+    options: &ChartOptions,
+    label: &impl Fn(f64) -> String,
+) -> Vec<String> {
+    let (low, high) = price_range(candles);
+    let top = options.height - 1;
+    let row_of = |price: f64| {
+        (((price - low) / (high - low)) * top as f64)
+            .round()
+            .clamp(0.0, top as f64) as usize
+    };
+    let margin = axis_width(candles, options.height, label);
+
+    let mut rows = Vec::with_capacity(options.height + 1);
+    for row in (0..options.height).rev() {
+        let mut line = if labelled_rows(options.height).contains(&row) {
+            let price = price_at(candles, options.height, row);
+            format!("{:>width$} ┤", label(price), width = margin)
+        } else {
+            format!("{:>width$} │", "", width = margin)
+        };
+
+        let mut previous: Option<f64> = None;
+        for candle in candles {
+            let Some(candle) = candle else {
+                line.push_str("  ");
+                continue;
+            };
+            let (glyph, rising) = if options.line {
+                let close = row_of(candle.close);
+                let from = previous.map_or(close, row_of);
+                let rising = previous.is_none_or(|previous| candle.close >= previous);
+                let glyph = if row == close {
+                    '●'
+                } else if row > from.min(close) && row < from.max(close) {
+                    '│'
+                } else {
+                    ' '
+                };
+                previous = Some(candle.close);
+                (glyph, rising)
+            } else {
+                let body = (
+                    row_of(candle.open.min(candle.close)),
+                    row_of(candle.open.max(candle.close)),
+                );
+                let glyph = if row >= body.0 && row <= body.1 {
+                    '█'
+                } else if row >= row_of(candle.low) && row <= row_of(candle.high) {
+                    '│'
+                } else {
+                    ' '
+                };
+                (glyph, candle.close >= candle.open)
+            };
+
+            if options.color && glyph != ' ' {
+                let color = if rising { GREEN } else { RED };
+                line.push_str(&format!("{}{}{} ", color, glyph, RESET));
+            } else {
+                line.push(glyph);
+                line.push(' ');
+            }
+        }
+        rows.push(line.trim_end().to_string());
+    }
+    rows.push(format!(
+        "{:>width$} └{}",
+        "",
+        "─".repeat(candles.len() * 2),
+        width = margin
+    ));
+    rows
+}
@@ -1,14 +1,46 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use clap::{Parser, Subcommand};
-use log::{error, info};
-use std::collections::HashSet;
+use futures::future::join_all;
+use log::{error, info, warn};
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::signal;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use tokio::time;
 
+mod alerts;
+mod chart;
+mod overrides;
+mod reload;
+mod remote;
+mod service;
+mod setup;
+mod standby;
+mod systemd;
+
+use alerts::AlertsCommand;
+use chart::{ChartOptions, Interval};
 use kanari_api::api;
-use kanari_oracle::config::Config;
+use kanari_api::auth::AuthMode;
+use kanari_api::control::UpdateControl;
+use kanari_api::database;
+use kanari_api::drain::Drain;
+use kanari_oracle::config::{Config, StoreBackend};
+use kanari_oracle::locale::Locale;
+use kanari_oracle::models::AssetType;
 use kanari_oracle::oracle::Oracle;
+use kanari_oracle::price_math;
+use kanari_oracle::round::UpdateReport;
+use kanari_oracle::scheduler;
+use kanari_oracle::table::{self, Column, Lookup, SortKey, TableOptions};
+use overrides::Overrides;
+use remote::RemoteClient;
+use service::ServiceCommand;
+use systemd::Systemd;
+
+/// How long shutdown waits for in-flight requests and the current update round
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
 
 #[derive(Parser)]
 #[command(name = "kanari")]
@@ -16,6 +48,28 @@ use kanari_oracle::oracle::Oracle;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Replay prices from this fixture file (written by `record`) instead of
+    /// querying providers
+    #[arg(long, global = true)]
+    offline: Option<String>,
+    /// Simulate random-walk prices instead of querying providers
+    #[arg(long, global = true)]
+    sandbox: bool,
+    /// Seed of the simulated prices (defaults to sandbox.seed)
+    #[arg(long, global = true, requires = "sandbox")]
+    seed: Option<u64>,
+    /// Standard deviation of each simulated step, in percent (defaults to
+    /// sandbox.volatility)
+    #[arg(long, global = true, requires = "sandbox")]
+    volatility: Option<f64>,
+    /// Show prices in this currency (e.g. thb), converted through the fx table
+    /// (defaults to display.currency)
+    #[arg(long, global = true)]
+    display_currency: Option<String>,
+    /// Locale of numbers in the output, e.g. th or de-CH (defaults to
+    /// display.locale, then LC_ALL, LC_NUMERIC or LANG)
+    #[arg(long, global = true)]
+    locale: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -25,9 +79,19 @@ enum Commands {
         /// Configuration file path
         #[arg(short, long, default_value = "config.json")]
         config: String,
-        /// Update interval in seconds
-        #[arg(short, long, default_value = "30")]
-        interval: u64,
+        /// Update interval in seconds (defaults to general.update_interval)
+        #[arg(short, long)]
+        interval: Option<u64>,
+        /// Order of the printed price tables: symbol, price or change
+        #[arg(long, default_value = "symbol")]
+        sort: SortKey,
+        /// Comma-separated columns of the printed price tables: symbol, price,
+        /// change, change%, volume, market_cap, source and age
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<Column>,
+        /// Print the price tables without colors
+        #[arg(long)]
+        no_color: bool,
     },
     /// Start the HTTP API server
     Server {
@@ -37,20 +101,37 @@ enum Commands {
         /// Port to run the API server on
         #[arg(short, long, default_value = "3000")]
         port: u16,
-        /// Update interval in seconds for background updates
-        #[arg(short, long, default_value = "30")]
-        interval: u64,
+        /// Update interval in seconds for background updates (defaults to
+        /// general.update_interval, which is hot-reloaded when not set here)
+        #[arg(short, long)]
+        interval: Option<u64>,
+        /// Serve prices from the shared store (`store` in the config) without
+        /// running background updates; a separate `start` process updates the store
+        #[arg(long)]
+        no_updater: bool,
+        /// How requests are authenticated: `database` (users and tokens in
+        /// Postgres), `tokens` (static list from KANARI_API_TOKENS) or `none`
+        #[arg(long, default_value = "database")]
+        auth: AuthMode,
     },
-    /// Get current price for a symbol
+    /// Get current prices for one or more symbols
     Price {
-        /// Symbol to get price for (e.g., BTC, AAPL)
-        symbol: String,
-        /// Asset type (crypto or stock)
-        #[arg(short, long, default_value = "crypto")]
+        /// Symbols to get prices for (e.g., BTC ETH AAPL), fetched concurrently
+        #[arg(required = true)]
+        symbols: Vec<String>,
+        /// Asset type (auto, crypto, stock or tvl); auto resolves from the configured
+        /// crypto and stock symbols
+        #[arg(short, long, default_value = "auto")]
         asset_type: String,
         /// Configuration file path
         #[arg(short, long, default_value = "config.json")]
         config: String,
+        /// Query a running API server (e.g., http://localhost:3000) instead of fetching directly
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// API token used with --remote
+        #[arg(short, long)]
+        token: Option<String>,
     },
     /// List all available symbols
     List {
@@ -60,12 +141,146 @@ enum Commands {
         /// Configuration file path
         #[arg(short, long, default_value = "config.json")]
         config: String,
+        /// Query a running API server (e.g., http://localhost:3000) instead of fetching directly
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// API token used with --remote
+        #[arg(short, long)]
+        token: Option<String>,
     },
     /// Show price statistics
     Stats {
         /// Configuration file path
         #[arg(short, long, default_value = "config.json")]
         config: String,
+        /// Query a running API server (e.g., http://localhost:3000) instead of fetching directly
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// API token used with --remote
+        #[arg(short, long)]
+        token: Option<String>,
+    },
+    /// Exit 0 when a price meets a condition and 1 when it does not, for shell
+    /// scripts; errors exit 2
+    Check {
+        /// Symbol to check (e.g., BTC, AAPL)
+        symbol: String,
+        /// Met when the price is above this level
+        #[arg(long, required_unless_present = "below")]
+        above: Option<f64>,
+        /// Met when the price is below this level; with --above, met when the
+        /// price is between the two
+        #[arg(long)]
+        below: Option<f64>,
+        /// Asset type (auto, crypto, stock or tvl)
+        #[arg(short, long, default_value = "auto")]
+        asset_type: String,
+        /// Configuration file path
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+        /// Query a running API server (e.g., http://localhost:3000) instead of fetching directly
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// API token used with --remote
+        #[arg(short, long)]
+        token: Option<String>,
+        /// Print nothing; only set the exit code
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Chart a symbol's recent prices from a running API server's history
+    Chart {
+        /// Symbol to chart (e.g., BTC, AAPL)
+        symbol: String,
+        /// Length of each bar (e.g., 15m, 1h, 1d)
+        #[arg(short, long, default_value = "1h")]
+        interval: Interval,
+        /// Number of bars, ending with the current interval
+        #[arg(short, long, default_value = "48")]
+        bars: usize,
+        /// Rows of the price axis
+        #[arg(long, default_value = "16")]
+        height: usize,
+        /// Draw a line through each bar's close instead of candlesticks
+        #[arg(long)]
+        line: bool,
+        /// Asset type (auto, crypto, stock, basket or tvl)
+        #[arg(short, long, default_value = "auto")]
+        asset_type: String,
+        /// API server whose price history is charted (e.g., http://localhost:3000)
+        #[arg(short, long)]
+        remote: String,
+        /// API token used with --remote
+        #[arg(short, long)]
+        token: Option<String>,
+    },
+    /// Compare a symbol's price across every enabled source
+    Compare {
+        /// Symbol to compare (e.g., BTC, AAPL)
+        symbol: String,
+        /// Asset type (crypto, stock or tvl)
+        #[arg(short, long, default_value = "crypto")]
+        asset_type: String,
+        /// Configuration file path
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+    },
+    /// Run the API server as a systemd or launchd service
+    Service {
+        #[command(subcommand)]
+        command: ServiceCommand,
+    },
+    /// Write a config file interactively, testing each provider API key entered
+    Setup {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+    },
+    /// Check a config file and report every problem found
+    CheckConfig {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+        /// Also report fields the config does not recognise
+        #[arg(short, long)]
+        strict: bool,
+    },
+    /// Fetch every configured symbol once and save the prices as a fixture for --offline
+    Record {
+        /// Fixture file to write
+        #[arg(short, long, default_value = "fixture.json")]
+        output: String,
+        /// Configuration file path
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+    },
+    /// Benchmark latency and error rate of each configured source
+    Bench {
+        /// Number of rounds to run against each source
+        #[arg(short, long, default_value = "10")]
+        rounds: u32,
+        /// Configuration file path
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+    },
+    /// Manage price alerts through a running API server, or directly in the
+    /// database of the config's database section or DATABASE_URL
+    Alerts {
+        #[command(subcommand)]
+        command: AlertsCommand,
+        /// API server to manage alerts on (e.g., http://localhost:3000)
+        #[arg(short, long, global = true)]
+        remote: Option<String>,
+        /// API token used with --remote; alerts belong to its owner
+        #[arg(short, long, global = true)]
+        token: Option<String>,
+        /// Without --remote: user whose alerts are managed in the database
+        #[arg(short, long, global = true)]
+        owner: Option<String>,
+        /// Without --remote: configuration file whose database section is
+        /// used, when it exists
+        #[arg(short, long, global = true, default_value = "config.json")]
+        config: String,
     },
 }
 
@@ -75,111 +290,469 @@ async fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     let cli = Cli::parse();
+    if let Some(locale) = cli.locale.as_deref()
+        && Locale::parse(locale).is_none()
+    {
+        anyhow::bail!("Unknown locale '{}'", locale);
+    }
+    let overrides = Overrides {
+        offline: cli.offline,
+        sandbox: cli.sandbox,
+        seed: cli.seed,
+        volatility: cli.volatility,
+        display_currency: cli.display_currency,
+        locale: cli.locale,
+    };
 
     match cli.command {
-        Commands::Start { config, interval } => start_oracle_service(config, interval).await,
+        Commands::Start {
+            config,
+            interval,
+            sort,
+            columns,
+            no_color,
+        } => {
+            let mut table = TableOptions {
+                sort,
+                columns,
+                ..TableOptions::default()
+            };
+            table.color &= !no_color;
+            start_oracle_service(config, interval, &table, &overrides).await
+        }
         Commands::Server {
             config,
             port,
             interval,
-        } => start_api_server_with_updates(config, port, interval).await,
+            no_updater,
+            auth,
+        } => {
+            start_api_server_with_updates(config, port, interval, no_updater, auth, &overrides)
+                .await
+        }
         Commands::Price {
+            symbols,
+            asset_type,
+            config,
+            remote,
+            token,
+        } => match remote {
+            Some(url) => {
+                RemoteClient::new(&url, token)?
+                    .display(overrides.display_currency.clone(), overrides.locale())
+                    .print_prices(&symbols, &asset_type)
+                    .await
+            }
+            None => get_prices(symbols, asset_type, config, &overrides).await,
+        },
+        Commands::List {
+            asset_type,
+            config,
+            remote,
+            token,
+        } => match remote {
+            Some(url) => {
+                if !matches!(asset_type.as_str(), "crypto" | "stock" | "all") {
+                    error!("Invalid asset type. Use 'crypto', 'stock', or 'all'");
+                    return Ok(());
+                }
+                RemoteClient::new(&url, token)?
+                    .print_symbols(&asset_type)
+                    .await
+            }
+            None => list_symbols(asset_type, config, &overrides).await,
+        },
+        Commands::Stats {
+            config,
+            remote,
+            token,
+        } => match remote {
+            Some(url) => {
+                RemoteClient::new(&url, token)?
+                    .display(overrides.display_currency.clone(), overrides.locale())
+                    .print_stats()
+                    .await
+            }
+            None => show_statistics(config, &overrides).await,
+        },
+        Commands::Check {
             symbol,
+            above,
+            below,
             asset_type,
             config,
-        } => get_single_price(symbol, asset_type, config).await,
-        Commands::List { asset_type, config } => list_symbols(asset_type, config).await,
-        Commands::Stats { config } => show_statistics(config).await,
+            remote,
+            token,
+            quiet,
+        } => {
+            if let (Some(above), Some(below)) = (above, below)
+                && above >= below
+            {
+                eprintln!("Error: --above must be less than --below");
+                std::process::exit(2);
+            }
+            let condition = Condition { above, below };
+            let quote = match remote {
+                Some(url) => match RemoteClient::new(&url, token) {
+                    Ok(client) => {
+                        client
+                            .display(overrides.display_currency.clone(), overrides.locale())
+                            .quote(&symbol, &asset_type)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                },
+                None => local_quote(&symbol, &asset_type, &config, &overrides).await,
+            };
+            check_condition(&symbol, quote, &condition, quiet)
+        }
+        Commands::Chart {
+            symbol,
+            interval,
+            bars,
+            height,
+            line,
+            asset_type,
+            remote,
+            token,
+        } => {
+            let options = ChartOptions {
+                interval,
+                bars,
+                height,
+                line,
+                color: TableOptions::default().color,
+                locale: overrides.locale(),
+            };
+            let client = RemoteClient::new(&remote, token)?;
+            chart::print_chart(&client, &symbol, &asset_type, &options).await
+        }
+        Commands::Compare {
+            symbol,
+            asset_type,
+            config,
+        } => compare_sources(symbol, asset_type, config, &overrides).await,
+        Commands::Record { output, config } => record_fixture(output, config).await,
+        Commands::Bench { rounds, config } => bench_sources(rounds, config, &overrides).await,
+        Commands::CheckConfig { config, strict } => check_config(config, strict).await,
+        Commands::Setup { config } => setup::run(&config).await,
+        Commands::Service { command } => service::run(command).await,
+        Commands::Alerts {
+            command,
+            remote,
+            token,
+            owner,
+            config,
+        } => alerts::run(command, remote, token, owner, &config).await,
     }
 }
 
-async fn start_oracle_service(config_path: String, interval: u64) -> Result<()> {
-    info!("Starting Kanari Oracle Service...");
-
-    let config = Config::from_file(&config_path)
+/// Load the config file with the modes chosen on the command line applied
+async fn load_config(config_path: &str, overrides: &Overrides) -> Result<Config> {
+    let mut config = Config::from_file(config_path)
         .await
         .context("Failed to load config")?;
-    let mut oracle = Oracle::new(config)
+    overrides.apply(&mut config);
+    Ok(config)
+}
+
+async fn start_oracle_service(
+    config_path: String,
+    interval: Option<u64>,
+    table: &TableOptions,
+    overrides: &Overrides,
+) -> Result<()> {
+    info!("Starting Kanari Oracle Service...");
+
+    let config = load_config(&config_path, overrides).await?;
+    let interval = interval.unwrap_or(config.general.update_interval);
+    let election = join_election(&config)?;
+    let oracle = Oracle::new(config)
         .await
         .context("Failed to initialize oracle")?;
+    // Rates for showing the printed prices in display.currency
+    let _fx = oracle
+        .config()
+        .display
+        .currency
+        .is_some()
+        .then(|| oracle.spawn_fx_updater());
+    let oracle = std::sync::Arc::new(tokio::sync::RwLock::new(oracle));
 
     info!("Oracle initialized successfully");
     info!("Update interval: {} seconds", interval);
 
-    let mut update_interval = time::interval(Duration::from_secs(interval));
+    let systemd = Systemd::from_env();
+    let _watchdog = systemd.spawn_watchdog();
+
+    let period = Duration::from_secs(interval);
+    let mut update_interval = time::interval(period);
+    update_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
 
     loop {
         update_interval.tick().await;
+        // A follower waits for the leader to stop before updating
+        if election
+            .as_ref()
+            .is_some_and(|(leader, _)| !*leader.borrow())
+        {
+            systemd.ready();
+            systemd.status("Standing by; another instance leads updates");
+            continue;
+        }
+        run_scheduled_round(&oracle, period, &systemd, table).await;
+    }
+}
+
+/// Compete for leadership of background updates when `ha` is configured
+fn join_election(config: &Config) -> Result<Option<(watch::Receiver<bool>, JoinHandle<()>)>> {
+    let Some(ha) = &config.ha else {
+        return Ok(None);
+    };
+    let pool = database::create_db_pool(&config.database)
+        .context("ha needs Postgres for leader election")?;
+    info!(
+        "HA enabled; competing for leadership on advisory lock {}",
+        ha.lock_id
+    );
+    Ok(Some(database::spawn_leader_election(pool, ha)))
+}
+
+/// Run one update round, spreading symbol batches across `period` so upstream
+/// sources see a steady trickle of requests instead of one burst per interval.
+/// Batches run on a clone of the oracle, so API reads are never blocked by a fetch.
+/// The batch reports are merged into the round's report, which is logged and
+/// kept as the last round, and the prices are printed as `table` says. The first
+/// round without failures tells systemd the service is ready.
+async fn run_scheduled_round(
+    oracle: &api::SharedOracle,
+    period: Duration,
+    systemd: &Systemd,
+    table: &TableOptions,
+) {
+    let start = time::Instant::now();
+    let batches = scheduler::plan_round(oracle.read().await.config(), period);
+    let mut report = UpdateReport::start();
+    let mut failed = 0;
+
+    for batch in batches {
+        time::sleep_until(start + batch.offset).await;
 
-        match oracle.update_all_prices().await {
-            Ok(count) => info!("Updated {} price feeds", count),
-            Err(e) => error!("Failed to update prices: {}", e),
+        // Cloned per batch so a config reload mid-round is picked up
+        let updater = oracle.read().await.clone();
+        let _busy = systemd.busy();
+        match updater
+            .update_symbols(batch.asset_type, &batch.symbols)
+            .await
+        {
+            Ok(batch_report) => report.merge(batch_report),
+            Err(e) => {
+                failed += 1;
+                error!(
+                    "Failed to update {} {} prices: {}",
+                    batch.symbols.len(),
+                    batch.asset_type.as_str(),
+                    e
+                )
+            }
         }
+    }
 
-        // Print current prices
-        oracle.print_current_prices();
+    let report = report.finish();
+    report.log();
+    let oracle = oracle.read().await;
+    oracle.record_round(report.clone());
+    oracle.print_current_prices(table);
+
+    systemd.status(&format!(
+        "Last round: {} price feeds updated, {} symbols failed, {} batches failed, took {}s",
+        report.updated,
+        report.failed,
+        failed,
+        start.elapsed().as_secs()
+    ));
+    if failed == 0 {
+        systemd.ready();
     }
 }
 
-async fn get_single_price(symbol: String, asset_type: String, config_path: String) -> Result<()> {
-    let config = Config::from_file(&config_path)
-        .await
-        .context("Failed to load config")?;
+/// Fetch every symbol concurrently and print one price, or a table of them
+/// with the error of each symbol that failed
+async fn get_prices(
+    symbols: Vec<String>,
+    asset_type: String,
+    config_path: String,
+    overrides: &Overrides,
+) -> Result<()> {
+    let config = load_config(&config_path, overrides).await?;
     let oracle = Oracle::new(config)
         .await
         .context("Failed to initialize oracle")?;
 
-    let price = match asset_type.as_str() {
-        "crypto" => {
-            let available: HashSet<String> = oracle
-                .get_crypto_symbols()
-                .into_iter()
-                .map(|s| s.to_lowercase())
-                .collect();
-            if !available.contains(&symbol.to_lowercase()) {
-                error!("Symbol '{}' not configured for crypto", symbol);
-                return Ok(());
-            }
-            oracle
-                .get_crypto_price(&symbol)
-                .await
-                .context("Failed to fetch crypto price")?
-        }
-        "stock" => {
-            let available: HashSet<String> = oracle
-                .get_stock_symbols()
-                .into_iter()
-                .map(|s| s.to_uppercase())
-                .collect();
-            if !available.contains(&symbol.to_uppercase()) {
-                error!("Symbol '{}' not configured for stock", symbol);
-                return Ok(());
-            }
-            oracle
-                .get_stock_price(&symbol)
-                .await
-                .context("Failed to fetch stock price")?
-        }
-        _ => {
-            error!("Invalid asset type. Use 'crypto' or 'stock'");
-            return Ok(());
-        }
-    };
+    let lookups = join_all(
+        symbols
+            .iter()
+            .map(|symbol| fetch_price(&oracle, symbol, &asset_type)),
+    );
+    let (lookups, ()) = tokio::join!(lookups, oracle.refresh_fx_rates());
+
+    if let [lookup] = lookups.as_slice() {
+        let (asset_type, price) = lookup.result.as_ref().map_err(|e| anyhow!("{}", e))?;
+        println!(
+            "Current price for {}: {}",
+            lookup.symbol.to_uppercase(),
+            oracle.amount_format(*asset_type).money(
+                &oracle.config().precision(*asset_type, &lookup.symbol),
+                price.price
+            )
+        );
+        println!("Last updated: {}", price.timestamp);
+        return Ok(());
+    }
 
     println!(
-        "Current price for {}: ${:.2}",
-        symbol.to_uppercase(),
-        price.price
+        "{}",
+        table::lookup_table(&oracle, &lookups, TableOptions::default().color)
     );
-    println!("Last updated: {}", price.timestamp);
-
+    let failed = lookups
+        .iter()
+        .filter(|lookup| lookup.result.is_err())
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} symbols failed", failed, lookups.len());
+    }
     Ok(())
 }
 
-async fn list_symbols(asset_type: String, config_path: String) -> Result<()> {
-    let config = Config::from_file(&config_path)
+/// Price levels of `check`; at least one is set
+struct Condition {
+    above: Option<f64>,
+    below: Option<f64>,
+}
+
+impl Condition {
+    fn holds(&self, price: f64) -> bool {
+        self.above.is_none_or(|level| price > level) && self.below.is_none_or(|level| price < level)
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => write!(f, "between {} and {}", above, below),
+            (Some(above), None) => write!(f, "above {}", above),
+            (None, Some(below)) => write!(f, "below {}", below),
+            (None, None) => write!(f, "any price"),
+        }
+    }
+}
+
+/// Price of `symbol` in the display currency, and how it is shown
+async fn local_quote(
+    symbol: &str,
+    asset_type: &str,
+    config_path: &str,
+    overrides: &Overrides,
+) -> Result<(f64, String)> {
+    let config = load_config(config_path, overrides).await?;
+    let oracle = Oracle::new(config)
         .await
-        .context("Failed to load config")?;
+        .context("Failed to initialize oracle")?;
+
+    let (lookup, ()) = tokio::join!(
+        fetch_price(&oracle, symbol, asset_type),
+        oracle.refresh_fx_rates()
+    );
+    let (asset_type, price) = lookup.result.map_err(|e| anyhow!("{}", e))?;
+    let amounts = oracle.amount_format(asset_type);
+    let value = amounts
+        .convert(price.price)
+        .ok_or_else(|| anyhow!("Price out of range in {}", amounts.currency))?;
+    let shown = amounts.money(&oracle.config().precision(asset_type, symbol), price.price);
+    Ok((value, shown))
+}
+
+/// Report whether the quoted price meets `condition` and exit with 0 if it
+/// does, 1 if it does not, or 2 if the price could not be had
+fn check_condition(
+    symbol: &str,
+    quote: Result<(f64, String)>,
+    condition: &Condition,
+    quiet: bool,
+) -> ! {
+    let (price, shown) = match quote {
+        Ok(quote) => quote,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(2);
+        }
+    };
+    let met = condition.holds(price);
+    if !quiet {
+        let verdict = if met { "is" } else { "is not" };
+        println!(
+            "{} at {} {} {}",
+            symbol.to_uppercase(),
+            shown,
+            verdict,
+            condition
+        );
+    }
+    std::process::exit(if met { 0 } else { 1 });
+}
+
+/// Look up one symbol, resolving its asset type when `asset_type` is "auto"
+async fn fetch_price(oracle: &Oracle, symbol: &str, asset_type: &str) -> Lookup {
+    let result = async {
+        let asset_type = match asset_type {
+            "auto" => oracle
+                .resolve_asset_type(symbol)
+                .map_err(|e| e.to_string())?,
+            other => other,
+        };
+        let asset_type = match asset_type {
+            "crypto" => AssetType::Crypto,
+            "stock" => AssetType::Stock,
+            "tvl" => AssetType::Tvl,
+            _ => {
+                return Err(
+                    "Invalid asset type. Use 'auto', 'crypto', 'stock' or 'tvl'".to_string()
+                );
+            }
+        };
+        let config = oracle.config();
+        if !config
+            .canonical_symbols(asset_type)
+            .contains(&config.canonical_symbol(asset_type, symbol))
+        {
+            return Err(format!(
+                "Symbol '{}' not configured for {}",
+                symbol,
+                asset_type.as_str()
+            ));
+        }
+        let price = match asset_type {
+            AssetType::Crypto => oracle.get_crypto_price(symbol).await,
+            AssetType::Stock => oracle.get_stock_price(symbol).await,
+            _ => oracle.get_tvl(symbol).await,
+        };
+        price
+            .map(|price| (asset_type, price))
+            .map_err(|e| format!("Failed to fetch price: {}", e))
+    }
+    .await;
+    Lookup {
+        symbol: symbol.to_string(),
+        result,
+    }
+}
+
+async fn list_symbols(
+    asset_type: String,
+    config_path: String,
+    overrides: &Overrides,
+) -> Result<()> {
+    let config = load_config(&config_path, overrides).await?;
     let oracle = Oracle::new(config)
         .await
         .context("Failed to initialize oracle")?;
@@ -215,11 +788,9 @@ async fn list_symbols(asset_type: String, config_path: String) -> Result<()> {
     Ok(())
 }
 
-async fn show_statistics(config_path: String) -> Result<()> {
-    let config = Config::from_file(&config_path)
-        .await
-        .context("Failed to load config")?;
-    let mut oracle = Oracle::new(config)
+async fn show_statistics(config_path: String, overrides: &Overrides) -> Result<()> {
+    let config = load_config(&config_path, overrides).await?;
+    let oracle = Oracle::new(config)
         .await
         .context("Failed to initialize oracle")?;
 
@@ -229,6 +800,7 @@ async fn show_statistics(config_path: String) -> Result<()> {
         .await
         .context("Failed to fetch prices for stats")?;
 
+    oracle.refresh_fx_rates().await;
     println!("=== Oracle Statistics ===");
     let stats = oracle.get_price_statistics();
 
@@ -242,12 +814,19 @@ async fn show_statistics(config_path: String) -> Result<()> {
             }
             "last_update" => println!("Last Update: {}", value.as_str().unwrap_or("N/A")),
             "avg_crypto_price" => println!(
-                "Average Crypto Price: ${:.2}",
-                value.as_f64().unwrap_or(0.0)
+                "Average Crypto Price: {}",
+                oracle.amount_format(AssetType::Crypto).money(
+                    &oracle.config().display.asset_rule(AssetType::Crypto),
+                    value.as_f64().unwrap_or(0.0)
+                )
+            ),
+            "avg_stock_price" => println!(
+                "Average Stock Price: {}",
+                oracle.amount_format(AssetType::Stock).money(
+                    &oracle.config().display.asset_rule(AssetType::Stock),
+                    value.as_f64().unwrap_or(0.0)
+                )
             ),
-            "avg_stock_price" => {
-                println!("Average Stock Price: ${:.2}", value.as_f64().unwrap_or(0.0))
-            }
             _ => println!("{}: {:?}", key, value),
         }
     }
@@ -255,53 +834,578 @@ async fn show_statistics(config_path: String) -> Result<()> {
     Ok(())
 }
 
+async fn compare_sources(
+    symbol: String,
+    asset_type: String,
+    config_path: String,
+    overrides: &Overrides,
+) -> Result<()> {
+    let config = load_config(&config_path, overrides).await?;
+    let oracle = Oracle::new(config)
+        .await
+        .context("Failed to initialize oracle")?;
+
+    let quotes = oracle
+        .compare_sources(&symbol, &asset_type)
+        .await
+        .context("Failed to compare sources")?;
+
+    let median = price_math::median(
+        quotes
+            .iter()
+            .filter_map(|q| q.result.as_ref().ok().map(|p| p.price)),
+    );
+
+    println!("\n=== Source comparison for {} ===", symbol.to_uppercase());
+    println!(
+        "{:<15} {:<14} {:<12} {:<10}",
+        "Source", "Price ($)", "Latency", "Deviation"
+    );
+    println!("{}", "-".repeat(70));
+
+    for quote in &quotes {
+        let latency = format!("{} ms", quote.latency.as_millis());
+        match &quote.result {
+            Ok(price) => {
+                let deviation = median
+                    .and_then(|m| price_math::deviation_percent(price.price, m))
+                    .map(|d| format!("{:+.3}%", d))
+                    .unwrap_or_else(|| "N/A".to_string());
+                println!(
+                    "{:<15} {:<14.4} {:<12} {:<10}",
+                    quote.source, price.price, latency, deviation
+                );
+            }
+            Err(e) => {
+                println!(
+                    "{:<15} {:<14} {:<12} error: {}",
+                    quote.source, "-", latency, e
+                );
+            }
+        }
+    }
+
+    match median {
+        Some(m) => println!("\nMedian: ${:.4}", m),
+        None => println!("\nNo source returned a price for {}", symbol),
+    }
+
+    Ok(())
+}
+
+async fn check_config(config_path: String, strict: bool) -> Result<()> {
+    let content = tokio::fs::read_to_string(&config_path)
+        .await
+        .with_context(|| format!("Failed to read config file '{}'", config_path))?;
+
+    let problems = Config::check_str(&content, strict);
+    if problems.is_empty() {
+        println!("{}: OK", config_path);
+        return Ok(());
+    }
+
+    println!("{}: {} problem(s) found", config_path, problems.len());
+    for problem in &problems {
+        println!("  {}", problem);
+    }
+    std::process::exit(1);
+}
+
+#[derive(Default)]
+struct SourceBench {
+    requests: u32,
+    errors: u32,
+    total_latency: Duration,
+}
+
+async fn record_fixture(output: String, config_path: String) -> Result<()> {
+    // Always recorded from the real providers, even if the config is offline
+    // or in sandbox mode
+    let mut config = load_config(&config_path, &Overrides::default()).await?;
+    config.offline = None;
+    config.sandbox = None;
+    let oracle = Oracle::new(config)
+        .await
+        .context("Failed to initialize oracle")?;
+
+    oracle
+        .update_all_prices()
+        .await
+        .context("Failed to fetch prices to record")?;
+
+    let fixture = oracle.record_fixture();
+    let content = serde_json::to_string_pretty(&fixture)?;
+    tokio::fs::write(&output, content)
+        .await
+        .with_context(|| format!("Failed to write fixture '{}'", output))?;
+
+    println!(
+        "Recorded {} crypto and {} stock prices to {}",
+        fixture.crypto.len(),
+        fixture.stocks.len(),
+        output
+    );
+    Ok(())
+}
+
+async fn bench_sources(rounds: u32, config_path: String, overrides: &Overrides) -> Result<()> {
+    let config = load_config(&config_path, overrides).await?;
+    let oracle = Oracle::new(config)
+        .await
+        .context("Failed to initialize oracle")?;
+
+    // Use the first configured symbol of each asset type as the probe
+    let probes: Vec<(&str, String)> = [
+        ("crypto", oracle.get_crypto_symbols().into_iter().next()),
+        ("stock", oracle.get_stock_symbols().into_iter().next()),
+    ]
+    .into_iter()
+    .filter_map(|(asset_type, symbol)| symbol.map(|s| (asset_type, s)))
+    .collect();
+
+    let mut results: HashMap<String, SourceBench> = HashMap::new();
+
+    for round in 1..=rounds {
+        info!("Benchmark round {}/{}", round, rounds);
+        for (asset_type, symbol) in &probes {
+            let quotes = oracle
+                .compare_sources(symbol, asset_type)
+                .await
+                .context("Failed to query sources")?;
+            for quote in quotes {
+                let entry = results.entry(quote.source).or_default();
+                entry.requests += 1;
+                entry.total_latency += quote.latency;
+                if quote.result.is_err() {
+                    entry.errors += 1;
+                }
+            }
+        }
+    }
+
+    // Rank by error rate first, then by average latency
+    let mut ranking: Vec<(String, f64, f64)> = results
+        .into_iter()
+        .filter(|(_, b)| b.requests > 0)
+        .map(|(source, b)| {
+            let error_rate = b.errors as f64 / b.requests as f64 * 100.0;
+            let avg_latency = b.total_latency.as_secs_f64() * 1000.0 / b.requests as f64;
+            (source, error_rate, avg_latency)
+        })
+        .collect();
+    ranking.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)));
+
+    println!("\n=== Source benchmark ({} rounds) ===", rounds);
+    println!(
+        "{:<6} {:<15} {:<14} {:<10}",
+        "Rank", "Source", "Avg latency", "Errors"
+    );
+    println!("{}", "-".repeat(70));
+    for (rank, (source, error_rate, avg_latency)) in ranking.iter().enumerate() {
+        println!(
+            "{:<6} {:<15} {:<14} {:<10}",
+            rank + 1,
+            source,
+            format!("{:.0} ms", avg_latency),
+            format!("{:.1}%", error_rate)
+        );
+    }
+
+    if ranking.is_empty() {
+        println!("No sources were benchmarked. Configure at least one symbol.");
+    }
+
+    Ok(())
+}
+
+/// Keep a replica's feeds in step with the shared store until shutdown
+async fn follow_store(
+    oracle: api::SharedOracle,
+    mut shutdown: watch::Receiver<bool>,
+    systemd: Systemd,
+) {
+    let period = Duration::from_millis(oracle.read().await.config().store.sync_interval);
+    let mut sync_interval = time::interval(period);
+    sync_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = sync_interval.tick() => {}
+            _ = shutdown.wait_for(|stop| *stop) => break,
+        }
+        let replica = oracle.read().await.clone();
+        let _busy = systemd.busy();
+        match replica.sync_from_store().await {
+            Ok(count) => {
+                systemd.ready();
+                systemd.status(&format!("Following the shared store: {} prices", count));
+            }
+            Err(e) => warn!("Failed to read prices from store: {}", e),
+        }
+    }
+}
+
+/// Run an update round every interval until shutdown. Config reloads publish a
+/// new interval through `interval_rx`; `control` can pause rounds or override the
+/// interval at runtime.
+async fn update_rounds(
+    oracle: &api::SharedOracle,
+    interval_rx: &mut watch::Receiver<u64>,
+    control: &UpdateControl,
+    shutdown: &mut watch::Receiver<bool>,
+    first_round_now: bool,
+    systemd: &Systemd,
+) {
+    let mut override_rx = control.subscribe_interval();
+    let effective = |interval_rx: &watch::Receiver<u64>,
+                     override_rx: &watch::Receiver<Option<u64>>| {
+        Duration::from_secs(override_rx.borrow().unwrap_or(*interval_rx.borrow()))
+    };
+    let mut period = effective(interval_rx, &override_rx);
+    let first = if first_round_now {
+        time::Instant::now()
+    } else {
+        time::Instant::now() + period
+    };
+    let mut update_interval = time::interval_at(first, period);
+    update_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        let changed = tokio::select! {
+            _ = update_interval.tick() => false,
+            Ok(()) = interval_rx.changed() => true,
+            Ok(()) = override_rx.changed() => true,
+            // Only checked between rounds, so a running round is never cut short
+            _ = shutdown.wait_for(|stop| *stop) => break,
+        };
+        if changed {
+            let new_period = effective(interval_rx, &override_rx);
+            if new_period != period {
+                period = new_period;
+                update_interval = time::interval_at(time::Instant::now() + period, period);
+                update_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            }
+            continue;
+        }
+        if control.is_paused() {
+            systemd.status("Background updates paused");
+            continue;
+        }
+        run_scheduled_round(oracle, period, systemd, &TableOptions::default()).await;
+    }
+}
+
+/// Update the symbols of each tenant with an `update_interval` of its own, on
+/// top of the deployment's rounds. Tenants are read from the current config on
+/// every tick, so reloads add, drop or re-time them. Nothing runs while updates
+/// are paused or, under HA, while another instance leads.
+async fn tenant_rounds(
+    oracle: api::SharedOracle,
+    control: UpdateControl,
+    mut stop: watch::Receiver<bool>,
+    leader: Option<watch::Receiver<bool>>,
+) {
+    let mut next_run: HashMap<String, time::Instant> = HashMap::new();
+    let mut tick = time::interval(Duration::from_secs(1));
+    tick.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {}
+            _ = stop.wait_for(|stop| *stop) => break,
+        }
+        let leading = leader.as_ref().is_none_or(|leader| *leader.borrow());
+        if control.is_paused() || !leading {
+            continue;
+        }
+
+        let updater = oracle.read().await.clone();
+        let config = updater.config();
+        let now = time::Instant::now();
+        next_run.retain(|name, _| config.tenant(name).is_some());
+        for tenant in &config.tenants {
+            let Some(secs) = tenant.update_interval else {
+                continue;
+            };
+            let period = Duration::from_secs(secs);
+            // The first interval is counted from when the tenant is first seen
+            let due = next_run.entry(tenant.name.clone()).or_insert(now + period);
+            if now < *due {
+                continue;
+            }
+            // Rounds that fell behind are not made up
+            *due = (*due + period).max(now);
+
+            let mut report = UpdateReport::start();
+            for asset_type in [AssetType::Crypto, AssetType::Stock, AssetType::Tvl] {
+                let symbols = config.tenant_symbols(tenant, asset_type);
+                if symbols.is_empty() {
+                    continue;
+                }
+                match updater.update_symbols(asset_type, &symbols).await {
+                    Ok(batch_report) => report.merge(batch_report),
+                    Err(e) => error!(
+                        "Failed to update {} {} prices of tenant {}: {}",
+                        symbols.len(),
+                        asset_type.as_str(),
+                        tenant.name,
+                        e
+                    ),
+                }
+            }
+            let report = report.finish();
+            info!(
+                "Tenant {} round: {} updated, {} failed in {} ms",
+                tenant.name, report.updated, report.failed, report.duration_ms
+            );
+        }
+    }
+}
+
+/// Run update rounds while this instance is the elected leader, and follow the
+/// shared store while another instance is. A new leader starts with a round,
+/// since the store may have gone stale while no one led.
+async fn lead_or_follow(
+    oracle: api::SharedOracle,
+    mut interval_rx: watch::Receiver<u64>,
+    control: UpdateControl,
+    mut shutdown: watch::Receiver<bool>,
+    mut leader: watch::Receiver<bool>,
+    systemd: Systemd,
+) {
+    loop {
+        tokio::select! {
+            _ = follow_store(oracle.clone(), shutdown.clone(), systemd.clone()) => return,
+            Ok(_) = leader.wait_for(|leading| *leading) => {}
+        }
+        info!("Leading background updates");
+
+        tokio::select! {
+            _ = update_rounds(&oracle, &mut interval_rx, &control, &mut shutdown, true, &systemd) => return,
+            Ok(_) = leader.wait_for(|leading| !*leading) => {
+                warn!("No longer the leader; following the shared store");
+            }
+        }
+    }
+}
+
+/// Resolve on SIGTERM, which Docker and Kubernetes send to stop a container;
+/// never resolves where the signal is unavailable
+async fn terminate() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+                return;
+            }
+            Err(e) => warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    }
+    std::future::pending::<()>().await
+}
+
 async fn start_api_server_with_updates(
     config_path: String,
     port: u16,
-    interval: u64,
+    interval: Option<u64>,
+    no_updater: bool,
+    auth: AuthMode,
+    overrides: &Overrides,
 ) -> Result<()> {
     info!("Starting Kanari Oracle API Server...");
 
-    let config = Config::from_file(&config_path)
-        .await
-        .context("Failed to load config")?;
+    let config = load_config(&config_path, overrides).await?;
+    if no_updater && config.store.backend == StoreBackend::Memory {
+        anyhow::bail!("--no-updater needs a shared store; set store.backend in the config");
+    }
+    let update_secs = interval.unwrap_or(config.general.update_interval);
+    // A replica without an updater never leads
+    let election = if no_updater {
+        None
+    } else {
+        join_election(&config)?
+    };
+    // A standby takes its prices from the primary until promoted
+    let replication = if no_updater {
+        None
+    } else {
+        config.replication.clone()
+    };
     let oracle = Oracle::new(config)
         .await
         .context("Failed to initialize oracle")?;
 
     info!("Oracle initialized successfully");
+
+    // Fill the feed before the listener is bound so clients never see an empty cache.
+    // A standby is warmed by the primary's stream instead.
+    let warmed = if replication.is_some() {
+        None
+    } else {
+        info!("Warming price feed...");
+        // Under HA the leader is not known yet; it starts with a round once elected
+        let warmed = if no_updater || election.is_some() {
+            oracle.sync_from_store().await
+        } else {
+            oracle
+                .update_all_prices()
+                .await
+                .map(|report| report.updated)
+        };
+        match warmed {
+            Ok(count) => {
+                info!("Warmed {} price feeds", count);
+                Some(count)
+            }
+            Err(e) => {
+                error!("Initial price update failed: {}", e);
+                None
+            }
+        }
+    };
+
     info!("Starting API server on port {}", port);
 
+    // Until the first successful round, systemd keeps the service "activating"
+    let systemd = Systemd::from_env();
+    let watchdog_handle = systemd.spawn_watchdog();
+
+    // Evaluate freshness SLOs; idles until an slo section is configured
+    let slo_handle = oracle.spawn_slo_evaluator();
+    // Fetch market sentiment; idles until a sentiment section is configured
+    let sentiment_handle = oracle.spawn_sentiment_updater();
+    // Fetch Bitcoin network statistics; idles until a network section is configured
+    let network_handle = oracle.spawn_network_updater();
+    // Fetch exchange rates; idles until an fx section is configured
+    let fx_handle = oracle.spawn_fx_updater();
+
     // Create shared oracle for both API and background updates
     let shared_oracle = std::sync::Arc::new(tokio::sync::RwLock::new(oracle));
     let shared_oracle_clone = shared_oracle.clone();
 
-    // Start background price updater
+    // Config reloads publish a new update interval through this channel
+    let (interval_tx, mut interval_rx) = watch::channel(update_secs);
+    // Flipped to true on shutdown; tasks finish their current work and exit
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut updater_shutdown = shutdown_rx.clone();
+    let mut api_shutdown = shutdown_rx;
+    // Started by `/admin/drain` or on shutdown; stops background updates
+    let drain = Drain::default();
+    let mut updater_stop = drain.subscribe();
+    // Paused or re-timed through `/admin/updates`
+    let updates = UpdateControl::default();
+    let updater_control = updates.clone();
+
+    // Start background price updater, follow the shared store, or switch between
+    // the two as leadership changes. A standby follows the primary and starts
+    // updating once promoted. Once drained, the task idles until shutdown.
+    let (leader, election_handle) = election.unzip();
+    // Tenants with an update interval of their own get extra rounds for their
+    // symbols. A replica or standby does not update, so it runs none.
+    let tenant_handle = (!no_updater && replication.is_none()).then(|| {
+        tokio::spawn(tenant_rounds(
+            shared_oracle.clone(),
+            updates.clone(),
+            drain.subscribe(),
+            leader.clone(),
+        ))
+    });
+    let updater_systemd = systemd.clone();
     let mut update_handle = tokio::spawn(async move {
-        let mut update_interval = time::interval(Duration::from_secs(interval));
-        loop {
-            update_interval.tick().await;
-            let mut oracle_lock = shared_oracle_clone.write().await;
-            match oracle_lock.update_all_prices().await {
-                Ok(count) => info!("Background update: Updated {} price feeds", count),
-                Err(e) => error!("Background update failed: {}", e),
+        if no_updater {
+            follow_store(shared_oracle_clone, updater_stop, updater_systemd).await;
+        } else if let Some(leader) = leader {
+            lead_or_follow(
+                shared_oracle_clone,
+                interval_rx,
+                updater_control,
+                updater_stop,
+                leader,
+                updater_systemd,
+            )
+            .await;
+        } else if let Some(replication) = replication {
+            let outcome = standby::follow_primary(
+                &shared_oracle_clone,
+                &replication,
+                &mut updater_stop,
+                &updater_systemd,
+            )
+            .await;
+            // Promotion lasts until restart; the feed may be stale, so update now
+            if let standby::Outcome::Promote = outcome {
+                update_rounds(
+                    &shared_oracle_clone,
+                    &mut interval_rx,
+                    &updater_control,
+                    &mut updater_stop,
+                    true,
+                    &updater_systemd,
+                )
+                .await;
             }
-            oracle_lock.print_current_prices();
+        } else {
+            // The warm-up already ran the first round
+            update_rounds(
+                &shared_oracle_clone,
+                &mut interval_rx,
+                &updater_control,
+                &mut updater_stop,
+                false,
+                &updater_systemd,
+            )
+            .await;
         }
+        // Releases the leader lock, so a follower takes over right away
+        if let Some(election_handle) = &election_handle {
+            election_handle.abort();
+        }
+        let _ = updater_shutdown.wait_for(|stop| *stop).await;
     });
 
+    // Watch the config file (and SIGHUP) for hot reloads
+    let reload_handle = tokio::spawn(reload::watch_config(
+        config_path.clone(),
+        shared_oracle.clone(),
+        interval_tx,
+        interval.is_some(),
+        overrides.clone(),
+    ));
+
     // Start API server with shared oracle
+    let api_drain = drain.clone();
     let mut api_handle = tokio::spawn(async move {
-        if let Err(e) = api::start_api_server_with_shared_oracle(shared_oracle, port).await {
+        let shutdown = async move {
+            let _ = api_shutdown.wait_for(|stop| *stop).await;
+        };
+        if let Err(e) = api::start_api_server_with_shared_oracle(
+            shared_oracle,
+            port,
+            auth,
+            Some(config_path),
+            api_drain,
+            updates,
+            shutdown,
+        )
+        .await
+        {
             error!("API server error: {}", e);
         }
     });
 
-    // Wait for either task to stop or Ctrl+C for graceful shutdown
+    if let Some(count) = warmed {
+        systemd.status(&format!("Warmed {} price feeds", count));
+        systemd.ready();
+    }
+
+    // Wait for either task to stop or Ctrl+C/SIGTERM for graceful shutdown
     tokio::select! {
         _ = signal::ctrl_c() => {
             info!("Received shutdown signal, stopping...");
         }
+        _ = terminate() => {
+            info!("Received SIGTERM, stopping...");
+        }
         _ = &mut update_handle => {
             error!("Background updater stopped unexpectedly");
         }
@@ -310,9 +1414,40 @@ async fn start_api_server_with_updates(
         }
     }
 
-    // Abort background tasks
-    update_handle.abort();
-    api_handle.abort();
+    // Stop accepting connections and let in-flight requests and the current
+    // update round finish, within a deadline
+    systemd.stopping();
+    drain.start();
+    let _ = shutdown_tx.send(true);
+    reload_handle.abort();
+    slo_handle.abort();
+    sentiment_handle.abort();
+    network_handle.abort();
+    fx_handle.abort();
+    if let Some(tenant_handle) = &tenant_handle {
+        tenant_handle.abort();
+    }
+    if let Some(watchdog_handle) = &watchdog_handle {
+        watchdog_handle.abort();
+    }
+
+    let finish = async {
+        if !api_handle.is_finished() {
+            let _ = (&mut api_handle).await;
+        }
+        if !update_handle.is_finished() {
+            let _ = (&mut update_handle).await;
+        }
+    };
+    if time::timeout(SHUTDOWN_GRACE_PERIOD, finish).await.is_err() {
+        warn!(
+            "Tasks still running after {} seconds, aborting",
+            SHUTDOWN_GRACE_PERIOD.as_secs()
+        );
+        update_handle.abort();
+        api_handle.abort();
+    }
 
+    info!("Shutdown complete");
     Ok(())
 }
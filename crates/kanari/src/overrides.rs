@@ -0,0 +1,59 @@
+use kanari_oracle::config::{Config, FxConfig, OfflineConfig, SandboxConfig};
+use kanari_oracle::locale::Locale;
+
+/// Modes chosen on the command line, applied to the config file when it is
+/// loaded and on every reload
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    /// Fixture given with `--offline`
+    pub offline: Option<String>,
+    pub sandbox: bool,
+    pub seed: Option<u64>,
+    pub volatility: Option<f64>,
+    /// Currency given with `--display-currency`
+    pub display_currency: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl Overrides {
+    /// Locale given with `--locale`, or else the environment's; for commands
+    /// that load no config
+    pub fn locale(&self) -> Locale {
+        self.locale
+            .as_deref()
+            .and_then(Locale::parse)
+            .unwrap_or_else(Locale::from_env)
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(fixture) = &self.offline {
+            config.offline = Some(OfflineConfig {
+                fixture: fixture.clone(),
+            });
+        }
+        if self.sandbox {
+            let sandbox = config.sandbox.get_or_insert_with(SandboxConfig::default);
+            if let Some(seed) = self.seed {
+                sandbox.seed = seed;
+            }
+            if let Some(volatility) = self.volatility {
+                sandbox.volatility = volatility;
+            }
+        }
+        if let Some(currency) = &self.display_currency {
+            let currency = currency.to_lowercase();
+            // The currency is added to the fx table, which is turned on with
+            // its defaults when the config has none
+            if currency != "usd" {
+                let fx = config.fx.get_or_insert_with(FxConfig::default);
+                if !fx.currencies.contains(&currency) {
+                    fx.currencies.push(currency.clone());
+                }
+            }
+            config.display.currency = Some(currency);
+        }
+        if let Some(locale) = &self.locale {
+            config.display.locale = Some(locale.clone());
+        }
+    }
+}
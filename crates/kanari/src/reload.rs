@@ -0,0 +1,144 @@
+use log::{error, info, warn};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tokio::time;
+
+use kanari_api::api::SharedOracle;
+use kanari_oracle::config::Config;
+
+use crate::overrides::Overrides;
+
+/// How often the config file's modification time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[cfg(unix)]
+type HangupSignal = tokio::signal::unix::Signal;
+#[cfg(not(unix))]
+type HangupSignal = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> Option<HangupSignal> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    match signal(SignalKind::hangup()) {
+        Ok(s) => Some(s),
+        Err(e) => {
+            warn!("Failed to install SIGHUP handler: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Option<HangupSignal> {
+    None
+}
+
+/// Wait for the next SIGHUP; never resolves where the signal is unavailable
+async fn hangup(signal: &mut Option<HangupSignal>) {
+    #[cfg(unix)]
+    {
+        if let Some(s) = signal.as_mut() {
+            s.recv().await;
+            return;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = signal;
+
+    std::future::pending::<()>().await
+}
+
+async fn modified_time(path: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Re-read the config file whenever it changes on disk or on SIGHUP, and apply
+/// it to the running oracle. `interval_tx` receives the new update interval unless
+/// it was fixed on the command line. Modes chosen on the command line stay in
+/// effect across reloads.
+pub async fn watch_config(
+    config_path: String,
+    oracle: SharedOracle,
+    interval_tx: watch::Sender<u64>,
+    fixed_interval: bool,
+    overrides: Overrides,
+) {
+    let mut hangup_signal = hangup_signal();
+    let mut poll = time::interval(POLL_INTERVAL);
+    let mut last_modified = modified_time(&config_path).await;
+
+    loop {
+        tokio::select! {
+            _ = poll.tick() => {
+                let modified = modified_time(&config_path).await;
+                if modified.is_none() || modified == last_modified {
+                    continue;
+                }
+                info!("Config file '{}' changed, reloading", config_path);
+            }
+            _ = hangup(&mut hangup_signal) => {
+                info!("Received SIGHUP, reloading '{}'", config_path);
+            }
+        }
+
+        last_modified = modified_time(&config_path).await;
+        reload(
+            &config_path,
+            &oracle,
+            &interval_tx,
+            fixed_interval,
+            &overrides,
+        )
+        .await;
+    }
+}
+
+async fn reload(
+    config_path: &str,
+    oracle: &SharedOracle,
+    interval_tx: &watch::Sender<u64>,
+    fixed_interval: bool,
+    overrides: &Overrides,
+) {
+    // Config::from_file writes a default config for missing files, which a reload must not do
+    if tokio::fs::metadata(config_path).await.is_err() {
+        warn!(
+            "Config file '{}' not found, keeping current configuration",
+            config_path
+        );
+        return;
+    }
+
+    let mut config = match Config::from_file(config_path).await {
+        Ok(config) => config,
+        Err(e) => {
+            error!(
+                "Failed to reload config, keeping current configuration: {}",
+                e
+            );
+            return;
+        }
+    };
+    overrides.apply(&mut config);
+    let update_interval = config.general.update_interval;
+
+    if let Err(e) = oracle.write().await.reload_config(config) {
+        error!(
+            "Rejected reloaded config, keeping current configuration: {}",
+            e
+        );
+        return;
+    }
+
+    if !fixed_interval {
+        interval_tx.send_if_modified(|current| {
+            if *current == update_interval {
+                return false;
+            }
+            info!("Update interval changed to {} seconds", update_interval);
+            *current = update_interval;
+            true
+        });
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::future::join_all;
+use kanari_api::models::{
+    CreateWebhookRequest, HistoryResponse, WebhookDelivery, WebhookDeliveryListResponse,
+    WebhookInfo, WebhookListResponse,
+};
+use kanari_oracle::locale::Locale;
+use kanari_oracle::precision::PrecisionRule;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    data: Option<T>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RemotePrice {
+    symbol: String,
+    price: f64,
+    /// Absent from older servers, which quote in US dollars by default
+    #[serde(default = "default_currency")]
+    currency: String,
+    timestamp: String,
+    asset_type: String,
+}
+
+fn default_currency() -> String {
+    "usd".to_string()
+}
+
+/// Default display rule of an asset type; the server's own `display` rules
+/// are not known here
+pub fn precision(asset_type: &str) -> PrecisionRule {
+    match asset_type {
+        "tvl" => PrecisionRule::whole(),
+        _ => PrecisionRule::default(),
+    }
+}
+
+#[derive(Deserialize)]
+struct RemoteSymbols {
+    crypto: Vec<String>,
+    stocks: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RemoteStats {
+    total_crypto_symbols: usize,
+    total_stock_symbols: usize,
+    last_update: String,
+    avg_crypto_price: f64,
+    avg_stock_price: f64,
+}
+
+/// Client for a running kanari API server, so CLI lookups reuse its warm cache
+pub struct RemoteClient {
+    client: Client,
+    base_url: String,
+    token: Option<String>,
+    /// Currency prices are asked in; the server's quote currency when None
+    currency: Option<String>,
+    locale: Locale,
+}
+
+impl RemoteClient {
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+            currency: None,
+            locale: Locale::from_env(),
+        })
+    }
+
+    /// Ask for prices in `currency`, converted by the server, and write numbers
+    /// the `locale` way
+    pub fn display(mut self, currency: Option<String>, locale: Locale) -> Self {
+        self.currency = currency.map(|currency| currency.to_lowercase());
+        self.locale = locale;
+        self
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        self.send(self.client.get(&url), &url).await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, mut request: RequestBuilder, url: &str) -> Result<T> {
+        if let Some(ref token) = self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach kanari server at {}", self.base_url))?;
+
+        let status = response.status();
+        let body: ApiResponse<T> = match response.json().await {
+            Ok(body) => body,
+            Err(_) if !status.is_success() => {
+                return Err(anyhow!("Server returned {} for {}", status, url));
+            }
+            Err(e) => return Err(e).context("Failed to parse server response"),
+        };
+
+        match (body.data, body.error) {
+            (Some(data), _) => Ok(data),
+            (None, Some(error)) => Err(anyhow!(error)),
+            (None, None) => Err(anyhow!("Server returned an empty response")),
+        }
+    }
+
+    /// Print the price of each symbol, fetched concurrently; a symbol the
+    /// server fails on is reported without hiding the others
+    pub async fn print_prices(&self, symbols: &[String], asset_type: &str) -> Result<()> {
+        let results = join_all(symbols.iter().map(|symbol| self.price(symbol, asset_type))).await;
+
+        let mut failed = 0;
+        for (symbol, result) in symbols.iter().zip(results) {
+            match result {
+                Ok(price) => {
+                    println!(
+                        "Current price for {} ({}): {}",
+                        price.symbol,
+                        price.asset_type,
+                        self.locale.money(
+                            &precision(&price.asset_type).format(price.price),
+                            &price.currency
+                        )
+                    );
+                    println!("Last updated: {}", price.timestamp);
+                }
+                Err(e) if symbols.len() == 1 => return Err(e),
+                Err(e) => {
+                    failed += 1;
+                    println!("Failed to get price for {}: {}", symbol.to_uppercase(), e);
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!("{} of {} symbols failed", failed, symbols.len()));
+        }
+        Ok(())
+    }
+
+    /// Price of `symbol` in the requested currency, and how it is shown
+    pub async fn quote(&self, symbol: &str, asset_type: &str) -> Result<(f64, String)> {
+        let price = self.price(symbol, asset_type).await?;
+        let shown = self.locale.money(
+            &precision(&price.asset_type).format(price.price),
+            &price.currency,
+        );
+        Ok((price.price, shown))
+    }
+
+    async fn price(&self, symbol: &str, asset_type: &str) -> Result<RemotePrice> {
+        // The server resolves "any" the same way the CLI resolves "auto"
+        let asset_type = if asset_type == "auto" {
+            "any"
+        } else {
+            asset_type
+        };
+
+        let mut path = format!("/price/{}/{}", asset_type, symbol);
+        if let Some(currency) = &self.currency {
+            path.push_str(&format!("?currency={}", currency));
+        }
+        self.get(&path).await
+    }
+
+    /// Prices of `symbol` published since `from`, oldest first, as kept by the
+    /// server's history
+    pub async fn history(
+        &self,
+        symbol: &str,
+        asset_type: &str,
+        from: DateTime<Utc>,
+    ) -> Result<HistoryResponse> {
+        let asset_type = if asset_type == "auto" {
+            "any"
+        } else {
+            asset_type
+        };
+        self.get(&format!(
+            "/history/{}/{}?from={}",
+            asset_type,
+            symbol,
+            from.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ))
+        .await
+    }
+
+    /// Register a webhook for the token's owner
+    pub async fn create_webhook(&self, webhook: &CreateWebhookRequest) -> Result<WebhookInfo> {
+        let url = format!("{}/webhooks", self.base_url);
+        self.send(self.client.post(&url).json(webhook), &url).await
+    }
+
+    /// Webhooks of the token's owner, newest first
+    pub async fn webhooks(&self) -> Result<Vec<WebhookInfo>> {
+        let list: WebhookListResponse = self.get("/webhooks").await?;
+        Ok(list.webhooks)
+    }
+
+    pub async fn delete_webhook(&self, id: i32) -> Result<()> {
+        let url = format!("{}/webhooks/{}", self.base_url, id);
+        let _: String = self.send(self.client.delete(&url), &url).await?;
+        Ok(())
+    }
+
+    /// The `limit` most recent delivery attempts of a webhook, newest first
+    pub async fn webhook_deliveries(&self, id: i32, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let list: WebhookDeliveryListResponse = self
+            .get(&format!("/webhooks/{}/deliveries?limit={}", id, limit))
+            .await?;
+        Ok(list.deliveries)
+    }
+
+    pub async fn print_symbols(&self, asset_type: &str) -> Result<()> {
+        let path = match asset_type {
+            "crypto" | "stock" => format!("/symbols?asset_type={}", asset_type),
+            _ => "/symbols".to_string(),
+        };
+        let symbols: RemoteSymbols = self.get(&path).await?;
+
+        if asset_type != "stock" {
+            println!("Available Cryptocurrencies:");
+            for symbol in &symbols.crypto {
+                println!("  {}", symbol);
+            }
+        }
+        if asset_type == "all" {
+            println!();
+        }
+        if asset_type != "crypto" {
+            println!("Available Stocks:");
+            for symbol in &symbols.stocks {
+                println!("  {}", symbol);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn print_stats(&self) -> Result<()> {
+        let stats: RemoteStats = self.get("/stats").await?;
+
+        println!("=== Oracle Statistics ({}) ===", self.base_url);
+        println!("Total Crypto Symbols: {}", stats.total_crypto_symbols);
+        println!("Total Stock Symbols: {}", stats.total_stock_symbols);
+        println!("Last Update: {}", stats.last_update);
+        // /stats does not name its currency; prices are quoted in US dollars by default
+        println!(
+            "Average Crypto Price: {}",
+            self.locale
+                .money(&precision("crypto").format(stats.avg_crypto_price), "usd")
+        );
+        println!(
+            "Average Stock Price: {}",
+            self.locale
+                .money(&precision("stock").format(stats.avg_stock_price), "usd")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,377 @@
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use kanari_api::auth::AuthMode;
+
+/// Install `kanari server` as a service of the operating system
+#[derive(Subcommand)]
+pub enum ServiceCommand {
+    /// Write a systemd unit (Linux) or launchd plist (macOS) running
+    /// `kanari server`, started at boot, or at login with --user
+    Install {
+        /// Install for the current user instead of system-wide
+        #[arg(long)]
+        user: bool,
+        /// Configuration file the service runs with
+        #[arg(short, long, default_value = "config.json")]
+        config: String,
+        /// Update interval in seconds passed to `server` (defaults to
+        /// general.update_interval)
+        #[arg(short, long)]
+        interval: Option<u64>,
+        /// Port passed to `server` (defaults to 3000)
+        #[arg(short, long)]
+        port: Option<u16>,
+        /// Authentication mode passed to `server` (defaults to database)
+        #[arg(long)]
+        auth: Option<AuthMode>,
+        /// File of KEY=VALUE lines, such as DATABASE_URL, set in the service's
+        /// environment
+        #[arg(short, long)]
+        env_file: Option<String>,
+        /// Name of the service
+        #[arg(long, default_value = "kanari")]
+        name: String,
+        /// Print the service definition instead of installing it
+        #[arg(long)]
+        print: bool,
+    },
+}
+
+/// What the installed service runs
+struct Service {
+    name: String,
+    user: bool,
+    program: PathBuf,
+    args: Vec<String>,
+    /// Directory of the config file, so relative paths in it keep working
+    working_directory: PathBuf,
+    env_file: Option<PathBuf>,
+}
+
+pub async fn run(command: ServiceCommand) -> Result<()> {
+    let ServiceCommand::Install {
+        user,
+        config,
+        interval,
+        port,
+        auth,
+        env_file,
+        name,
+        print,
+    } = command;
+
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        bail!(
+            "Service name '{}' may only use letters, digits, '-', '_' and '.'",
+            name
+        );
+    }
+    let config = existing_file(&config, "Config file")?;
+    let env_file = env_file
+        .map(|path| existing_file(&path, "Environment file"))
+        .transpose()?;
+
+    let mut args = vec![
+        "server".to_string(),
+        "--config".to_string(),
+        config.display().to_string(),
+    ];
+    if let Some(interval) = interval {
+        args.extend(["--interval".to_string(), interval.to_string()]);
+    }
+    if let Some(port) = port {
+        args.extend(["--port".to_string(), port.to_string()]);
+    }
+    if let Some(auth) = auth {
+        args.extend(["--auth".to_string(), auth.as_str().to_string()]);
+    }
+    let service = Service {
+        name,
+        user,
+        program: std::env::current_exe().context("Failed to locate the kanari executable")?,
+        args,
+        working_directory: config
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("/")),
+        env_file,
+    };
+
+    if cfg!(target_os = "linux") {
+        install_systemd(&service, print).await
+    } else if cfg!(target_os = "macos") {
+        install_launchd(&service, print).await
+    } else {
+        bail!(
+            "Installing a service is supported with systemd and launchd only; on this system run `{}` under your service manager",
+            command_line(&service)
+        )
+    }
+}
+
+/// `path` made absolute, when it names a file
+fn existing_file(path: &str, what: &str) -> Result<PathBuf> {
+    let absolute = std::path::absolute(path).with_context(|| format!("Invalid path '{}'", path))?;
+    if !absolute.is_file() {
+        bail!("{} '{}' not found", what, absolute.display());
+    }
+    Ok(absolute)
+}
+
+/// Home directory of the current user, for per-user services
+fn home() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .context("HOME is not set; cannot install a --user service")
+}
+
+async fn install_systemd(service: &Service, print: bool) -> Result<()> {
+    let unit = systemd_unit(service);
+    if print {
+        print!("{}", unit);
+        return Ok(());
+    }
+
+    let directory = if service.user {
+        home()?.join(".config/systemd/user")
+    } else {
+        PathBuf::from("/etc/systemd/system")
+    };
+    let path = directory.join(format!("{}.service", service.name));
+    write_definition(&directory, &path, &unit, service.user).await?;
+
+    let scope: &[&str] = if service.user { &["--user"] } else { &[] };
+    let reloaded = Command::new("systemctl")
+        .args(scope)
+        .arg("daemon-reload")
+        .status()
+        .is_ok_and(|status| status.success());
+    if !reloaded {
+        println!(
+            "Could not reload systemd; run `systemctl {}daemon-reload`",
+            flag(scope)
+        );
+    }
+    println!(
+        "Start it now and at every {} with: systemctl {}enable --now {}",
+        if service.user { "login" } else { "boot" },
+        flag(scope),
+        service.name
+    );
+    println!(
+        "Follow its logs with: journalctl {}-u {} -f",
+        flag(scope),
+        service.name
+    );
+    Ok(())
+}
+
+fn flag(scope: &[&str]) -> String {
+    scope.iter().map(|arg| format!("{} ", arg)).collect()
+}
+
+/// Unit of a `Type=notify` service, so systemd waits for the first good
+/// update round and restarts the server when its watchdog stops
+fn systemd_unit(service: &Service) -> String {
+    let mut unit = String::new();
+    unit.push_str("[Unit]\n");
+    unit.push_str("Description=Kanari price oracle API server\n");
+    unit.push_str("Wants=network-online.target\n");
+    unit.push_str("After=network-online.target\n\n");
+    unit.push_str("[Service]\n");
+    unit.push_str("Type=notify\n");
+    unit.push_str(&format!("ExecStart={}\n", command_line(service)));
+    unit.push_str(&format!(
+        "WorkingDirectory={}\n",
+        systemd_path(&service.working_directory)
+    ));
+    if let Some(env_file) = &service.env_file {
+        unit.push_str(&format!("EnvironmentFile={}\n", systemd_path(env_file)));
+    }
+    unit.push_str("WatchdogSec=60\n");
+    unit.push_str("Restart=on-failure\n\n");
+    unit.push_str("[Install]\n");
+    unit.push_str(if service.user {
+        "WantedBy=default.target\n"
+    } else {
+        "WantedBy=multi-user.target\n"
+    });
+    unit
+}
+
+/// The service's command, quoted for a unit's ExecStart= or a shell
+fn command_line(service: &Service) -> String {
+    std::iter::once(service.program.display().to_string())
+        .chain(service.args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A path for settings that take one unquoted, where only specifiers expand
+fn systemd_path(path: &Path) -> String {
+    path.display().to_string().replace('%', "%%")
+}
+
+fn systemd_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | '$' | '%'))
+    {
+        return arg.to_string();
+    }
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "$$")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+async fn install_launchd(service: &Service, print: bool) -> Result<()> {
+    let environment = match &service.env_file {
+        Some(path) => {
+            let content = tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to read '{}'", path.display()))?;
+            env_pairs(&content)
+        }
+        None => Vec::new(),
+    };
+    let plist = launchd_plist(service, &environment)?;
+    if print {
+        print!("{}", plist);
+        return Ok(());
+    }
+
+    let directory = if service.user {
+        home()?.join("Library/LaunchAgents")
+    } else {
+        PathBuf::from("/Library/LaunchDaemons")
+    };
+    let path = directory.join(format!("{}.plist", launchd_label(service)));
+    write_definition(&directory, &path, &plist, service.user).await?;
+
+    println!(
+        "Start it now and at every {} with: launchctl load -w {}",
+        if service.user { "login" } else { "boot" },
+        path.display()
+    );
+    if service.env_file.is_some() {
+        println!(
+            "The environment file was copied into the plist; run install again after changing it."
+        );
+    }
+    Ok(())
+}
+
+fn launchd_label(service: &Service) -> String {
+    format!("network.kanari.{}", service.name)
+}
+
+/// Property list of a job kept alive from load, logging to /var/log, or to
+/// ~/Library/Logs for user agents. launchd has no environment files, so
+/// `environment` is written into it.
+fn launchd_plist(service: &Service, environment: &[(String, String)]) -> Result<String> {
+    let log = if service.user {
+        home()?.join(format!("Library/Logs/{}.log", service.name))
+    } else {
+        PathBuf::from(format!("/var/log/{}.log", service.name))
+    };
+
+    let mut plist = String::new();
+    plist.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    plist.push_str("<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n");
+    plist.push_str("<plist version=\"1.0\">\n<dict>\n");
+    plist.push_str(&format!(
+        "  <key>Label</key>\n  <string>{}</string>\n",
+        xml_escape(&launchd_label(service))
+    ));
+    plist.push_str("  <key>ProgramArguments</key>\n  <array>\n");
+    for arg in std::iter::once(service.program.display().to_string()).chain(service.args.clone()) {
+        plist.push_str(&format!("    <string>{}</string>\n", xml_escape(&arg)));
+    }
+    plist.push_str("  </array>\n");
+    plist.push_str(&format!(
+        "  <key>WorkingDirectory</key>\n  <string>{}</string>\n",
+        xml_escape(&service.working_directory.display().to_string())
+    ));
+    if !environment.is_empty() {
+        plist.push_str("  <key>EnvironmentVariables</key>\n  <dict>\n");
+        for (key, value) in environment {
+            plist.push_str(&format!(
+                "    <key>{}</key>\n    <string>{}</string>\n",
+                xml_escape(key),
+                xml_escape(value)
+            ));
+        }
+        plist.push_str("  </dict>\n");
+    }
+    plist.push_str("  <key>RunAtLoad</key>\n  <true/>\n");
+    plist.push_str("  <key>KeepAlive</key>\n  <true/>\n");
+    for key in ["StandardOutPath", "StandardErrorPath"] {
+        plist.push_str(&format!(
+            "  <key>{}</key>\n  <string>{}</string>\n",
+            key,
+            xml_escape(&log.display().to_string())
+        ));
+    }
+    plist.push_str("</dict>\n</plist>\n");
+    Ok(plist)
+}
+
+/// KEY=VALUE lines of an environment file; blank lines, comments and an
+/// `export ` prefix are skipped, and quotes around a value are removed
+fn env_pairs(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Write a service definition, pointing at --user when a system directory
+/// cannot be written
+async fn write_definition(directory: &Path, path: &Path, content: &str, user: bool) -> Result<()> {
+    tokio::fs::create_dir_all(directory)
+        .await
+        .with_context(|| format!("Failed to create '{}'", directory.display()))?;
+    if let Err(e) = tokio::fs::write(path, content).await {
+        if !user && e.kind() == std::io::ErrorKind::PermissionDenied {
+            bail!(
+                "Permission denied writing '{}'; run as root, or pass --user to install for your user",
+                path.display()
+            );
+        }
+        return Err(e).with_context(|| format!("Failed to write '{}'", path.display()));
+    }
+    println!("Installed {}", path.display());
+    Ok(())
+}
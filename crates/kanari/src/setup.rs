@@ -0,0 +1,171 @@
+use anyhow::{Context, Result, bail};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use kanari_oracle::config::Config;
+use kanari_oracle::locale::Locale;
+use kanari_oracle::oracle::Oracle;
+use kanari_oracle::precision::PrecisionRule;
+
+/// A provider whose API key the wizard asks for
+struct Provider {
+    /// Name in the `sources` section
+    source: &'static str,
+    title: &'static str,
+    signup: &'static str,
+    /// Configured symbol the key is tested with
+    test_symbol: &'static str,
+    stock: bool,
+    set_key: fn(&mut Config, String),
+}
+
+/// Providers that use a key, in the order they are asked for. Binance serves
+/// its public prices without one, and Yahoo Finance has none.
+const PROVIDERS: [Provider; 3] = [
+    Provider {
+        source: "coingecko",
+        title: "CoinGecko",
+        signup: "https://www.coingecko.com/api",
+        test_symbol: "bitcoin",
+        stock: false,
+        set_key: |config, key| config.crypto.coingecko_api_key = Some(key),
+    },
+    Provider {
+        source: "alpha_vantage",
+        title: "Alpha Vantage",
+        signup: "https://www.alphavantage.co/support/#api-key",
+        test_symbol: "AAPL",
+        stock: true,
+        set_key: |config, key| config.stocks.alpha_vantage_api_key = Some(key),
+    },
+    Provider {
+        source: "finnhub",
+        title: "Finnhub",
+        signup: "https://finnhub.io/register",
+        test_symbol: "AAPL",
+        stock: true,
+        set_key: |config, key| config.stocks.finnhub_api_key = Some(key),
+    },
+];
+
+/// Ask for the key of each provider, test every key with a live request, and
+/// write the default config with the working keys to `config_path`, once it
+/// passes the checks of `check-config`
+pub async fn run(config_path: &str) -> Result<()> {
+    // Retry warnings would interleave with the questions; a failed test is
+    // reported with its error anyway
+    if std::env::var_os("RUST_LOG").is_none() {
+        log::set_max_level(log::LevelFilter::Error);
+    }
+
+    println!("Kanari setup writes {}.", config_path);
+    if Path::new(config_path).exists()
+        && !confirm(&format!("{} exists. Overwrite it?", config_path))?
+    {
+        println!("Nothing written.");
+        return Ok(());
+    }
+    println!(
+        "Prices work without API keys; keys raise rate limits and add Alpha Vantage and Finnhub."
+    );
+    println!("Press Enter to skip a provider.\n");
+
+    let mut config = Config::default();
+    let mut keyed = Vec::new();
+    for provider in &PROVIDERS {
+        loop {
+            let key = ask(&format!(
+                "{} API key ({}): ",
+                provider.title, provider.signup
+            ))?;
+            if key.is_empty() {
+                break;
+            }
+
+            print!("  Testing {} key... ", provider.title);
+            io::stdout().flush()?;
+            match test_key(provider, &key).await {
+                Ok(price) => {
+                    println!("ok, {} is {}", provider.test_symbol.to_uppercase(), price);
+                    (provider.set_key)(&mut config, key);
+                    keyed.push(provider.title);
+                    break;
+                }
+                Err(e) => {
+                    println!("failed: {}", e);
+                    if !confirm("  Try another key?")? {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&config).context("Failed to serialize config")?;
+    let problems = Config::check_str(&content, true);
+    if !problems.is_empty() {
+        for problem in &problems {
+            println!("  {}", problem);
+        }
+        bail!(
+            "Generated config has {} problem(s); nothing written",
+            problems.len()
+        );
+    }
+    tokio::fs::write(config_path, content)
+        .await
+        .with_context(|| format!("Failed to write config file '{}'", config_path))?;
+
+    println!();
+    if keyed.is_empty() {
+        println!("Wrote {} without API keys.", config_path);
+    } else {
+        println!("Wrote {} with keys for {}.", config_path, keyed.join(", "));
+    }
+    println!(
+        "Keys are stored inline; see \"Secrets\" in the README to read them from files or the environment instead."
+    );
+    println!(
+        "Start the oracle with: kanari start --config {}",
+        config_path
+    );
+    Ok(())
+}
+
+/// Fetch the provider's test symbol with `key` only, from that provider only
+async fn test_key(provider: &Provider, key: &str) -> Result<String> {
+    let mut config = Config::default();
+    (provider.set_key)(&mut config, key.to_string());
+    let oracle = Oracle::new(config)
+        .await
+        .context("Failed to initialize oracle")?;
+
+    let price = if provider.stock {
+        oracle
+            .get_stock_price_from(provider.test_symbol, provider.source)
+            .await?
+    } else {
+        oracle
+            .get_crypto_price_from(provider.test_symbol, provider.source)
+            .await?
+    };
+    Ok(Locale::from_env().money(&PrecisionRule::default().format(price.price), "usd"))
+}
+
+/// A line read from stdin, trimmed; empty at end of input
+fn ask(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("Failed to read answer")?;
+    Ok(line.trim().to_string())
+}
+
+/// Whether the user answers yes; no by default
+fn confirm(question: &str) -> Result<bool> {
+    let answer = ask(&format!("{} [y/N] ", question))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use log::{info, warn};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::{self, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+use kanari_api::api::SharedOracle;
+use kanari_oracle::config::ReplicationConfig;
+use kanari_oracle::subscriber::PriceUpdate;
+
+use crate::systemd::Systemd;
+
+/// Pause between attempts to reach the primary
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// How a standby stopped following its primary
+pub enum Outcome {
+    /// The primary was silent for `silence`; the standby should fetch itself
+    Promote,
+    Shutdown,
+}
+
+/// Keep the feeds warm with every price the primary streams, reconnecting as
+/// needed, until the primary has sent nothing for `replication.silence` seconds
+pub async fn follow_primary(
+    oracle: &SharedOracle,
+    replication: &ReplicationConfig,
+    shutdown: &mut watch::Receiver<bool>,
+    systemd: &Systemd,
+) -> Outcome {
+    let silence = Duration::from_secs(replication.silence);
+    let mut last_heard = Instant::now();
+    info!("Standing by for primary {}", replication.primary);
+
+    loop {
+        let deadline = last_heard + silence;
+        tokio::select! {
+            _ = time::sleep_until(deadline) => break,
+            _ = shutdown.wait_for(|stop| *stop) => return Outcome::Shutdown,
+            result = stream(oracle, replication, &mut last_heard, systemd) => {
+                if let Err(e) = result {
+                    warn!("Lost primary stream: {:#}", e);
+                }
+            }
+        }
+        tokio::select! {
+            _ = time::sleep(RECONNECT_DELAY) => {}
+            _ = shutdown.wait_for(|stop| *stop) => return Outcome::Shutdown,
+        }
+        if last_heard.elapsed() >= silence {
+            break;
+        }
+    }
+
+    warn!(
+        "Primary silent for {}s; promoting this standby to fetch prices itself",
+        silence.as_secs()
+    );
+    Outcome::Promote
+}
+
+/// Apply the primary's updates until its stream closes or fails. Only price
+/// updates count as hearing from the primary, so a primary whose updater is
+/// stuck is treated like one that is down.
+async fn stream(
+    oracle: &SharedOracle,
+    replication: &ReplicationConfig,
+    last_heard: &mut Instant,
+    systemd: &Systemd,
+) -> Result<()> {
+    let mut request = replication
+        .primary
+        .as_str()
+        .into_client_request()
+        .context("Invalid replication.primary")?;
+    if let Some(token) = &replication.token {
+        request.headers_mut().insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Bearer {}", token))
+                .context("Invalid replication token")?,
+        );
+    }
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .context("Failed to connect to primary")?;
+    info!("Following primary {}", replication.primary);
+
+    while let Some(message) = socket.next().await {
+        let text = match message? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let update: PriceUpdate = match serde_json::from_str(&text) {
+            Ok(update) => update,
+            Err(e) => {
+                warn!("Ignoring unreadable message from primary: {}", e);
+                continue;
+            }
+        };
+        *last_heard = Instant::now();
+
+        let standby = oracle.read().await.clone();
+        let received = update.prices.len();
+        standby.replicate(update);
+        systemd.ready();
+        systemd.status(&format!(
+            "Standby: {} prices received from {}",
+            received, replication.primary
+        ));
+    }
+    Ok(())
+}
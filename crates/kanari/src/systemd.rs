@@ -0,0 +1,167 @@
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+
+/// Notifications to systemd (`Type=notify`, `WatchdogSec=`) through
+/// `$NOTIFY_SOCKET`. Every method does nothing when the process was not started
+/// by systemd.
+#[derive(Clone, Default)]
+pub struct Systemd {
+    inner: Option<Arc<Inner>>,
+}
+
+struct Inner {
+    address: String,
+    /// `WATCHDOG_USEC`, when systemd expects keepalives for this process
+    watchdog: Option<Duration>,
+    ready: AtomicBool,
+    /// Start of the update work in progress, if any
+    busy_since: Mutex<Option<Instant>>,
+}
+
+impl Systemd {
+    pub fn from_env() -> Self {
+        let Some(address) = std::env::var("NOTIFY_SOCKET")
+            .ok()
+            .filter(|address| !address.is_empty())
+        else {
+            return Self::default();
+        };
+
+        // WATCHDOG_PID names the process the watchdog is meant for, when set
+        let for_us = std::env::var("WATCHDOG_PID")
+            .ok()
+            .and_then(|pid| pid.trim().parse::<u32>().ok())
+            .is_none_or(|pid| pid == std::process::id());
+        let watchdog = std::env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.trim().parse::<u64>().ok())
+            .filter(|usec| *usec > 0 && for_us)
+            .map(Duration::from_micros);
+
+        Self {
+            inner: Some(Arc::new(Inner {
+                address,
+                watchdog,
+                ready: AtomicBool::new(false),
+                busy_since: Mutex::new(None),
+            })),
+        }
+    }
+
+    /// Tell systemd startup is complete; only the first call is sent
+    pub fn ready(&self) {
+        if let Some(inner) = &self.inner
+            && !inner.ready.swap(true, Ordering::Relaxed)
+        {
+            info!("Notifying systemd that the service is ready");
+            inner.notify("READY=1");
+        }
+    }
+
+    /// One-line status shown by `systemctl status`
+    pub fn status(&self, status: &str) {
+        if let Some(inner) = &self.inner {
+            inner.notify(&format!("STATUS={}", status.replace('\n', " ")));
+        }
+    }
+
+    pub fn stopping(&self) {
+        if let Some(inner) = &self.inner {
+            inner.notify("STOPPING=1");
+        }
+    }
+
+    /// Mark update work in progress until the guard is dropped. Keepalives stop
+    /// once one piece of work outlasts the watchdog timeout, so systemd restarts
+    /// an updater stuck on it.
+    pub fn busy(&self) -> Busy {
+        if let Some(inner) = &self.inner {
+            *inner.busy_since.lock().unwrap() = Some(Instant::now());
+        }
+        Busy {
+            systemd: self.clone(),
+        }
+    }
+
+    /// Send `WATCHDOG=1` at half the watchdog timeout while the updater is not
+    /// stuck. None when systemd expects no keepalives.
+    pub fn spawn_watchdog(&self) -> Option<JoinHandle<()>> {
+        let inner = self.inner.clone()?;
+        let timeout = inner.watchdog?;
+        info!(
+            "Sending systemd watchdog keepalives every {}ms",
+            (timeout / 2).as_millis()
+        );
+
+        Some(tokio::spawn(async move {
+            let mut keepalive = time::interval(timeout / 2);
+            let mut stuck = false;
+            loop {
+                keepalive.tick().await;
+                let busy_since = *inner.busy_since.lock().unwrap();
+                match busy_since {
+                    Some(since) if since.elapsed() >= timeout => {
+                        if !stuck {
+                            warn!(
+                                "Update work running for {}s; withholding systemd watchdog keepalives",
+                                since.elapsed().as_secs()
+                            );
+                            stuck = true;
+                        }
+                    }
+                    _ => {
+                        stuck = false;
+                        inner.notify("WATCHDOG=1");
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Update work in progress, see [`Systemd::busy`]
+pub struct Busy {
+    systemd: Systemd,
+}
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        if let Some(inner) = &self.systemd.inner {
+            *inner.busy_since.lock().unwrap() = None;
+        }
+    }
+}
+
+impl Inner {
+    #[cfg(unix)]
+    fn notify(&self, state: &str) {
+        use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+        // A leading '@' names a socket in the abstract namespace
+        let address = match self.address.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                SocketAddr::from_abstract_name(name.as_bytes())
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                warn!("Abstract NOTIFY_SOCKET {} is not supported", self.address);
+                return;
+            }
+            None => SocketAddr::from_pathname(&self.address),
+        };
+        let sent = address
+            .and_then(|address| UnixDatagram::unbound()?.send_to_addr(state.as_bytes(), &address));
+        if let Err(e) = sent {
+            warn!("Failed to notify systemd ({}): {}", state, e);
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn notify(&self, _state: &str) {}
+}